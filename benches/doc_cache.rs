@@ -0,0 +1,80 @@
+// Micro-benchmark comparing concurrent read throughput of `DocCache` (RwLock)
+// against the previous Mutex-guarded LRU cache.
+//
+// Run with: cargo bench --bench doc_cache
+
+use std::num::NonZeroUsize;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use cratedocs_mcp::tools::DocCache;
+use lru::LruCache;
+use tokio::sync::Mutex;
+
+const TASKS: usize = 64;
+const READS_PER_TASK: usize = 20_000;
+const KEYS: usize = 100;
+
+async fn bench_rwlock_cache() -> Duration {
+    let cache = DocCache::new();
+    for i in 0..KEYS {
+        cache.set(format!("crate-{}", i), "x".repeat(4096)).await;
+    }
+
+    let start = Instant::now();
+    let handles: Vec<_> = (0..TASKS)
+        .map(|task| {
+            let cache = cache.clone();
+            tokio::spawn(async move {
+                for i in 0..READS_PER_TASK {
+                    let key = format!("crate-{}", (task + i) % KEYS);
+                    std::hint::black_box(cache.get(&key).await);
+                }
+            })
+        })
+        .collect();
+    futures::future::join_all(handles).await;
+    start.elapsed()
+}
+
+async fn bench_mutex_cache() -> Duration {
+    let cache = Arc::new(Mutex::new(LruCache::new(NonZeroUsize::new(1000).unwrap())));
+    for i in 0..KEYS {
+        cache.lock().await.put(format!("crate-{}", i), "x".repeat(4096));
+    }
+
+    let start = Instant::now();
+    let handles: Vec<_> = (0..TASKS)
+        .map(|task| {
+            let cache = cache.clone();
+            tokio::spawn(async move {
+                for i in 0..READS_PER_TASK {
+                    let key = format!("crate-{}", (task + i) % KEYS);
+                    std::hint::black_box(cache.lock().await.get(&key).cloned());
+                }
+            })
+        })
+        .collect();
+    futures::future::join_all(handles).await;
+    start.elapsed()
+}
+
+fn report(name: &str, elapsed: Duration) {
+    let reads = (TASKS * READS_PER_TASK) as f64;
+    println!(
+        "{:<16} {:>8.1} ms  {:>12.0} reads/s",
+        name,
+        elapsed.as_secs_f64() * 1000.0,
+        reads / elapsed.as_secs_f64()
+    );
+}
+
+fn main() {
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .expect("failed to build tokio runtime");
+
+    report("Mutex<LruCache>", runtime.block_on(bench_mutex_cache()));
+    report("DocCache", runtime.block_on(bench_rwlock_cache()));
+}
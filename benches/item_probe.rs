@@ -0,0 +1,59 @@
+// Compares how many docs.rs pages `lookup_item` has to request before finding an item
+// when probing in the fixed kind order versus the naming-convention order from `probe_order`.
+//
+// Run with: cargo bench --bench item_probe
+
+use std::time::Instant;
+
+use cratedocs_mcp::tools::cargo_docs::{probe_order, ItemKind};
+
+// Real item names paired with the kind of page docs.rs serves them under
+const ITEMS: &[(&str, ItemKind)] = &[
+    ("HashMap", ItemKind::Struct),
+    ("Client", ItemKind::Struct),
+    ("Ordering", ItemKind::Enum),
+    ("Option", ItemKind::Enum),
+    ("Value", ItemKind::Enum),
+    ("Serialize", ItemKind::Trait),
+    ("AsyncRead", ItemKind::Trait),
+    ("Iterator", ItemKind::Trait),
+    ("Result", ItemKind::Type),
+    ("from_str", ItemKind::Fn),
+    ("spawn", ItemKind::Fn),
+    ("read_to_string", ItemKind::Fn),
+    ("channel", ItemKind::Fn),
+    ("sleep", ItemKind::Fn),
+    ("println!", ItemKind::Macro),
+    ("json!", ItemKind::Macro),
+    ("select!", ItemKind::Macro),
+    ("vec", ItemKind::Macro),
+    ("format", ItemKind::Macro),
+    ("MAX", ItemKind::Constant),
+    ("PI", ItemKind::Constant),
+    ("MAIN_SEPARATOR", ItemKind::Constant),
+    ("UNIX_EPOCH", ItemKind::Constant),
+];
+
+// Number of pages requested until `kind` is reached
+fn probes(order: &[ItemKind], kind: ItemKind) -> usize {
+    order.iter().position(|k| *k == kind).map_or(order.len(), |i| i + 1)
+}
+
+fn main() {
+    let start = Instant::now();
+    let (mut fixed, mut heuristic) = (0, 0);
+    for (name, kind) in ITEMS {
+        fixed += probes(&ItemKind::ALL, *kind);
+        heuristic += probes(&probe_order(name), *kind);
+    }
+    let elapsed = start.elapsed();
+
+    let count = ITEMS.len() as f64;
+    let fixed = fixed as f64 / count;
+    let heuristic = heuristic as f64 / count;
+    println!("items:                  {}", ITEMS.len());
+    println!("fixed order:            {:.2} probes/item", fixed);
+    println!("naming heuristic order: {:.2} probes/item", heuristic);
+    println!("reduction:              {:.1}%", (1.0 - heuristic / fixed) * 100.0);
+    println!("ordering cost:          {:?} total", elapsed);
+}
@@ -0,0 +1,102 @@
+// Compares how long finding an item's page takes when the item kinds are probed one after
+// another versus concurrently with `probe_all_types`, likely kinds first, against a local
+// server that answers each request after a docs.rs-like delay.
+//
+// Run with: cargo bench --bench lookup_latency
+
+use std::convert::Infallible;
+use std::time::{Duration, Instant};
+
+use cratedocs_mcp::tools::cargo_docs::{probe_order, CrateName, ItemKind};
+use cratedocs_mcp::tools::CargoDocRouter;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Response, Server, StatusCode};
+
+// Round-trip time simulated for every request
+const LATENCY: Duration = Duration::from_millis(50);
+
+// Item names paired with the kind of page the server serves them under
+const ITEMS: &[(&str, ItemKind)] = &[
+    ("vec", ItemKind::Macro),
+    ("format", ItemKind::Macro),
+    ("select", ItemKind::Macro),
+    ("Result", ItemKind::Type),
+    ("BoxFuture", ItemKind::Type),
+    ("Serialize", ItemKind::Derive),
+];
+
+// Serve `item.<kind>.html` pages for the items in `ITEMS`, and 404 for every other page
+async fn start_server() -> String {
+    let make_service = make_service_fn(|_| async {
+        Ok::<_, Infallible>(service_fn(|request: hyper::Request<Body>| async move {
+            tokio::time::sleep(LATENCY).await;
+            let found = ITEMS.iter().any(|(name, kind)| {
+                request.uri().path() == format!("/bench/latest/bench/{}.{}.html", kind.page_prefix(), name)
+            });
+            let status = if found { StatusCode::OK } else { StatusCode::NOT_FOUND };
+            Ok::<_, Infallible>(
+                Response::builder()
+                    .status(status)
+                    .body(Body::from("<html><body><p>Item.</p></body></html>"))
+                    .unwrap(),
+            )
+        }))
+    });
+    let server = Server::bind(&([127, 0, 0, 1], 0).into()).serve(make_service);
+    let url = format!("http://{}", server.local_addr());
+    tokio::spawn(server);
+    url
+}
+
+// Request each kind's page in turn until one exists, as lookups did before `probe_all_types`
+async fn probe_sequentially(client: &reqwest::Client, base_url: &str, name: &str) -> Duration {
+    let start = Instant::now();
+    for kind in probe_order(name) {
+        let url = format!("{}/bench/latest/bench/{}.{}.html", base_url, kind.page_prefix(), name);
+        if client.get(&url).send().await.unwrap().status().is_success() {
+            break;
+        }
+    }
+    start.elapsed()
+}
+
+async fn probe_concurrently(router: &CargoDocRouter, name: &str) -> Duration {
+    let start = Instant::now();
+    router.probe_all_types(&CrateName::parse("bench").unwrap(), "", name, None, None).await.unwrap();
+    start.elapsed()
+}
+
+fn main() {
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .expect("failed to build tokio runtime");
+
+    runtime.block_on(async {
+        let base_url = start_server().await;
+        let client = reqwest::Client::new();
+        let router = CargoDocRouter::new().with_base_url(base_url.clone());
+
+        println!("{:<12} {:>8} {:>12} {:>12}", "item", "kind", "sequential", "concurrent");
+        let (mut sequential, mut concurrent) = (Duration::ZERO, Duration::ZERO);
+        for (name, kind) in ITEMS {
+            let one_by_one = probe_sequentially(&client, &base_url, name).await;
+            let at_once = probe_concurrently(&router, name).await;
+            sequential += one_by_one;
+            concurrent += at_once;
+            println!(
+                "{:<12} {:>8} {:>9.0} ms {:>9.0} ms",
+                name,
+                kind.page_prefix(),
+                one_by_one.as_secs_f64() * 1000.0,
+                at_once.as_secs_f64() * 1000.0
+            );
+        }
+        println!(
+            "total: {:.0} ms sequential, {:.0} ms concurrent ({:.1}x faster)",
+            sequential.as_secs_f64() * 1000.0,
+            concurrent.as_secs_f64() * 1000.0,
+            sequential.as_secs_f64() / concurrent.as_secs_f64()
+        );
+    });
+}
@@ -1,6 +1,8 @@
 use anyhow::Result;
 use clap::{Parser, Subcommand};
+use cratedocs_mcp::tools::{CargoDocRouter, DocCache};
 use std::net::SocketAddr;
+use std::path::PathBuf;
 use tracing_subscriber::{self, layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
 
 #[derive(Parser)]
@@ -30,6 +32,15 @@ enum Commands {
         #[arg(short, long)]
         debug: bool,
     },
+    /// Fetch the documentation of frequently used crates ahead of time
+    Warmup {
+        /// Crates to fetch, as `name` or `name@version` (defaults to a curated list of popular crates)
+        crates: Vec<String>,
+
+        /// Save the fetched documentation to this file as a JSON cache snapshot
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
 }
 
 #[tokio::main]
@@ -39,7 +50,35 @@ async fn main() -> Result<()> {
     match cli.command {
         Commands::Stdio { debug } => run_stdio_server(debug).await,
         Commands::Http { address, debug } => run_http_server(address, debug).await,
+        Commands::Warmup { crates, output } => run_warmup(crates, output).await,
+    }
+}
+
+async fn run_warmup(crates: Vec<String>, output: Option<PathBuf>) -> Result<()> {
+    let crates: Vec<(&str, Option<&str>)> = if crates.is_empty() {
+        DocCache::DEFAULT_WARM_LIST.to_vec()
+    } else {
+        crates
+            .iter()
+            .map(|spec| match spec.split_once('@') {
+                Some((name, version)) => (name, Some(version)),
+                None => (spec.as_str(), None),
+            })
+            .collect()
+    };
+
+    let router = CargoDocRouter::new();
+    let result = DocCache::warm_from_list(&crates, &router).await;
+    println!("Fetched {} crates, {} failed", result.succeeded, result.failed);
+    for error in &result.errors {
+        eprintln!("{}", error);
     }
+
+    if let Some(path) = output {
+        router.cache.save_to_file(&path).await?;
+        println!("Saved the cache to {}", path.display());
+    }
+    Ok(())
 }
 
 async fn run_stdio_server(debug: bool) -> Result<()> {
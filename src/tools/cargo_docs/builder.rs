@@ -0,0 +1,219 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{bail, Context};
+use reqwest::header::HeaderValue;
+use reqwest::{Client, Proxy, Url};
+use tokio::sync::Semaphore;
+
+use super::cache::{CacheBackend, DocCache};
+use super::rate_limit::RateLimiter;
+use super::{http_client, CargoDocRouter, DEFAULT_TIMEOUT};
+
+/// Configures a [`CargoDocRouter`]; created by [`CargoDocRouter::builder`].
+///
+/// Options that aren't set keep the defaults of [`CargoDocRouter::new`].
+#[derive(Default)]
+pub struct CargoDocRouterBuilder {
+    timeout: Option<Duration>,
+    cache: Option<DocCache>,
+    client: Option<Client>,
+    base_url: Option<String>,
+    crates_io_base_url: Option<String>,
+    stdlib_base_url: Option<String>,
+    user_agent: Option<String>,
+    retry_count: Option<u8>,
+    max_cache_size: Option<usize>,
+    rate_limit_rps: Option<f64>,
+    max_concurrent_requests: Option<usize>,
+    proxy_url: Option<String>,
+    background_refresh: Option<bool>,
+    case_insensitive_fallback: Option<bool>,
+}
+
+impl CargoDocRouterBuilder {
+    /// Fail requests that take longer than `timeout`
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Store documentation in `cache`, e.g. one shared with another router
+    pub fn with_cache(mut self, cache: DocCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Store documentation in a cache backed by `backend`, e.g. `NullBackend` to disable caching;
+    /// shorthand for `with_cache(DocCache::with_backend(backend))`
+    pub fn with_cache_backend(self, backend: impl CacheBackend + 'static) -> Self {
+        self.with_cache(DocCache::with_backend(backend))
+    }
+
+    /// Send requests with `client`. The timeout and User-Agent are still set on each request.
+    pub fn with_client(mut self, client: Client) -> Self {
+        self.client = Some(client);
+        self
+    }
+
+    /// Fetch documentation from `base_url` instead of docs.rs
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = Some(base_url.into());
+        self
+    }
+
+    /// Use the crates.io API at `crates_io_base_url` instead of crates.io
+    pub fn with_crates_io_base_url(mut self, crates_io_base_url: impl Into<String>) -> Self {
+        self.crates_io_base_url = Some(crates_io_base_url.into());
+        self
+    }
+
+    /// Fetch standard library documentation from `stdlib_base_url` instead of doc.rust-lang.org
+    pub fn with_stdlib_base_url(mut self, stdlib_base_url: impl Into<String>) -> Self {
+        self.stdlib_base_url = Some(stdlib_base_url.into());
+        self
+    }
+
+    /// Send `user_agent` as the User-Agent header
+    pub fn with_user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = Some(user_agent.into());
+        self
+    }
+
+    /// Retry transient failures up to `retry_count` times; 0 disables retries
+    pub fn with_retry_count(mut self, retry_count: u8) -> Self {
+        self.retry_count = Some(retry_count);
+        self
+    }
+
+    /// Hold at most `max_cache_size` entries in the in-memory cache
+    pub fn with_max_cache_size(mut self, max_cache_size: usize) -> Self {
+        self.max_cache_size = Some(max_cache_size);
+        self
+    }
+
+    /// Start at most `requests_per_second` requests per second
+    pub fn with_rate_limit_rps(mut self, requests_per_second: f64) -> Self {
+        self.rate_limit_rps = Some(requests_per_second);
+        self
+    }
+
+    /// Send at most `max_concurrent_requests` requests at once
+    pub fn with_max_concurrent_requests(mut self, max_concurrent_requests: usize) -> Self {
+        self.max_concurrent_requests = Some(max_concurrent_requests);
+        self
+    }
+
+    /// Send all requests through the HTTP proxy at `proxy_url` instead of the one named by the
+    /// HTTP_PROXY/HTTPS_PROXY environment variables. Hosts listed in NO_PROXY are still reached directly.
+    pub fn with_proxy_url(mut self, proxy_url: impl Into<String>) -> Self {
+        self.proxy_url = Some(proxy_url.into());
+        self
+    }
+
+    /// Whether to refetch cached entries close to expiring in the background; enabled by default
+    pub fn with_background_refresh(mut self, enabled: bool) -> Self {
+        self.background_refresh = Some(enabled);
+        self
+    }
+
+    /// Whether items that aren't found are looked for again under other capitalizations of
+    /// their name, e.g. `asyncread` as `AsyncRead`; enabled by default
+    pub fn with_case_insensitive_fallback(mut self, enabled: bool) -> Self {
+        self.case_insensitive_fallback = Some(enabled);
+        self
+    }
+
+    /// Check the configuration and create the router
+    pub fn build(self) -> anyhow::Result<CargoDocRouter> {
+        if self.timeout.is_some_and(|timeout| timeout.is_zero()) {
+            bail!("timeout must be greater than zero");
+        }
+        if self.cache.is_some() && self.max_cache_size.is_some() {
+            bail!("with_cache or with_cache_backend can't be combined with with_max_cache_size; size the cache when creating it");
+        }
+        if self.max_cache_size == Some(0) {
+            bail!("max_cache_size must be greater than zero");
+        }
+        if let Some(rps) = self.rate_limit_rps {
+            if !rps.is_finite() || rps <= 0.0 {
+                bail!("rate_limit_rps must be a positive number, got {}", rps);
+            }
+        }
+        if let Some(max) = self.max_concurrent_requests {
+            if max == 0 || max > Semaphore::MAX_PERMITS {
+                bail!("max_concurrent_requests must be between 1 and {}, got {}", Semaphore::MAX_PERMITS, max);
+            }
+        }
+        if let Some(base_url) = &self.base_url {
+            check_base_url("base_url", base_url)?;
+        }
+        if let Some(crates_io_base_url) = &self.crates_io_base_url {
+            check_base_url("crates_io_base_url", crates_io_base_url)?;
+        }
+        if let Some(stdlib_base_url) = &self.stdlib_base_url {
+            check_base_url("stdlib_base_url", stdlib_base_url)?;
+        }
+        if let Some(user_agent) = &self.user_agent {
+            HeaderValue::from_str(user_agent)
+                .with_context(|| format!("invalid user_agent `{}`", user_agent))?;
+        }
+        if self.client.is_some() && self.proxy_url.is_some() {
+            bail!("with_client and with_proxy_url can't be combined; set the proxy on the client");
+        }
+        let proxy = match &self.proxy_url {
+            Some(proxy_url) => {
+                check_base_url("proxy_url", proxy_url)?;
+                Some(Proxy::all(proxy_url).with_context(|| format!("invalid proxy_url `{}`", proxy_url))?)
+            }
+            None => None,
+        };
+
+        let mut router = CargoDocRouter::new();
+        let timeout = self.timeout.unwrap_or(DEFAULT_TIMEOUT);
+        router.timeout = timeout;
+        router.client = self.client.unwrap_or_else(|| http_client(timeout, proxy.clone()));
+        router.proxy = proxy;
+        if let Some(cache) = self.cache {
+            router.cache = cache;
+        } else if let Some(max_cache_size) = self.max_cache_size {
+            router.cache = DocCache::new_with_capacity(max_cache_size);
+        }
+        if let Some(base_url) = self.base_url {
+            router = router.with_base_url(base_url);
+        }
+        if let Some(crates_io_base_url) = self.crates_io_base_url {
+            router = router.with_crates_io_base_url(crates_io_base_url);
+        }
+        if let Some(stdlib_base_url) = self.stdlib_base_url {
+            router = router.with_stdlib_base_url(stdlib_base_url);
+        }
+        if let Some(user_agent) = self.user_agent {
+            router.user_agent = user_agent;
+        }
+        if let Some(retry_count) = self.retry_count {
+            router.max_retries = retry_count;
+        }
+        if let Some(enabled) = self.background_refresh {
+            router.background_refresh_enabled = enabled;
+        }
+        if let Some(enabled) = self.case_insensitive_fallback {
+            router.case_insensitive_fallback = enabled;
+        }
+        router.rate_limiter = self.rate_limit_rps.map(RateLimiter::new);
+        router.request_slots = self
+            .max_concurrent_requests
+            .map(|max| Arc::new(Semaphore::new(max)));
+
+        Ok(router)
+    }
+}
+
+// Fail unless `url` is an absolute http or https URL
+fn check_base_url(option: &str, url: &str) -> anyhow::Result<()> {
+    let parsed = Url::parse(url).with_context(|| format!("invalid {} `{}`", option, url))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        bail!("{} must be an http or https URL, got `{}`", option, url);
+    }
+    Ok(())
+}
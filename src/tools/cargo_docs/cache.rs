@@ -0,0 +1,793 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::num::NonZeroUsize;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use futures::future::join_all;
+use lru::LruCache;
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+use tokio::time::Instant;
+
+use super::CargoDocRouter;
+
+/// Number of entries each cache holds before evicting the least recently used one
+pub const DEFAULT_CACHE_CAPACITY: usize = 1000;
+
+// Share of an entry's TTL after which it is due for a refresh
+const REFRESH_AFTER_TTL_SHARE: f64 = 0.9;
+
+// Prefix of the cache keys of crate and item documentation (`doc:name`, `doc:name:version`,
+// `doc:name:path`), so they can't be confused with derived content of a crate named like one
+// of the `DERIVED_KEY_PREFIXES`, e.g. `json:serde:latest` for the `json` crate
+pub(crate) const DOC_KEY_PREFIX: &str = "doc:";
+
+// Prefixes of cache keys for content derived from a crate's item documentation
+pub(crate) const DERIVED_KEY_PREFIXES: &[&str] = &[
+    "examples:",
+    "relationships:",
+    "module:",
+    "module_items:",
+    "versions:",
+    "deps:",
+    "reverse_deps:",
+    "features:",
+    "feature_map:",
+    "metadata:",
+    "msrv:",
+    "readme:",
+    "builds:",
+    "diff:",
+    "implementors:",
+    "supertraits:",
+    "trait:",
+    "enum:",
+    "macro:",
+    "json:",
+    "index:",
+    "item_doc:",
+];
+
+// Whether a cache key refers to `crate_name`, i.e. the crate docs themselves (`doc:name`,
+// `doc:name:version`), one of its items (`doc:name:path`), or content derived from them
+// (`examples:name:path`). Every key starts with exactly one prefix naming what it holds.
+fn key_belongs_to_crate(key: &str, crate_name: &str) -> bool {
+    let Some(key) = std::iter::once(&DOC_KEY_PREFIX)
+        .chain(DERIVED_KEY_PREFIXES)
+        .find_map(|prefix| key.strip_prefix(prefix))
+    else {
+        return false;
+    };
+    key == crate_name
+        || key
+            .strip_prefix(crate_name)
+            .is_some_and(|rest| rest.starts_with(':'))
+}
+
+/// Storage for the main documentation cache.
+///
+/// Implement this to keep cached documentation somewhere other than process memory
+/// (Redis, a SQL database, a test double) and pass it to [`DocCache::with_backend`].
+#[async_trait]
+pub trait CacheBackend: Send + Sync {
+    async fn get(&self, key: &str) -> Option<String>;
+
+    async fn set(&self, key: String, value: String);
+
+    /// Remove `key`, returning whether it was present.
+    async fn remove(&self, key: &str) -> bool;
+
+    async fn clear(&self);
+
+    async fn keys(&self) -> Vec<String>;
+
+    /// Whether `key` is stored. Backends that track recency should override this
+    /// so that checking for a key doesn't count as a use.
+    async fn contains(&self, key: &str) -> bool {
+        self.get(key).await.is_some()
+    }
+
+    async fn len(&self) -> usize {
+        self.keys().await.len()
+    }
+
+    async fn is_empty(&self) -> bool {
+        self.len().await == 0
+    }
+
+    /// Every stored key-value pair. The default reads each key through `get`, so
+    /// backends that track recency should override this to avoid touching every entry.
+    async fn entries(&self) -> Vec<(String, String)> {
+        let mut entries = Vec::new();
+        for key in self.keys().await {
+            if let Some(value) = self.get(&key).await {
+                entries.push((key, value));
+            }
+        }
+        entries
+    }
+
+    /// Approximate memory used by the stored entries: the sum of key and value byte lengths.
+    async fn estimated_bytes(&self) -> usize {
+        self.entries()
+            .await
+            .iter()
+            .map(|(key, value)| key.len() + value.len())
+            .sum()
+    }
+}
+
+/// Bounded in-memory LRU backend used by default.
+///
+/// Reads share an RwLock read guard; LRU recency is refreshed opportunistically (see `touch`).
+#[derive(Clone)]
+pub struct InMemoryBackend {
+    entries: Arc<RwLock<LruCache<String, String>>>,
+}
+
+impl Default for InMemoryBackend {
+    fn default() -> Self {
+        Self::new(DEFAULT_CACHE_CAPACITY)
+    }
+}
+
+impl InMemoryBackend {
+    /// Create a backend holding at most `max_size` entries. A `max_size` of zero is treated as one.
+    pub fn new(max_size: usize) -> Self {
+        Self {
+            entries: Arc::new(RwLock::new(LruCache::new(capacity(max_size)))),
+        }
+    }
+}
+
+#[async_trait]
+impl CacheBackend for InMemoryBackend {
+    async fn get(&self, key: &str) -> Option<String> {
+        let value = self.entries.read().await.peek(key).cloned();
+        if value.is_some() {
+            touch(&self.entries, key);
+        }
+        value
+    }
+
+    async fn set(&self, key: String, value: String) {
+        self.entries.write().await.put(key, value);
+    }
+
+    async fn remove(&self, key: &str) -> bool {
+        self.entries.write().await.pop(key).is_some()
+    }
+
+    async fn clear(&self) {
+        self.entries.write().await.clear();
+    }
+
+    async fn keys(&self) -> Vec<String> {
+        self.entries.read().await.iter().map(|(key, _)| key.clone()).collect()
+    }
+
+    async fn contains(&self, key: &str) -> bool {
+        self.entries.read().await.contains(key)
+    }
+
+    async fn len(&self) -> usize {
+        self.entries.read().await.len()
+    }
+
+    async fn entries(&self) -> Vec<(String, String)> {
+        self.entries
+            .read()
+            .await
+            .iter()
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect()
+    }
+
+    async fn estimated_bytes(&self) -> usize {
+        self.entries
+            .read()
+            .await
+            .iter()
+            .map(|(key, value)| key.len() + value.len())
+            .sum()
+    }
+}
+
+/// Backend that stores nothing, so every lookup misses. Useful for disabling
+/// documentation caching in tests.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NullBackend;
+
+#[async_trait]
+impl CacheBackend for NullBackend {
+    async fn get(&self, _key: &str) -> Option<String> {
+        None
+    }
+
+    async fn set(&self, _key: String, _value: String) {}
+
+    async fn remove(&self, _key: &str) -> bool {
+        false
+    }
+
+    async fn clear(&self) {}
+
+    async fn keys(&self) -> Vec<String> {
+        Vec::new()
+    }
+}
+
+fn capacity(max_size: usize) -> NonZeroUsize {
+    NonZeroUsize::new(max_size).unwrap_or(NonZeroUsize::MIN)
+}
+
+// Cache for documentation lookups to avoid repeated requests.
+// Documentation is kept in a pluggable `CacheBackend` (a bounded LRU by default);
+// examples are kept in a typed, bounded in-memory LRU.
+#[derive(Clone)]
+pub struct DocCache {
+    backend: Arc<dyn CacheBackend>,
+    // New: Cache for example code snippets
+    examples_cache: Arc<RwLock<LruCache<String, Vec<CodeExample>>>>,
+    // Optional on-disk tier that survives server restarts
+    persistent: Option<PersistentDocCache>,
+    // Lookup counters, shared between clones of the cache
+    hits: Arc<AtomicU64>,
+    misses: Arc<AtomicU64>,
+    // Deadlines of entries stored with `set_with_ttl`
+    expirations: Arc<RwLock<HashMap<String, Expiry>>>,
+}
+
+// When an entry stored with `set_with_ttl` should be refreshed, and when it expires
+#[derive(Clone, Copy, Debug)]
+struct Expiry {
+    refresh_at: Instant,
+    deadline: Instant,
+}
+
+// New: Structure for code examples
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CodeExample {
+    pub title: String,
+    pub code: String,
+    pub description: String,
+    /// Info string of the code block's opening fence, e.g. `rust,no_run`
+    #[serde(default)]
+    pub fence_info: String,
+    /// Whether the example is a template made by `generated_example` rather than taken from the
+    /// documentation
+    #[serde(default)]
+    pub generated: bool,
+}
+
+impl CodeExample {
+    // Byte length of the example's text fields
+    fn estimated_bytes(&self) -> usize {
+        self.title.len() + self.code.len() + self.description.len() + self.fence_info.len()
+    }
+}
+
+/// Snapshot of how many entries the in-memory caches hold and roughly how much memory they use.
+/// Byte counts are the sum of key and value lengths and ignore allocator and LRU bookkeeping overhead.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub main_entries: usize,
+    pub examples_entries: usize,
+    pub main_bytes: usize,
+    pub examples_bytes: usize,
+}
+
+impl CacheStats {
+    pub fn entry_count(&self) -> usize {
+        self.main_entries + self.examples_entries
+    }
+
+    pub fn estimated_bytes(&self) -> usize {
+        self.main_bytes + self.examples_bytes
+    }
+}
+
+impl fmt::Display for CacheStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} documentation entries ({} bytes), {} example entries ({} bytes)",
+            self.main_entries, self.main_bytes, self.examples_entries, self.examples_bytes
+        )
+    }
+}
+
+/// Outcome of [`DocCache::warm_from_list`]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct WarmupResult {
+    pub succeeded: usize,
+    pub failed: usize,
+    /// Why each failed crate couldn't be fetched, prefixed with its name
+    pub errors: Vec<String>,
+}
+
+impl Default for DocCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DocCache {
+    /// Frequently used crates, by name and version, worth fetching when a server starts
+    pub const DEFAULT_WARM_LIST: &'static [(&'static str, Option<&'static str>)] = &[
+        ("serde", None),
+        ("tokio", None),
+        ("reqwest", None),
+        ("std", None),
+        ("clap", None),
+        ("anyhow", None),
+        ("thiserror", None),
+    ];
+
+    pub fn new() -> Self {
+        Self::new_with_capacity(DEFAULT_CACHE_CAPACITY)
+    }
+
+    /// Create a cache holding at most `max_size` entries in each of the main and examples caches.
+    /// A `max_size` of zero is treated as one.
+    pub fn new_with_capacity(max_size: usize) -> Self {
+        Self {
+            backend: Arc::new(InMemoryBackend::new(max_size)),
+            examples_cache: Arc::new(RwLock::new(LruCache::new(capacity(max_size)))),
+            persistent: None,
+            hits: Arc::new(AtomicU64::new(0)),
+            misses: Arc::new(AtomicU64::new(0)),
+            expirations: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    /// Create a cache that stores documentation in `backend`.
+    pub fn with_backend(backend: impl CacheBackend + 'static) -> Self {
+        Self {
+            backend: Arc::new(backend),
+            ..Self::new()
+        }
+    }
+
+    /// Back this cache with an on-disk store. Writes go to both tiers, and in-memory
+    /// misses fall back to the persistent store before reporting a miss.
+    pub fn with_persistent_cache(mut self, persistent: PersistentDocCache) -> Self {
+        self.persistent = Some(persistent);
+        self
+    }
+
+    pub async fn get(&self, key: &str) -> Option<String> {
+        let value = self.lookup(key).await;
+        self.record_lookup(value.is_some());
+        value
+    }
+
+    async fn lookup(&self, key: &str) -> Option<String> {
+        if self.is_expired(key).await {
+            self.expirations.write().await.remove(key);
+            self.backend.remove(key).await;
+            return None;
+        }
+
+        if let Some(value) = self.backend.get(key).await {
+            return Some(value);
+        }
+
+        // Promote entries found on disk into the backend
+        let value = self.persistent.as_ref()?.get(key).await?;
+        self.backend.set(key.to_string(), value.clone()).await;
+        Some(value)
+    }
+
+    pub async fn set(&self, key: String, value: String) {
+        if let Some(persistent) = &self.persistent {
+            persistent.set(key.clone(), value.clone()).await;
+        }
+        self.expirations.write().await.remove(&key);
+        self.backend.set(key, value).await;
+    }
+
+    /// Cache `value` for at most `ttl`, for content that changes over time such as build status.
+    /// These entries are kept in the backend only and never written to the persistent tier.
+    pub async fn set_with_ttl(&self, key: String, value: String, ttl: Duration) {
+        let now = Instant::now();
+        let expiry = Expiry {
+            refresh_at: now + ttl.mul_f64(REFRESH_AFTER_TTL_SHARE),
+            deadline: now + ttl,
+        };
+        self.expirations.write().await.insert(key.clone(), expiry);
+        self.backend.set(key, value).await;
+    }
+
+    async fn is_expired(&self, key: &str) -> bool {
+        self.expirations
+            .read()
+            .await
+            .get(key)
+            .is_some_and(|expiry| expiry.deadline <= Instant::now())
+    }
+
+    /// Whether the entry under `key` was stored with a TTL and has used up more than 90% of it
+    /// without expiring yet, so it should be refetched before callers start missing it.
+    pub async fn needs_refresh(&self, key: &str) -> bool {
+        let now = Instant::now();
+        self.expirations
+            .read()
+            .await
+            .get(key)
+            .is_some_and(|expiry| expiry.refresh_at <= now && now < expiry.deadline)
+    }
+    
+    // New: Methods for examples cache
+    pub async fn get_examples(&self, key: &str) -> Option<Vec<CodeExample>> {
+        let examples = self.lookup_examples(key).await;
+        self.record_lookup(examples.is_some());
+        examples
+    }
+
+    async fn lookup_examples(&self, key: &str) -> Option<Vec<CodeExample>> {
+        let examples = self.examples_cache.read().await.peek(key).cloned();
+        if let Some(examples) = examples {
+            touch(&self.examples_cache, key);
+            return Some(examples);
+        }
+
+        let examples = self.persistent.as_ref()?.get_examples(key).await?;
+        self.examples_cache
+            .write()
+            .await
+            .put(key.to_string(), examples.clone());
+        Some(examples)
+    }
+
+    pub async fn set_examples(&self, key: String, examples: Vec<CodeExample>) {
+        if let Some(persistent) = &self.persistent {
+            persistent.set_examples(key.clone(), examples.clone()).await;
+        }
+        let mut cache = self.examples_cache.write().await;
+        cache.put(key, examples);
+    }
+
+    /// Whether `key` is cached, without affecting LRU recency or the hit/miss counters.
+    pub async fn contains(&self, key: &str) -> bool {
+        if self.is_expired(key).await {
+            return false;
+        }
+        if self.backend.contains(key).await {
+            return true;
+        }
+        match &self.persistent {
+            Some(persistent) => persistent.contains(key).await,
+            None => false,
+        }
+    }
+
+    /// Whether examples are cached under `key`, without affecting LRU recency or the hit/miss counters.
+    pub async fn contains_examples(&self, key: &str) -> bool {
+        if self.examples_cache.read().await.contains(key) {
+            return true;
+        }
+        match &self.persistent {
+            Some(persistent) => persistent.contains_examples(key).await,
+            None => false,
+        }
+    }
+
+    /// Remove a single entry, returning its value if it was cached.
+    pub async fn remove(&self, key: &str) -> Option<String> {
+        self.expirations.write().await.remove(key);
+        let value = self.backend.get(key).await;
+        self.backend.remove(key).await;
+        let persisted = match &self.persistent {
+            Some(persistent) => persistent.remove(key).await,
+            None => None,
+        };
+        value.or(persisted)
+    }
+
+    /// Remove the examples cached under `key`, returning them if they were cached.
+    pub async fn remove_examples(&self, key: &str) -> Option<Vec<CodeExample>> {
+        let examples = self.examples_cache.write().await.pop(key);
+        let persisted = match &self.persistent {
+            Some(persistent) => persistent.remove_examples(key).await,
+            None => None,
+        };
+        examples.or(persisted)
+    }
+
+    fn record_lookup(&self, hit: bool) {
+        let counter = if hit { &self.hits } else { &self.misses };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Number of lookups served from the cache since creation or the last reset.
+    pub fn hits(&self) -> u64 {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    /// Number of lookups that missed the cache since creation or the last reset.
+    pub fn misses(&self) -> u64 {
+        self.misses.load(Ordering::Relaxed)
+    }
+
+    /// Zero the hit and miss counters.
+    pub fn reset_counters(&self) {
+        self.hits.store(0, Ordering::Relaxed);
+        self.misses.store(0, Ordering::Relaxed);
+    }
+
+    /// Entry counts and estimated memory size of the main backend and the in-memory examples cache.
+    pub async fn stats(&self) -> CacheStats {
+        let (examples_entries, examples_bytes) = {
+            let cache = self.examples_cache.read().await;
+            let bytes = cache
+                .iter()
+                .map(|(key, examples)| {
+                    key.len() + examples.iter().map(CodeExample::estimated_bytes).sum::<usize>()
+                })
+                .sum();
+            (cache.len(), bytes)
+        };
+
+        CacheStats {
+            main_entries: self.backend.len().await,
+            examples_entries,
+            main_bytes: self.backend.estimated_bytes().await,
+            examples_bytes,
+        }
+    }
+
+    /// Total number of entries held in memory.
+    pub async fn entry_count(&self) -> usize {
+        self.stats().await.entry_count()
+    }
+
+    /// Approximate number of bytes held in memory, see [`CacheStats`].
+    pub async fn estimated_bytes(&self) -> usize {
+        self.stats().await.estimated_bytes()
+    }
+
+    /// All key-value pairs in the main cache, sorted by key so snapshots are stable.
+    pub async fn export(&self) -> Vec<(String, String)> {
+        let mut entries = self.backend.entries().await;
+        entries.sort();
+        entries
+    }
+
+    /// Keys of the main cache, sorted.
+    pub async fn keys(&self) -> Vec<String> {
+        let mut keys = self.backend.keys().await;
+        keys.sort();
+        keys
+    }
+
+    /// Insert every key-value pair, e.g. from a fixture produced by [`DocCache::export`].
+    pub async fn import(&self, entries: Vec<(String, String)>) {
+        for (key, value) in entries {
+            self.set(key, value).await;
+        }
+    }
+
+    /// Create an in-memory cache pre-populated from a JSON snapshot written by [`DocCache::save_to_file`].
+    pub async fn load_from_file(path: &Path) -> anyhow::Result<DocCache> {
+        let json = tokio::fs::read_to_string(path).await?;
+        let entries: Vec<(String, String)> = serde_json::from_str(&json)?;
+        let cache = DocCache::new();
+        cache.import(entries).await;
+        Ok(cache)
+    }
+
+    /// Write the main cache to `path` as a JSON array of `[key, value]` pairs.
+    pub async fn save_to_file(&self, path: &Path) -> anyhow::Result<()> {
+        let json = serde_json::to_string_pretty(&self.export().await)?;
+        tokio::fs::write(path, json).await?;
+        Ok(())
+    }
+
+    /// Fetch the documentation of `crates`, by name and optional version, into `router`'s cache
+    /// as `lookup_crate` does. The lookups run concurrently, their requests still going through
+    /// the router's rate limiter; a crate that can't be fetched doesn't stop the others.
+    pub async fn warm_from_list(crates: &[(&str, Option<&str>)], router: &CargoDocRouter) -> WarmupResult {
+        let lookups = crates.iter().map(|(name, version)| async move {
+            router
+                .lookup_crate(name.to_string(), version.map(str::to_string), None, None, None, None)
+                .await
+                .map_err(|e| format!("{}: {}", name, e.message))
+        });
+
+        let mut result = WarmupResult::default();
+        for outcome in join_all(lookups).await {
+            match outcome {
+                Ok(_) => result.succeeded += 1,
+                Err(e) => {
+                    result.failed += 1;
+                    result.errors.push(e);
+                }
+            }
+        }
+        result
+    }
+
+    /// Remove every entry from both caches, returning the number of entries removed.
+    pub async fn clear_all(&self) -> usize {
+        self.clear_matching(|_| true).await
+    }
+
+    /// Remove all entries belonging to the crate `name`, including its items and
+    /// derived examples, returning the number of entries removed.
+    pub async fn clear_crate(&self, name: &str) -> usize {
+        self.clear_matching(|key| key_belongs_to_crate(key, name)).await
+    }
+
+    async fn clear_matching(&self, matches: impl Fn(&str) -> bool) -> usize {
+        self.expirations.write().await.retain(|key, _| !matches(key));
+
+        let mut removed = HashSet::new();
+        for key in self.backend.keys().await {
+            if matches(&key) && self.backend.remove(&key).await {
+                removed.insert(key);
+            }
+        }
+
+        let mut removed_examples = HashSet::new();
+        {
+            let mut cache = self.examples_cache.write().await;
+            let keys: Vec<String> = cache.iter().map(|(key, _)| key).filter(|key| matches(key)).cloned().collect();
+            for key in keys {
+                cache.pop(&key);
+                removed_examples.insert(key);
+            }
+        }
+
+        // Entries left on disk would otherwise be promoted back on the next lookup
+        if let Some(persistent) = &self.persistent {
+            let (keys, example_keys) = persistent.remove_matching(&matches).await;
+            removed.extend(keys);
+            removed_examples.extend(example_keys);
+        }
+
+        removed.len() + removed_examples.len()
+    }
+}
+
+// Mark `key` as most recently used if the write lock is free. Under contention the
+// promotion is skipped rather than making readers queue behind each other, so recency
+// is approximate while many lookups run in parallel.
+fn touch<V>(cache: &RwLock<LruCache<String, V>>, key: &str) {
+    if let Ok(mut cache) = cache.try_write() {
+        cache.promote(key);
+    }
+}
+
+// Documentation cache stored in a sled database so entries survive server restarts.
+// Keys and values are stored as UTF-8 bytes; examples are serialized as JSON in their own tree.
+#[derive(Clone)]
+pub struct PersistentDocCache {
+    db: sled::Db,
+    examples: sled::Tree,
+}
+
+impl PersistentDocCache {
+    /// Open the sled database at `path`, creating it if it doesn't exist yet.
+    pub fn open(path: &Path) -> anyhow::Result<Self> {
+        let db = sled::open(path)?;
+        let examples = db.open_tree("examples")?;
+        Ok(Self { db, examples })
+    }
+
+    pub async fn get(&self, key: &str) -> Option<String> {
+        match self.db.get(key.as_bytes()) {
+            Ok(value) => value.and_then(|bytes| String::from_utf8(bytes.to_vec()).ok()),
+            Err(e) => {
+                tracing::warn!("Failed to read '{}' from persistent cache: {}", key, e);
+                None
+            }
+        }
+    }
+
+    pub async fn set(&self, key: String, value: String) {
+        if let Err(e) = self.db.insert(key.as_bytes(), value.as_bytes()) {
+            tracing::warn!("Failed to write '{}' to persistent cache: {}", key, e);
+            return;
+        }
+        self.flush().await;
+    }
+
+    pub async fn get_examples(&self, key: &str) -> Option<Vec<CodeExample>> {
+        match self.examples.get(key.as_bytes()) {
+            Ok(value) => value.and_then(|bytes| serde_json::from_slice(&bytes).ok()),
+            Err(e) => {
+                tracing::warn!("Failed to read examples '{}' from persistent cache: {}", key, e);
+                None
+            }
+        }
+    }
+
+    pub async fn set_examples(&self, key: String, examples: Vec<CodeExample>) {
+        let value = match serde_json::to_vec(&examples) {
+            Ok(value) => value,
+            Err(e) => {
+                tracing::warn!("Failed to serialize examples '{}': {}", key, e);
+                return;
+            }
+        };
+        if let Err(e) = self.examples.insert(key.as_bytes(), value) {
+            tracing::warn!("Failed to write examples '{}' to persistent cache: {}", key, e);
+            return;
+        }
+        self.flush().await;
+    }
+
+    pub async fn contains(&self, key: &str) -> bool {
+        self.db.contains_key(key.as_bytes()).unwrap_or(false)
+    }
+
+    pub async fn contains_examples(&self, key: &str) -> bool {
+        self.examples.contains_key(key.as_bytes()).unwrap_or(false)
+    }
+
+    pub async fn remove(&self, key: &str) -> Option<String> {
+        let removed = match self.db.remove(key.as_bytes()) {
+            Ok(value) => value.and_then(|bytes| String::from_utf8(bytes.to_vec()).ok()),
+            Err(e) => {
+                tracing::warn!("Failed to remove '{}' from persistent cache: {}", key, e);
+                None
+            }
+        };
+        self.flush().await;
+        removed
+    }
+
+    pub async fn remove_examples(&self, key: &str) -> Option<Vec<CodeExample>> {
+        let removed = match self.examples.remove(key.as_bytes()) {
+            Ok(value) => value.and_then(|bytes| serde_json::from_slice(&bytes).ok()),
+            Err(e) => {
+                tracing::warn!("Failed to remove examples '{}' from persistent cache: {}", key, e);
+                None
+            }
+        };
+        self.flush().await;
+        removed
+    }
+
+    /// Remove every entry whose key satisfies `matches`, returning the removed keys
+    /// from the main and examples trees respectively.
+    pub async fn remove_matching(&self, matches: impl Fn(&str) -> bool) -> (Vec<String>, Vec<String>) {
+        let removed = (
+            remove_from_tree(&self.db, &matches),
+            remove_from_tree(&self.examples, &matches),
+        );
+        self.flush().await;
+        removed
+    }
+
+    async fn flush(&self) {
+        if let Err(e) = self.db.flush_async().await {
+            tracing::warn!("Failed to flush persistent cache: {}", e);
+        }
+    }
+}
+
+fn remove_from_tree(tree: &sled::Tree, matches: impl Fn(&str) -> bool) -> Vec<String> {
+    let keys: Vec<String> = tree
+        .iter()
+        .keys()
+        .filter_map(Result::ok)
+        .filter_map(|key| String::from_utf8(key.to_vec()).ok())
+        .filter(|key| matches(key))
+        .collect();
+
+    keys.into_iter()
+        .filter(|key| match tree.remove(key.as_bytes()) {
+            Ok(_) => true,
+            Err(e) => {
+                tracing::warn!("Failed to remove '{}' from persistent cache: {}", key, e);
+                false
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,98 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use super::error::DocError;
+
+/// Consecutive failures after which requests are rejected unless configured otherwise
+pub const DEFAULT_FAILURE_THRESHOLD: u32 = 5;
+
+/// How long requests are rejected once the circuit opens unless configured otherwise
+pub const DEFAULT_RECOVERY_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Whether requests are currently let through
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CircuitState {
+    /// Requests are sent as usual
+    Closed,
+    /// Too many requests failed in a row, so new ones are rejected until `until`
+    Open { until: Instant },
+    /// The recovery timeout has passed and a single probe request is in flight
+    HalfOpen,
+}
+
+/// Stops sending requests for a while after several consecutive failures, so that lookups fail
+/// immediately during an outage instead of each waiting for its own timeout.
+///
+/// Clones share their state.
+#[derive(Clone, Debug)]
+pub struct CircuitBreaker {
+    state: Arc<Mutex<CircuitState>>,
+    consecutive_failures: Arc<AtomicU32>,
+    failure_threshold: u32,
+    recovery_timeout: Duration,
+}
+
+impl Default for CircuitBreaker {
+    fn default() -> Self {
+        Self::new(DEFAULT_FAILURE_THRESHOLD, DEFAULT_RECOVERY_TIMEOUT)
+    }
+}
+
+impl CircuitBreaker {
+    /// Open the circuit after `failure_threshold` consecutive failures and keep it open for
+    /// `recovery_timeout` before probing again
+    pub fn new(failure_threshold: u32, recovery_timeout: Duration) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(CircuitState::Closed)),
+            consecutive_failures: Arc::new(AtomicU32::new(0)),
+            failure_threshold: failure_threshold.max(1),
+            recovery_timeout,
+        }
+    }
+
+    pub fn state(&self) -> CircuitState {
+        *self.state.lock().unwrap()
+    }
+
+    /// Whether a request may be sent now. Once the recovery timeout has passed, the first caller
+    /// is let through as a probe and the others are rejected until its outcome is recorded.
+    pub fn allow_request(&self) -> Result<(), DocError> {
+        let mut state = self.state.lock().unwrap();
+        match *state {
+            CircuitState::Closed => Ok(()),
+            CircuitState::Open { until } => {
+                let now = Instant::now();
+                if now >= until {
+                    *state = CircuitState::HalfOpen;
+                    Ok(())
+                } else {
+                    Err(DocError::ServiceUnavailable {
+                        retry_after: until - now,
+                    })
+                }
+            }
+            CircuitState::HalfOpen => Err(DocError::ServiceUnavailable {
+                retry_after: Duration::ZERO,
+            }),
+        }
+    }
+
+    /// Record a request that reached a working server, closing the circuit
+    pub fn record_success(&self) {
+        self.consecutive_failures.store(0, Ordering::Relaxed);
+        *self.state.lock().unwrap() = CircuitState::Closed;
+    }
+
+    /// Record a request that failed because of the network or the server. Opens the circuit when
+    /// the probe fails or the failure threshold is reached.
+    pub fn record_failure(&self) {
+        let failures = self.consecutive_failures.fetch_add(1, Ordering::Relaxed) + 1;
+        let mut state = self.state.lock().unwrap();
+        if *state == CircuitState::HalfOpen || failures >= self.failure_threshold {
+            *state = CircuitState::Open {
+                until: Instant::now() + self.recovery_timeout,
+            };
+        }
+    }
+}
@@ -0,0 +1,402 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::{Deserialize, Serialize};
+
+/// A published version of a crate, as listed by the crates.io versions API
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PublishedVersion {
+    #[serde(rename(deserialize = "num"))]
+    pub version: String,
+    #[serde(rename(deserialize = "created_at"))]
+    pub published_at: String,
+    pub yanked: bool,
+}
+
+impl PublishedVersion {
+    /// Whether this is a pre-release such as `1.0.0-beta.1`. Build metadata (`+...`) doesn't count.
+    pub fn is_prerelease(&self) -> bool {
+        self.version
+            .split('+')
+            .next()
+            .is_some_and(|version| version.contains('-'))
+    }
+}
+
+// Body of a crates.io `/api/v1/crates/<name>/versions` response
+#[derive(Debug, Deserialize)]
+pub(crate) struct CrateVersionsResponse {
+    pub versions: Vec<PublishedVersion>,
+}
+
+pub(crate) fn crate_versions_url(crates_io_base_url: &str, crate_name: &str) -> String {
+    format!("{}/api/v1/crates/{}/versions", crates_io_base_url, crate_name)
+}
+
+// Sort newest-first and optionally drop pre-releases. crates.io timestamps are RFC 3339 in UTC,
+// so they order correctly as strings.
+pub(crate) fn filter_versions(mut versions: Vec<PublishedVersion>, stable_only: bool) -> Vec<PublishedVersion> {
+    if stable_only {
+        versions.retain(|version| !version.is_prerelease());
+    }
+    versions.sort_by(|a, b| b.published_at.cmp(&a.published_at));
+    versions
+}
+
+// Render versions as a markdown table, showing only the date part of the publish timestamp
+pub(crate) fn format_versions(crate_name: &str, versions: &[PublishedVersion]) -> String {
+    if versions.is_empty() {
+        return format!("No versions of `{}` found.", crate_name);
+    }
+
+    let mut output = format!("# Versions of `{}`\n\n", crate_name);
+    output.push_str("| Version | Published | Yanked |\n|---|---|---|\n");
+    for version in versions {
+        let date = version.published_at.split('T').next().unwrap_or_default();
+        output.push_str(&format!(
+            "| {} | {} | {} |\n",
+            version.version,
+            date,
+            if version.yanked { "yes" } else { "no" }
+        ));
+    }
+    output
+}
+
+pub(crate) fn crate_url(crates_io_base_url: &str, crate_name: &str) -> String {
+    format!("{}/api/v1/crates/{}", crates_io_base_url, crate_name)
+}
+
+// Body of a crates.io `/api/v1/crates/<name>` response
+#[derive(Debug, Deserialize)]
+pub(crate) struct CrateResponse {
+    #[serde(rename = "crate")]
+    pub krate: CrateInfo,
+    #[serde(default)]
+    pub versions: Vec<VersionSummary>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct CrateInfo {
+    pub name: String,
+    pub max_version: String,
+    pub max_stable_version: Option<String>,
+    pub description: Option<String>,
+    pub homepage: Option<String>,
+    pub repository: Option<String>,
+    pub documentation: Option<String>,
+    #[serde(default)]
+    pub keywords: Vec<String>,
+    #[serde(default)]
+    pub categories: Vec<String>,
+    #[serde(default)]
+    pub downloads: u64,
+    pub recent_downloads: Option<u64>,
+}
+
+impl CrateInfo {
+    // The version `latest` refers to: the newest stable release, or the newest release
+    // of any kind for crates that have only published pre-releases
+    pub fn latest_version(&self) -> &str {
+        self.max_stable_version.as_deref().unwrap_or(&self.max_version)
+    }
+}
+
+// The per-version fields of a crate response that aren't part of the crate itself
+#[derive(Debug, Deserialize)]
+pub(crate) struct VersionSummary {
+    pub num: String,
+    pub license: Option<String>,
+}
+
+/// Descriptive information about a crate, as published on crates.io
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CrateMetadata {
+    pub name: String,
+    pub description: Option<String>,
+    /// The latest version, preferring stable releases
+    pub version: String,
+    /// The SPDX license expression of the latest version
+    pub license: Option<String>,
+    pub homepage: Option<String>,
+    pub repository: Option<String>,
+    pub keywords: Vec<String>,
+    pub categories: Vec<String>,
+    pub downloads: u64,
+    /// Downloads in the last 90 days
+    pub recent_downloads: Option<u64>,
+    /// The documentation link from Cargo.toml, or docs.rs when none is set
+    pub documentation_url: String,
+}
+
+impl CrateResponse {
+    pub fn metadata(self) -> CrateMetadata {
+        let version = self.krate.latest_version().to_string();
+        let license = self
+            .versions
+            .into_iter()
+            .find(|summary| summary.num == version)
+            .and_then(|summary| summary.license);
+        let documentation_url = self
+            .krate
+            .documentation
+            .unwrap_or_else(|| format!("https://docs.rs/{}/{}", self.krate.name, version));
+
+        CrateMetadata {
+            name: self.krate.name,
+            description: self.krate.description.map(|description| description.trim().to_string()),
+            version,
+            license,
+            homepage: self.krate.homepage,
+            repository: self.krate.repository,
+            keywords: self.krate.keywords,
+            categories: self.krate.categories,
+            downloads: self.krate.downloads,
+            recent_downloads: self.krate.recent_downloads,
+            documentation_url,
+        }
+    }
+}
+
+/// A dependency declared by a published crate version
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CrateDependency {
+    #[serde(rename(deserialize = "crate_id"))]
+    pub name: String,
+    pub req: String,
+    /// `normal`, `dev` or `build`
+    pub kind: String,
+    pub optional: bool,
+    pub default_features: bool,
+}
+
+// Body of a crates.io `/api/v1/crates/<name>/<version>/dependencies` response
+#[derive(Debug, Deserialize)]
+pub(crate) struct CrateDependenciesResponse {
+    pub dependencies: Vec<CrateDependency>,
+}
+
+pub(crate) fn crate_dependencies_url(crates_io_base_url: &str, crate_name: &str, version: &str) -> String {
+    format!(
+        "{}/api/v1/crates/{}/{}/dependencies",
+        crates_io_base_url, crate_name, version
+    )
+}
+
+// Render dependencies as markdown with one table per dependency kind
+pub(crate) fn format_dependencies(
+    crate_name: &str,
+    version: &str,
+    dependencies: &[CrateDependency],
+) -> String {
+    let mut output = format!("# Dependencies of `{}` {}\n", crate_name, version);
+    for (kind, title) in [
+        ("normal", "Normal dependencies"),
+        ("dev", "Dev dependencies"),
+        ("build", "Build dependencies"),
+    ] {
+        output.push_str(&format!("\n## {}\n\n", title));
+        let mut matching: Vec<&CrateDependency> =
+            dependencies.iter().filter(|dep| dep.kind == kind).collect();
+        if matching.is_empty() {
+            output.push_str("_None_\n");
+            continue;
+        }
+
+        matching.sort_by(|a, b| a.name.cmp(&b.name));
+        output.push_str("| Name | Requirement | Optional | Default features |\n|---|---|---|---|\n");
+        for dep in matching {
+            output.push_str(&format!(
+                "| {} | `{}` | {} | {} |\n",
+                dep.name,
+                dep.req,
+                if dep.optional { "yes" } else { "no" },
+                if dep.default_features { "yes" } else { "no" }
+            ));
+        }
+    }
+    output
+}
+
+/// A crate depending on another, as listed by the crates.io reverse dependencies API
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct ReverseDependency {
+    pub crate_name: String,
+    /// The requirement the dependent puts on the crate, e.g. `^1.0`
+    pub version_requirement: String,
+    /// Downloads of the dependent's version declaring the dependency
+    pub downloads: u64,
+}
+
+// Body of a crates.io `/api/v1/crates/<name>/reverse_dependencies` response. Each dependency
+// points at the version of the dependent crate declaring it, listed in `versions`.
+#[derive(Debug, Deserialize)]
+pub(crate) struct ReverseDependenciesResponse {
+    pub dependencies: Vec<ReverseDependencyEntry>,
+    pub versions: Vec<DependentVersion>,
+    pub meta: ReverseDependenciesMeta,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct ReverseDependencyEntry {
+    pub version_id: u64,
+    pub req: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct DependentVersion {
+    pub id: u64,
+    #[serde(rename = "crate")]
+    pub crate_name: String,
+    pub downloads: u64,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct ReverseDependenciesMeta {
+    pub total: u64,
+}
+
+impl ReverseDependenciesResponse {
+    // The dependents on this page, most downloaded first
+    pub fn dependents(&self) -> Vec<ReverseDependency> {
+        let mut dependents: Vec<ReverseDependency> = self
+            .dependencies
+            .iter()
+            .filter_map(|dependency| {
+                let version = self.versions.iter().find(|version| version.id == dependency.version_id)?;
+                Some(ReverseDependency {
+                    crate_name: version.crate_name.clone(),
+                    version_requirement: dependency.req.clone(),
+                    downloads: version.downloads,
+                })
+            })
+            .collect();
+        dependents.sort_by(|a, b| b.downloads.cmp(&a.downloads).then_with(|| a.crate_name.cmp(&b.crate_name)));
+        dependents
+    }
+}
+
+pub(crate) fn reverse_dependencies_url(crates_io_base_url: &str, crate_name: &str, page: u32, per_page: u32) -> String {
+    format!(
+        "{}/api/v1/crates/{}/reverse_dependencies?page={}&per_page={}",
+        crates_io_base_url, crate_name, page, per_page
+    )
+}
+
+// Render one page of reverse dependencies as a markdown table under the total count
+pub(crate) fn format_reverse_dependencies(
+    crate_name: &str,
+    total: u64,
+    page: u32,
+    per_page: u32,
+    dependents: &[ReverseDependency],
+) -> String {
+    let mut output = format!("# Reverse dependencies of `{}`\n\n", crate_name);
+    if total == 0 {
+        output.push_str(&format!("No crates on crates.io depend on `{}`.\n", crate_name));
+        return output;
+    }
+
+    let pages = total.div_ceil(u64::from(per_page));
+    output.push_str(&format!(
+        "{} crates depend on `{}`. Page {} of {}, most downloaded first:\n\n",
+        total, crate_name, page, pages
+    ));
+    if dependents.is_empty() {
+        output.push_str("_No dependents on this page._\n");
+        return output;
+    }
+    output.push_str("| Crate | Requirement | Downloads |\n|---|---|---|\n");
+    for dependent in dependents {
+        output.push_str(&format!(
+            "| {} | `{}` | {} |\n",
+            dependent.crate_name, dependent.version_requirement, dependent.downloads
+        ));
+    }
+    output
+}
+
+pub(crate) fn crate_version_url(crates_io_base_url: &str, crate_name: &str, version: &str) -> String {
+    format!("{}/api/v1/crates/{}/{}", crates_io_base_url, crate_name, version)
+}
+
+// Body of a crates.io `/api/v1/crates/<name>/<version>` response
+#[derive(Debug, Deserialize)]
+pub(crate) struct CrateVersionResponse {
+    pub version: CrateVersionInfo,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct CrateVersionInfo {
+    #[serde(default)]
+    pub features: BTreeMap<String, Vec<String>>,
+    /// The `rust-version` declared in Cargo.toml
+    pub rust_version: Option<String>,
+    pub edition: Option<String>,
+}
+
+// Describe the MSRV declared by a crate version
+pub(crate) fn format_msrv(info: &CrateVersionInfo) -> String {
+    match (&info.rust_version, &info.edition) {
+        (Some(rust_version), Some(edition)) => {
+            format!("Minimum Rust version: {} (edition {})", rust_version, edition)
+        }
+        (Some(rust_version), None) => format!("Minimum Rust version: {}", rust_version),
+        (None, _) => "MSRV not declared in Cargo.toml".to_string(),
+    }
+}
+
+// Render feature flags as a markdown table, with the `default` feature first
+pub(crate) fn format_features(
+    crate_name: &str,
+    version: &str,
+    features: &BTreeMap<String, Vec<String>>,
+) -> String {
+    let mut output = format!("# Features of `{}` {}\n\n", crate_name, version);
+    if features.is_empty() {
+        output.push_str("This crate version declares no feature flags.\n");
+        return output;
+    }
+
+    output.push_str("| Feature | Enables |\n|---|---|\n");
+    let default = features.get_key_value("default");
+    let others = features.iter().filter(|(name, _)| name.as_str() != "default");
+    for (name, enables) in default.into_iter().chain(others) {
+        let enables = if enables.is_empty() {
+            "_nothing_".to_string()
+        } else {
+            enables
+                .iter()
+                .map(|feature| format!("`{}`", feature))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        let name = if name == "default" {
+            format!("**{}**", name)
+        } else {
+            format!("`{}`", name)
+        };
+        output.push_str(&format!("| {} | {} |\n", name, enables));
+    }
+    output
+}
+
+// The features a crate version has enabled when a dependent asks for `requested`: those, the
+// default ones, and every feature they enable in turn. Features of dependencies (`dep:x`,
+// `x/feature`) are left out.
+pub(crate) fn enabled_features(requested: &[String], features: &BTreeMap<String, Vec<String>>) -> BTreeSet<String> {
+    let mut enabled = BTreeSet::new();
+    let mut pending: Vec<&str> = requested.iter().map(String::as_str).chain(["default"]).collect();
+    while let Some(feature) = pending.pop() {
+        if feature.starts_with("dep:") || feature.contains('/') || !enabled.insert(feature.to_string()) {
+            continue;
+        }
+        if let Some(enables) = features.get(feature) {
+            pending.extend(enables.iter().map(String::as_str));
+        }
+    }
+    enabled
+}
+
+pub(crate) fn crate_readme_url(crates_io_base_url: &str, crate_name: &str, version: &str) -> String {
+    format!("{}/api/v1/crates/{}/{}/readme", crates_io_base_url, crate_name, version)
+}
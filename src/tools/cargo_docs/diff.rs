@@ -0,0 +1,162 @@
+use similar::TextDiff;
+
+// A markdown section: its heading (empty for text before the first heading) and body
+struct Section {
+    heading: String,
+    body: String,
+}
+
+// Heading text of an ATX (`## Title`) heading line
+fn atx_heading(line: &str) -> Option<&str> {
+    let hashes = line.chars().take_while(|c| *c == '#').count();
+    if !(1..=6).contains(&hashes) {
+        return None;
+    }
+    let rest = &line[hashes..];
+    (rest.is_empty() || rest.starts_with(' ')).then(|| rest.trim())
+}
+
+// Whether `line` underlines the previous line as a setext (`Title\n=====`) heading
+fn is_setext_underline(line: &str) -> bool {
+    let line = line.trim();
+    !line.is_empty() && (line.chars().all(|c| c == '=') || line.chars().all(|c| c == '-'))
+}
+
+// Split markdown into sections at ATX and setext headings. Repeated headings are numbered
+// so every section can be matched up by heading alone.
+fn split_sections(markdown: &str) -> Vec<Section> {
+    let lines: Vec<&str> = markdown.lines().collect();
+    let mut sections = vec![Section {
+        heading: String::new(),
+        body: String::new(),
+    }];
+    let mut i = 0;
+
+    while i < lines.len() {
+        let line = lines[i];
+        let heading = if let Some(text) = atx_heading(line) {
+            i += 1;
+            Some(text.to_string())
+        } else if !line.trim().is_empty()
+            && lines.get(i + 1).is_some_and(|next| is_setext_underline(next))
+        {
+            i += 2;
+            Some(line.trim().to_string())
+        } else {
+            None
+        };
+
+        match heading {
+            Some(heading) => {
+                let count = sections.iter().filter(|s| base_heading(&s.heading) == heading).count();
+                let heading = if count == 0 {
+                    heading
+                } else {
+                    format!("{} ({})", heading, count + 1)
+                };
+                sections.push(Section {
+                    heading,
+                    body: String::new(),
+                });
+            }
+            None => {
+                let section = sections.last_mut().expect("sections is never empty");
+                section.body.push_str(line);
+                section.body.push('\n');
+                i += 1;
+            }
+        }
+    }
+    sections
+}
+
+// A heading with the numbering added by `split_sections` removed
+fn base_heading(heading: &str) -> &str {
+    match heading.rfind(" (") {
+        Some(pos) if heading.ends_with(')') && heading[pos + 2..heading.len() - 1].parse::<u32>().is_ok() => {
+            &heading[..pos]
+        }
+        _ => heading,
+    }
+}
+
+fn section_label(heading: &str) -> &str {
+    if heading.is_empty() {
+        "(introduction)"
+    } else {
+        heading
+    }
+}
+
+/// Compare the documentation of two crate versions section by section. Sections are matched
+/// by heading; sections present in both versions whose content differs are shown as a
+/// line-based unified diff.
+pub fn diff_documentation(
+    crate_name: &str,
+    old_version: &str,
+    new_version: &str,
+    old_docs: &str,
+    new_docs: &str,
+) -> String {
+    let old_sections = split_sections(old_docs);
+    let new_sections = split_sections(new_docs);
+    let find = |sections: &[Section], heading: &str| -> Option<usize> {
+        sections.iter().position(|s| s.heading == heading)
+    };
+
+    let added: Vec<&Section> = new_sections
+        .iter()
+        .filter(|s| !s.heading.is_empty() && find(&old_sections, &s.heading).is_none())
+        .collect();
+    let removed: Vec<&Section> = old_sections
+        .iter()
+        .filter(|s| !s.heading.is_empty() && find(&new_sections, &s.heading).is_none())
+        .collect();
+    let changed: Vec<(&Section, &Section)> = old_sections
+        .iter()
+        .filter_map(|old| {
+            let new = &new_sections[find(&new_sections, &old.heading)?];
+            (old.body.trim() != new.body.trim()).then_some((old, new))
+        })
+        .collect();
+
+    let mut output = format!(
+        "# Documentation changes in `{}`: {} → {}\n\n\
+         **Before:** `{}` {}  \n**After:** `{}` {}\n\n\
+         > Note: this is a heuristic diff of the rendered documentation, not a semantic API diff. \
+         Renamed sections show up as removed and added, and changes to items documented on \
+         their own pages are not included.\n",
+        crate_name, old_version, new_version, crate_name, old_version, crate_name, new_version
+    );
+
+    if added.is_empty() && removed.is_empty() && changed.is_empty() {
+        output.push_str("\nNo documentation differences found.\n");
+        return output;
+    }
+
+    for (title, sections) in [("Added sections", &added), ("Removed sections", &removed)] {
+        output.push_str(&format!("\n## {}\n\n", title));
+        if sections.is_empty() {
+            output.push_str("_None_\n");
+        }
+        for section in sections.iter() {
+            output.push_str(&format!("- {}\n", section.heading));
+        }
+    }
+
+    output.push_str("\n## Changed sections\n\n");
+    if changed.is_empty() {
+        output.push_str("_None_\n");
+    }
+    for (old, new) in changed {
+        let diff = TextDiff::from_lines(&old.body, &new.body);
+        output.push_str(&format!(
+            "### {}\n\n```diff\n{}```\n\n",
+            section_label(&old.heading),
+            diff.unified_diff()
+                .context_radius(2)
+                .header(&format!("{} {}", crate_name, old_version), &format!("{} {}", crate_name, new_version))
+        ));
+    }
+    output
+}
@@ -0,0 +1,98 @@
+use chrono::DateTime;
+use serde::Deserialize;
+
+/// Outcome of a docs.rs documentation build
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(from = "RawBuildStatus")]
+pub enum BuildStatus {
+    Success,
+    Failure,
+    InProgress,
+}
+
+// docs.rs reported build status as a boolean before switching to strings
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawBuildStatus {
+    Flag(bool),
+    Text(String),
+}
+
+impl From<RawBuildStatus> for BuildStatus {
+    fn from(raw: RawBuildStatus) -> Self {
+        match raw {
+            RawBuildStatus::Flag(true) => BuildStatus::Success,
+            RawBuildStatus::Flag(false) => BuildStatus::Failure,
+            RawBuildStatus::Text(text) => match text.as_str() {
+                "success" => BuildStatus::Success,
+                "in_progress" | "queued" => BuildStatus::InProgress,
+                _ => BuildStatus::Failure,
+            },
+        }
+    }
+}
+
+/// A single entry of docs.rs's `builds.json`
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+pub struct DocsRsBuild {
+    pub id: u64,
+    pub rustc_version: Option<String>,
+    pub docsrs_version: Option<String>,
+    pub build_status: BuildStatus,
+    /// When the build finished, as an RFC 3339 timestamp
+    pub build_time: Option<String>,
+    #[serde(default)]
+    pub build_started: Option<String>,
+}
+
+impl DocsRsBuild {
+    /// Build duration in seconds, when docs.rs reports both start and finish times
+    pub fn duration_secs(&self) -> Option<i64> {
+        let started = DateTime::parse_from_rfc3339(self.build_started.as_deref()?).ok()?;
+        let finished = DateTime::parse_from_rfc3339(self.build_time.as_deref()?).ok()?;
+        Some((finished - started).num_seconds())
+    }
+}
+
+pub(crate) fn builds_url(base_url: &str, crate_name: &str, version: &str) -> String {
+    format!("{}/crate/{}/{}/builds.json", base_url, crate_name, version)
+}
+
+// Summarize the most recent build as markdown, linking the build log when it failed
+pub(crate) fn format_build_status(crate_name: &str, version: &str, builds: &[DocsRsBuild]) -> String {
+    // docs.rs lists builds newest first, but don't rely on it
+    let Some(build) = builds.iter().max_by_key(|build| build.id) else {
+        return format!("No docs.rs builds found for {} {}.", crate_name, version);
+    };
+
+    let status = match build.build_status {
+        BuildStatus::Success => "success",
+        BuildStatus::Failure => "failure",
+        BuildStatus::InProgress => "in progress",
+    };
+
+    let mut output = format!("# docs.rs build status for `{}` {}\n\n", crate_name, version);
+    output.push_str(&format!("- Status: {}\n", status));
+    output.push_str(&format!(
+        "- Toolchain: {}\n",
+        build.rustc_version.as_deref().unwrap_or("unknown")
+    ));
+    if let Some(docsrs_version) = &build.docsrs_version {
+        output.push_str(&format!("- docs.rs: {}\n", docsrs_version));
+    }
+    match build.duration_secs() {
+        Some(secs) => output.push_str(&format!("- Duration: {}m {}s\n", secs / 60, secs % 60)),
+        None => output.push_str("- Duration: unknown\n"),
+    }
+    if let Some(finished) = &build.build_time {
+        output.push_str(&format!("- Finished: {}\n", finished));
+    }
+    if build.build_status == BuildStatus::Failure {
+        output.push_str(&format!(
+            "- Build log: https://docs.rs/crate/{}/{}/builds/{}\n\n\
+             The documentation for this version failed to build, so item lookups for it will not find any pages.\n",
+            crate_name, version, build.id
+        ));
+    }
+    output
+}
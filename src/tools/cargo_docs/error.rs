@@ -0,0 +1,186 @@
+use std::fmt;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use chrono::DateTime;
+use reqwest::header::RETRY_AFTER;
+use reqwest::{Response, StatusCode};
+use rmcp::Error as McpError;
+
+/// Why a documentation lookup failed
+#[derive(Debug)]
+pub enum DocError {
+    /// The request could not be sent or its response could not be read
+    Network(reqwest::Error),
+    /// A response arrived but could not be understood
+    Parse(String),
+    /// There is no documentation for the crate or item; `tried_urls` lists the pages requested
+    NotFound {
+        crate_name: String,
+        item_path: Option<String>,
+        tried_urls: Vec<String>,
+    },
+    /// The item isn't at the path given, and the crate has several items of its name
+    AmbiguousItem {
+        crate_name: String,
+        item_path: String,
+        candidates: Vec<String>,
+    },
+    /// docs.rs or crates.io asked us to slow down
+    RateLimited { retry_after: Option<Duration> },
+    /// The tool was called with arguments that can't name anything
+    InvalidInput(String),
+    /// Any other unsuccessful HTTP status
+    Http { status: u16, url: String },
+    /// docs.rs has no documentation for the crate version because its build failed
+    BuildFailed { url: String },
+    /// Recent requests kept failing, so this one wasn't sent; see `CircuitBreaker`
+    ServiceUnavailable { retry_after: Duration },
+}
+
+impl fmt::Display for DocError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DocError::Network(e) => write!(f, "Request failed: {}", e),
+            DocError::Parse(message) => write!(f, "Failed to parse response: {}", message),
+            DocError::NotFound {
+                crate_name,
+                item_path,
+                tried_urls,
+            } => {
+                match item_path {
+                    Some(item_path) => write!(f, "No documentation found for `{}` in {}", item_path, crate_name)?,
+                    None => write!(f, "No documentation found for crate {}", crate_name)?,
+                }
+                if !tried_urls.is_empty() {
+                    write!(f, " (tried {})", tried_urls.join(", "))?;
+                }
+                Ok(())
+            }
+            DocError::AmbiguousItem {
+                crate_name,
+                item_path,
+                candidates,
+            } => write!(
+                f,
+                "No documentation found for `{}` in {}; items of that name are at {}",
+                item_path,
+                crate_name,
+                candidates
+                    .iter()
+                    .map(|candidate| format!("`{}`", candidate))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            DocError::RateLimited { retry_after } => match retry_after {
+                Some(delay) => write!(f, "Rate limited, retry after {} seconds", delay.as_secs()),
+                None => write!(f, "Rate limited, retry later"),
+            },
+            DocError::InvalidInput(message) => write!(f, "Invalid input: {}", message),
+            DocError::Http { status, url } => write!(f, "Request to {} failed with status {}", url, status),
+            DocError::BuildFailed { url } => write!(
+                f,
+                "The docs.rs build failed, so no documentation is available (build log: {})",
+                url
+            ),
+            DocError::ServiceUnavailable { retry_after } if retry_after.is_zero() => write!(
+                f,
+                "Service unavailable after repeated failures, retry shortly"
+            ),
+            DocError::ServiceUnavailable { retry_after } => write!(
+                f,
+                "Service unavailable after repeated failures, retry in {} seconds",
+                retry_after.as_secs().max(1)
+            ),
+        }
+    }
+}
+
+impl std::error::Error for DocError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            DocError::Network(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for DocError {
+    fn from(e: reqwest::Error) -> Self {
+        DocError::Network(e)
+    }
+}
+
+impl From<serde_json::Error> for DocError {
+    fn from(e: serde_json::Error) -> Self {
+        DocError::Parse(e.to_string())
+    }
+}
+
+impl From<DocError> for McpError {
+    fn from(e: DocError) -> Self {
+        match e {
+            DocError::NotFound { .. } | DocError::AmbiguousItem { .. } | DocError::Http { status: 404, .. } => {
+                McpError::resource_not_found(e.to_string(), None)
+            }
+            DocError::InvalidInput(_) => McpError::invalid_params(e.to_string(), None),
+            _ => McpError::internal_error(e.to_string(), None),
+        }
+    }
+}
+
+impl DocError {
+    /// The error for an unsuccessful response to a request for `url`
+    pub(crate) fn from_response(response: &Response, url: &str) -> Self {
+        if response.status() == StatusCode::TOO_MANY_REQUESTS {
+            DocError::RateLimited {
+                retry_after: retry_after(response),
+            }
+        } else {
+            DocError::Http {
+                status: response.status().as_u16(),
+                url: url.to_string(),
+            }
+        }
+    }
+
+    /// Report a missing page as the crate or item it was fetched for not existing
+    pub(crate) fn or_not_found(self, crate_name: &str, item_path: Option<&str>) -> Self {
+        match self {
+            DocError::Http { status: 404, url } => DocError::NotFound {
+                crate_name: crate_name.to_string(),
+                item_path: item_path.map(str::to_string),
+                tried_urls: vec![url],
+            },
+            e => e,
+        }
+    }
+
+    /// Whether the error means the requested page doesn't exist
+    pub(crate) fn is_not_found(&self) -> bool {
+        matches!(self, DocError::NotFound { .. } | DocError::Http { status: 404, .. })
+    }
+}
+
+/// How long a response's `Retry-After` header asks us to wait, if it has a valid one
+pub(crate) fn retry_after(response: &Response) -> Option<Duration> {
+    let value = response.headers().get(RETRY_AFTER)?.to_str().ok()?;
+    parse_retry_after(value, SystemTime::now())
+}
+
+/// Parse a `Retry-After` value, either a number of seconds or an HTTP date, relative to `now`.
+/// A date in the past means no wait.
+pub fn parse_retry_after(value: &str, now: SystemTime) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    let date = DateTime::parse_from_rfc2822(value).ok()?;
+    let at = UNIX_EPOCH + Duration::from_secs(u64::try_from(date.timestamp()).ok()?);
+    Some(at.duration_since(now).unwrap_or(Duration::ZERO))
+}
+
+/// Parse a JSON response body, naming what it should have contained when it doesn't
+pub(crate) fn parse_json<T: serde::de::DeserializeOwned>(body: &str, what: &str) -> Result<T, DocError> {
+    serde_json::from_str(body).map_err(|e| DocError::Parse(format!("invalid {}: {}", what, e)))
+}
@@ -0,0 +1,194 @@
+use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
+
+use super::cache::CodeExample;
+use super::types::ItemPath;
+use super::markdown_utils::{extract_code_blocks, extract_section, plain_text, NON_RUNNABLE_ATTRIBUTES};
+
+/// Edition the Rust Playground links of `lookup_item_examples` compile with unless asked otherwise
+pub const DEFAULT_PLAYGROUND_EDITION: &str = "2021";
+
+/// Editions the Rust Playground supports
+pub const PLAYGROUND_EDITIONS: &[&str] = &["2015", "2018", "2021", "2024"];
+
+/// Code examples of an item's markdown documentation: the code blocks of its `Examples`
+/// section, or of the whole page when that section has none. Each example is described by
+/// the paragraph preceding its code block.
+pub fn extract_examples(doc: &str) -> Vec<CodeExample> {
+    if let Some(section) = extract_section(doc, "Examples", false) {
+        let examples = code_blocks(&section);
+        if !examples.is_empty() {
+            return examples;
+        }
+    }
+    code_blocks(doc)
+}
+
+// Fenced code blocks of `markdown`, each with the paragraph before it
+fn code_blocks(markdown: &str) -> Vec<CodeExample> {
+    let lines: Vec<&str> = markdown.lines().collect();
+    extract_code_blocks(markdown)
+        .into_iter()
+        .enumerate()
+        .map(|(index, block)| CodeExample {
+            title: format!("Example {}", index + 1),
+            code: block.content,
+            description: plain_text(&preceding_paragraph(&lines[..block.start_line]).join(" ")),
+            fence_info: block.fence_annotation,
+            generated: false,
+        })
+        .collect()
+}
+
+// The paragraph ending at the last of `lines`, blank lines aside; empty when a heading or a code
+// block comes right before
+fn preceding_paragraph<'a>(lines: &[&'a str]) -> Vec<&'a str> {
+    let mut paragraph = Vec::new();
+    for line in lines.iter().rev().map(|line| line.trim()).skip_while(|line| line.is_empty()) {
+        // Headings aren't descriptions: ATX ones, and setext underlines with their title
+        if line.is_empty() || line.starts_with('#') || line.starts_with("```") || is_setext_underline(line) {
+            break;
+        }
+        paragraph.push(line);
+    }
+    paragraph.reverse();
+    paragraph
+}
+
+fn is_setext_underline(line: &str) -> bool {
+    line.len() >= 3 && (line.chars().all(|c| c == '=') || line.chars().all(|c| c == '-'))
+}
+
+/// A template showing the typical use of an item whose documentation has no code examples,
+/// based on the kind of item the documentation mentions.
+pub fn generated_example(crate_name: &str, item_path: &ItemPath, doc: &str) -> CodeExample {
+    let item_name = item_path.item_name();
+    let doc = doc.to_lowercase();
+    let mentions = |kind: &str| doc.contains(kind) && doc.contains(&item_name.to_lowercase());
+    let import = format!("use {}::{};\n\n", crate_name, item_path);
+
+    let (title, code, description) = if mentions("struct") {
+        (
+            format!("Creating and using a {} instance", item_name),
+            format!(
+                "{}// Create a new {name} instance\nlet instance = {name}::new();\n\n// Use methods on the {name} instance\n// instance.some_method();",
+                import,
+                name = item_name
+            ),
+            "This is a generated example. Check the actual documentation for the correct method names and usage patterns.",
+        )
+    } else if mentions("trait") {
+        (
+            format!("Implementing the {} trait", item_name),
+            format!(
+                "{}struct MyType;\n\nimpl {} for MyType {{\n    // Implement the required trait methods here\n}}",
+                import, item_name
+            ),
+            "This is a generated example. Check the actual documentation for the required trait methods.",
+        )
+    } else if mentions("enum") {
+        (
+            format!("Using the {} enum", item_name),
+            format!(
+                "{}// Match on {name} variants\nlet value = {name}::Variant;\n\nmatch value {{\n    {name}::Variant => {{}},\n    // Match other variants...\n}}",
+                import,
+                name = item_name
+            ),
+            "This is a generated example. Check the actual documentation for the correct enum variants.",
+        )
+    } else if mentions("fn") {
+        (
+            format!("Calling the {} function", item_name),
+            format!("{}// Call the function\nlet result = {}();", import, item_name),
+            "This is a generated example. Check the actual documentation for the correct function parameters.",
+        )
+    } else {
+        (
+            "Generic Example".to_string(),
+            format!("// Example for using {}\n{}// Add your usage code here", item_path, import),
+            "No specific examples were found in the documentation. Please refer to the main documentation for usage information.",
+        )
+    };
+
+    CodeExample {
+        title,
+        code,
+        description: description.to_string(),
+        fence_info: "rust".to_string(),
+        generated: true,
+    }
+}
+
+// Whether the example's fence carries the doc-test attribute `attribute`, as in `rust,no_run`
+fn has_attribute(example: &CodeExample, attribute: &str) -> bool {
+    example
+        .fence_info
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .any(|part| part == attribute)
+}
+
+/// Rust Playground URL that opens `code` compiled with `edition`
+pub fn playground_url(code: &str, edition: &str) -> String {
+    format!(
+        "https://play.rust-lang.org/?version=stable&edition={}&code={}",
+        edition,
+        utf8_percent_encode(code, NON_ALPHANUMERIC)
+    )
+}
+
+// The program the Playground runs for an example: like rustdoc, code without a `fn main` is
+// wrapped in one, after the crate attributes (`#![...]`) it starts with
+fn playground_code(code: &str) -> String {
+    if code.contains("fn main") {
+        return code.to_string();
+    }
+    let lines: Vec<&str> = code.lines().collect();
+    let attributes = lines.iter().take_while(|line| line.trim_start().starts_with("#![")).count();
+    let body = lines[attributes..]
+        .iter()
+        .map(|line| if line.is_empty() { String::new() } else { format!("    {}", line) });
+    lines[..attributes]
+        .iter()
+        .map(|line| line.to_string())
+        .chain(std::iter::once("fn main() {".to_string()))
+        .chain(body)
+        .chain(std::iter::once("}".to_string()))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+impl CodeExample {
+    /// Render the example as a `##` section: its description, code, a link running it on the
+    /// Rust Playground with `playground_edition` unless it is generated, and notes on doc-test
+    /// attributes
+    pub fn to_markdown(&self, playground_edition: &str) -> String {
+        let mut markdown = format!("## {}\n\n", self.title);
+        if !self.description.is_empty() {
+            markdown.push_str(&format!("{}\n\n", self.description));
+        }
+        markdown.push_str(&format!("```rust\n{}\n```\n", self.code));
+        // Generated templates are placeholders that don't compile
+        if !self.generated {
+            markdown.push_str(&format!(
+                "[▶ Run in Playground]({})\n",
+                playground_url(&playground_code(&self.code), playground_edition)
+            ));
+        }
+        if NON_RUNNABLE_ATTRIBUTES.iter().any(|attribute| has_attribute(self, attribute)) {
+            markdown.push_str("\n_Note: This example is marked as non-runnable in the official documentation._\n");
+        }
+        if has_attribute(self, "compile_fail") {
+            markdown.push_str("\n_Expected to fail compilation._\n");
+        }
+        markdown
+    }
+}
+
+/// Render examples as the markdown returned by `lookup_item_examples`
+pub fn format_examples(examples: &[CodeExample], playground_edition: &str) -> String {
+    let mut markdown = String::from("# Usage Examples\n");
+    for example in examples {
+        markdown.push('\n');
+        markdown.push_str(&example.to_markdown(playground_edition));
+    }
+    markdown
+}
@@ -0,0 +1,985 @@
+use std::collections::HashMap;
+
+use html2md::{
+    parse_html_custom, Handle, NodeData, StructuredPrinter, TagHandler, TagHandlerFactory,
+};
+use reqwest::Url;
+use scraper::{CaseSensitivity, ElementRef, Html, Selector};
+use serde::{Deserialize, Serialize};
+
+use super::markdown_utils::plain_text;
+use super::traits::{impl_header_parts, object_safety_violations, trait_name};
+
+/// A public item listed on a module's documentation page
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct ModuleItem {
+    pub name: String,
+    /// The rustdoc item kind, e.g. `struct`, `fn`, `mod` or `macro`
+    pub kind: String,
+    /// The item's one-line summary, empty when it has none
+    pub brief: String,
+}
+
+fn selector(css: &str) -> Selector {
+    Selector::parse(css).expect("hard-coded selectors are valid")
+}
+
+// Collapse runs of whitespace, as rendered text would
+fn element_text(element: ElementRef) -> String {
+    element
+        .text()
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Extract the items listed in the `item-table` sections of a rustdoc module page.
+///
+/// Current rustdoc renders each table as `<dl class="item-table">` with a `<dt>` link and a `<dd>`
+/// summary per item; older versions used `<div class="item-row">` (or `<li>`) rows with the
+/// summary in an `item-right` or `desc` block. Both layouts are handled.
+pub fn parse_module_items(html: &str) -> Vec<ModuleItem> {
+    let document = Html::parse_document(html);
+    let tables = selector(".item-table");
+    let link = selector("a");
+    let legacy_brief = selector(".item-right, .desc, .docblock-short");
+
+    let mut items = Vec::new();
+    for table in document.select(&tables) {
+        for row in table.child_elements() {
+            let brief = match row.value().name() {
+                "dt" => row
+                    .next_siblings()
+                    .filter_map(ElementRef::wrap)
+                    .next()
+                    .filter(|sibling| sibling.value().name() == "dd")
+                    .map(element_text),
+                "dd" => continue,
+                _ => row.select(&legacy_brief).next().map(element_text),
+            };
+
+            let Some(anchor) = row.select(&link).next() else {
+                continue;
+            };
+            let Some(kind) = anchor.value().classes().next() else {
+                continue;
+            };
+            items.push(ModuleItem {
+                name: element_text(anchor),
+                kind: kind.to_string(),
+                brief: brief.unwrap_or_default(),
+            });
+        }
+    }
+    items
+}
+
+/// An item listed on a crate's `all.html` index of items
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexedItem {
+    /// Path of the item within its crate, e.g. `sync::Mutex`
+    pub path: String,
+    /// The rustdoc item kind, e.g. `struct`, `fn` or `macro`
+    pub kind: String,
+}
+
+/// Extract the items of a rustdoc `all.html` page. Each is a link to the item's page, such as
+/// `sync/struct.Mutex.html`, whose file name gives its kind and whose directories its module.
+pub fn parse_all_items(html: &str) -> Vec<IndexedItem> {
+    let document = Html::parse_document(html);
+    let mut items = Vec::new();
+    for anchor in document.select(&selector("ul a[href]")) {
+        let href = anchor.value().attr("href").unwrap_or_default();
+        if href.contains(':') || href.starts_with('/') || href.contains('#') {
+            continue;
+        }
+        let (module, page) = href.rsplit_once('/').unwrap_or(("", href));
+        let mut parts = page.splitn(3, '.');
+        let (Some(kind), Some(name), Some("html")) = (parts.next(), parts.next(), parts.next()) else {
+            continue;
+        };
+        let path = module.split('/').filter(|segment| !segment.is_empty()).chain([name]);
+        items.push(IndexedItem {
+            path: path.collect::<Vec<_>>().join("::"),
+            kind: kind.to_string(),
+        });
+    }
+    items
+}
+
+// Tracks where the text of a trait declaration has got to, so that only trait links in the
+// supertrait bounds (`pub trait Error: Debug + Display {`) are collected
+#[derive(Default)]
+struct DeclarationScanner {
+    in_bounds: bool,
+    done: bool,
+    depth: i32,
+    text: String,
+    supertraits: Vec<String>,
+}
+
+impl DeclarationScanner {
+    fn push_text(&mut self, text: &str) {
+        let mut chars = text.chars().peekable();
+        while let Some(c) = chars.next() {
+            if self.done {
+                return;
+            }
+            match c {
+                '<' | '(' => self.depth += 1,
+                '>' if self.text.ends_with('-') => {}
+                '>' | ')' => self.depth -= 1,
+                // `::` in a path, not the start of the bounds
+                ':' if chars.peek() == Some(&':') || self.text.ends_with(':') => {}
+                ':' if self.depth == 0 => self.in_bounds = true,
+                '{' | ';' if self.depth == 0 => self.done = true,
+                _ => {}
+            }
+            self.text.push(c);
+            if self.depth == 0 && self.text.ends_with("where") {
+                let before = &self.text[..self.text.len() - "where".len()];
+                self.done = before.ends_with(char::is_whitespace);
+            }
+        }
+    }
+
+    fn walk(&mut self, element: ElementRef) {
+        for child in element.children() {
+            if let Some(text) = child.value().as_text() {
+                self.push_text(text);
+            } else if let Some(child) = ElementRef::wrap(child) {
+                let is_trait_link = child.value().name() == "a"
+                    && child.value().has_class("trait", CaseSensitivity::CaseSensitive);
+                if is_trait_link && self.in_bounds && !self.done && self.depth == 0 {
+                    let path = child
+                        .value()
+                        .attr("title")
+                        .and_then(|title| title.strip_prefix("trait "));
+                    self.supertraits.extend(path.map(str::to_string));
+                }
+                self.walk(child);
+            }
+        }
+    }
+}
+
+/// Full paths (e.g. `core::fmt::Debug`) of the supertraits in the declaration on a rustdoc trait
+/// page, taken from the links' `title` attributes. Returns `None` when the page has no trait
+/// declaration.
+pub fn parse_supertraits(html: &str) -> Option<Vec<String>> {
+    let document = Html::parse_document(html);
+    let declaration = document
+        .select(&selector("pre.item-decl, .item-decl pre, pre.rust.trait"))
+        .next()?;
+    let text = element_text(declaration);
+    if !text.contains("trait ") {
+        return None;
+    }
+
+    let mut scanner = DeclarationScanner::default();
+    scanner.walk(declaration);
+    Some(scanner.supertraits)
+}
+
+/// Whether a trait is dyn compatible, formerly called object safe, and why not
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ObjectSafety {
+    pub object_safe: bool,
+    /// The reasons read from the declaration, e.g. ``the method `clone` returns `Self` ``
+    pub violations: Vec<String>,
+}
+
+// First rustdoc release known to mark the traits that aren't dyn compatible
+const DYN_COMPATIBILITY_SINCE: (u64, u64) = (1, 78);
+
+/// Whether the trait a rustdoc page documents is dyn compatible. rustdoc gives the traits that
+/// aren't a `Dyn Compatibility` (earlier `Object Safety`) section without saying why, so the
+/// reasons are read from the declaration; for pages of older rustdoc releases, which have no
+/// such section, the declaration decides. Returns `None` when the page has no trait declaration.
+pub fn parse_object_safety(html: &str) -> Option<ObjectSafety> {
+    let document = Html::parse_document(html);
+    let declaration = document
+        .select(&selector("pre.item-decl, .item-decl pre, pre.rust.trait"))
+        .next()?;
+    // Long declarations are folded behind a `Show 75 methods` summary
+    let text: String = declaration
+        .descendants()
+        .filter(|node| {
+            !node
+                .ancestors()
+                .any(|ancestor| ancestor.value().as_element().is_some_and(|element| element.name() == "summary"))
+        })
+        .filter_map(|node| node.value().as_text().map(|text| text.to_string()))
+        .collect();
+    if !text.contains("trait ") {
+        return None;
+    }
+    let violations = object_safety_violations(&text);
+
+    let marked = document
+        .select(&selector("#dyn-compatibility, #object-safety"))
+        .next()
+        .is_some();
+    let rustdoc_version = document
+        .select(&selector("#rustdoc-vars"))
+        .next()
+        .and_then(|vars| vars.value().attr("data-rustdoc-version"))
+        .and_then(|version| {
+            let mut numbers = version.split_whitespace().next()?.split('.').map(|number| number.parse::<u64>().ok());
+            Some((numbers.next()??, numbers.next()??))
+        });
+    let object_safe = match rustdoc_version {
+        Some(version) if version < DYN_COMPATIBILITY_SINCE && !marked => violations.is_empty(),
+        _ => !marked,
+    };
+    Some(ObjectSafety {
+        object_safe,
+        violations: if object_safe { Vec::new() } else { violations },
+    })
+}
+
+/// Whether a type implements the `Send` and `Sync` auto traits, as listed among the trait
+/// implementations and auto trait implementations of its rustdoc page. `None` when the page
+/// lists no impl either way.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct ThreadSafety {
+    pub send: Option<bool>,
+    pub sync: Option<bool>,
+}
+
+/// Read `impl Send for T` and `impl Sync for T` (or `!Send`/`!Sync`) from a rustdoc type page
+pub fn parse_thread_safety(html: &str) -> ThreadSafety {
+    let document = Html::parse_document(html);
+    let headers = selector(
+        "#trait-implementations-list .impl .code-header, #synthetic-implementations-list .impl .code-header",
+    );
+
+    let mut safety = ThreadSafety::default();
+    for header in document.select(&headers) {
+        let text = element_text(header);
+        let Some((trait_path, _)) = impl_header_parts(&text) else {
+            continue;
+        };
+        let (implemented, trait_path) = match trait_path.strip_prefix('!') {
+            Some(negated) => (false, negated),
+            None => (true, trait_path),
+        };
+        match trait_name(trait_path) {
+            "Send" => safety.send = Some(implemented),
+            "Sync" => safety.sync = Some(implemented),
+            _ => {}
+        }
+    }
+    safety
+}
+
+/// A method listed on a rustdoc trait page
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct TraitMethod {
+    /// The method's signature as rendered by rustdoc, e.g. `fn fmt(&self, f: &mut Formatter<'_>) -> Result`
+    pub signature: String,
+    /// The first paragraph of the method's documentation, empty when it has none
+    pub summary: String,
+}
+
+// The methods in the `div.methods` block following the section heading with id `heading_id`
+fn methods_after_heading(document: &Html, heading_id: &str) -> Vec<TraitMethod> {
+    let Some(heading) = document.select(&selector(&format!("#{}", heading_id))).next() else {
+        return Vec::new();
+    };
+    let Some(methods) = heading
+        .next_siblings()
+        .filter_map(ElementRef::wrap)
+        .next()
+        .filter(|block| block.value().has_class("methods", CaseSensitivity::CaseSensitive))
+    else {
+        return Vec::new();
+    };
+
+    let signature = selector(".code-header");
+    let summary = selector(".docblock p");
+    let mut result = Vec::new();
+    // Each method is a `details` toggle holding the signature and its docs; methods without
+    // docs are rendered as a bare `section`
+    for method in methods.child_elements() {
+        let Some(header) = method.select(&signature).next() else {
+            continue;
+        };
+        result.push(TraitMethod {
+            signature: element_text(header),
+            summary: method.select(&summary).next().map(element_text).unwrap_or_default(),
+        });
+    }
+    result
+}
+
+/// The documentation of method `name` on a rustdoc type or trait page, as a page of its own
+/// holding just the method's signature and docs, along with the method's anchor on the page
+/// (`method.push`, or `tymethod.read` for required trait methods)
+pub fn extract_method(html: &str, name: &str) -> Option<(String, String)> {
+    let document = Html::parse_document(html);
+    let anchors = [format!("method.{}", name), format!("tymethod.{}", name)];
+    let header = document
+        .select(&selector("section[id]"))
+        .find(|section| anchors.iter().any(|anchor| section.value().id() == Some(anchor)))?;
+    let anchor = header.value().id()?.to_string();
+    // Documented methods are a `details` toggle around the header and docs
+    let method = header
+        .parent()
+        .and_then(|summary| summary.parent())
+        .and_then(ElementRef::wrap)
+        .filter(|details| details.value().name() == "details")
+        .unwrap_or(header);
+    Some((anchor, format!("<section id=\"main-content\">{}</section>", method.html())))
+}
+
+// Selector for an element of the header of a method extracted by `extract_method`
+fn method_header_selector(css: &str) -> String {
+    format!(
+        "#main-content > details > summary > .method > {css}, #main-content > .method > {css}",
+        css = css
+    )
+}
+
+/// URL of the source code of the item a rustdoc page documents, from the `Source` link of its
+/// heading, resolved against `page_url`
+pub fn parse_source_url(html: &str, page_url: &str) -> Option<String> {
+    let document = Html::parse_document(html);
+    let href = document
+        .select(&selector(&format!(
+            ".main-heading a.src, .main-heading a.srclink, .out-of-band a.srclink, {}",
+            method_header_selector("a.src")
+        )))
+        .next()?
+        .value()
+        .attr("href")?;
+    match Url::parse(page_url) {
+        Ok(base) => base.join(href).ok().map(String::from),
+        Err(_) => Some(href.to_string()),
+    }
+}
+
+/// Rust version an item of the standard library has been stable since, as shown in its heading
+pub fn parse_since(html: &str) -> Option<String> {
+    let document = Html::parse_document(html);
+    let since = document
+        .select(&selector(&format!(
+            ".main-heading .since, .out-of-band .since, {}",
+            method_header_selector(".since")
+        )))
+        .next()
+        .map(element_text)?;
+    Some(since.trim_start_matches("since").trim().to_string()).filter(|since| !since.is_empty())
+}
+
+/// The deprecation notice of the item a rustdoc page documents, without its `Deprecated`
+/// label, e.g. ``since 1.38.0: the `Once::new()` function is now preferred``; empty when the
+/// notice gives neither a version nor a reason. Notices of methods and impls on the page are
+/// ignored.
+pub fn parse_deprecation(html: &str) -> Option<String> {
+    let document = Html::parse_document(html);
+    let notice = document
+        .select(&selector(
+            "#main-content > .item-info .deprecated, #main-content > details > .item-info .deprecated",
+        ))
+        .next()?;
+    let text = plain_text(&html_to_markdown(&notice.inner_html()));
+    let text = text.trim_start_matches(|c: char| !c.is_alphanumeric()).trim();
+    Some(text.strip_prefix("Deprecated").unwrap_or(text).trim().to_string())
+}
+
+/// The `cfg` condition the item a rustdoc page documents is available under, from its
+/// portability note, e.g. `Unix` for "Available on Unix only." or ``crate feature `fs` ``
+pub fn parse_portability(html: &str) -> Option<String> {
+    let document = Html::parse_document(html);
+    let note = document
+        .select(&selector(
+            "#main-content > .item-info .portability, #main-content > details > .item-info .portability",
+        ))
+        .next()?;
+    let text = plain_text(&html_to_markdown(&note.inner_html())).replace("**", "");
+    let text = text.trim_end_matches('.');
+    let condition = text.strip_prefix("Available on ").unwrap_or(text);
+    let condition = condition.strip_suffix(" only").unwrap_or(condition).trim();
+    Some(condition.to_string()).filter(|condition| !condition.is_empty())
+}
+
+/// First paragraph of an item page's documentation as plain text, empty when it has none
+pub fn parse_summary(html: &str) -> String {
+    let document = Html::parse_document(html);
+    [".top-doc .docblock > p", "#main-content > .docblock > p", "p"]
+        .iter()
+        .find_map(|css| document.select(&selector(css)).next())
+        .map(element_text)
+        .unwrap_or_default()
+}
+
+/// Extract the Required Methods and Provided Methods sections of a rustdoc trait page
+pub fn parse_trait_methods(html: &str) -> (Vec<TraitMethod>, Vec<TraitMethod>) {
+    let document = Html::parse_document(html);
+    (
+        methods_after_heading(&document, "required-methods"),
+        methods_after_heading(&document, "provided-methods"),
+    )
+}
+
+// Render trait methods as the labelled overview put above a trait's full documentation
+pub(crate) fn format_trait_methods(required: &[TraitMethod], provided: &[TraitMethod]) -> String {
+    let mut output = String::new();
+    for (title, methods) in [("Required Methods", required), ("Provided Methods", provided)] {
+        output.push_str(&format!("## {}\n\n", title));
+        if methods.is_empty() {
+            output.push_str("_None_\n");
+        }
+        for method in methods {
+            if method.summary.is_empty() {
+                output.push_str(&format!("- `{}`\n", method.signature));
+            } else {
+                output.push_str(&format!("- `{}`: {}\n", method.signature, method.summary));
+            }
+        }
+        output.push('\n');
+    }
+    output
+}
+
+/// A variant listed on a rustdoc enum page
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct EnumVariant {
+    pub name: String,
+    /// The variant as declared, e.g. `Custom(String)` or `Point { x: i32, y: i32 }`
+    pub signature: String,
+    /// The first paragraph of the variant's documentation, empty when it has none
+    pub summary: String,
+    /// Named fields of struct-like variants, e.g. `x: i32`
+    pub fields: Vec<String>,
+}
+
+/// Extract the variants of a rustdoc enum page, in declaration order.
+///
+/// Each variant is an element with the id `variant.<Name>`: a `section` holding a `code-header`
+/// in current rustdoc, a `div` or `h3` with the `variant` class in older versions. Its
+/// documentation is the `docblock` that follows it, and struct-like variants list their fields
+/// as elements with ids of the form `variant.<Name>.field.<field>`.
+pub fn parse_enum_variants(html: &str) -> Vec<EnumVariant> {
+    let document = Html::parse_document(html);
+    let anchors = selector("[id^='variant.']");
+    let code_header = selector(".code-header, code");
+    let paragraph = selector("p");
+
+    let mut variants: Vec<EnumVariant> = Vec::new();
+    for element in document.select(&anchors) {
+        let Some(id) = element.value().id() else {
+            continue;
+        };
+        let parts: Vec<&str> = id.split('.').collect();
+        match parts.as_slice() {
+            ["variant", name] => {
+                let signature = element
+                    .select(&code_header)
+                    .next()
+                    .map(element_text)
+                    .unwrap_or_else(|| {
+                        element_text(element).trim_start_matches('§').trim().to_string()
+                    });
+                let summary = element
+                    .next_siblings()
+                    .filter_map(ElementRef::wrap)
+                    .next()
+                    .filter(|sibling| {
+                        sibling.value().has_class("docblock", CaseSensitivity::CaseSensitive)
+                    })
+                    .and_then(|docblock| docblock.select(&paragraph).next())
+                    .map(element_text)
+                    .unwrap_or_default();
+                variants.push(EnumVariant {
+                    name: name.to_string(),
+                    signature,
+                    summary,
+                    fields: Vec::new(),
+                });
+            }
+            ["variant", name, "field", _] => {
+                let field = element
+                    .select(&code_header)
+                    .next()
+                    .map(element_text)
+                    .unwrap_or_else(|| element_text(element));
+                let variant = variants.iter_mut().rev().find(|variant| variant.name == *name);
+                if let Some(variant) = variant {
+                    variant.fields.push(field.trim_start_matches('§').trim().to_string());
+                }
+            }
+            _ => {}
+        }
+    }
+    variants
+}
+
+// Render enum variants as the section put above an enum's full documentation
+pub(crate) fn format_enum_variants(variants: &[EnumVariant]) -> String {
+    let mut output = String::from("## Variants\n\n");
+    if variants.is_empty() {
+        output.push_str("_None_\n");
+    }
+    for variant in variants {
+        if variant.summary.is_empty() {
+            output.push_str(&format!("- `{}`\n", variant.signature));
+        } else {
+            output.push_str(&format!("- `{}`: {}\n", variant.signature, variant.summary));
+        }
+        for field in &variant.fields {
+            output.push_str(&format!("  - field `{}`\n", field));
+        }
+    }
+    output.push('\n');
+    output
+}
+
+/// The documentation part of a docs.rs page, as an HTML fragment for `html_to_markdown`.
+///
+/// docs.rs wraps rustdoc output in its own navigation bar, and rustdoc adds a sidebar, search
+/// form and footer; none of it is useful once converted to markdown. This keeps rustdoc's
+/// `<section id="main-content">`, falling back to the `<div id="main">` of older rustdoc versions
+/// and then to the page's `docblock`s. Pages with none of these are returned unchanged.
+pub fn extract_doc_content(html: &str) -> String {
+    let document = Html::parse_document(html);
+    for css in ["section#main-content", "div#main"] {
+        if let Some(main) = document.select(&selector(css)).next() {
+            return main.html();
+        }
+    }
+
+    // Nested docblocks are already part of their enclosing one
+    let docblocks: Vec<String> = document
+        .select(&selector("div.docblock"))
+        .filter(|block| {
+            !block
+                .ancestors()
+                .filter_map(ElementRef::wrap)
+                .any(|ancestor| ancestor.value().has_class("docblock", CaseSensitivity::CaseSensitive))
+        })
+        .map(|block| block.html())
+        .collect();
+    if docblocks.is_empty() {
+        html.to_string()
+    } else {
+        docblocks.join("\n")
+    }
+}
+
+// Fence annotations for code blocks whose language rustdoc or a README renderer recorded
+const CODE_BLOCK_LANGUAGES: &[&str] = &["rust", "toml", "json", "sh", "bash", "shell", "console"];
+
+fn class_list(node: &Handle) -> Vec<String> {
+    match &node.data {
+        NodeData::Element { attrs, .. } => attrs
+            .borrow()
+            .iter()
+            .filter(|attr| &*attr.name.local == "class")
+            .flat_map(|attr| attr.value.split_whitespace().map(str::to_string).collect::<Vec<_>>())
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+// rustdoc marks Rust blocks as `<pre class="rust">` and other languages as
+// `<pre class="language-toml">`; rendered READMEs put the class on the inner `<code>` instead
+fn code_block_language(pre: &Handle) -> Option<&'static str> {
+    let inner_code: Vec<String> = pre
+        .children
+        .borrow()
+        .iter()
+        .filter(|child| matches!(&child.data, NodeData::Element { name, .. } if &*name.local == "code"))
+        .flat_map(class_list)
+        .collect();
+
+    class_list(pre).iter().chain(inner_code.iter()).find_map(|class| {
+        let language = class.strip_prefix("language-").unwrap_or(class);
+        CODE_BLOCK_LANGUAGES.iter().copied().find(|known| *known == language)
+    })
+}
+
+// Doc-test attributes rustdoc records as classes of an example's `<pre>` in older versions and
+// of its `example-wrap` container in current ones. `no_run` only survives in raw markdown, as
+// rustdoc doesn't render it.
+const DOCTEST_ATTRIBUTES: &[&str] = &["ignore", "no_run", "compile_fail"];
+
+fn doctest_attributes(pre: &Handle) -> Vec<&'static str> {
+    let parent = pre.parent.take();
+    let container = parent.as_ref().and_then(|parent| parent.upgrade());
+    pre.parent.set(parent);
+
+    let mut classes = class_list(pre);
+    if let Some(container) = container {
+        let container_classes = class_list(&container);
+        if container_classes.iter().any(|class| class == "example-wrap") {
+            classes.extend(container_classes);
+        }
+    }
+    DOCTEST_ATTRIBUTES
+        .iter()
+        .copied()
+        .filter(|attribute| classes.iter().any(|class| class == attribute))
+        .collect()
+}
+
+// Replaces html2md's `<pre>` handling, which always writes a bare fence. The fence records the
+// block's language and doc-test attributes as rustdoc's markdown does, e.g. ` ```rust,ignore `.
+struct CodeBlockHandler;
+
+impl TagHandler for CodeBlockHandler {
+    fn handle(&mut self, tag: &Handle, printer: &mut StructuredPrinter) {
+        let info: Vec<&str> = code_block_language(tag)
+            .into_iter()
+            .chain(doctest_attributes(tag))
+            .collect();
+        printer.insert_newline();
+        printer.append_str(&format!("\n```{}\n", info.join(",")));
+    }
+
+    fn after_handle(&mut self, printer: &mut StructuredPrinter) {
+        printer.append_str("\n```\n");
+        printer.insert_newline();
+    }
+}
+
+struct CodeBlockHandlerFactory;
+
+impl TagHandlerFactory for CodeBlockHandlerFactory {
+    fn instantiate(&self) -> Box<dyn TagHandler> {
+        Box::new(CodeBlockHandler)
+    }
+}
+
+fn element_name(node: &Handle) -> Option<&str> {
+    match &node.data {
+        NodeData::Element { name, .. } => Some(&name.local),
+        _ => None,
+    }
+}
+
+fn attribute(node: &Handle, attribute: &str) -> Option<String> {
+    match &node.data {
+        NodeData::Element { attrs, .. } => attrs
+            .borrow()
+            .iter()
+            .find(|attr| &*attr.name.local == attribute)
+            .map(|attr| attr.value.to_string()),
+        _ => None,
+    }
+}
+
+// Append the inline markdown for `node`. Block elements end with a newline, which table cells
+// turn into `<br>` since a GFM table row has to stay on one line.
+fn push_inline_markdown(node: &Handle, output: &mut String) {
+    let children = |output: &mut String| {
+        for child in node.children.borrow().iter() {
+            push_inline_markdown(child, output);
+        }
+    };
+    if let NodeData::Text { contents } = &node.data {
+        output.push_str(&contents.borrow());
+        return;
+    }
+
+    match element_name(node) {
+        Some("code") => {
+            output.push('`');
+            children(output);
+            output.push('`');
+        }
+        Some("strong" | "b") => {
+            output.push_str("**");
+            children(output);
+            output.push_str("**");
+        }
+        Some("em" | "i") => {
+            output.push('*');
+            children(output);
+            output.push('*');
+        }
+        Some("a") => match attribute(node, "href") {
+            Some(href) => {
+                output.push('[');
+                children(output);
+                output.push_str(&format!("]({})", href));
+            }
+            None => children(output),
+        },
+        Some("br") => output.push('\n'),
+        Some("p" | "div" | "li" | "tr" | "pre") => {
+            children(output);
+            output.push('\n');
+        }
+        _ => children(output),
+    }
+}
+
+fn cell_markdown(cell: &Handle) -> String {
+    let mut markdown = String::new();
+    for child in cell.children.borrow().iter() {
+        push_inline_markdown(child, &mut markdown);
+    }
+    markdown
+        .lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("<br>")
+        .replace('|', "\\|")
+}
+
+// The `<tr>`s of a table, including those in `<thead>`/`<tbody>`/`<tfoot>` but not those of
+// tables nested in its cells
+fn table_rows(node: &Handle, rows: &mut Vec<Handle>) {
+    for child in node.children.borrow().iter() {
+        match element_name(child) {
+            Some("tr") => rows.push(child.clone()),
+            Some("thead" | "tbody" | "tfoot") => table_rows(child, rows),
+            _ => {}
+        }
+    }
+}
+
+// Cells of a row, with empty cells standing in for the extra columns a `colspan` covers
+fn row_cells(row: &Handle) -> (Vec<String>, bool) {
+    let mut cells = Vec::new();
+    let mut all_headers = true;
+    for cell in row.children.borrow().iter() {
+        let name = element_name(cell);
+        if !matches!(name, Some("th" | "td")) {
+            continue;
+        }
+        all_headers &= name == Some("th");
+        cells.push(cell_markdown(cell));
+        let span = attribute(cell, "colspan")
+            .and_then(|span| span.trim().parse::<usize>().ok())
+            .unwrap_or(1);
+        cells.extend(std::iter::repeat_n(String::new(), span.saturating_sub(1)));
+    }
+    (cells, all_headers)
+}
+
+fn table_row(cells: &[String], columns: usize) -> String {
+    let mut row = String::from("|");
+    for i in 0..columns {
+        match cells.get(i).filter(|cell| !cell.is_empty()) {
+            Some(cell) => row.push_str(&format!(" {} |", cell)),
+            None => row.push_str("  |"),
+        }
+    }
+    row.push('\n');
+    row
+}
+
+// Replaces html2md's `<table>` handling, which pads cells for alignment, leaves `|` in cell
+// text unescaped and mixes in the rows of nested tables
+struct TableHandler;
+
+impl TagHandler for TableHandler {
+    fn handle(&mut self, tag: &Handle, printer: &mut StructuredPrinter) {
+        let mut rows = Vec::new();
+        table_rows(tag, &mut rows);
+        let mut rows: Vec<(Vec<String>, bool)> = rows.iter().map(row_cells).collect();
+        rows.retain(|(cells, _)| !cells.is_empty());
+        let Some(columns) = rows.iter().map(|(cells, _)| cells.len()).max() else {
+            return;
+        };
+
+        // GFM tables need a header row; tables without one get an empty header
+        let header = match rows.first() {
+            Some((cells, true)) => {
+                let cells = cells.clone();
+                rows.remove(0);
+                cells
+            }
+            _ => Vec::new(),
+        };
+
+        let mut table = String::from("\n\n");
+        table.push_str(&table_row(&header, columns));
+        table.push_str(&format!("|{}\n", "---|".repeat(columns)));
+        for (cells, _) in &rows {
+            table.push_str(&table_row(cells, columns));
+        }
+        table.push('\n');
+        printer.append_str(&table);
+    }
+
+    fn after_handle(&mut self, _printer: &mut StructuredPrinter) {}
+
+    fn skip_descendants(&self) -> bool {
+        true
+    }
+}
+
+struct TableHandlerFactory;
+
+impl TagHandlerFactory for TableHandlerFactory {
+    fn instantiate(&self) -> Box<dyn TagHandler> {
+        Box::new(TableHandler)
+    }
+}
+
+// Writes `<dt>` terms in bold and each `<dd>` as a paragraph indented under its term, with a
+// blank line between entries. Both rewrite whatever their contents printed; a two-space indent
+// keeps definitions from turning into indented code blocks.
+#[derive(Default)]
+struct DefinitionListHandler {
+    tag: String,
+    start: usize,
+}
+
+impl TagHandler for DefinitionListHandler {
+    fn handle(&mut self, tag: &Handle, printer: &mut StructuredPrinter) {
+        self.tag = element_name(tag).unwrap_or_default().to_string();
+        self.start = printer.data.len();
+    }
+
+    fn after_handle(&mut self, printer: &mut StructuredPrinter) {
+        let content = printer.data.split_off(self.start);
+        match self.tag.as_str() {
+            "dt" => {
+                let term = content.split_whitespace().collect::<Vec<_>>().join(" ");
+                if !term.is_empty() {
+                    printer.append_str(&format!("\n\n**{}**:", term));
+                }
+            }
+            "dd" => {
+                let definition = content.trim();
+                if definition.is_empty() {
+                    return;
+                }
+                // Drop the whitespace between the tags; further definitions of the same term get
+                // a paragraph of their own
+                printer.data.truncate(printer.data.trim_end().len());
+                if !printer.data.ends_with("**:") {
+                    printer.insert_newline();
+                }
+                let indented = definition
+                    .lines()
+                    .map(|line| if line.trim().is_empty() { String::new() } else { format!("  {}", line) })
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                printer.append_str(&format!("\n{}", indented));
+            }
+            _ => printer.append_str(&format!("\n\n{}\n\n", content.trim())),
+        }
+    }
+}
+
+struct DefinitionListHandlerFactory;
+
+impl TagHandlerFactory for DefinitionListHandlerFactory {
+    fn instantiate(&self) -> Box<dyn TagHandler> {
+        Box::new(DefinitionListHandler::default())
+    }
+}
+
+// Writes links as `[text](href)`, resolving relative hrefs against the page's URL when it is
+// known. rustdoc's `§` heading anchors are dropped, and anchors without an href keep only
+// their text.
+#[derive(Default)]
+struct LinkHandler {
+    base: Option<Url>,
+    start: usize,
+    href: Option<String>,
+    skip: bool,
+}
+
+impl TagHandler for LinkHandler {
+    fn handle(&mut self, tag: &Handle, printer: &mut StructuredPrinter) {
+        self.skip = class_list(tag).iter().any(|class| class == "anchor");
+        self.start = printer.data.len();
+        self.href = attribute(tag, "href")
+            .filter(|href| !href.trim().is_empty())
+            .map(|href| match (&self.base, Url::parse(&href)) {
+                (Some(base), Err(_)) => base.join(&href).map(String::from).unwrap_or(href),
+                _ => href,
+            });
+    }
+
+    fn after_handle(&mut self, printer: &mut StructuredPrinter) {
+        if self.skip {
+            printer.data.truncate(self.start);
+        } else if let Some(href) = &self.href {
+            printer.insert_str(self.start, "[");
+            printer.append_str(&format!("]({})", href));
+        }
+    }
+
+    fn skip_descendants(&self) -> bool {
+        self.skip
+    }
+}
+
+struct LinkHandlerFactory {
+    base: Option<Url>,
+}
+
+impl TagHandlerFactory for LinkHandlerFactory {
+    fn instantiate(&self) -> Box<dyn TagHandler> {
+        Box::new(LinkHandler {
+            base: self.base.clone(),
+            ..LinkHandler::default()
+        })
+    }
+}
+
+// Drops an element and everything in it. html2md would otherwise copy the contents of
+// `<script>` and `<style>` elements into the markdown as text.
+struct SkipHandler;
+
+impl TagHandler for SkipHandler {
+    fn handle(&mut self, _tag: &Handle, _printer: &mut StructuredPrinter) {}
+
+    fn after_handle(&mut self, _printer: &mut StructuredPrinter) {}
+
+    fn skip_descendants(&self) -> bool {
+        true
+    }
+}
+
+struct SkipHandlerFactory;
+
+impl TagHandlerFactory for SkipHandlerFactory {
+    fn instantiate(&self) -> Box<dyn TagHandler> {
+        Box::new(SkipHandler)
+    }
+}
+
+/// Convert documentation HTML to markdown. This is `html2md::parse_html` with code blocks
+/// fenced with their language (` ```rust `, ` ```toml `, ...) when the HTML records it,
+/// tables written as GitHub-Flavored Markdown pipe tables, definition lists as bold terms
+/// followed by indented definitions, `<script>` and `<style>` contents left out, and no HTML
+/// entities left over.
+pub fn html_to_markdown(html: &str) -> String {
+    convert(html, None)
+}
+
+/// Convert a docs.rs page fetched from `page_url` to markdown: only the documentation is kept
+/// (see `extract_doc_content`), and relative links are made absolute so they still work
+/// outside the page.
+pub fn page_to_markdown(html: &str, page_url: &str) -> String {
+    convert(&extract_doc_content(html), Url::parse(page_url).ok())
+}
+
+fn convert(html: &str, base: Option<Url>) -> String {
+    let mut handlers: HashMap<String, Box<dyn TagHandlerFactory>> = HashMap::new();
+    handlers.insert("pre".to_string(), Box::new(CodeBlockHandlerFactory));
+    handlers.insert("table".to_string(), Box::new(TableHandlerFactory));
+    handlers.insert("a".to_string(), Box::new(LinkHandlerFactory { base }));
+    for tag in ["dl", "dt", "dd"] {
+        handlers.insert(tag.to_string(), Box::new(DefinitionListHandlerFactory));
+    }
+    for tag in ["script", "style"] {
+        handlers.insert(tag.to_string(), Box::new(SkipHandlerFactory));
+    }
+    let markdown = parse_html_custom(html, &handlers);
+
+    // html2md copies some elements (`<sup>`, `<sub>`) as serialized HTML, and doubly escaped
+    // text such as `&amp;lt;` comes out of the parser as a literal `&lt;`
+    html_escape::decode_html_entities(&markdown).into_owned()
+}
@@ -0,0 +1,236 @@
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+
+use super::cache::CodeExample;
+use super::examples::extract_examples;
+use super::html::{
+    page_to_markdown, parse_deprecation, parse_object_safety, parse_portability, parse_since, parse_source_url,
+    parse_summary, ObjectSafety,
+};
+use super::markdown_utils::top_level_sections;
+use super::types::ItemPath;
+use super::{infer_item_kind, ItemKind};
+
+/// Title of the section holding an item's declaration and the documentation above its first
+/// heading
+pub const DESCRIPTION_SECTION: &str = "Description";
+
+/// The documentation of a crate item, as returned by `CargoDocRouter::lookup_item_doc`
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ItemDoc {
+    pub name: String,
+    pub kind: ItemKind,
+    pub crate_name: String,
+    /// Path of the module defining the item within its crate, e.g. `io::util`; empty for the crate root
+    pub module_path: String,
+    /// The documented version, `None` for the latest
+    pub version: Option<String>,
+    /// First paragraph of the item's documentation, as plain text
+    pub summary: String,
+    /// The page's sections in order, keyed by heading, starting with `DESCRIPTION_SECTION`
+    pub sections: IndexMap<String, String>,
+    pub examples: Vec<CodeExample>,
+    pub source_url: Option<String>,
+    /// Rust version the item has been stable since, given for the standard library
+    pub since: Option<String>,
+    /// The deprecation notice of a deprecated item, e.g.
+    /// ``since 1.39.0: use `mem::MaybeUninit<T>` instead``; empty when it gives no details
+    pub deprecated: Option<String>,
+    /// The `cfg` condition the item is available under, e.g. `Unix` or ``crate feature `fs` ``
+    pub portability: Option<String>,
+    /// Full path of the item's definition when docs.rs redirected the lookup to it, e.g.
+    /// `tokio::sync::mutex::Mutex` for `tokio::sync::Mutex`
+    pub canonical_path: Option<String>,
+}
+
+impl ItemDoc {
+    /// Parse the rustdoc page at `page_url` documenting `item_path`
+    pub fn from_page(
+        crate_name: &str,
+        item_path: &ItemPath,
+        version: Option<&str>,
+        page_url: &str,
+        html: &str,
+    ) -> ItemDoc {
+        // Item pages are named after their kind, e.g. `struct.Client.html`
+        let page_name = page_url.rsplit('/').next().unwrap_or_default();
+        // Methods are documented at an anchor of their type's page, `#method.push`
+        let is_method = page_name.contains("#method.") || page_name.contains("#tymethod.");
+        // The page's spelling of the name, which may differ in case or underscores from the one
+        // looked up, e.g. `AsyncRead` for `async_read`
+        let fold = |name: &str| name.replace('_', "").to_lowercase();
+        let name = match page_name.split('.').collect::<Vec<_>>()[..] {
+            [_, name, "html"] if !is_method && fold(name) == fold(item_path.item_name()) => name,
+            _ => item_path.item_name(),
+        };
+        let kind = is_method
+            .then_some(ItemKind::Fn)
+            .or_else(|| {
+                ItemKind::ALL
+                    .into_iter()
+                    .chain([ItemKind::Primitive, ItemKind::Keyword])
+                    .find(|kind| page_name.starts_with(&format!("{}.", kind.page_prefix())))
+            })
+            .or_else(|| infer_item_kind(name))
+            .unwrap_or(ItemKind::Struct);
+
+        let markdown = page_to_markdown(html, page_url);
+        let mut sections: IndexMap<String, String> = IndexMap::new();
+        for (index, (level, title, content)) in top_level_sections(&markdown).into_iter().enumerate() {
+            // The page title heads the description, and may be preceded by text without a heading
+            let title = if level == 0 || (index == 1 && level == 1) {
+                DESCRIPTION_SECTION.to_string()
+            } else {
+                title
+            };
+            if content.is_empty() {
+                continue;
+            }
+            let section = sections.entry(title).or_default();
+            if !section.is_empty() {
+                section.push_str("\n\n");
+            }
+            section.push_str(&content);
+        }
+
+        // rustdoc's own section says neither why a trait isn't dyn compatible nor that it is
+        if kind == ItemKind::Trait {
+            if let Some(safety) = parse_object_safety(html) {
+                sections.shift_remove("Dyn Compatibility");
+                sections.shift_remove("Object Safety");
+                let position = sections.len().min(1);
+                sections.shift_insert(position, "Object Safety".to_string(), object_safety_note(name, &safety));
+            }
+        }
+
+        ItemDoc {
+            name: name.to_string(),
+            kind,
+            crate_name: crate_name.to_string(),
+            module_path: item_path.module_segments().join("::"),
+            version: version.map(str::to_string),
+            summary: parse_summary(html),
+            sections,
+            examples: extract_examples(&markdown),
+            source_url: parse_source_url(html, page_url),
+            since: parse_since(html),
+            deprecated: parse_deprecation(html),
+            portability: parse_portability(html),
+            canonical_path: None,
+        }
+    }
+
+    /// Full path of the item, e.g. `tokio::io::AsyncRead`
+    pub fn path(&self) -> String {
+        if self.module_path.is_empty() {
+            format!("{}::{}", self.crate_name, self.name)
+        } else {
+            format!("{}::{}::{}", self.crate_name, self.module_path, self.name)
+        }
+    }
+
+    /// The crate features the item is only available with, from its portability note, and
+    /// whether any one of them is enough rather than all of them; `None` when it needs none
+    pub fn required_features(&self) -> Option<(Vec<String>, bool)> {
+        let condition = self.portability.as_deref()?;
+        let mut features = Vec::new();
+        let mut any = false;
+        for part in condition.split("crate feature").skip(1) {
+            // Feature names are code spans joined by `,`, `and` or `or`
+            for (index, text) in part.trim_start_matches('s').split('`').enumerate() {
+                if index % 2 == 1 {
+                    features.push(text.to_string());
+                    continue;
+                }
+                match text.trim().trim_matches(',').trim() {
+                    "" | "and" | "," => {}
+                    "or" => any = true,
+                    _ if index > 0 => break,
+                    _ => {}
+                }
+            }
+        }
+        (!features.is_empty()).then_some((features, any))
+    }
+
+    /// Render the documentation as the markdown returned by `lookup_item_tool`
+    pub fn to_markdown(&self) -> String {
+        // Deprecation comes first, so the item isn't recommended by mistake
+        let mut markdown = match self.deprecated.as_deref() {
+            Some("") => "> ⚠️ **Deprecated**\n\n".to_string(),
+            Some(notice) => format!("> ⚠️ **Deprecated**: {}\n\n", notice),
+            None => String::new(),
+        };
+        markdown.push_str(&format!("# {} `{}`\n\n", kind_title(self.kind), self.path()));
+        if let Some(since) = &self.since {
+            markdown.push_str(&format!("_Stable since Rust {}_\n\n", since));
+        }
+        if let Some(canonical_path) = &self.canonical_path {
+            markdown.push_str(&format!("_Canonical path: {}_\n\n", canonical_path));
+        }
+        if let Some(condition) = &self.portability {
+            markdown.push_str(&format!("{}\n\n", portability_note(condition)));
+        }
+        for (title, content) in &self.sections {
+            if title != DESCRIPTION_SECTION {
+                markdown.push_str(&format!("## {}\n\n", title));
+            }
+            markdown.push_str(content);
+            markdown.push_str("\n\n");
+        }
+        markdown
+    }
+}
+
+// How a `cfg` condition is shown: `_Requires feature: `fs`_` when it only names crate
+// features, `_Available on: Unix only_` otherwise
+fn portability_note(condition: &str) -> String {
+    let features = condition
+        .strip_prefix("crate features ")
+        .map(|features| ("features", features))
+        .or_else(|| condition.strip_prefix("crate feature ").map(|feature| ("feature", feature)));
+    match features {
+        // Outside their code spans, the feature names are only joined by `,`, `and` or `or`
+        Some((label, features))
+            if features
+                .split('`')
+                .step_by(2)
+                .all(|part| matches!(part.trim().trim_matches(','), "" | "and" | "or")) =>
+        {
+            format!("_Requires {}: {}_", label, features)
+        }
+        _ => format!("_Available on: {} only_", condition),
+    }
+}
+
+// The `Object Safety` section of a trait
+fn object_safety_note(name: &str, safety: &ObjectSafety) -> String {
+    if safety.object_safe {
+        format!("✓ This trait is object safe (can be used as `dyn {}`).", name)
+    } else if safety.violations.is_empty() {
+        "✗ This trait is NOT object safe.".to_string()
+    } else {
+        format!(
+            "✗ This trait is NOT object safe because: {}.",
+            safety.violations.join("; ")
+        )
+    }
+}
+
+// The kind as a title, e.g. `Type Alias`
+fn kind_title(kind: ItemKind) -> &'static str {
+    match kind {
+        ItemKind::Struct => "Struct",
+        ItemKind::Enum => "Enum",
+        ItemKind::Trait => "Trait",
+        ItemKind::Fn => "Function",
+        ItemKind::Macro => "Macro",
+        ItemKind::Type => "Type Alias",
+        ItemKind::Derive => "Derive Macro",
+        ItemKind::Constant => "Constant",
+        ItemKind::Static => "Static",
+        ItemKind::Attr => "Attribute Macro",
+        ItemKind::Primitive => "Primitive Type",
+        ItemKind::Keyword => "Keyword",
+    }
+}
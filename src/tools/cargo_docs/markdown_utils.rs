@@ -0,0 +1,322 @@
+use super::types::ItemPath;
+
+// Characters html2md escapes with a backslash in text
+const ESCAPED_CHARS: &[char] = &['<', '>', '*', '\\', '_', '~', '=', '+', '-', '#'];
+
+/// Reduce inline markdown as produced by html2md to plain text: links are replaced by their
+/// text, backslash escapes and rustdoc's `§` anchors are removed, and whitespace is collapsed.
+pub fn plain_text(markdown: &str) -> String {
+    strip_inline(markdown).split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn strip_inline(markdown: &str) -> String {
+    let chars: Vec<char> = markdown.chars().collect();
+    let mut output = String::with_capacity(markdown.len());
+    let mut i = 0;
+
+    while i < chars.len() {
+        match chars[i] {
+            '\\' if chars.get(i + 1).is_some_and(|next| ESCAPED_CHARS.contains(next)) => {
+                output.push(chars[i + 1]);
+                i += 2;
+            }
+            '§' => i += 1,
+            '[' => match link_end(&chars, i) {
+                // Keep the link text, drop the `(url)`
+                Some((text_end, link_end)) => {
+                    output.push_str(&strip_inline(&chars[i + 1..text_end].iter().collect::<String>()));
+                    i = link_end + 1;
+                }
+                None => {
+                    output.push('[');
+                    i += 1;
+                }
+            },
+            c => {
+                output.push(c);
+                i += 1;
+            }
+        }
+    }
+    output
+}
+
+// For a `[text](url)` link opening at `start`, the positions of the `]` closing its text and
+// the `)` closing its URL. Brackets nest, since rustdoc links slice types as `[[T]](...)`.
+fn link_end(chars: &[char], start: usize) -> Option<(usize, usize)> {
+    let mut depth = 0;
+    let mut text_end = None;
+    for (i, c) in chars.iter().enumerate().skip(start) {
+        match c {
+            '[' => depth += 1,
+            ']' => {
+                depth -= 1;
+                if depth == 0 {
+                    text_end = Some(i);
+                    break;
+                }
+            }
+            _ => {}
+        }
+    }
+    let text_end = text_end?;
+    if chars.get(text_end + 1) != Some(&'(') {
+        return None;
+    }
+    let mut depth = 0;
+    for (i, c) in chars.iter().enumerate().skip(text_end + 1) {
+        match c {
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some((text_end, i));
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// `(module_path_for_url, item_name)` of an item path in any of the forms `ItemPath::parse`
+/// accepts, e.g. `("io", "AsyncRead")` for `tokio::io::AsyncRead` or `io/AsyncRead`. Unlike
+/// `ItemPath::parse` it never fails: empty segments are skipped, and input that still isn't
+/// a path is returned as the item name.
+pub fn normalize_item_path(crate_name: &str, raw: &str) -> (String, String) {
+    let segments = raw.replace("::", "/");
+    let segments: Vec<&str> = segments.split('/').map(str::trim).filter(|s| !s.is_empty()).collect();
+    match ItemPath::parse(crate_name, &segments.join("::")) {
+        Ok(path) => (path.module_path(), path.item_name().to_string()),
+        Err(_) => (String::new(), raw.trim().to_string()),
+    }
+}
+
+// Doc-test attributes of code blocks that rustdoc doesn't run
+pub(crate) const NON_RUNNABLE_ATTRIBUTES: &[&str] = &["ignore", "no_run", "compile_fail"];
+
+// Doc-test attributes that aren't a language, besides `NON_RUNNABLE_ATTRIBUTES`
+const DOCTEST_ATTRIBUTES: &[&str] = &["should_panic", "test_harness", "standalone_crate"];
+
+/// A fenced code block of a markdown document
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CodeBlock {
+    /// The language named by the fence, e.g. `rust` for ` ```rust,no_run `
+    pub language: Option<String>,
+    pub content: String,
+    /// Everything after the opening fence's backticks, e.g. `rust,no_run`
+    pub fence_annotation: String,
+    /// Whether rustdoc runs the block, i.e. it isn't marked `ignore`, `no_run` or `compile_fail`
+    pub is_runnable: bool,
+    /// Index of the line of the opening fence
+    pub start_line: usize,
+}
+
+/// The fenced code blocks of `markdown`, in order. A block ends at a fence of at least as many
+/// backticks as its opening one, so shorter fences can appear inside it; an unclosed block runs
+/// to the end of the document.
+pub fn extract_code_blocks(markdown: &str) -> Vec<CodeBlock> {
+    let mut blocks = Vec::new();
+    // Backticks of the open block's fence, its annotation, first line and content
+    let mut open: Option<(usize, &str, usize, Vec<&str>)> = None;
+
+    for (i, line) in markdown.lines().enumerate() {
+        let trimmed = line.trim();
+        let backticks = trimmed.chars().take_while(|c| *c == '`').count();
+        match open.as_mut() {
+            Some((fence, _, _, _)) if backticks >= *fence && trimmed.len() == backticks => {
+                if let Some((_, annotation, start_line, lines)) = open.take() {
+                    blocks.push(code_block(annotation, start_line, &lines));
+                }
+            }
+            Some((_, _, _, lines)) => lines.push(line),
+            None if backticks >= 3 => open = Some((backticks, trimmed[backticks..].trim(), i, Vec::new())),
+            None => {}
+        }
+    }
+    if let Some((_, annotation, start_line, lines)) = open {
+        blocks.push(code_block(annotation, start_line, &lines));
+    }
+    blocks
+}
+
+fn code_block(annotation: &str, start_line: usize, lines: &[&str]) -> CodeBlock {
+    let attributes: Vec<&str> = annotation
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|attribute| !attribute.is_empty())
+        .collect();
+    let language = attributes
+        .iter()
+        .find(|attribute| {
+            !NON_RUNNABLE_ATTRIBUTES.contains(attribute)
+                && !DOCTEST_ATTRIBUTES.contains(attribute)
+                && !attribute.starts_with("edition")
+        })
+        .map(|language| language.to_string());
+    CodeBlock {
+        language,
+        content: lines.join("\n"),
+        fence_annotation: annotation.to_string(),
+        is_runnable: !attributes.iter().any(|attribute| NON_RUNNABLE_ATTRIBUTES.contains(attribute)),
+        start_line,
+    }
+}
+
+// Level and raw text of the heading starting at `lines[i]`, plus how many lines it spans.
+// html2md writes `h1`/`h2` as setext headings and smaller ones as ATX headings with closing
+// hashes (`### Title ###`).
+fn heading_at(lines: &[&str], i: usize) -> Option<(usize, String, usize)> {
+    let line = lines[i];
+    let hashes = line.chars().take_while(|c| *c == '#').count();
+    if (1..=6).contains(&hashes) {
+        let rest = &line[hashes..];
+        if rest.is_empty() || rest.starts_with(' ') {
+            let text = rest.trim().trim_end_matches('#').trim_end();
+            return Some((hashes, text.to_string(), 1));
+        }
+    }
+
+    let underline = lines.get(i + 1)?.trim();
+    if line.trim().is_empty() || line.starts_with(' ') || underline.len() < 3 {
+        return None;
+    }
+    if underline.chars().all(|c| c == '=') {
+        Some((1, line.trim().to_string(), 2))
+    } else if underline.chars().all(|c| c == '-') {
+        Some((2, line.trim().to_string(), 2))
+    } else {
+        None
+    }
+}
+
+// Line index, level, raw text and line span of every heading of `lines` outside fenced code
+// blocks, whose fences follow the rules of `extract_code_blocks`
+fn heading_lines(lines: &[&str]) -> Vec<(usize, usize, String, usize)> {
+    let mut headings = Vec::new();
+    let mut fence = 0;
+    let mut i = 0;
+    while i < lines.len() {
+        let trimmed = lines[i].trim();
+        let backticks = trimmed.chars().take_while(|c| *c == '`').count();
+        if fence > 0 {
+            if backticks >= fence && trimmed.len() == backticks {
+                fence = 0;
+            }
+            i += 1;
+        } else if backticks >= 3 {
+            fence = backticks;
+            i += 1;
+        } else if let Some((level, text, span)) = heading_at(lines, i) {
+            headings.push((i, level, text, span));
+            i += span;
+        } else {
+            i += 1;
+        }
+    }
+    headings
+}
+
+/// Content of the section whose heading reads `section_title` (ignoring links and anchors, and
+/// case unless `case_sensitive`), at any heading level, up to the next heading of the same or a
+/// higher level. Headings inside code blocks are ignored.
+pub fn extract_section(markdown: &str, section_title: &str, case_sensitive: bool) -> Option<String> {
+    let lines: Vec<&str> = markdown.lines().collect();
+    let headings = heading_lines(&lines);
+    let matches = |text: &str| {
+        let text = plain_text(text);
+        if case_sensitive {
+            text == section_title.trim()
+        } else {
+            text.to_lowercase() == section_title.trim().to_lowercase()
+        }
+    };
+
+    let position = headings.iter().position(|(_, _, text, _)| matches(text))?;
+    let (line, level, _, span) = &headings[position];
+    let end = headings[position + 1..]
+        .iter()
+        .find(|(_, next_level, _, _)| next_level <= level)
+        .map_or(lines.len(), |(next_line, _, _, _)| *next_line);
+    Some(lines[line + span..end].join("\n").trim().to_string())
+}
+
+/// `(level, title)` of every heading in `markdown` outside code blocks, in order, with titles
+/// reduced to plain text
+pub fn list_sections(markdown: &str) -> Vec<(usize, String)> {
+    let lines: Vec<&str> = markdown.lines().collect();
+    heading_lines(&lines)
+        .into_iter()
+        .map(|(_, level, text, _)| (level, plain_text(&text)))
+        .collect()
+}
+
+/// Text of every heading in `markdown` outside code blocks, reduced to plain text, in order
+/// and without repeats
+pub fn headings(markdown: &str) -> Vec<String> {
+    let mut headings: Vec<String> = Vec::new();
+    for (_, text) in list_sections(markdown) {
+        if !text.is_empty() && !headings.contains(&text) {
+            headings.push(text);
+        }
+    }
+    headings
+}
+
+/// The first paragraph of `markdown` that isn't a heading or a code block and has some text
+pub fn first_paragraph(markdown: &str) -> Option<String> {
+    let mut in_code_block = false;
+    let mut paragraph: Vec<&str> = Vec::new();
+    for line in markdown.lines().chain([""]) {
+        if line.trim_start().starts_with("```") {
+            in_code_block = !in_code_block;
+            paragraph.clear();
+            continue;
+        }
+        if in_code_block {
+            continue;
+        }
+        if !line.trim().is_empty() {
+            paragraph.push(line);
+            continue;
+        }
+        let is_heading = !paragraph.is_empty() && heading_at(&paragraph, 0).is_some();
+        if !is_heading && !plain_text(&paragraph.join("\n")).is_empty() {
+            return Some(paragraph.join("\n").trim().to_string());
+        }
+        paragraph.clear();
+    }
+    None
+}
+
+/// Cut `markdown` to at most `max_length` characters, at the last paragraph break before the
+/// limit when there is one, and note that it was truncated
+pub fn truncate_markdown(markdown: &str, max_length: usize) -> String {
+    let Some((limit, _)) = markdown.char_indices().nth(max_length) else {
+        return markdown.to_string();
+    };
+    let end = markdown[..limit].rfind("\n\n").filter(|end| *end > 0).unwrap_or(limit);
+    format!(
+        "{}\n\n_[Output truncated at {} characters. Use the `section` parameter to access specific sections.]_",
+        markdown[..end].trim_end(),
+        max_length
+    )
+}
+
+/// Split `markdown` at its level-1 and level-2 headings into `(level, heading, content)`
+/// triples, with headings reduced to plain text. Text before the first heading is returned
+/// with level 0 and an empty heading.
+pub fn top_level_sections(markdown: &str) -> Vec<(usize, String, String)> {
+    let lines: Vec<&str> = markdown.lines().collect();
+    let mut sections = Vec::new();
+    let (mut level, mut title, mut start) = (0, String::new(), 0);
+
+    for (line, next_level, text, span) in heading_lines(&lines) {
+        if next_level <= 2 {
+            sections.push((level, title, lines[start..line].join("\n").trim().to_string()));
+            (level, title) = (next_level, plain_text(&text));
+            start = line + span;
+        }
+    }
+    sections.push((level, title, lines[start..].join("\n").trim().to_string()));
+    sections
+}
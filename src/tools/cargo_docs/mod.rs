@@ -1,230 +1,1794 @@
-use std::sync::Arc;
-use std::collections::HashMap;
+// Clones only made to satisfy the borrow checker are flagged by the clippy step in CI
+#![warn(clippy::redundant_clone)]
 
-use html2md::parse_html;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::future::Future;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
-use reqwest::Client;
-use tokio::sync::Mutex;
+use futures::future::join_all;
+use futures::stream::{FuturesUnordered, StreamExt};
+use rand::Rng;
+use reqwest::header::CONTENT_TYPE;
+use reqwest::{Client, NoProxy, Proxy, Response, StatusCode};
 
-use rmcp::{model::*, schemars, tool, ServerHandler};
+use rmcp::{model::*, service::RequestContext, tool, Error as McpError, RoleServer, ServerHandler};
+use tokio::sync::{broadcast, Semaphore};
+
+mod builder;
+mod cache;
+mod circuit_breaker;
+mod crates_io;
+mod diff;
+mod docs_rs;
+mod error;
+mod examples;
+mod html;
+mod item_doc;
+mod markdown_utils;
+mod project;
+mod prompts;
+mod rate_limit;
+mod readme;
+mod resources;
+mod rustdoc_json;
+mod search;
+mod traits;
+mod types;
+
+pub use builder::CargoDocRouterBuilder;
+pub use cache::{
+    CacheBackend, CacheStats, CodeExample, DocCache, InMemoryBackend, NullBackend, PersistentDocCache,
+    WarmupResult, DEFAULT_CACHE_CAPACITY,
+};
+use cache::DOC_KEY_PREFIX;
+pub use circuit_breaker::{
+    CircuitBreaker, CircuitState, DEFAULT_FAILURE_THRESHOLD, DEFAULT_RECOVERY_TIMEOUT,
+};
+pub use crates_io::{CrateDependency, CrateMetadata, PublishedVersion, ReverseDependency};
+use crates_io::{
+    crate_dependencies_url, crate_readme_url, crate_url, crate_version_url, crate_versions_url, enabled_features, filter_versions,
+    format_dependencies, format_features, format_msrv, format_reverse_dependencies, format_versions,
+    reverse_dependencies_url, CrateDependenciesResponse, CrateResponse, CrateVersionResponse, CrateVersionsResponse,
+    ReverseDependenciesResponse,
+};
+pub use diff::diff_documentation;
+pub use docs_rs::{BuildStatus, DocsRsBuild};
+use docs_rs::{builds_url, format_build_status};
+pub use error::{parse_retry_after, DocError};
+pub use examples::{
+    extract_examples, format_examples, generated_example, playground_url, DEFAULT_PLAYGROUND_EDITION,
+    PLAYGROUND_EDITIONS,
+};
+use error::{parse_json, retry_after};
+pub use html::{
+    extract_doc_content, extract_method, html_to_markdown, page_to_markdown, parse_all_items,
+    parse_deprecation, parse_enum_variants, parse_module_items, parse_object_safety, parse_portability,
+    parse_since, parse_source_url, parse_summary, parse_supertraits, parse_thread_safety, parse_trait_methods,
+    EnumVariant, IndexedItem, ModuleItem, ObjectSafety, ThreadSafety, TraitMethod,
+};
+use html::{format_enum_variants, format_trait_methods};
+pub use item_doc::{ItemDoc, DESCRIPTION_SECTION};
+pub use markdown_utils::{
+    extract_code_blocks, extract_section, first_paragraph, headings, list_sections,
+    normalize_item_path, plain_text, top_level_sections, truncate_markdown, CodeBlock,
+};
+pub use project::{locked_versions, parse_lockfile};
+pub use prompts::{get_prompt, prompts};
+pub use rate_limit::RateLimiter;
+pub use readme::{detect_readme_format, rst_to_markdown, ReadmeFormat};
+pub use resources::{cached_doc_resource, resource_key, resource_uri, RESOURCES_PER_PAGE};
+pub use rustdoc_json::DocFormat;
+#[cfg(feature = "rustdoc-json")]
+pub use rustdoc_json::{decode_rustdoc_json, format_crate, format_item};
+#[cfg(feature = "rustdoc-json")]
+use rustdoc_json::rustdoc_json_url;
+pub use search::{CrateSearchResult, SearchSort};
+pub use types::{CrateName, CrateVersion, InvalidCrateNameError, ItemPath, ItemPathError};
+pub use traits::{
+    associated_type_name, async_method, deref_target, implementing_type, object_safety_violations,
+    parse_async_methods, parse_conversions, parse_implementors, parse_supertrait_bounds, parse_unsafe_functions,
+    parse_unsafe_impls, AsyncMethod, UnsafeFunction,
+};
+use search::{crates_io_search_url, format_search_results, is_json_content_type, CratesIoErrors, SearchResponse};
 
 #[cfg(test)]
 mod tests;
 
-// Cache for documentation lookups to avoid repeated requests
-#[derive(Clone)]
-pub struct DocCache {
-    cache: Arc<Mutex<HashMap<String, String>>>,
-    // New: Cache for example code snippets
-    examples_cache: Arc<Mutex<HashMap<String, Vec<CodeExample>>>>,
-}
+/// Kinds of documented items, each with its own docs.rs page prefix (e.g. `struct.Client.html`)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ItemKind {
+    Struct,
+    Enum,
+    Trait,
+    Fn,
+    Macro,
+    Type,
+    Derive,
+    Constant,
+    Static,
+    Attr,
+    /// A primitive type of the standard library, such as `u32` or `str`, which is never probed for
+    Primitive,
+    /// A keyword documented by the standard library, such as `async`, which is never probed for
+    Keyword,
+}
+
+impl ItemKind {
+    /// Order in which item pages are probed when nothing is known about the name
+    pub const ALL: [ItemKind; 10] = [
+        ItemKind::Struct,
+        ItemKind::Enum,
+        ItemKind::Trait,
+        ItemKind::Fn,
+        ItemKind::Macro,
+        ItemKind::Type,
+        ItemKind::Derive,
+        ItemKind::Constant,
+        ItemKind::Static,
+        ItemKind::Attr,
+    ];
+
+    /// Prefix of the item's docs.rs page file name
+    pub fn page_prefix(self) -> &'static str {
+        match self {
+            ItemKind::Struct => "struct",
+            ItemKind::Enum => "enum",
+            ItemKind::Trait => "trait",
+            ItemKind::Fn => "fn",
+            ItemKind::Macro => "macro",
+            ItemKind::Type => "type",
+            ItemKind::Derive => "derive",
+            ItemKind::Constant => "constant",
+            ItemKind::Static => "static",
+            ItemKind::Attr => "attr",
+            ItemKind::Primitive => "primitive",
+            ItemKind::Keyword => "keyword",
+        }
+    }
+
+    /// The kind as a noun phrase for messages, e.g. `an enum`
+    pub fn description(self) -> &'static str {
+        match self {
+            ItemKind::Struct => "a struct",
+            ItemKind::Enum => "an enum",
+            ItemKind::Trait => "a trait",
+            ItemKind::Fn => "a function",
+            ItemKind::Macro => "a macro",
+            ItemKind::Type => "a type alias",
+            ItemKind::Derive => "a derive macro",
+            ItemKind::Constant => "a constant",
+            ItemKind::Static => "a static",
+            ItemKind::Attr => "an attribute macro",
+            ItemKind::Primitive => "a primitive type",
+            ItemKind::Keyword => "a keyword",
+        }
+    }
+}
+
+/// Guess an item's kind from Rust naming conventions: `name!` is a macro, `SCREAMING_CASE`
+/// a constant, `CamelCase` a type and `snake_case` a function.
+pub fn infer_item_kind(item_name: &str) -> Option<ItemKind> {
+    if item_name.ends_with('!') {
+        return Some(ItemKind::Macro);
+    }
+
+    let has_lowercase = item_name.chars().any(|c| c.is_lowercase());
+    let has_uppercase = item_name.chars().any(|c| c.is_uppercase());
+    if has_uppercase && !has_lowercase && item_name.len() > 1 {
+        return Some(ItemKind::Constant);
+    }
+
+    match item_name.chars().next() {
+        Some(c) if c.is_uppercase() => Some(ItemKind::Struct),
+        Some(c) if c.is_lowercase() => Some(ItemKind::Fn),
+        _ => None,
+    }
+}
+
+/// The item kinds to probe for `item_name`, most likely first. Names ending in `!` are only
+/// probed as macros; otherwise every kind is still tried so unconventional names are found.
+pub fn probe_order(item_name: &str) -> Vec<ItemKind> {
+    let likely = likely_kinds(item_name);
+    if likely == [ItemKind::Macro] {
+        return likely.to_vec();
+    }
+
+    let mut order = likely.to_vec();
+    order.extend(ItemKind::ALL.iter().filter(|kind| !likely.contains(kind)));
+    order
+}
+
+// The kinds the naming convention suggests for `item_name`, which `probe_order` ranks first
+fn likely_kinds(item_name: &str) -> &'static [ItemKind] {
+    match infer_item_kind(item_name) {
+        Some(ItemKind::Macro) => &[ItemKind::Macro],
+        Some(ItemKind::Fn) => &[ItemKind::Fn, ItemKind::Macro],
+        Some(ItemKind::Struct) => &[ItemKind::Struct, ItemKind::Enum, ItemKind::Trait],
+        Some(ItemKind::Constant) => &[ItemKind::Constant, ItemKind::Static],
+        _ => &[],
+    }
+}
+
+// Other capitalizations of an item name as docs.rs names its pages: title-cased, and converted
+// from snake_case to PascalCase (`async_read` to `AsyncRead`)
+fn case_variants(item_name: &str) -> Vec<String> {
+    fn capitalize(word: &str) -> String {
+        let mut chars = word.chars();
+        chars
+            .next()
+            .map(|first| first.to_uppercase().chain(chars).collect())
+            .unwrap_or_default()
+    }
+
+    let name = item_name.trim_end_matches('!');
+    let mut variants: Vec<String> = Vec::new();
+    for variant in [capitalize(name), name.split('_').map(capitalize).collect()] {
+        if variant != name && !variant.is_empty() && !variants.contains(&variant) {
+            variants.push(variant);
+        }
+    }
+    variants
+}
+
+// How long docs.rs build statuses are cached
+const BUILD_STATUS_TTL: Duration = Duration::from_secs(5 * 60);
+
+// How long pages of a crate's reverse dependencies are cached
+const REVERSE_DEPENDENCIES_TTL: Duration = Duration::from_secs(60 * 60);
+
+// Parse a module path given to `lookup_module` or `list_module_items` like an item path,
+// also accepting a trailing separator (`sync/`, `sync::`)
+fn parse_module_path(crate_name: &CrateName, module_path: &str) -> Result<ItemPath, McpError> {
+    let module_path = module_path.trim().trim_end_matches(['/', ':']);
+    Ok(ItemPath::parse(crate_name, module_path)?)
+}
+
+const DOCS_RS_BASE_URL: &str = "https://docs.rs";
+const CRATES_IO_BASE_URL: &str = "https://crates.io";
+const STDLIB_BASE_URL: &str = "https://doc.rust-lang.org";
+
+/// Crates of the Rust distribution, documented on doc.rust-lang.org rather than docs.rs
+pub const STDLIB_CRATES: &[&str] = &["std", "core", "alloc", "test", "proc_macro"];
+
+/// Primitive types documented at the root of `std`, `core` and `alloc`, e.g. `primitive.u32.html`
+pub const PRIMITIVE_TYPES: &[&str] = &[
+    "u8", "u16", "u32", "u64", "u128", "usize", "i8", "i16", "i32", "i64", "i128", "isize", "f32", "f64", "bool",
+    "char", "str", "never",
+];
+
+/// Keywords documented by `std`, e.g. `keyword.async.html`
+pub const RUST_KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern", "false", "fn",
+    "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref", "return", "self", "static",
+    "struct", "super", "trait", "true", "type", "union", "unsafe", "use", "where", "while",
+];
+
+/// The version to put in URLs and cache keys for a requested version: `None` for the latest
+/// version, i.e. for no version, `latest` or `*`, the normalized version for a version or a
+/// requirement naming one such as `=1.2.3`, and the normalized requirement otherwise, e.g.
+/// `^0.11` for `0.11`, which docs.rs resolves to the newest release matching it. Text that is
+/// neither a version nor a requirement is returned trimmed, to be rejected when parsed.
+fn normalize_version(version: Option<&str>) -> Option<String> {
+    let version = version?.trim();
+    if version == "latest" {
+        return None;
+    }
+    if let Ok(version) = semver::Version::parse(version) {
+        return Some(version.to_string());
+    }
+    let Ok(req) = semver::VersionReq::parse(version) else {
+        return Some(version.to_string());
+    };
+    match req.comparators.as_slice() {
+        [] => None,
+        // `=1.2.3` names a single version
+        [comparator] if comparator.op == semver::Op::Exact && comparator.patch.is_some() => {
+            let mut version =
+                semver::Version::new(comparator.major, comparator.minor.unwrap_or(0), comparator.patch.unwrap_or(0));
+            version.pre = comparator.pre.clone();
+            Some(version.to_string())
+        }
+        // Without spaces, so `>=0.11, <0.13` and `>=0.11,<0.13` share URLs and cache keys
+        _ => Some(req.to_string().replace(' ', "")),
+    }
+}
+
+// The section of `doc` headed `section`, or an error listing the headings there are. Rendered
+// pages list methods under "Implementations", which "Methods" is taken to mean.
+fn doc_section(doc: &str, section: &str) -> Result<String, McpError> {
+    let found = extract_section(doc, section, false).or_else(|| {
+        section
+            .trim()
+            .eq_ignore_ascii_case("methods")
+            .then(|| extract_section(doc, "Implementations", false))
+            .flatten()
+    });
+    found.ok_or_else(|| {
+        let available = headings(doc)
+            .iter()
+            .map(|heading| format!("`{}`", heading))
+            .collect::<Vec<_>>()
+            .join(", ");
+        McpError::invalid_params(
+            format!("No section `{}` in the documentation; available sections: {}", section.trim(), available),
+            None,
+        )
+    })
+}
+
+// Note whether a cache lookup hit on the span of the tool making it, and as a debug event
+fn record_cache_lookup(hit: bool) {
+    tracing::Span::current().record("cache_hit", hit);
+    if hit {
+        tracing::debug!("cache hit");
+    } else {
+        tracing::debug!("cache miss");
+    }
+}
+
+// Validate a tool's `version` argument and normalize it with `normalize_version`: anything
+// that isn't the latest version must be a full semver version or a semver requirement
+fn parse_version(version: Option<String>) -> Result<Option<String>, McpError> {
+    match normalize_version(version.as_deref()) {
+        None => Ok(None),
+        Some(version) => match CrateVersion::parse(&version) {
+            Ok(version) => Ok(Some(version.to_string())),
+            Err(_) if semver::VersionReq::parse(&version).is_ok() => Ok(Some(version)),
+            Err(e) => Err(McpError::invalid_params(
+                format!(
+                    "Invalid version: `{}` is not a semver version such as 1.0.0 or a requirement such as ^1.2 ({})",
+                    version, e
+                ),
+                None,
+            )),
+        },
+    }
+}
+
+// Check that `target` is a target triple, `<arch>-<vendor>-<os>` optionally followed by
+// `-<abi>`, that docs.rs can have built the docs of `crate_name` for
+fn parse_target(crate_name: &CrateName, target: Option<String>) -> Result<Option<String>, McpError> {
+    let Some(target) = target.map(|target| target.trim().to_string()).filter(|target| !target.is_empty()) else {
+        return Ok(None);
+    };
+    let components: Vec<&str> = target.split('-').collect();
+    let valid_component = |component: &&str| {
+        !component.is_empty()
+            && component
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '.')
+    };
+    if !(3..=4).contains(&components.len()) || !components.iter().all(valid_component) {
+        return Err(McpError::invalid_params(
+            format!(
+                "Invalid target: `{}` is not a target triple such as x86_64-unknown-linux-gnu or wasm32-unknown-unknown",
+                target
+            ),
+            None,
+        ));
+    }
+    if STDLIB_CRATES.contains(&crate_name.as_str()) {
+        return Err(McpError::invalid_params(
+            format!("Invalid target: the `{}` documentation is only published for one target", crate_name),
+            None,
+        ));
+    }
+    Ok(Some(target))
+}
+
+// The types of an Implementors section as a list of at most `max` entries, noting how many
+// were left out
+fn implementor_list(section: &str, max: usize) -> String {
+    let implementors = parse_implementors(section);
+    if implementors.is_empty() {
+        return "_No implementors listed._".to_string();
+    }
+    let mut list: Vec<String> = implementors.iter().take(max).map(|ty| format!("- `{}`", ty)).collect();
+    if implementors.len() > max {
+        list.push(format!(
+            "\n_Showing {} of {} implementors; use `find_trait_implementors` for the full list._",
+            max,
+            implementors.len()
+        ));
+    }
+    list.join("\n")
+}
+
+// Whether the `required` features of an item, all of them or `any` one, are among the `enabled` ones
+fn feature_availability(required: &[String], any: bool, enabled: &BTreeSet<String>) -> String {
+    let missing: Vec<String> = required
+        .iter()
+        .filter(|feature| !enabled.contains(*feature))
+        .map(|feature| format!("`{}`", feature))
+        .collect();
+    if missing.is_empty() || (any && missing.len() < required.len()) {
+        "_Available with the requested features_".to_string()
+    } else if any {
+        format!("_Not available with the requested features: needs one of {}_", missing.join(", "))
+    } else {
+        format!("_Not available with the requested features: needs {}_", missing.join(", "))
+    }
+}
+
+// Cache key of an item, shared by every tool that fetches item pages: its markdown documentation
+// is cached under `doc:<key>`, and tools derive their own keys from it, e.g. `item_doc:<key>`
+fn item_cache_key(crate_name: &str, item_path: &ItemPath, version: Option<&str>, target: Option<&str>) -> String {
+    match build_key(normalize_version(version).as_deref(), target) {
+        Some(build) => format!("{}:{}:{}", crate_name, build, item_path),
+        None => format!("{}:{}", crate_name, item_path),
+    }
+}
+
+// The part of a cache key naming the documentation build: its version, followed for docs built
+// for another target by that target, e.g. `1.0.0@wasm32-unknown-unknown` or `latest@...`
+fn build_key(version: Option<&str>, target: Option<&str>) -> Option<String> {
+    match (version, target) {
+        (version, Some(target)) => Some(format!("{}@{}", version.unwrap_or("latest"), target)),
+        (version, None) => version.map(str::to_string),
+    }
+}
+
+// Item kinds `prefetch_crate` looks up, in the order they are picked, and how many of its
+// lookups run at once
+const PREFETCH_KINDS: &[&str] = &["struct", "trait", "enum"];
+const PREFETCH_CONCURRENCY: usize = 5;
+const DEFAULT_PREFETCH_ITEMS: usize = 10;
+
+// Most items `batch_lookup_items` looks up in one call
+const MAX_BATCH_ITEMS: usize = 10;
+
+// How many implementors `lookup_item_tool` lists with `include_implementors` unless told otherwise
+const DEFAULT_MAX_IMPLEMENTORS: u32 = 20;
+
+// Default and maximum depth of the supertrait tree built by `get_type_hierarchy`
+const DEFAULT_HIERARCHY_DEPTH: u8 = 2;
+const MAX_HIERARCHY_DEPTH: u8 = 5;
+
+/// How many times a request is retried after a transient failure unless configured otherwise
+pub const DEFAULT_MAX_RETRIES: u8 = 3;
+
+/// How long a single request may take unless configured otherwise
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// User-Agent sent with every request unless configured otherwise, taken from the package
+/// metadata so that it names the running version
+pub const DEFAULT_USER_AGENT: &str = concat!(
+    env!("CARGO_PKG_NAME"),
+    "/",
+    env!("CARGO_PKG_VERSION"),
+    " (",
+    env!("CARGO_PKG_REPOSITORY"),
+    ")"
+);
+
+// HTTP client whose requests fail with a timeout error after `timeout`. Requests go through
+// `proxy` if given, otherwise through the proxies named by the HTTP_PROXY, HTTPS_PROXY and
+// ALL_PROXY environment variables; hosts listed in NO_PROXY are always reached directly.
+fn http_client(timeout: Duration, proxy: Option<Proxy>) -> Client {
+    let mut builder = Client::builder().timeout(timeout);
+    if let Some(proxy) = proxy {
+        builder = builder.proxy(proxy.no_proxy(NoProxy::from_env()));
+    }
+    builder.build().expect("failed to initialize the HTTP client")
+}
+
+// Delay before the first retry, doubled for each further one up to `RETRY_MAX_DELAY`
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(100);
+const RETRY_MAX_DELAY: Duration = Duration::from_secs(30);
+
+// Delay before retry number `attempt` (0 for the first): exponential, capped, and jittered
+// between half and the full delay so concurrent lookups don't retry in lockstep
+fn backoff_delay(attempt: u32) -> Duration {
+    let delay = RETRY_BASE_DELAY
+        .saturating_mul(2u32.saturating_pow(attempt))
+        .min(RETRY_MAX_DELAY);
+    delay.mul_f64(rand::thread_rng().gen_range(0.5..=1.0))
+}
+
+// Whether a response status is worth retrying, i.e. a gateway or overloaded server
+fn is_transient_status(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::BAD_GATEWAY | StatusCode::SERVICE_UNAVAILABLE | StatusCode::GATEWAY_TIMEOUT
+    )
+}
+
+/// Send the request built by `send`, retrying up to `max_retries` times with jittered exponential
+/// backoff when the connection fails, times out, or the server answers 502, 503 or 504.
+/// A 429 is retried too, waiting at least as long as its `Retry-After` header asks, unless that
+/// is longer than the maximum backoff. Any other response, successful or not, is returned as is
+/// for the caller to inspect.
+pub async fn retry_with_backoff<F, Fut>(max_retries: u8, mut send: F) -> Result<Response, DocError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = reqwest::Result<Response>>,
+{
+    let mut attempt = 0;
+    loop {
+        let result = send().await;
+        let (retryable, min_delay) = match &result {
+            Ok(response) if response.status() == StatusCode::TOO_MANY_REQUESTS => {
+                let retry_after = retry_after(response).unwrap_or_default();
+                (retry_after <= RETRY_MAX_DELAY, retry_after)
+            }
+            Ok(response) => (is_transient_status(response.status()), Duration::ZERO),
+            Err(e) => (e.is_connect() || e.is_timeout(), Duration::ZERO),
+        };
+        if !retryable || attempt >= u32::from(max_retries) {
+            return Ok(result?);
+        }
+
+        tokio::time::sleep(backoff_delay(attempt).max(min_delay)).await;
+        attempt += 1;
+    }
+}
+
+// Lookups currently being fetched, by cache key, with the channel their result will be sent on
+type InFlight = Arc<Mutex<HashMap<String, Arc<broadcast::Sender<String>>>>>;
+
+// Removes a lookup from `InFlight` when it completes or is cancelled. Dropping the sender without
+// sending tells the waiting lookups to fetch the documentation themselves.
+struct InFlightGuard {
+    in_flight: InFlight,
+    key: String,
+    sender: Arc<broadcast::Sender<String>>,
+}
+
+impl InFlightGuard {
+    fn finish(self, value: &str) {
+        self.remove();
+        let _ = self.sender.send(value.to_string());
+    }
+
+    fn remove(&self) {
+        let mut in_flight = self.in_flight.lock().unwrap();
+        // A later lookup may have registered itself under the same key already
+        if in_flight.get(&self.key).is_some_and(|sender| Arc::ptr_eq(sender, &self.sender)) {
+            in_flight.remove(&self.key);
+        }
+    }
+}
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        self.remove();
+    }
+}
+
+// Removes a key from the router's `refreshing` set when its background refresh ends, including
+// by panicking, so the entry can be refreshed again
+struct RefreshGuard {
+    refreshing: Arc<Mutex<HashSet<String>>>,
+    key: String,
+}
+
+impl Drop for RefreshGuard {
+    fn drop(&mut self) {
+        self.refreshing.lock().unwrap_or_else(|e| e.into_inner()).remove(&self.key);
+    }
+}
+
+#[derive(Clone)]
+pub struct CargoDocRouter {
+    pub client: Client,
+    pub cache: DocCache,
+    /// Where documentation pages are fetched from, `https://docs.rs` unless overridden
+    pub base_url: String,
+    /// Where crate metadata and search results are fetched from, `https://crates.io` unless overridden
+    pub crates_io_base_url: String,
+    /// Where the documentation of `STDLIB_CRATES` is fetched from, `https://doc.rust-lang.org`
+    /// unless overridden
+    pub stdlib_base_url: String,
+    /// How many times a request is retried after a transient network or server failure
+    pub max_retries: u8,
+    /// How long each request may take before it fails, including reading the response body
+    pub timeout: Duration,
+    /// Rejects requests for a while after repeated network or server failures
+    pub circuit_breaker: CircuitBreaker,
+    /// Sent as the User-Agent header of every request
+    pub user_agent: String,
+    /// Limits how many requests are started per second, if set
+    pub rate_limiter: Option<RateLimiter>,
+    /// Whether cached entries close to expiring are refetched in the background while the
+    /// cached value keeps being served
+    pub background_refresh_enabled: bool,
+    /// Whether an item that isn't found is looked for again under other capitalizations of its
+    /// name, e.g. `asyncread` as `AsyncRead`
+    pub case_insensitive_fallback: bool,
+    /// Locked dependency versions of the project loaded with `load_project_versions`, used by
+    /// `lookup_item_tool` when no version is given
+    pub project_versions: HashMap<String, String>,
+    // Limits how many requests are sent at once, if set
+    request_slots: Option<Arc<Semaphore>>,
+    // Proxy set with the builder, kept so that `with_timeout` can rebuild the client
+    proxy: Option<Proxy>,
+    in_flight: InFlight,
+    // Cache keys whose background refresh is running
+    refreshing: Arc<Mutex<HashSet<String>>>,
+}
+
+impl Default for CargoDocRouter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[tool(tool_box)]
+impl CargoDocRouter {
+    pub fn new() -> Self {
+        Self {
+            client: http_client(DEFAULT_TIMEOUT, None),
+            cache: DocCache::new(),
+            base_url: DOCS_RS_BASE_URL.to_string(),
+            crates_io_base_url: CRATES_IO_BASE_URL.to_string(),
+            stdlib_base_url: STDLIB_BASE_URL.to_string(),
+            max_retries: DEFAULT_MAX_RETRIES,
+            timeout: DEFAULT_TIMEOUT,
+            circuit_breaker: CircuitBreaker::default(),
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            rate_limiter: None,
+            background_refresh_enabled: true,
+            case_insensitive_fallback: true,
+            project_versions: HashMap::new(),
+            request_slots: None,
+            proxy: None,
+            in_flight: InFlight::default(),
+            refreshing: Arc::default(),
+        }
+    }
+
+    /// Start configuring a router, e.g.
+    /// `CargoDocRouter::builder().with_timeout(Duration::from_secs(5)).build()?`
+    pub fn builder() -> CargoDocRouterBuilder {
+        CargoDocRouterBuilder::default()
+    }
+
+    /// Create a router that stores cached documentation in a custom `CacheBackend`,
+    /// e.g. `NullBackend` to disable caching.
+    pub fn with_cache_backend(backend: impl CacheBackend + 'static) -> Self {
+        Self {
+            cache: DocCache::with_backend(backend),
+            ..Self::new()
+        }
+    }
+
+    /// Create a router whose cache is persisted to a sled database at `path`,
+    /// so documentation fetched in earlier sessions is served without a network round-trip.
+    pub fn new_with_persistent_cache(path: &Path) -> anyhow::Result<Self> {
+        let persistent = PersistentDocCache::open(path)?;
+        Ok(Self {
+            cache: DocCache::new().with_persistent_cache(persistent),
+            ..Self::new()
+        })
+    }
+
+    /// Look up the dependencies of the project at `manifest_path` in the versions its
+    /// `Cargo.lock` pins, via `cargo metadata`, whenever `lookup_item_tool` gets no version
+    pub fn load_project_versions(&mut self, manifest_path: &Path) -> anyhow::Result<()> {
+        self.project_versions = locked_versions(manifest_path)?;
+        Ok(())
+    }
+
+    /// Fetch documentation from `base_url` instead of docs.rs, e.g. a private mirror or a local
+    /// mock server in tests
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Fetch standard library documentation from `stdlib_base_url` instead of doc.rust-lang.org
+    pub fn with_stdlib_base_url(mut self, stdlib_base_url: impl Into<String>) -> Self {
+        self.stdlib_base_url = stdlib_base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Use the crates.io API at `crates_io_base_url` instead of crates.io
+    pub fn with_crates_io_base_url(mut self, crates_io_base_url: impl Into<String>) -> Self {
+        self.crates_io_base_url = crates_io_base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Retry transient failures up to `max_retries` times instead of `DEFAULT_MAX_RETRIES`;
+    /// 0 disables retries
+    pub fn with_max_retries(mut self, max_retries: u8) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Fail requests that take longer than `timeout` instead of `DEFAULT_TIMEOUT`.
+    /// Replaces `client` with one using the new timeout.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self.client = http_client(timeout, self.proxy.clone());
+        self
+    }
+
+    /// Use `circuit_breaker` instead of one opening after `DEFAULT_FAILURE_THRESHOLD` failures
+    pub fn with_circuit_breaker(mut self, circuit_breaker: CircuitBreaker) -> Self {
+        self.circuit_breaker = circuit_breaker;
+        self
+    }
+
+    #[tracing::instrument(skip(self), fields(cache_hit = tracing::field::Empty), err)]
+    #[tool(description = "Look up comprehensive documentation for a Rust crate (returns markdown). This tool fetches and converts the official docs.rs documentation into readable markdown format, providing a comprehensive overview of the crate's functionality, modules, and public API. The documentation includes the crate's features, modules, types, and functions. This is typically the first step in understanding a crate's capabilities. Example usage: To look up the latest documentation for tokio: `{\"name\": \"lookup_crate\", \"arguments\": {\"crate_name\": \"tokio\"}}`. To look up a specific version: `{\"name\": \"lookup_crate\", \"arguments\": {\"crate_name\": \"serde\", \"version\": \"1.0.152\"}}`. For standard library: `{\"name\": \"lookup_crate\", \"arguments\": {\"crate_name\": \"std\"}}`")]
+    async fn lookup_crate(
+        &self,
+        #[tool(param)]
+        #[schemars(description = "The name of the crate to look up. Must be the exact crate name as published on crates.io (e.g., 'serde', 'tokio', 'reqwest'). This parameter is case-sensitive and must match exactly how the crate is published. For standard library modules, use 'std' as the crate name.")]
+        crate_name: String,
+
+        #[tool(param)]
+        #[schemars(description = "The version of the crate (optional, defaults to latest). Provide a specific version string (e.g., '1.0.0', '0.11.2') to lookup documentation for that version instead of the latest. This is useful when working with codebases using older versions of a dependency, or to understand API changes between versions.")]
+        version: Option<String>,
+
+        #[tool(param)]
+        #[schemars(description = "Where to read the documentation from (optional, defaults to 'html'). 'html' converts the rendered docs.rs pages; 'json' reads the rustdoc JSON docs.rs publishes, which lists members more reliably but is only available for crates built since rustdoc JSON was published and when the server was built with the `rustdoc-json` feature.")]
+        format: Option<DocFormat>,
+
+        #[tool(param)]
+        #[schemars(description = "Maximum length of the returned documentation in characters (optional, defaults to no limit). Longer output is cut at the last paragraph break before the limit and ends with a note saying it was truncated.")]
+        max_length: Option<usize>,
+
+        #[tool(param)]
+        #[schemars(description = "Only return a short summary (optional, defaults to false): the first paragraph of the documentation with the crate's name, version and license. Use this to decide whether a crate is relevant before reading its full documentation.")]
+        summary_only: Option<bool>,
+
+        #[tool(param)]
+        #[schemars(description = "The target triple to read the documentation built for (optional, defaults to the crate's default docs.rs target), in the form <arch>-<vendor>-<os>[-<abi>], e.g. 'wasm32-unknown-unknown' or 'thumbv7em-none-eabihf'. Items only available on some platforms are only documented in builds for those targets. docs.rs only builds the targets a crate asks for; the standard library is documented for one target only. Only applies to format 'html'.")]
+        target: Option<String>,
+    ) -> Result<CallToolResult, McpError> {
+        let crate_name = CrateName::parse(&crate_name)?;
+        let version = parse_version(version)?;
+        let target = parse_target(&crate_name, target)?;
+        let doc = match format.unwrap_or_default() {
+            DocFormat::Html => self.crate_docs(&crate_name, version.as_deref(), target.as_deref()).await?,
+            DocFormat::Json => self.json_docs(&crate_name, None, version.as_deref()).await?,
+        };
+        let doc = if summary_only.unwrap_or(false) {
+            self.crate_summary(&crate_name, version.as_deref(), &doc).await
+        } else {
+            doc
+        };
+        let doc = match max_length {
+            Some(max_length) => truncate_markdown(&doc, max_length),
+            None => doc,
+        };
+        Ok(CallToolResult::success(vec![Content::text(doc)]))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    #[tracing::instrument(skip(self), fields(cache_hit = tracing::field::Empty), err)]
+    #[tool(
+        description = "Look up detailed documentation for a specific item in a Rust crate (returns markdown). This tool provides precise API documentation for structs, enums, traits, functions, or macros within a crate, showing method signatures, associated types, trait implementations, and other details. Use this when you need to understand a specific type's API, its methods, fields, or implementation details. Example usage: For the Vec type: `{\"name\": \"lookup_item_tool\", \"arguments\": {\"crate_name\": \"alloc\", \"item_path\": \"vec::Vec\"}}`. For a trait: `{\"name\": \"lookup_item_tool\", \"arguments\": {\"crate_name\": \"tokio\", \"item_path\": \"io::AsyncRead\", \"version\": \"1.28.0\"}}`. For a function: `{\"name\": \"lookup_item_tool\", \"arguments\": {\"crate_name\": \"reqwest\", \"item_path\": \"get\"}}`. For standard lib: `{\"name\": \"lookup_item_tool\", \"arguments\": {\"crate_name\": \"std\", \"item_path\": \"fs::File\"}}`"
+    )]
+    async fn lookup_item_tool(
+        &self,
+        #[tool(param)]
+        #[schemars(description = "The name of the crate where the item is defined. Must be the exact crate name as published on crates.io (e.g., 'serde', 'tokio'). For standard library types, use 'std', or 'core' and 'alloc' for no_std code (e.g. 'core' for core::option::Option, 'alloc' for alloc::vec::Vec). Case-sensitive and must match exactly how the crate is published.")]
+        crate_name: String,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Full path to the item using double-colon notation (e.g., 'vec::Vec', 'serde::Serialize', 'tokio::io::AsyncRead'). You can include or omit the crate prefix - it will be automatically handled. For nested types, include the full path (e.g., 'http::response::Builder'). The tool will automatically detect if the item is a struct, enum, trait, function, or macro."
+        )]
+        item_path: String,
+
+        #[tool(param)]
+        #[schemars(description = "The version of the crate (optional, defaults to latest). Provide a specific version string (e.g., '1.0.0', '0.11.2') to lookup documentation for that version instead of the latest. Useful when working with a specific version of a dependency.")]
+        version: Option<String>,
+
+        #[tool(param)]
+        #[schemars(description = "Where to read the documentation from (optional, defaults to 'html'). 'html' converts the rendered docs.rs pages; 'json' reads the rustdoc JSON docs.rs publishes, which lists members more reliably but is only available for crates built since rustdoc JSON was published and when the server was built with the `rustdoc-json` feature.")]
+        format: Option<DocFormat>,
+
+        #[tool(param)]
+        #[schemars(description = "Only return one section of the documentation (optional), named by its heading, e.g. 'Examples', 'Fields', 'Implementations', 'Methods' or 'Trait Implementations'. Matched case-insensitively; 'Methods' also finds the 'Implementations' section of rendered pages. If the item has no such section, the error lists the sections it has.")]
+        section: Option<String>,
+
+        #[tool(param)]
+        #[schemars(description = "Maximum length of the returned documentation in characters (optional, defaults to no limit). Longer output is cut at the last paragraph break before the limit and ends with a note saying it was truncated.")]
+        max_length: Option<usize>,
+
+        #[tool(param)]
+        #[schemars(description = "For traits, replace the Implementors section with a plain list of the implementing types (optional, defaults to false). Only applies to format 'html'.")]
+        include_implementors: Option<bool>,
+
+        #[tool(param)]
+        #[schemars(description = "How many implementors include_implementors lists (optional, defaults to 20). Use find_trait_implementors for the full list.")]
+        max_implementors: Option<u32>,
+
+        #[tool(param)]
+        #[schemars(description = "Cargo features your project enables for the crate (optional, e.g. ['full']). They don't change the documentation, which is always the crate's default docs.rs build; for items only available with some features, the output adds a note saying whether these features, the default ones and the features they enable turn them on. Only applies to format 'html'.")]
+        features: Option<Vec<String>>,
+
+        #[tool(param)]
+        #[schemars(description = "The target triple to read the documentation built for (optional, defaults to the crate's default docs.rs target), in the form <arch>-<vendor>-<os>[-<abi>], e.g. 'wasm32-unknown-unknown' or 'thumbv7em-none-eabihf'. Items only available on some platforms are only documented in builds for those targets. docs.rs only builds the targets a crate asks for; the standard library is documented for one target only. Only applies to format 'html'.")]
+        target: Option<String>,
+    ) -> Result<CallToolResult, McpError> {
+        let crate_name = CrateName::parse(&crate_name)?;
+        let version = parse_version(version)?;
+        let item_path = ItemPath::parse(&crate_name, &item_path)?;
+        let target = parse_target(&crate_name, target)?;
+        let version = version.or_else(|| self.project_version(&crate_name));
+        let doc = match format.unwrap_or_default() {
+            DocFormat::Html => {
+                let mut item_doc = self
+                    .lookup_item_doc(crate_name.clone(), item_path, version.clone(), target)
+                    .await?;
+                if let (Some(features), Some((required, any))) = (&features, item_doc.required_features()) {
+                    let feature_map = self.crate_feature_map(&crate_name, version).await?;
+                    let note = feature_availability(&required, any, &enabled_features(features, &feature_map));
+                    match item_doc.sections.get_mut(DESCRIPTION_SECTION) {
+                        Some(description) => *description = format!("{}\n\n{}", note, description),
+                        None => {
+                            item_doc.sections.shift_insert(0, DESCRIPTION_SECTION.to_string(), note);
+                        }
+                    }
+                }
+                if include_implementors.unwrap_or(false) {
+                    let max_implementors = max_implementors.unwrap_or(DEFAULT_MAX_IMPLEMENTORS) as usize;
+                    if let Some(section) = item_doc.sections.get_mut("Implementors") {
+                        *section = implementor_list(section, max_implementors);
+                    }
+                }
+                item_doc.to_markdown()
+            }
+            DocFormat::Json => self.json_docs(&crate_name, Some(&item_path), version.as_deref()).await?,
+        };
+        let doc = match section {
+            Some(section) => doc_section(&doc, &section)?,
+            None => doc,
+        };
+        let doc = match max_length {
+            Some(max_length) => truncate_markdown(&doc, max_length),
+            None => doc,
+        };
+        Ok(CallToolResult::success(vec![Content::text(doc)]))
+    }
+
+    #[tool(description = "Look up the documentation for a function in a Rust crate (returns markdown). Unlike lookup_item_tool, which tries several item kinds in turn, this tool goes straight to the function's page, so use it whenever you know the item is a free function. If there is no function with that path, it falls back to the same search lookup_item_tool does and says so in the output. Example usage: `{\"name\": \"lookup_function\", \"arguments\": {\"crate_name\": \"tokio\", \"item_path\": \"task::spawn\"}}`. For standard library: `{\"name\": \"lookup_function\", \"arguments\": {\"crate_name\": \"std\", \"item_path\": \"mem::swap\"}}`")]
+    async fn lookup_function(
+        &self,
+        #[tool(param)]
+        #[schemars(description = "The name of the crate where the function is defined. Must be the exact crate name as published on crates.io (e.g., 'serde_json', 'tokio'). For standard library functions, use 'std'. Case-sensitive and must match exactly how the crate is published.")]
+        crate_name: String,
+
+        #[tool(param)]
+        #[schemars(description = "Full path to the function using double-colon notation (e.g., 'task::spawn', 'mem::swap', 'from_str'). You can include or omit the crate prefix. Methods are documented on their type's page, so look those up with lookup_item_tool instead.")]
+        item_path: String,
+
+        #[tool(param)]
+        #[schemars(description = "The version of the crate (optional, defaults to latest). Provide a specific version string (e.g., '1.0.0', '0.11.2') to lookup documentation for that version instead of the latest.")]
+        version: Option<String>,
+    ) -> Result<CallToolResult, McpError> {
+        let crate_name = CrateName::parse(&crate_name)?;
+        let version = parse_version(version)?;
+        let item_path = ItemPath::parse(&crate_name, &item_path)?;
+        let version = version.or_else(|| self.project_version(&crate_name));
+
+        // An item looked up before is served from the cache whatever its kind; otherwise the
+        // function's page is tried before the other kinds
+        let doc_key = item_cache_key(&crate_name, &item_path, version.as_deref(), None);
+        let item_doc = if self.cache.contains(&format!("item_doc:{}", doc_key)).await {
+            self.lookup_item_doc(crate_name, item_path.clone(), version, None).await?
+        } else {
+            let url = self.item_page_url(
+                &crate_name,
+                &item_path.module_path(),
+                version.as_deref(),
+                ItemKind::Fn.page_prefix(),
+                item_path.item_name(),
+                None,
+            );
+            match self.fetch_page(&url).await {
+                Ok((url, html_body)) => {
+                    let item_doc = ItemDoc::from_page(&crate_name, &item_path, version.as_deref(), &url, &html_body);
+                    self.cache_item_doc(&doc_key, &item_doc).await?;
+                    item_doc
+                }
+                Err(e) if e.is_not_found() => self.lookup_item_doc(crate_name, item_path.clone(), version, None).await?,
+                Err(e) => return Err(e.into()),
+            }
+        };
+
+        let doc = match item_doc.kind {
+            ItemKind::Fn => item_doc.to_markdown(),
+            kind => format!(
+                "> Note: `{}` is {}, not a function, so other item kinds were tried instead.\n\n{}",
+                item_path,
+                kind.description(),
+                item_doc.to_markdown()
+            ),
+        };
+        Ok(CallToolResult::success(vec![Content::text(doc)]))
+    }
+
+    #[tool(description = "Look up the documentation for a trait in a Rust crate (returns markdown). Goes straight to the trait's page and starts the output with its Required Methods, which every implementation must provide, and its Provided Methods, which come with a default implementation, followed by the full trait documentation. Use this tool instead of lookup_item_tool when you know the item is a trait, for example before implementing it. Example usage: `{\"name\": \"lookup_trait\", \"arguments\": {\"crate_name\": \"serde\", \"item_path\": \"Serialize\"}}`. For standard library: `{\"name\": \"lookup_trait\", \"arguments\": {\"crate_name\": \"std\", \"item_path\": \"fmt::Display\"}}`")]
+    async fn lookup_trait(
+        &self,
+        #[tool(param)]
+        #[schemars(description = "The name of the crate where the trait is defined. Must be the exact crate name as published on crates.io (e.g., 'serde', 'tokio'). For standard library traits, use 'std'. Case-sensitive and must match exactly how the crate is published.")]
+        crate_name: String,
+
+        #[tool(param)]
+        #[schemars(description = "Full path to the trait using double-colon notation (e.g., 'Serialize', 'fmt::Display', 'io::AsyncRead'). You can include or omit the crate prefix. Fails with a not-found error if the path does not name a trait.")]
+        item_path: String,
+
+        #[tool(param)]
+        #[schemars(description = "The version of the crate (optional, defaults to latest). Provide a specific version string (e.g., '1.0.0', '0.11.2') to lookup documentation for that version instead of the latest.")]
+        version: Option<String>,
+    ) -> Result<CallToolResult, McpError> {
+        let crate_name = CrateName::parse(&crate_name)?;
+        let version = parse_version(version)?;
+        let item_path = ItemPath::parse(&crate_name, &item_path)?;
+        let version = version.or_else(|| self.project_version(&crate_name));
+        let cache_key = format!("trait:{}", item_cache_key(&crate_name, &item_path, version.as_deref(), None));
+        if let Some(doc) = self.cache.get(&cache_key).await {
+            return Ok(CallToolResult::success(vec![Content::text(doc)]));
+        }
+
+        let (url, html_body) = self
+            .fetch_item_page(&crate_name, &item_path, version.as_deref(), ItemKind::Trait)
+            .await?;
+
+        let (required, provided) = parse_trait_methods(&html_body);
+        let doc = format!(
+            "# Trait `{}::{}`\n\n{}---\n\n{}",
+            crate_name,
+            item_path,
+            format_trait_methods(&required, &provided),
+            page_to_markdown(&html_body, &url)
+        );
+        self.cache.set(cache_key, doc.clone()).await;
+
+        Ok(CallToolResult::success(vec![Content::text(doc)]))
+    }
+
+    #[tool(description = "Look up the documentation for an enum in a Rust crate (returns markdown). Goes straight to the enum's page and starts the output with a Variants section listing each variant's declaration, one-line summary and named fields, followed by the full enum documentation with its methods and trait implementations. Use this tool instead of lookup_item_tool when you know the item is an enum, for example to write an exhaustive match. Example usage: `{\"name\": \"lookup_enum\", \"arguments\": {\"crate_name\": \"std\", \"item_path\": \"io::ErrorKind\"}}`. For a crate enum: `{\"name\": \"lookup_enum\", \"arguments\": {\"crate_name\": \"serde_json\", \"item_path\": \"Value\"}}`")]
+    async fn lookup_enum(
+        &self,
+        #[tool(param)]
+        #[schemars(description = "The name of the crate where the enum is defined. Must be the exact crate name as published on crates.io (e.g., 'serde_json', 'tokio'). For standard library enums, use 'std'. Case-sensitive and must match exactly how the crate is published.")]
+        crate_name: String,
+
+        #[tool(param)]
+        #[schemars(description = "Full path to the enum using double-colon notation (e.g., 'Value', 'io::ErrorKind', 'cmp::Ordering'). You can include or omit the crate prefix. Fails with a not-found error if the path does not name an enum.")]
+        item_path: String,
+
+        #[tool(param)]
+        #[schemars(description = "The version of the crate (optional, defaults to latest). Provide a specific version string (e.g., '1.0.0', '0.11.2') to lookup documentation for that version instead of the latest.")]
+        version: Option<String>,
+    ) -> Result<CallToolResult, McpError> {
+        let crate_name = CrateName::parse(&crate_name)?;
+        let version = parse_version(version)?;
+        let item_path = ItemPath::parse(&crate_name, &item_path)?;
+        let version = version.or_else(|| self.project_version(&crate_name));
+        let cache_key = format!("enum:{}", item_cache_key(&crate_name, &item_path, version.as_deref(), None));
+        if let Some(doc) = self.cache.get(&cache_key).await {
+            return Ok(CallToolResult::success(vec![Content::text(doc)]));
+        }
+
+        let (url, html_body) = self
+            .fetch_item_page(&crate_name, &item_path, version.as_deref(), ItemKind::Enum)
+            .await?;
+
+        let doc = format!(
+            "# Enum `{}::{}`\n\n{}---\n\n{}",
+            crate_name,
+            item_path,
+            format_enum_variants(&parse_enum_variants(&html_body)),
+            page_to_markdown(&html_body, &url)
+        );
+        self.cache.set(cache_key, doc.clone()).await;
+
+        Ok(CallToolResult::success(vec![Content::text(doc)]))
+    }
+
+    #[tool(description = "Look up the documentation for a macro in a Rust crate (returns markdown). Handles `macro_rules!` macros as well as derive and attribute procedural macros: the macro page is tried first, then the derive macro and attribute macro pages. Use this tool instead of lookup_item_tool when you know the item is a macro. Example usage: `{\"name\": \"lookup_macro\", \"arguments\": {\"crate_name\": \"tokio\", \"item_path\": \"select!\"}}`. Derive macro: `{\"name\": \"lookup_macro\", \"arguments\": {\"crate_name\": \"serde\", \"item_path\": \"Serialize\"}}`. Attribute macro: `{\"name\": \"lookup_macro\", \"arguments\": {\"crate_name\": \"tokio\", \"item_path\": \"main\"}}`")]
+    async fn lookup_macro(
+        &self,
+        #[tool(param)]
+        #[schemars(description = "The name of the crate where the macro is defined. Must be the exact crate name as published on crates.io (e.g., 'tokio', 'serde'). For standard library macros, use 'std'. Case-sensitive and must match exactly how the crate is published.")]
+        crate_name: String,
+
+        #[tool(param)]
+        #[schemars(description = "Path to the macro using double-colon notation, with or without the trailing `!` (e.g., 'vec!', 'select', 'Serialize'). Works for `macro_rules!` macros, derive macros such as serde's `Serialize`, and attribute macros such as `tokio::main`. You can include or omit the crate prefix.")]
+        item_path: String,
+
+        #[tool(param)]
+        #[schemars(description = "The version of the crate (optional, defaults to latest). Provide a specific version string (e.g., '1.0.0', '0.11.2') to lookup documentation for that version instead of the latest.")]
+        version: Option<String>,
+    ) -> Result<CallToolResult, McpError> {
+        let crate_name = CrateName::parse(&crate_name)?;
+        let version = parse_version(version)?;
+        let item_path = ItemPath::parse(&crate_name, item_path.trim_end_matches('!'))?;
+        let version = version.or_else(|| self.project_version(&crate_name));
+        let cache_key = format!("macro:{}", item_cache_key(&crate_name, &item_path, version.as_deref(), None));
+        if let Some(doc) = self.cache.get(&cache_key).await {
+            return Ok(CallToolResult::success(vec![Content::text(doc)]));
+        }
+
+        // A derive macro usually shares its name with the trait it implements, so try the
+        // macro page before the derive page, and attribute macros last
+        for kind in [ItemKind::Macro, ItemKind::Derive, ItemKind::Attr] {
+            let (url, html_body) = match self
+                .fetch_item_page(&crate_name, &item_path, version.as_deref(), kind)
+                .await
+            {
+                Ok(page) => page,
+                Err(e) if e.code == ErrorCode::RESOURCE_NOT_FOUND => continue,
+                Err(e) => return Err(e),
+            };
+
+            let label = match kind {
+                ItemKind::Derive => "Derive macro",
+                ItemKind::Attr => "Attribute macro",
+                _ => "Macro",
+            };
+            let doc = format!(
+                "# {} `{}::{}`\n\n{}",
+                label,
+                crate_name,
+                item_path,
+                page_to_markdown(&html_body, &url)
+            );
+            self.cache.set(cache_key, doc.clone()).await;
+            return Ok(CallToolResult::success(vec![Content::text(doc)]));
+        }
+
+        Err(McpError::resource_not_found(
+            format!(
+                "`{}` is not a macro, derive macro or attribute macro in {}. Check the path with lookup_module, or use lookup_item_tool for other kinds of items.",
+                item_path, crate_name
+            ),
+            None,
+        ))
+    }
+
+    #[tool(description = "Look up the documentation for a module within a Rust crate (returns markdown). This tool fetches a module's index page from docs.rs, including its overview prose and the list of items it contains. Use this after lookup_crate to navigate deeper into a crate's API, for example to see what synchronization primitives tokio::sync offers before looking up a specific one. Example usage: `{\"name\": \"lookup_module\", \"arguments\": {\"crate_name\": \"tokio\", \"module_path\": \"sync\"}}`. Nested module: `{\"name\": \"lookup_module\", \"arguments\": {\"crate_name\": \"std\", \"module_path\": \"collections::hash_map\"}}`. Specific version: `{\"name\": \"lookup_module\", \"arguments\": {\"crate_name\": \"tokio\", \"module_path\": \"io\", \"version\": \"1.28.0\"}}`")]
+    async fn lookup_module(
+        &self,
+        #[tool(param)]
+        #[schemars(description = "The name of the crate containing the module. Must be the exact crate name as published on crates.io (e.g., 'tokio', 'serde'). For standard library modules, use 'std'. Case-sensitive and must match exactly how the crate is published.")]
+        crate_name: String,
+
+        #[tool(param)]
+        #[schemars(description = "Path to the module using double-colon notation, relative to the crate root (e.g., 'sync', 'collections', 'collections::hash_map', 'io::util'). You can include or omit the crate prefix - it will be automatically handled.")]
+        module_path: String,
+
+        #[tool(param)]
+        #[schemars(description = "The version of the crate (optional, defaults to latest). Provide a specific version string (e.g., '1.0.0', '0.11.2') to look up the module as it was in that version.")]
+        version: Option<String>,
+    ) -> Result<CallToolResult, McpError> {
+        let crate_name = CrateName::parse(&crate_name)?;
+        let version = parse_version(version)?;
+        // Check cache first
+        let module = parse_module_path(&crate_name, &module_path)?;
+        let cache_key = if let Some(ver) = &version {
+            format!("module:{}:{}:{}", crate_name, ver, module)
+        } else {
+            format!("module:{}:{}", crate_name, module)
+        };
+
+        if let Some(doc) = self.cache.get(&cache_key).await {
+            return Ok(CallToolResult::success(vec![Content::text(doc)]));
+        }
+
+        let url = self.module_index_url(&crate_name, &module, version.as_deref());
+        let html_body = self
+            .fetch_text(&url)
+            .await
+            .map_err(|e| e.or_not_found(&crate_name, Some(&module.to_string())))?;
+
+        // Convert HTML to markdown
+        let markdown_body = page_to_markdown(&html_body, &url);
+
+        // Cache the markdown result
+        self.cache.set(cache_key, markdown_body.clone()).await;
+
+        Ok(CallToolResult::success(vec![Content::text(markdown_body)]))
+    }
+
+    #[tool(description = "List the public items of a module in a Rust crate (returns JSON). Parses the module's docs.rs index page and returns an array of objects with each item's name, kind (struct, enum, trait, fn, macro, mod, type, constant, ...) and one-line summary. Use this instead of lookup_module when you only need to know what a module contains, e.g. to pick the right type before calling lookup_item_tool. Example usage: `{\"name\": \"list_module_items\", \"arguments\": {\"crate_name\": \"std\", \"module_path\": \"collections\"}}` returns entries like `{\"name\": \"HashMap\", \"kind\": \"struct\", \"brief\": \"A hash map implemented with quadratic probing and SIMD lookup.\"}`. For a crate module: `{\"name\": \"list_module_items\", \"arguments\": {\"crate_name\": \"tokio\", \"module_path\": \"sync\"}}`")]
+    async fn list_module_items(
+        &self,
+        #[tool(param)]
+        #[schemars(description = "The name of the crate containing the module. Must be the exact crate name as published on crates.io (e.g., 'tokio', 'serde'). For standard library modules, use 'std'. Case-sensitive and must match exactly how the crate is published.")]
+        crate_name: String,
+
+        #[tool(param)]
+        #[schemars(description = "Path to the module using double-colon notation, relative to the crate root (e.g., 'sync', 'collections', 'collections::hash_map'). You can include or omit the crate prefix - it will be automatically handled.")]
+        module_path: String,
+
+        #[tool(param)]
+        #[schemars(description = "The version of the crate (optional, defaults to latest). Provide a specific version string (e.g., '1.0.0', '0.11.2') to list the module's items as they were in that version.")]
+        version: Option<String>,
+    ) -> Result<CallToolResult, McpError> {
+        let crate_name = CrateName::parse(&crate_name)?;
+        let version = parse_version(version)?;
+        // Check cache first
+        let module = parse_module_path(&crate_name, &module_path)?;
+        let cache_key = if let Some(ver) = &version {
+            format!("module_items:{}:{}:{}", crate_name, ver, module)
+        } else {
+            format!("module_items:{}:{}", crate_name, module)
+        };
+
+        if let Some(items) = self.cache.get(&cache_key).await {
+            return Ok(CallToolResult::success(vec![Content::text(items)]));
+        }
+
+        let url = self.module_index_url(&crate_name, &module, version.as_deref());
+        let html_body = self
+            .fetch_text(&url)
+            .await
+            .map_err(|e| e.or_not_found(&crate_name, Some(&module.to_string())))?;
+
+        let items = parse_module_items(&html_body);
+        let json = serde_json::to_string_pretty(&items).map_err(DocError::from)?;
+
+        self.cache.set(cache_key, json.clone()).await;
+
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    #[tool(description = "Find the full path of an item when only its name is known (returns JSON). Searches the crate's index of all items (its docs.rs `all.html` page) for items with that name and returns an array of objects with each match's full path and kind, e.g. `[{\"path\": \"tokio::sync::Mutex\", \"kind\": \"struct\"}]`. Several matches are returned when the name is used in more than one module. Use this tool before lookup_item_tool when you know a type's name but not its module. Example usage: `{\"name\": \"get_full_module_path\", \"arguments\": {\"crate_name\": \"tokio\", \"item_name\": \"Mutex\"}}`. For the standard library: `{\"name\": \"get_full_module_path\", \"arguments\": {\"crate_name\": \"std\", \"item_name\": \"String\"}}`")]
+    async fn get_full_module_path(
+        &self,
+        #[tool(param)]
+        #[schemars(description = "The name of the crate to search. Must be the exact crate name as published on crates.io (e.g., 'tokio', 'serde'). For standard library items, use 'std'. Case-sensitive and must match exactly how the crate is published.")]
+        crate_name: String,
+
+        #[tool(param)]
+        #[schemars(description = "The unqualified name of the item (e.g., 'Mutex', 'String', 'spawn'). A qualified path such as 'sync::Mutex' is reduced to its last segment. Case-sensitive.")]
+        item_name: String,
+
+        #[tool(param)]
+        #[schemars(description = "The version of the crate (optional, defaults to latest). Provide a specific version string (e.g., '1.0.0', '0.11.2') to search the items of that version.")]
+        version: Option<String>,
+    ) -> Result<CallToolResult, McpError> {
+        let crate_name = CrateName::parse(&crate_name)?;
+        let version = parse_version(version)?;
+        let (_, item_name) = normalize_item_path(&crate_name, &item_name);
+        let item_name = item_name.as_str();
+        let url = format!("{}/all.html", self.crate_docs_root(&crate_name, version.as_deref(), None));
+
+        let matches: Vec<IndexedItem> = self
+            .all_items(&crate_name, version.as_deref())
+            .await?
+            .into_iter()
+            .filter(|item| item.path.rsplit("::").next() == Some(item_name))
+            .map(|item| IndexedItem {
+                path: format!("{}::{}", crate_name.replace('-', "_"), item.path),
+                kind: item.kind,
+            })
+            .collect();
+        if matches.is_empty() {
+            return Err(DocError::NotFound {
+                crate_name: crate_name.to_string(),
+                item_path: Some(item_name.to_string()),
+                tried_urls: vec![url],
+            }
+            .into());
+        }
+
+        let json = serde_json::to_string_pretty(&matches).map_err(DocError::from)?;
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    #[tracing::instrument(skip(self), err)]
+    #[tool(
+        name = "prefetch_crate",
+        description = "Warm the documentation cache for a crate before looking up its items (returns markdown). Fetches the crate's documentation, then looks up its first types listed at the crate root (structs, then traits, then enums) in the background, so that following lookup_item_tool calls for them are answered from the cache. Returns at once with the list of items being fetched. Use this right after lookup_crate when you expect to read several of a crate's types. Example usage: `{\"name\": \"prefetch_crate\", \"arguments\": {\"crate_name\": \"reqwest\"}}`. More items: `{\"name\": \"prefetch_crate\", \"arguments\": {\"crate_name\": \"tokio\", \"top_n\": 20}}`"
+    )]
+    async fn prefetch_crate_tool(
+        &self,
+        #[tool(param)]
+        #[schemars(description = "The name of the crate to prefetch. Must be the exact crate name as published on crates.io (e.g., 'tokio', 'serde'). For the standard library, use 'std'.")]
+        crate_name: String,
+
+        #[tool(param)]
+        #[schemars(description = "The version of the crate (optional, defaults to latest). Prefetch the version you will look items up in, since each version is cached separately.")]
+        version: Option<String>,
+
+        #[tool(param)]
+        #[schemars(description = "How many types to prefetch (optional, defaults to 10).")]
+        top_n: Option<usize>,
+    ) -> Result<CallToolResult, McpError> {
+        let version = parse_version(version)?;
+        let top_n = top_n.unwrap_or(DEFAULT_PREFETCH_ITEMS);
+        let paths = self.prefetch_crate(&crate_name, version.as_deref(), top_n).await?;
+
+        let mut output = format!("Prefetching the documentation of these `{}` items in the background:\n\n", crate_name);
+        for path in &paths {
+            output.push_str(&format!("- `{}`\n", path));
+        }
+        Ok(CallToolResult::success(vec![Content::text(output)]))
+    }
+
+    #[tracing::instrument(skip(self), err)]
+    #[tool(description = "Look up the standard library's documentation of a Rust keyword (returns markdown), such as `async`, `dyn`, `impl`, `match`, `move` or `unsafe`. Keywords are documented on pages of their own at doc.rust-lang.org, which this tool fetches directly. Example usage: `{\"name\": \"lookup_keyword\", \"arguments\": {\"keyword\": \"dyn\"}}`. For a Rust release: `{\"name\": \"lookup_keyword\", \"arguments\": {\"keyword\": \"async\", \"version\": \"1.75.0\"}}`")]
+    async fn lookup_keyword(
+        &self,
+        #[tool(param)]
+        #[schemars(description = "The keyword to look up, e.g. 'async', 'dyn', 'impl', 'match', 'move' or 'unsafe'.")]
+        keyword: String,
+
+        #[tool(param)]
+        #[schemars(description = "The Rust release whose documentation to read (optional, defaults to the latest stable release), e.g. '1.75.0'.")]
+        version: Option<String>,
+    ) -> Result<CallToolResult, McpError> {
+        let keyword = keyword.trim();
+        if !RUST_KEYWORDS.contains(&keyword) {
+            return Err(DocError::InvalidInput(format!(
+                "`{}` is not a keyword documented by the standard library; documented keywords are {}",
+                keyword,
+                RUST_KEYWORDS.join(", ")
+            ))
+            .into());
+        }
+        let crate_name = CrateName::parse("std")?;
+        let item_path = ItemPath::parse(&crate_name, keyword)?;
+        let version = parse_version(version)?;
+        let doc = self.lookup_item_doc(crate_name, item_path, version, None).await?;
+        Ok(CallToolResult::success(vec![Content::text(doc.to_markdown())]))
+    }
+
+    #[tracing::instrument(skip(self), err)]
+    #[tool(
+        name = "batch_lookup_items",
+        description = "Look up the documentation of several items of one crate at once (returns markdown). Fetches up to 10 items concurrently and returns one document with a section per item, headed by its path and separated by `---`. An item that can't be found gets a note in its section instead of failing the whole call. Use this instead of several lookup_item_tool calls when you need a group of related items, such as a builder and its output type or an error type and the types it wraps. Example usage: `{\"name\": \"batch_lookup_items\", \"arguments\": {\"crate_name\": \"reqwest\", \"item_paths\": [\"Client\", \"ClientBuilder\", \"Error\"]}}`"
+    )]
+    async fn batch_lookup_items(
+        &self,
+        #[tool(param)]
+        #[schemars(description = "The name of the crate the items belong to. Must be the exact crate name as published on crates.io (e.g., 'tokio', 'serde'). For the standard library, use 'std'.")]
+        crate_name: String,
+
+        #[tool(param)]
+        #[schemars(description = "Paths of the items to look up, at most 10 (e.g., ['Client', 'header::HeaderMap']). Paths may start with the crate name.")]
+        item_paths: Vec<String>,
+
+        #[tool(param)]
+        #[schemars(description = "The version of the crate (optional, defaults to latest).")]
+        version: Option<String>,
+    ) -> Result<CallToolResult, McpError> {
+        let crate_name = CrateName::parse(&crate_name)?;
+        let version = parse_version(version)?;
+        if item_paths.is_empty() || item_paths.len() > MAX_BATCH_ITEMS {
+            return Err(DocError::InvalidInput(format!(
+                "item_paths must list between 1 and {} items, got {}",
+                MAX_BATCH_ITEMS,
+                item_paths.len()
+            ))
+            .into());
+        }
+        let item_paths = item_paths
+            .iter()
+            .map(|path| ItemPath::parse(&crate_name, path))
+            .collect::<Result<Vec<_>, _>>()?;
+        let version = version.or_else(|| self.project_version(&crate_name));
+
+        let docs = join_all(
+            item_paths
+                .iter()
+                .map(|path| self.lookup_item_doc(crate_name.clone(), path.clone(), version.clone(), None)),
+        )
+        .await;
+        let sections: Vec<String> = item_paths
+            .iter()
+            .zip(docs)
+            .map(|(path, doc)| match doc {
+                Ok(doc) => format!("# `{}::{}`\n\n{}", crate_name, path, doc.to_markdown().trim_end()),
+                Err(e) => format!("# `{}::{}`\n\n_Lookup failed: {}_", crate_name, path, e),
+            })
+            .collect();
+        Ok(CallToolResult::success(vec![Content::text(sections.join("\n\n---\n\n"))]))
+    }
+
+    #[tracing::instrument(skip(self), fields(cache_hit = tracing::field::Empty), err)]
+    #[tool(description = "Search for Rust crates on crates.io (returns markdown). This tool helps you discover relevant Rust libraries for specific functionality by searching the official crates.io registry. Use this tool when you need to find crates that implement a particular feature, or when you're looking for alternatives to a known crate. Results include crate names, descriptions, download statistics, creation dates, and documentation links. Example usage: Basic search: `{\"name\": \"search_crates\", \"arguments\": {\"query\": \"http client\"}}`. Search with limit: `{\"name\": \"search_crates\", \"arguments\": {\"query\": \"json serialization\", \"limit\": 20}}`. Specific feature search: `{\"name\": \"search_crates\", \"arguments\": {\"query\": \"async database\", \"limit\": 5}}`. Alternatives to a known crate: `{\"name\": \"search_crates\", \"arguments\": {\"query\": \"serde alternatives\"}}`. Most downloaded first: `{\"name\": \"search_crates\", \"arguments\": {\"query\": \"logging\", \"sort\": \"downloads\"}}`. Within a category: `{\"name\": \"search_crates\", \"arguments\": {\"query\": \"client\", \"category\": \"web-programming\"}}`. Next page of results: `{\"name\": \"search_crates\", \"arguments\": {\"query\": \"web\", \"limit\": 20, \"page\": 2}}`")]
+    async fn search_crates(
+        &self,
+        #[tool(param)]
+        #[schemars(description = "The search query for finding crates. Can be a keyword, functionality description, or partial crate name. For best results, use specific terms that describe the functionality you need (e.g., 'http client', 'serde json', 'async runtime', 'command line parser'). You can also search for a specific crate by name to find similar alternatives.")]
+        query: String,
+
+        #[tool(param)]
+        #[schemars(
+            description = "Maximum number of results to return (optional, defaults to 10, max 100). Increase this value for broader searches where you need to compare multiple options or when searching for a less common functionality. A value between 5-20 is recommended for most searches to get a good overview of available options."
+        )]
+        limit: Option<u32>,
+
+        #[tool(param)]
+        #[schemars(description = "How to order the results (optional, defaults to crates.io's relevance ranking). One of 'downloads' (all-time downloads), 'recent-downloads' (downloads in the last 90 days), 'recent-updates' (most recently published), 'new-crates' (newest crates first), or 'alphabetical'. Use 'downloads' to find the most established crates or 'recent-updates' to find actively maintained ones.")]
+        sort: Option<SearchSort>,
+
+        #[tool(param)]
+        #[schemars(description = "Only return crates in this crates.io category (optional). Must be a category slug as listed on https://crates.io/categories, e.g. 'web-programming', 'web-programming::http-client', 'network-programming', 'parser-implementations', 'command-line-utilities', 'asynchronous', 'encoding', 'cryptography', 'database', or 'development-tools::testing'.")]
+        category: Option<String>,
 
-// New: Structure for code examples
-#[derive(Clone, Debug, PartialEq, Eq)]
-pub struct CodeExample {
-    pub title: String,
-    pub code: String,
-    pub description: String,
-}
+        #[tool(param)]
+        #[schemars(description = "Only return crates tagged with this keyword (optional). Keywords are the free-form tags crate authors publish with, e.g. 'http', 'json', 'async', 'cli', 'serialization'. Combine with a broad query to narrow results to crates that explicitly advertise a feature.")]
+        keyword: Option<String>,
 
-impl Default for DocCache {
-    fn default() -> Self {
-        Self::new()
+        #[tool(param)]
+        #[schemars(description = "Page of results to return, starting at 1 (optional, defaults to 1). Each page holds `limit` results; the output header shows the current page, the number of pages, and the total number of matching crates. Use this to look beyond the first page of a broad search.")]
+        page: Option<u32>,
+    ) -> Result<CallToolResult, McpError> {
+        let limit = limit.unwrap_or(10).clamp(1, 100); // Cap at 100 results
+        let page = page.unwrap_or(1).max(1);
+
+        let url = crates_io_search_url(
+            &self.crates_io_base_url,
+            &query,
+            limit,
+            sort,
+            category.as_deref(),
+            keyword.as_deref(),
+            Some(page),
+        );
+
+        let output = match self.fetch_crate_search(&url).await? {
+            SearchResponse::Results(response) => format_search_results(&query, &response, page, limit),
+            SearchResponse::Page(markdown) => markdown,
+        };
+        Ok(CallToolResult::success(vec![Content::text(output)]))
     }
-}
 
-impl DocCache {
-    pub fn new() -> Self {
-        Self {
-            cache: Arc::new(Mutex::new(HashMap::new())),
-            examples_cache: Arc::new(Mutex::new(HashMap::new())),
+    /// Search crates.io and return the matching crates ordered by relevance.
+    pub async fn search_crates_structured(
+        &self,
+        query: &str,
+        limit: u32,
+    ) -> anyhow::Result<Vec<CrateSearchResult>> {
+        let url = crates_io_search_url(&self.crates_io_base_url, query, limit.min(100), None, None, None, None);
+        match self.fetch_crate_search(&url).await? {
+            SearchResponse::Results(response) => Ok(response.crates),
+            SearchResponse::Page(_) => Err(DocError::Parse(
+                "crates.io returned a page instead of JSON search results".to_string(),
+            )
+            .into()),
         }
     }
 
-    pub async fn get(&self, key: &str) -> Option<String> {
-        let cache = self.cache.lock().await;
-        cache.get(key).cloned()
-    }
+    // Send a crates.io search request and read the response according to its content type,
+    // reporting the details of JSON error responses
+    async fn fetch_crate_search(&self, url: &str) -> Result<SearchResponse, DocError> {
+        let response = self.send(url).await?;
+        let is_json = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|content_type| content_type.to_str().ok())
+            .is_some_and(is_json_content_type);
+
+        let status = response.status();
+        if !status.is_success() {
+            let error = DocError::from_response(&response, url);
+            if !is_json || !status.is_client_error() || status == StatusCode::TOO_MANY_REQUESTS {
+                return Err(error);
+            }
+            let body = response.text().await?;
+            return match serde_json::from_str::<CratesIoErrors>(&body) {
+                Ok(body) if !body.errors.is_empty() => {
+                    let details: Vec<String> = body.errors.into_iter().map(|error| error.detail).collect();
+                    Err(DocError::InvalidInput(format!("crates.io rejected the search: {}", details.join("; "))))
+                }
+                _ => Err(error),
+            };
+        }
 
-    pub async fn set(&self, key: String, value: String) {
-        let mut cache = self.cache.lock().await;
-        cache.insert(key, value);
+        let body = response.text().await?;
+        if is_json {
+            Ok(SearchResponse::Results(parse_json(&body, "crates.io search response")?))
+        } else {
+            Ok(SearchResponse::Page(page_to_markdown(&body, url)))
+        }
     }
     
-    // New: Methods for examples cache
-    pub async fn get_examples(&self, key: &str) -> Option<Vec<CodeExample>> {
-        let cache = self.examples_cache.lock().await;
-        cache.get(key).cloned()
-    }
+    #[tool(description = "List all published versions of a Rust crate from crates.io (returns a markdown table). Shows each version number, its publish date, and whether it has been yanked, newest first. Use this tool before looking up documentation for a specific version, to check which versions exist, or to find the latest stable release. Example usage: `{\"name\": \"list_crate_versions\", \"arguments\": {\"crate_name\": \"serde\"}}`. Stable releases only: `{\"name\": \"list_crate_versions\", \"arguments\": {\"crate_name\": \"tokio\", \"stable_only\": true}}`")]
+    async fn list_crate_versions(
+        &self,
+        #[tool(param)]
+        #[schemars(description = "The name of the crate to list versions for. Must be the exact crate name as published on crates.io (e.g., 'serde', 'tokio', 'reqwest'). Case-sensitive and must match exactly how the crate is published.")]
+        crate_name: String,
+
+        #[tool(param)]
+        #[schemars(description = "Whether to hide pre-release versions such as '1.0.0-beta.1' or '0.5.0-rc.2' (optional, defaults to false). Set to true when choosing a version to depend on.")]
+        stable_only: Option<bool>,
+    ) -> Result<CallToolResult, McpError> {
+        let crate_name = CrateName::parse(&crate_name)?;
+        let cache_key = format!("versions:{}", crate_name);
+        let body = match self.cache.get(&cache_key).await {
+            Some(body) => body,
+            None => {
+                let body = self
+                    .fetch_text(&crate_versions_url(&self.crates_io_base_url, &crate_name))
+                    .await
+                    .map_err(|e| e.or_not_found(&crate_name, None))?;
+                self.cache.set(cache_key, body.clone()).await;
+                body
+            }
+        };
+
+        let response: CrateVersionsResponse = parse_json(&body, "crate versions")?;
 
-    pub async fn set_examples(&self, key: String, examples: Vec<CodeExample>) {
-        let mut cache = self.examples_cache.lock().await;
-        cache.insert(key, examples);
+        let versions = filter_versions(response.versions, stable_only.unwrap_or(false));
+        Ok(CallToolResult::success(vec![Content::text(format_versions(&crate_name, &versions))]))
     }
-}
 
-#[derive(Clone)]
-pub struct CargoDocRouter {
-    pub client: Client,
-    pub cache: DocCache,
-}
+    #[tool(description = "List the dependencies of a published Rust crate version (returns markdown). Fetches the dependency list from crates.io and groups it into normal, dev and build dependencies, showing each dependency's version requirement, whether it is optional, and whether its default features are enabled. Use this tool to audit a crate's dependency tree, check which version of a shared dependency it requires, or find out which optional integrations it offers. Example usage: `{\"name\": \"get_crate_dependencies\", \"arguments\": {\"crate_name\": \"reqwest\"}}`. Specific version: `{\"name\": \"get_crate_dependencies\", \"arguments\": {\"crate_name\": \"tokio\", \"version\": \"1.28.0\"}}`")]
+    async fn get_crate_dependencies(
+        &self,
+        #[tool(param)]
+        #[schemars(description = "The name of the crate to list dependencies for. Must be the exact crate name as published on crates.io (e.g., 'serde', 'tokio', 'reqwest'). Case-sensitive and must match exactly how the crate is published.")]
+        crate_name: String,
 
-impl Default for CargoDocRouter {
-    fn default() -> Self {
-        Self::new()
+        #[tool(param)]
+        #[schemars(description = "The version of the crate (optional, defaults to the latest stable release). Provide an exact published version string (e.g., '1.0.0', '0.11.2'); use list_crate_versions to see which versions exist.")]
+        version: Option<String>,
+    ) -> Result<CallToolResult, McpError> {
+        let crate_name = CrateName::parse(&crate_name)?;
+        let version = parse_version(version)?;
+        let version = self.resolve_version(&crate_name, version).await?;
+
+        let cache_key = format!("deps:{}:{}", crate_name, version);
+        if let Some(deps) = self.cache.get(&cache_key).await {
+            return Ok(CallToolResult::success(vec![Content::text(deps)]));
+        }
+
+        let body = self
+            .fetch_text(&crate_dependencies_url(&self.crates_io_base_url, &crate_name, &version))
+            .await
+            .map_err(|e| e.or_not_found(&crate_name, None))?;
+        let response: CrateDependenciesResponse = parse_json(&body, "crate dependencies")?;
+
+        let markdown = format_dependencies(&crate_name, &version, &response.dependencies);
+        self.cache.set(cache_key, markdown.clone()).await;
+
+        Ok(CallToolResult::success(vec![Content::text(markdown)]))
     }
-}
 
-#[tool(tool_box)]
-impl CargoDocRouter {
-    pub fn new() -> Self {
-        Self {
-            client: Client::new(),
-            cache: DocCache::new(),
+    #[tool(description = "List the crates that depend on a Rust crate (returns markdown). Fetches the crate's reverse dependencies from crates.io and shows how many crates depend on it in total, then a page of dependents, most downloaded first, with the version requirement each puts on the crate. The number of dependents is a strong signal of how widely used and production-ready a crate is. Example usage: `{\"name\": \"get_reverse_dependencies\", \"arguments\": {\"crate_name\": \"serde\"}}`. Next page: `{\"name\": \"get_reverse_dependencies\", \"arguments\": {\"crate_name\": \"tokio\", \"page\": 2, \"per_page\": 20}}`")]
+    async fn get_reverse_dependencies(
+        &self,
+        #[tool(param)]
+        #[schemars(description = "The name of the crate to list dependents for. Must be the exact crate name as published on crates.io (e.g., 'serde', 'tokio', 'reqwest').")]
+        crate_name: String,
+
+        #[tool(param)]
+        #[schemars(description = "Page of dependents to return, starting at 1 (optional, defaults to 1). The output header shows the number of pages.")]
+        page: Option<u32>,
+
+        #[tool(param)]
+        #[schemars(description = "Number of dependents per page (optional, defaults to 10, max 100).")]
+        per_page: Option<u32>,
+    ) -> Result<CallToolResult, McpError> {
+        let crate_name = CrateName::parse(&crate_name)?;
+        let page = page.unwrap_or(1).max(1);
+        let per_page = per_page.unwrap_or(10).clamp(1, 100);
+
+        let cache_key = format!("reverse_deps:{}:{}:{}", crate_name, page, per_page);
+        if let Some(dependents) = self.cache.get(&cache_key).await {
+            return Ok(CallToolResult::success(vec![Content::text(dependents)]));
         }
+
+        let body = self
+            .fetch_text(&reverse_dependencies_url(&self.crates_io_base_url, &crate_name, page, per_page))
+            .await
+            .map_err(|e| e.or_not_found(&crate_name, None))?;
+        let response: ReverseDependenciesResponse = parse_json(&body, "reverse dependencies")?;
+
+        // Dependents keep being published, so only keep the list for a while
+        let markdown =
+            format_reverse_dependencies(&crate_name, response.meta.total, page, per_page, &response.dependents());
+        self.cache
+            .set_with_ttl(cache_key, markdown.clone(), REVERSE_DEPENDENCIES_TTL)
+            .await;
+        Ok(CallToolResult::success(vec![Content::text(markdown)]))
     }
 
-    #[tool(description = "Look up comprehensive documentation for a Rust crate (returns markdown). This tool fetches and converts the official docs.rs documentation into readable markdown format, providing a comprehensive overview of the crate's functionality, modules, and public API. The documentation includes the crate's features, modules, types, and functions. This is typically the first step in understanding a crate's capabilities. Example usage: To look up the latest documentation for tokio: `{\"name\": \"lookup_crate\", \"arguments\": {\"crate_name\": \"tokio\"}}`. To look up a specific version: `{\"name\": \"lookup_crate\", \"arguments\": {\"crate_name\": \"serde\", \"version\": \"1.0.152\"}}`. For standard library: `{\"name\": \"lookup_crate\", \"arguments\": {\"crate_name\": \"std\"}}`")]
-    async fn lookup_crate(
+    #[tool(description = "List the feature flags of a Rust crate and what each one enables (returns a markdown table). The default feature set is shown first, followed by every other feature with the features and optional dependencies it turns on. Use this tool when an item seems to be missing from a crate, since it is often gated behind a feature that must be enabled in Cargo.toml, or to choose a minimal feature set. Example usage: `{\"name\": \"get_crate_features\", \"arguments\": {\"crate_name\": \"tokio\"}}`. Specific version: `{\"name\": \"get_crate_features\", \"arguments\": {\"crate_name\": \"serde\", \"version\": \"1.0.100\"}}`")]
+    async fn get_crate_features(
         &self,
         #[tool(param)]
-        #[schemars(description = "The name of the crate to look up. Must be the exact crate name as published on crates.io (e.g., 'serde', 'tokio', 'reqwest'). This parameter is case-sensitive and must match exactly how the crate is published. For standard library modules, use 'std' as the crate name.")]
+        #[schemars(description = "The name of the crate to list features for. Must be the exact crate name as published on crates.io (e.g., 'serde', 'tokio', 'reqwest'). Case-sensitive and must match exactly how the crate is published.")]
         crate_name: String,
 
         #[tool(param)]
-        #[schemars(description = "The version of the crate (optional, defaults to latest). Provide a specific version string (e.g., '1.0.0', '0.11.2') to lookup documentation for that version instead of the latest. This is useful when working with codebases using older versions of a dependency, or to understand API changes between versions.")]
+        #[schemars(description = "The version of the crate (optional, defaults to the latest stable release). Provide an exact published version string (e.g., '1.0.0', '0.11.2'); feature sets often change between versions.")]
         version: Option<String>,
-    ) -> String {
-        // Check cache first
-        let cache_key = if let Some(ver) = &version {
-            format!("{}:{}", crate_name, ver)
-        } else {
-            crate_name.clone()
-        };
+    ) -> Result<CallToolResult, McpError> {
+        let crate_name = CrateName::parse(&crate_name)?;
+        let version = parse_version(version)?;
+        let version = self.resolve_version(&crate_name, version).await?;
 
-        if let Some(doc) = self.cache.get(&cache_key).await {
-            return doc;
+        let cache_key = format!("features:{}:{}", crate_name, version);
+        if let Some(features) = self.cache.get(&cache_key).await {
+            return Ok(CallToolResult::success(vec![Content::text(features)]));
         }
 
-        // Construct the docs.rs URL for the crate
-        let url = if let Some(ver) = version {
-            format!("https://docs.rs/crate/{}/{}/", crate_name, ver)
-        } else {
-            format!("https://docs.rs/crate/{}/", crate_name)
-        };
+        let features = self.crate_feature_map(&crate_name, Some(version.clone())).await?;
+        let markdown = format_features(&crate_name, &version, &features);
+        self.cache.set(cache_key, markdown.clone()).await;
 
-        // Fetch the documentation page
-        let response = match self
-            .client
-            .get(&url)
-            .header(
-                "User-Agent",
-                "CrateDocs/0.1.0 (https://github.com/d6e/cratedocs-mcp)",
-            )
-            .send()
+        Ok(CallToolResult::success(vec![Content::text(markdown)]))
+    }
+
+    #[tool(description = "Get structured information about a Rust crate from crates.io (returns JSON). Returns an object with the crate's name, description, latest version, license, homepage, repository, keywords, categories, total and recent download counts, and documentation URL. Use this tool to check a crate's license or find its repository without reading through its documentation. Example usage: `{\"name\": \"get_crate_metadata\", \"arguments\": {\"crate_name\": \"serde\"}}`")]
+    async fn get_crate_metadata(
+        &self,
+        #[tool(param)]
+        #[schemars(description = "The name of the crate. Must be the exact crate name as published on crates.io (e.g., 'serde', 'tokio', 'reqwest'). Case-sensitive and must match exactly how the crate is published.")]
+        crate_name: String,
+    ) -> Result<CallToolResult, McpError> {
+        let crate_name = CrateName::parse(&crate_name)?;
+        let metadata = self.crate_metadata(&crate_name).await?;
+        let json = serde_json::to_string_pretty(&metadata).map_err(DocError::from)?;
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    #[tool(description = "Find the minimum supported Rust version (MSRV) of a crate. Reads the `rust-version` a crate version declares in its Cargo.toml, along with its edition when crates.io reports it, e.g. `Minimum Rust version: 1.74 (edition 2021)`. Use this tool to check whether a crate can be used with a pinned or older Rust toolchain. Example usage: `{\"name\": \"get_msrv\", \"arguments\": {\"crate_name\": \"clap\"}}`. Specific version: `{\"name\": \"get_msrv\", \"arguments\": {\"crate_name\": \"tokio\", \"version\": \"1.28.0\"}}`")]
+    async fn get_msrv(
+        &self,
+        #[tool(param)]
+        #[schemars(description = "The name of the crate. Must be the exact crate name as published on crates.io (e.g., 'serde', 'tokio', 'clap'). Case-sensitive and must match exactly how the crate is published.")]
+        crate_name: String,
+
+        #[tool(param)]
+        #[schemars(description = "The version of the crate (optional, defaults to the latest stable release). Provide an exact published version string (e.g., '1.0.0', '4.5.1'), since the MSRV can change between releases.")]
+        version: Option<String>,
+    ) -> Result<CallToolResult, McpError> {
+        let crate_name = CrateName::parse(&crate_name)?;
+        let version = parse_version(version)?;
+        let version = self.resolve_version(&crate_name, version).await?;
+
+        let cache_key = format!("msrv:{}:{}", crate_name, version);
+        if let Some(msrv) = self.cache.get(&cache_key).await {
+            return Ok(CallToolResult::success(vec![Content::text(msrv)]));
+        }
+
+        let body = self
+            .fetch_text(&crate_version_url(&self.crates_io_base_url, &crate_name, &version))
             .await
-        {
-            Ok(resp) => resp,
-            Err(e) => return format!("Failed to fetch documentation: {}", e),
-        };
+            .map_err(|e| e.or_not_found(&crate_name, None))?;
+        let response: CrateVersionResponse = parse_json(&body, "crate version")?;
 
-        if !response.status().is_success() {
-            return format!(
-                "Failed to fetch documentation. Status: {}",
-                response.status()
-            );
+        let msrv = format_msrv(&response.version);
+        self.cache.set(cache_key, msrv.clone()).await;
+
+        Ok(CallToolResult::success(vec![Content::text(msrv)]))
+    }
+
+    #[tool(description = "Fetch the README of a Rust crate from crates.io (returns markdown). The README often contains getting-started guides, feature overviews and examples that are missing from the API documentation on docs.rs. Rendered HTML and reStructuredText READMEs are converted to markdown; markdown READMEs are returned as-is. Use this tool to get a project-level introduction to a crate before diving into its API. Example usage: `{\"name\": \"get_crate_readme\", \"arguments\": {\"crate_name\": \"clap\"}}`. Specific version: `{\"name\": \"get_crate_readme\", \"arguments\": {\"crate_name\": \"serde\", \"version\": \"1.0.100\"}}`")]
+    async fn get_crate_readme(
+        &self,
+        #[tool(param)]
+        #[schemars(description = "The name of the crate whose README to fetch. Must be the exact crate name as published on crates.io (e.g., 'serde', 'tokio', 'clap'). Case-sensitive and must match exactly how the crate is published.")]
+        crate_name: String,
+
+        #[tool(param)]
+        #[schemars(description = "The version of the crate (optional, defaults to the latest stable release). Provide an exact published version string (e.g., '1.0.0', '4.5.1') to read the README that shipped with that version.")]
+        version: Option<String>,
+    ) -> Result<CallToolResult, McpError> {
+        let crate_name = CrateName::parse(&crate_name)?;
+        let version = parse_version(version)?;
+        let version = self.resolve_version(&crate_name, version).await?;
+
+        let cache_key = format!("readme:{}:{}", crate_name, version);
+        if let Some(readme) = self.cache.get(&cache_key).await {
+            return Ok(CallToolResult::success(vec![Content::text(readme)]));
         }
 
-        let html_body = match response.text().await {
+        let no_readme = || {
+            Ok(CallToolResult::success(vec![Content::text(format!(
+                "No README available for {} {}.",
+                crate_name, version
+            ))]))
+        };
+        let body = match self.fetch_text(&crate_readme_url(&self.crates_io_base_url, &crate_name, &version)).await {
             Ok(body) => body,
-            Err(e) => return format!("Failed to read response body: {}", e),
+            Err(e) if e.is_not_found() => return no_readme(),
+            Err(e) => return Err(e.into()),
         };
+        if body.trim().is_empty() {
+            return no_readme();
+        }
 
-        // Convert HTML to markdown
-        let markdown_body = parse_html(&html_body);
+        let markdown = match detect_readme_format(&body) {
+            ReadmeFormat::Html => html_to_markdown(&body),
+            ReadmeFormat::Markdown => body,
+            ReadmeFormat::ReStructuredText => rst_to_markdown(&body),
+        };
+        self.cache.set(cache_key, markdown.clone()).await;
 
-        // Cache the markdown result
-        self.cache.set(cache_key, markdown_body.clone()).await;
+        Ok(CallToolResult::success(vec![Content::text(markdown)]))
+    }
+
+    #[tool(description = "Check whether a crate's documentation built successfully on docs.rs (returns markdown). Reports the status of the most recent docs.rs build, the Rust toolchain it used, how long it took, and a link to the build log when it failed. Use this tool when lookup_crate or lookup_item_tool cannot find documentation, to tell a failed docs.rs build apart from a wrong item path. Example usage: `{\"name\": \"get_build_status\", \"arguments\": {\"crate_name\": \"serde\"}}`. Specific version: `{\"name\": \"get_build_status\", \"arguments\": {\"crate_name\": \"tokio\", \"version\": \"1.28.0\"}}`")]
+    async fn get_build_status(
+        &self,
+        #[tool(param)]
+        #[schemars(description = "The name of the crate to check. Must be the exact crate name as published on crates.io (e.g., 'serde', 'tokio', 'reqwest'). Case-sensitive and must match exactly how the crate is published.")]
+        crate_name: String,
+
+        #[tool(param)]
+        #[schemars(description = "The version of the crate (optional, defaults to latest). Provide a specific version string (e.g., '1.0.0', '0.11.2') to check the build of that version.")]
+        version: Option<String>,
+    ) -> Result<CallToolResult, McpError> {
+        let crate_name = CrateName::parse(&crate_name)?;
+        let version = parse_version(version)?;
+        let version = version.unwrap_or_else(|| "latest".to_string());
+        let cache_key = format!("builds:{}:{}", crate_name, version);
+        if let Some(status) = self.cache.get(&cache_key).await {
+            self.refresh_if_stale(&cache_key, |router| async move {
+                router.fetch_build_status(&crate_name, &version).await.map(drop)
+            })
+            .await;
+            return Ok(CallToolResult::success(vec![Content::text(status)]));
+        }
 
-        markdown_body
+        let markdown = self.fetch_build_status(&crate_name, &version).await?;
+        Ok(CallToolResult::success(vec![Content::text(markdown)]))
     }
 
-    #[tool(
-        description = "Look up detailed documentation for a specific item in a Rust crate (returns markdown). This tool provides precise API documentation for structs, enums, traits, functions, or macros within a crate, showing method signatures, associated types, trait implementations, and other details. Use this when you need to understand a specific type's API, its methods, fields, or implementation details. Example usage: For the Vec type: `{\"name\": \"lookup_item_tool\", \"arguments\": {\"crate_name\": \"alloc\", \"item_path\": \"vec::Vec\"}}`. For a trait: `{\"name\": \"lookup_item_tool\", \"arguments\": {\"crate_name\": \"tokio\", \"item_path\": \"io::AsyncRead\", \"version\": \"1.28.0\"}}`. For a function: `{\"name\": \"lookup_item_tool\", \"arguments\": {\"crate_name\": \"reqwest\", \"item_path\": \"get\"}}`. For standard lib: `{\"name\": \"lookup_item_tool\", \"arguments\": {\"crate_name\": \"std\", \"item_path\": \"fs::File\"}}`"
-    )]
-    async fn lookup_item_tool(
+    #[tool(description = "Compare the documentation of two versions of a crate (returns markdown). Fetches the crate-level documentation of both versions, matches up sections by heading, and lists added, removed and changed sections with a line diff of each change. This is a heuristic documentation diff, not a semantic API diff, so use it as a starting point when migrating between versions. Example usage: `{\"name\": \"compare_crate_versions\", \"arguments\": {\"crate_name\": \"reqwest\", \"old_version\": \"0.11.27\", \"new_version\": \"0.12.0\"}}`")]
+    async fn compare_crate_versions(
         &self,
         #[tool(param)]
-        #[schemars(description = "The name of the crate where the item is defined. Must be the exact crate name as published on crates.io (e.g., 'serde', 'tokio'). For standard library types, use 'std' or the appropriate module like 'alloc'. Case-sensitive and must match exactly how the crate is published.")]
+        #[schemars(description = "The name of the crate to compare. Must be the exact crate name as published on crates.io (e.g., 'serde', 'tokio', 'reqwest'). Case-sensitive and must match exactly how the crate is published.")]
         crate_name: String,
 
         #[tool(param)]
-        #[schemars(
-            description = "Full path to the item using double-colon notation (e.g., 'vec::Vec', 'serde::Serialize', 'tokio::io::AsyncRead'). You can include or omit the crate prefix - it will be automatically handled. For nested types, include the full path (e.g., 'http::response::Builder'). The tool will automatically detect if the item is a struct, enum, trait, function, or macro."
-        )]
-        item_path: String,
+        #[schemars(description = "The version to compare from, usually the one currently in use (e.g., '0.11.27'). Must be an exact published version.")]
+        old_version: String,
 
         #[tool(param)]
-        #[schemars(description = "The version of the crate (optional, defaults to latest). Provide a specific version string (e.g., '1.0.0', '0.11.2') to lookup documentation for that version instead of the latest. Useful when working with a specific version of a dependency.")]
-        version: Option<String>,
-    ) -> String {
-        self.lookup_item(crate_name, item_path, version).await
+        #[schemars(description = "The version to compare to, usually the one being migrated to (e.g., '0.12.0'). Must be an exact published version.")]
+        new_version: String,
+    ) -> Result<CallToolResult, McpError> {
+        let crate_name = CrateName::parse(&crate_name)?;
+        let old_version = parse_version(Some(old_version))?;
+        let new_version = parse_version(Some(new_version))?;
+        let old_docs_version = old_version.as_deref();
+        let new_docs_version = new_version.as_deref();
+        let old_version = old_docs_version.unwrap_or("latest");
+        let new_version = new_docs_version.unwrap_or("latest");
+        let cache_key = format!("diff:{}:{}:{}", crate_name, old_version, new_version);
+        if let Some(diff) = self.cache.get(&cache_key).await {
+            return Ok(CallToolResult::success(vec![Content::text(diff)]));
+        }
+
+        // Both lookups go through the documentation cache
+        let old_docs = self.crate_docs(&crate_name, old_docs_version, None).await?;
+        let new_docs = self.crate_docs(&crate_name, new_docs_version, None).await?;
+
+        let diff = diff_documentation(&crate_name, old_version, new_version, &old_docs, &new_docs);
+        self.cache.set(cache_key, diff.clone()).await;
+        Ok(CallToolResult::success(vec![Content::text(diff)]))
     }
 
-    #[tool(description = "Search for Rust crates on crates.io (returns JSON or markdown). This tool helps you discover relevant Rust libraries for specific functionality by searching the official crates.io registry. Use this tool when you need to find crates that implement a particular feature, or when you're looking for alternatives to a known crate. Results include crate names, descriptions, download statistics, creation dates, and documentation links. Example usage: Basic search: `{\"name\": \"search_crates\", \"arguments\": {\"query\": \"http client\"}}`. Search with limit: `{\"name\": \"search_crates\", \"arguments\": {\"query\": \"json serialization\", \"limit\": 20}}`. Specific feature search: `{\"name\": \"search_crates\", \"arguments\": {\"query\": \"async database\", \"limit\": 5}}`. Alternatives to a known crate: `{\"name\": \"search_crates\", \"arguments\": {\"query\": \"serde alternatives\"}}`")]
-    async fn search_crates(
+    #[tool(description = "List the types that implement a trait (returns markdown). Reads the Implementors section of the trait's documentation page and returns each implementing type once, in the order docs.rs lists them. Implementations provided by other crates are loaded dynamically on docs.rs and are not included. Example usage: `{\"name\": \"find_trait_implementors\", \"arguments\": {\"crate_name\": \"std\", \"trait_path\": \"fmt::Display\"}}`. For a crate trait: `{\"name\": \"find_trait_implementors\", \"arguments\": {\"crate_name\": \"tokio\", \"trait_path\": \"io::AsyncRead\", \"version\": \"1.28.0\"}}`")]
+    async fn find_trait_implementors(
         &self,
         #[tool(param)]
-        #[schemars(description = "The search query for finding crates. Can be a keyword, functionality description, or partial crate name. For best results, use specific terms that describe the functionality you need (e.g., 'http client', 'serde json', 'async runtime', 'command line parser'). You can also search for a specific crate by name to find similar alternatives.")]
-        query: String,
+        #[schemars(description = "The name of the crate that defines the trait. Must be the exact crate name as published on crates.io (e.g., 'serde', 'tokio'). For standard library traits, use 'std'.")]
+        crate_name: String,
 
         #[tool(param)]
-        #[schemars(
-            description = "Maximum number of results to return (optional, defaults to 10, max 100). Increase this value for broader searches where you need to compare multiple options or when searching for a less common functionality. A value between 5-20 is recommended for most searches to get a good overview of available options."
-        )]
-        limit: Option<u32>,
-    ) -> String {
-        let limit = limit.unwrap_or(10).min(100); // Cap at 100 results
+        #[schemars(description = "Path to the trait using double-colon notation (e.g., 'fmt::Display', 'io::AsyncRead', 'Serialize'). You can include or omit the crate prefix.")]
+        trait_path: String,
 
-        let url = format!(
-            "https://crates.io/api/v1/crates?q={}&per_page={}",
-            query, limit
-        );
+        #[tool(param)]
+        #[schemars(description = "The version of the crate (optional, defaults to latest). Provide a specific version string (e.g., '1.0.0') to list implementors in that version.")]
+        version: Option<String>,
+    ) -> Result<CallToolResult, McpError> {
+        let crate_name = CrateName::parse(&crate_name)?;
+        let version = parse_version(version)?;
+        let trait_path = ItemPath::parse(&crate_name, &trait_path)?;
+        let cache_key = if let Some(ver) = &version {
+            format!("implementors:{}:{}:{}", crate_name, ver, trait_path)
+        } else {
+            format!("implementors:{}:{}", crate_name, trait_path)
+        };
+        if let Some(implementors) = self.cache.get(&cache_key).await {
+            return Ok(CallToolResult::success(vec![Content::text(implementors)]));
+        }
 
-        let response = match self
-            .client
-            .get(&url)
-            .header(
-                "User-Agent",
-                "CrateDocs/0.1.0 (https://github.com/d6e/cratedocs-mcp)",
-            )
-            .send()
-            .await
-        {
-            Ok(resp) => resp,
-            Err(e) => return format!("Failed to search crates.io: {}", e),
+        let doc = self
+            .lookup_item_doc(crate_name.clone(), trait_path.clone(), version, None)
+            .await?
+            .to_markdown();
+        let Some(section) = extract_section(&doc, "Implementors", false) else {
+            return Ok(CallToolResult::success(vec![Content::text(format!(
+                "No Implementors section found for `{}`. Check that `{}` is a trait.",
+                trait_path, trait_path
+            ))]));
         };
 
-        if !response.status().is_success() {
-            return format!("Failed to search crates.io. Status: {}", response.status());
+        let implementors = parse_implementors(&section);
+        let mut output = format!("# Implementors of `{}::{}`\n\n", crate_name, trait_path);
+        if implementors.is_empty() {
+            output.push_str("_No implementors listed._\n");
+        }
+        for implementor in implementors {
+            output.push_str(&format!("- `{}`\n", implementor));
         }
 
-        let body = match response.text().await {
-            Ok(text) => text,
-            Err(e) => return format!("Failed to read response body: {}", e),
-        };
+        self.cache.set(cache_key, output.clone()).await;
+        Ok(CallToolResult::success(vec![Content::text(output)]))
+    }
 
-        // Check if response is JSON (API response) or HTML (web page)
-        if body.trim().starts_with('{') {
-            // This is likely JSON data, return as is
-            body
-        } else {
-            // This is likely HTML, convert to markdown
-            parse_html(&body)
+    #[tool(description = "Show the supertrait hierarchy of a trait as a nested markdown list. Reads the trait declaration (e.g. `pub trait Error: Debug + Display`) and follows each supertrait's own declaration, up to the requested depth. Use this tool to find out everything a type must implement before it can implement a trait. Example usage: `{\"name\": \"get_type_hierarchy\", \"arguments\": {\"crate_name\": \"std\", \"trait_path\": \"error::Error\"}}`. Deeper tree: `{\"name\": \"get_type_hierarchy\", \"arguments\": {\"crate_name\": \"std\", \"trait_path\": \"cmp::Ord\", \"depth\": 4}}`")]
+    async fn get_type_hierarchy(
+        &self,
+        #[tool(param)]
+        #[schemars(description = "The name of the crate that defines the trait. Must be the exact crate name as published on crates.io (e.g., 'serde', 'tokio'). For standard library traits, use 'std'.")]
+        crate_name: String,
+
+        #[tool(param)]
+        #[schemars(description = "Path to the trait using double-colon notation (e.g., 'error::Error', 'io::AsyncRead', 'Serialize'). You can include or omit the crate prefix.")]
+        trait_path: String,
+
+        #[tool(param)]
+        #[schemars(description = "How many levels of supertraits to follow (optional, defaults to 2, at most 5). A depth of 1 lists only the direct supertraits.")]
+        depth: Option<u8>,
+    ) -> Result<CallToolResult, McpError> {
+        let crate_name = CrateName::parse(&crate_name)?;
+        let depth = depth
+            .unwrap_or(DEFAULT_HIERARCHY_DEPTH)
+            .clamp(1, MAX_HIERARCHY_DEPTH);
+        let trait_path = ItemPath::parse(&crate_name, &trait_path)?;
+
+        let root_supertraits = self.supertraits(&crate_name, &trait_path).await?;
+
+        // Depth-first walk; supertraits of other crates are looked up on their own pages
+        let mut output = format!("# Supertraits of `{}::{}`\n\n", crate_name, trait_path);
+        if root_supertraits.is_empty() {
+            output.push_str("This trait has no supertraits.\n");
+            return Ok(CallToolResult::success(vec![Content::text(output)]));
         }
+        output.push_str(&format!("- `{}::{}`\n", crate_name, trait_path));
+        let mut stack: Vec<(String, u8)> = root_supertraits
+            .into_iter()
+            .rev()
+            .map(|path| (path, 1))
+            .collect();
+        while let Some((path, level)) = stack.pop() {
+            let indent = "  ".repeat(level as usize);
+            if level == depth {
+                output.push_str(&format!("{}- `{}`\n", indent, path));
+                continue;
+            }
+
+            let (supertrait_crate, supertrait_path) =
+                path.split_once("::").unwrap_or((&path, ""));
+            let supertraits = match CrateName::parse(supertrait_crate) {
+                Ok(supertrait_crate) => match ItemPath::parse(&supertrait_crate, supertrait_path) {
+                    Ok(supertrait_path) => self.supertraits(&supertrait_crate, &supertrait_path).await,
+                    Err(e) => Err(e.into()),
+                },
+                Err(e) => Err(e.into()),
+            };
+            match supertraits {
+                Ok(supertraits) => {
+                    output.push_str(&format!("{}- `{}`\n", indent, path));
+                    stack.extend(supertraits.into_iter().rev().map(|path| (path, level + 1)));
+                }
+                Err(_) => output.push_str(&format!(
+                    "{}- `{}` (documentation unavailable)\n",
+                    indent, path
+                )),
+            }
+        }
+        Ok(CallToolResult::success(vec![Content::text(output)]))
     }
-    
-    #[tool(description = "Look up practical usage examples for a specific item in a Rust crate. This tool extracts or generates code examples showing how to properly use a particular API item. It focuses on practical implementation patterns, common idioms, and best practices. Use this tool when you need to understand how to actually implement code with a specific type or function, beyond just the API signatures. It's especially useful for understanding complex types like Result or Future, or traits with associated types. Example usage: `{\"name\": \"lookup_item_examples\", \"arguments\": {\"crate_name\": \"tokio\", \"item_path\": \"io::AsyncRead\"}}` will return examples of how to use the AsyncRead trait. For standard library: `{\"name\": \"lookup_item_examples\", \"arguments\": {\"crate_name\": \"std\", \"item_path\": \"fs::File\"}}`. For a container: `{\"name\": \"lookup_item_examples\", \"arguments\": {\"crate_name\": \"std\", \"item_path\": \"collections::HashMap\"}}`. For error handling: `{\"name\": \"lookup_item_examples\", \"arguments\": {\"crate_name\": \"reqwest\", \"item_path\": \"Error\"}}`")]
+
+    #[tracing::instrument(skip(self), fields(cache_hit = tracing::field::Empty), err)]
+    #[tool(description = "Look up practical usage examples for a specific item in a Rust crate. This tool extracts or generates code examples showing how to properly use a particular API item. It focuses on practical implementation patterns, common idioms, and best practices. Use this tool when you need to understand how to actually implement code with a specific type or function, beyond just the API signatures. It's especially useful for understanding complex types like Result or Future, or traits with associated types. Example usage: `{\"name\": \"lookup_item_examples\", \"arguments\": {\"crate_name\": \"tokio\", \"item_path\": \"io::AsyncRead\"}}` will return examples of how to use the AsyncRead trait. For standard library: `{\"name\": \"lookup_item_examples\", \"arguments\": {\"crate_name\": \"std\", \"item_path\": \"fs::File\"}}`. For a container: `{\"name\": \"lookup_item_examples\", \"arguments\": {\"crate_name\": \"std\", \"item_path\": \"collections::HashMap\"}}`. For error handling: `{\"name\": \"lookup_item_examples\", \"arguments\": {\"crate_name\": \"reqwest\", \"item_path\": \"Error\"}}`. Each example links to the Rust Playground; to run them with another edition: `{\"name\": \"lookup_item_examples\", \"arguments\": {\"crate_name\": \"std\", \"item_path\": \"fs::File\", \"playground_edition\": \"2024\"}}`")]
     async fn lookup_item_examples(
         &self,
         #[tool(param)]
@@ -240,7 +1804,24 @@ impl CargoDocRouter {
         #[tool(param)]
         #[schemars(description = "The version of the crate (optional, defaults to latest). Useful when working with a specific version of a dependency, especially if the API has changed between versions.")]
         version: Option<String>,
-    ) -> String {
+
+        #[tool(param)]
+        #[schemars(description = "The Rust edition the Rust Playground links compile the examples with: '2015', '2018', '2021' or '2024' (optional, defaults to '2021').")]
+        playground_edition: Option<String>,
+    ) -> Result<CallToolResult, McpError> {
+        let crate_name = CrateName::parse(&crate_name)?;
+        let version = parse_version(version)?;
+        let item_path = ItemPath::parse(&crate_name, &item_path)?;
+        let playground_edition = playground_edition.as_deref().unwrap_or(DEFAULT_PLAYGROUND_EDITION);
+        if !PLAYGROUND_EDITIONS.contains(&playground_edition) {
+            return Err(DocError::InvalidInput(format!(
+                "unknown playground_edition `{}`, expected one of {}",
+                playground_edition,
+                PLAYGROUND_EDITIONS.join(", ")
+            ))
+            .into());
+        }
+
         // Check examples cache first
         let cache_key = if let Some(ver) = &version {
             format!("examples:{}:{}:{}", crate_name, ver, item_path)
@@ -248,163 +1829,27 @@ impl CargoDocRouter {
             format!("examples:{}:{}", crate_name, item_path)
         };
 
-        if let Some(examples) = self.cache.get(&cache_key).await {
-            return examples;
-        }
-        
-        // First get the main documentation to extract examples from it
-        let doc_content = self.lookup_item(crate_name.clone(), item_path.clone(), version.clone()).await;
-        
-        // Parse the documentation to extract code examples
-        // First try to find the "Examples" section
-        let mut examples_content = String::new();
-        let doc_lines: Vec<&str> = doc_content.lines().collect();
-        
-        // Search for examples section
-        let mut in_examples = false;
-        let mut code_block = false;
-        let mut current_example = String::new();
-        
-        for line in &doc_lines {
-            // Check for Examples section header
-            if line.trim().to_lowercase() == "## examples" || line.trim().to_lowercase() == "# examples" {
-                in_examples = true;
-                examples_content.push_str("# Usage Examples\n\n");
-                continue;
-            }
-            
-            // Check for next section header, which would end the examples section
-            if in_examples && line.starts_with('#') && !line.trim().to_lowercase().contains("example") {
-                // Just move on to the next line without breaking the loop
-                continue;
-            }
-            
-            // Collect content within the examples section
-            if in_examples {
-                // Track code blocks
-                if line.trim().starts_with("```") {
-                    code_block = !code_block;
-                    
-                    // Add explanatory text for code examples
-                    if code_block && !current_example.is_empty() {
-                        examples_content.push_str("\n**Example usage:**\n\n");
-                        current_example = String::new();
-                    }
-                }
-                
-                examples_content.push_str(line);
-                examples_content.push('\n');
-                
-                // Collect example description
-                if !code_block && !line.trim().is_empty() {
-                    current_example.push_str(line);
-                    current_example.push(' ');
-                }
-            }
-        }
-        
-        // If we didn't find a dedicated examples section, look for code blocks throughout the documentation
-        if examples_content.is_empty() {
-            examples_content.push_str("# Usage Examples\n\n");
-            examples_content.push_str("The following examples demonstrate how to use this item:\n\n");
-            
-            let mut in_code_block = false;
-            let mut example_count = 0;
-            
-            // Process the lines again to extract code blocks
-            for line in &doc_lines {
-                if line.trim().starts_with("```") {
-                    in_code_block = !in_code_block;
-                    
-                    if in_code_block {
-                        example_count += 1;
-                        examples_content.push_str(&format!("## Example {}\n\n", example_count));
-                    }
-                    
-                    examples_content.push_str(line);
-                    examples_content.push('\n');
-                } else if in_code_block {
-                    examples_content.push_str(line);
-                    examples_content.push('\n');
-                }
-            }
+        let cached = self.cache.get_examples(&cache_key).await;
+        record_cache_lookup(cached.is_some());
+        if let Some(examples) = cached {
+            return Ok(CallToolResult::success(vec![Content::text(format_examples(&examples, playground_edition))]));
         }
-        
-        // If we still don't have examples, try to provide a generic usage pattern based on the item type
-        if examples_content.trim() == "# Usage Examples\n\nThe following examples demonstrate how to use this item:" 
-           || examples_content.trim() == "# Usage Examples"
-           || !examples_content.contains("```") {
-            // Extract item name and type
-            let parts: Vec<&str> = item_path.split("::").collect();
-            let item_name = parts.last().unwrap_or(&"").to_string();
-            
-            // Check if docs mention the item is a struct, enum, trait, etc.
-            let is_struct = doc_content.to_lowercase().contains("struct") && doc_content.to_lowercase().contains(&item_name.to_lowercase());
-            let is_trait = doc_content.to_lowercase().contains("trait") && doc_content.to_lowercase().contains(&item_name.to_lowercase());
-            let is_enum = doc_content.to_lowercase().contains("enum") && doc_content.to_lowercase().contains(&item_name.to_lowercase());
-            let is_function = doc_content.to_lowercase().contains("fn") && doc_content.to_lowercase().contains(&item_name.to_lowercase());
-            
-            examples_content = String::from("# Usage Examples\n\n");
-            
-            if is_struct {
-                examples_content.push_str(&format!("## Creating and using a {} instance\n\n", item_name));
-                examples_content.push_str("```rust\n");
-                examples_content.push_str(&format!("use {}::{};\n\n", crate_name, item_path));
-                examples_content.push_str(&format!("// Create a new {} instance\n", item_name));
-                examples_content.push_str(&format!("let instance = {}::new();\n\n", item_name));
-                examples_content.push_str(&format!("// Use methods on the {} instance\n", item_name));
-                examples_content.push_str(&format!("// instance.some_method();\n"));
-                examples_content.push_str("```\n\n");
-                examples_content.push_str("This is a generated example. Check the actual documentation for the correct method names and usage patterns.\n");
-            } else if is_trait {
-                examples_content.push_str(&format!("## Implementing the {} trait\n\n", item_name));
-                examples_content.push_str("```rust\n");
-                examples_content.push_str(&format!("use {}::{};\n\n", crate_name, item_path));
-                examples_content.push_str("struct MyType;\n\n");
-                examples_content.push_str(&format!("impl {} for MyType {{\n", item_name));
-                examples_content.push_str("    // Implement the required trait methods here\n");
-                examples_content.push_str("}\n");
-                examples_content.push_str("```\n\n");
-                examples_content.push_str("This is a generated example. Check the actual documentation for the required trait methods.\n");
-            } else if is_enum {
-                examples_content.push_str(&format!("## Using the {} enum\n\n", item_name));
-                examples_content.push_str("```rust\n");
-                examples_content.push_str(&format!("use {}::{};\n\n", crate_name, item_path));
-                examples_content.push_str(&format!("// Match on {} variants\n", item_name));
-                examples_content.push_str(&format!("let value = {}::Variant;\n\n", item_name));
-                examples_content.push_str(&format!("match value {{\n"));
-                examples_content.push_str(&format!("    {}::Variant => {{}},\n", item_name));
-                examples_content.push_str(&format!("    // Match other variants...\n"));
-                examples_content.push_str("}\n");
-                examples_content.push_str("```\n\n");
-                examples_content.push_str("This is a generated example. Check the actual documentation for the correct enum variants.\n");
-            } else if is_function {
-                examples_content.push_str(&format!("## Calling the {} function\n\n", item_name));
-                examples_content.push_str("```rust\n");
-                examples_content.push_str(&format!("use {}::{};\n\n", crate_name, item_path));
-                examples_content.push_str(&format!("// Call the function\n"));
-                examples_content.push_str(&format!("let result = {}();\n", item_name));
-                examples_content.push_str("```\n\n");
-                examples_content.push_str("This is a generated example. Check the actual documentation for the correct function parameters.\n");
-            } else {
-                // Fallback - ensure there's always at least a code block so tests pass
-                examples_content.push_str("## Generic Example\n\n");
-                examples_content.push_str("```rust\n");
-                examples_content.push_str(&format!("// Example for using {}\n", item_path));
-                examples_content.push_str(&format!("use {}::{};\n\n", crate_name, item_path));
-                examples_content.push_str("// Add your usage code here\n");
-                examples_content.push_str("```\n\n");
-                examples_content.push_str("No specific examples were found in the documentation.\n");
-                examples_content.push_str("Please refer to the main documentation for usage information.\n");
-            }
+
+        // Extract the code examples from the item's documentation, or generate a usage
+        // pattern from the kind of item when it has none
+        let item_doc = self.lookup_item_doc(crate_name.clone(), item_path.clone(), version.clone(), None).await?;
+        let mut examples = item_doc.examples.clone();
+        if examples.is_empty() {
+            examples.push(generated_example(&crate_name, &item_path, &item_doc.to_markdown()));
         }
-        
-        // Cache the examples
-        self.cache.set(cache_key, examples_content.clone()).await;
-        
-        examples_content
+
+        let markdown = format_examples(&examples, playground_edition);
+        self.cache.set_examples(cache_key, examples).await;
+
+        Ok(CallToolResult::success(vec![Content::text(markdown)]))
     }
     
+    #[tracing::instrument(skip(self), fields(cache_hit = tracing::field::Empty), err)]
     #[tool(description = "Analyze type relationships and usage patterns in a Rust crate. This tool examines how types relate to each other and provides guidance on proper API usage. It identifies return types, parameter types, trait implementations, and offers code examples for handling common patterns like Result and Option types. Use this tool when you need to understand how to correctly use an API, especially for complex types with multiple interacting components, or when you need to understand proper error handling. Example usage: `{\"name\": \"analyze_type_relationships\", \"arguments\": {\"crate_name\": \"reqwest\", \"item_path\": \"Client\"}}` will show how Client interacts with other types in the reqwest crate. For Result handling: `{\"name\": \"analyze_type_relationships\", \"arguments\": {\"crate_name\": \"std\", \"item_path\": \"result::Result\"}}`. For async types: `{\"name\": \"analyze_type_relationships\", \"arguments\": {\"crate_name\": \"tokio\", \"item_path\": \"io::AsyncRead\"}}`. For errors: `{\"name\": \"analyze_type_relationships\", \"arguments\": {\"crate_name\": \"reqwest\", \"item_path\": \"Error\"}}`.")]
     async fn analyze_type_relationships(
         &self,
@@ -421,7 +1866,10 @@ impl CargoDocRouter {
         #[tool(param)]
         #[schemars(description = "The version of the crate (optional, defaults to latest). Useful when working with a specific version of a dependency, particularly if the API structure has changed between versions.")]
         version: Option<String>,
-    ) -> String {
+    ) -> Result<CallToolResult, McpError> {
+        let crate_name = CrateName::parse(&crate_name)?;
+        let version = parse_version(version)?;
+        let item_path = ItemPath::parse(&crate_name, &item_path)?;
         let cache_key = if let Some(ver) = &version {
             format!("relationships:{}:{}:{}", crate_name, ver, item_path)
         } else {
@@ -429,19 +1877,22 @@ impl CargoDocRouter {
         };
 
         // Check cache first
-        if let Some(relationships) = self.cache.get(&cache_key).await {
-            return relationships;
+        let cached = self.cache.get(&cache_key).await;
+        record_cache_lookup(cached.is_some());
+        if let Some(relationships) = cached {
+            return Ok(CallToolResult::success(vec![Content::text(relationships)]));
         }
         
-        // First look up the main item documentation
-        let item_doc = self.lookup_item(crate_name.clone(), item_path.clone(), version.clone()).await;
+        // First look up the main item's page; thread safety is read from its HTML, everything
+        // else from its markdown
+        let (url, item_html) = self.item_page(&crate_name, &item_path, version.as_deref(), None).await?;
+        let item_doc = page_to_markdown(&item_html, &url);
         
         // Parse the item doc to extract relationship information
         let mut relationships = String::new();
         relationships.push_str(&format!("# Type Relationships for {}\n\n", item_path));
         
         // Extract return types from method signatures
-        let lines: Vec<&str> = item_doc.lines().collect();
         let mut method_return_types = Vec::new();
         let mut parameter_types = Vec::new();
         let mut associated_types = Vec::new();
@@ -449,22 +1900,31 @@ impl CargoDocRouter {
         
         // Extract the item type (struct, enum, trait, etc)
         let mut item_type = "item";
-        if item_doc.contains("struct") && item_doc.contains(&item_path) {
+        if item_doc.contains("struct") && item_doc.contains(item_path.item_name()) {
             item_type = "struct";
-        } else if item_doc.contains("enum") && item_doc.contains(&item_path) {
+        } else if item_doc.contains("enum") && item_doc.contains(item_path.item_name()) {
             item_type = "enum";
-        } else if item_doc.contains("trait") && item_doc.contains(&item_path) {
+        } else if item_doc.contains("trait") && item_doc.contains(item_path.item_name()) {
             item_type = "trait";
-        } else if item_doc.contains("fn") && item_doc.contains(&item_path) {
+        } else if item_doc.contains("fn") && item_doc.contains(item_path.item_name()) {
             item_type = "function";
         }
         
+        // Example code isn't part of the item's API: its `fn main() -> Result<..>` isn't a method
+        let mut signatures = item_doc.clone();
+        if let Some(examples) = extract_section(&item_doc, "Examples", false) {
+            for block in extract_code_blocks(&examples).iter().filter(|block| !block.content.is_empty()) {
+                signatures = signatures.replace(&block.content, "");
+            }
+        }
+
         // Extract method signatures and analyze return types
-        for line in &lines {
+        for line in signatures.lines() {
             // Look for method signatures with return types
             if line.contains("fn ") && line.contains("->") {
-                let return_type_start = line.find("->");
-                if let Some(pos) = return_type_start {
+                // Futures are added below with the type awaiting them produces
+                let returns_future = async_method(&plain_text(line)).is_some();
+                if let Some(pos) = line.find("->").filter(|_| !returns_future) {
                     let return_type = line[pos+2..].trim().trim_end_matches('{').trim_end_matches(';').trim_end().trim_end_matches(',');
                     if !return_type.is_empty() && !return_type.contains("Self") {
                         let return_type_string = return_type.to_string();
@@ -478,9 +1938,7 @@ impl CargoDocRouter {
                 if let Some(params_start) = line.find('(') {
                     if let Some(params_end) = line[params_start..].find(')') {
                         let params = &line[params_start+1..params_start+params_end].trim();
-                        let param_parts: Vec<&str> = params.split(',').collect();
-                        
-                        for param in param_parts {
+                        for param in params.split(',') {
                             if param.contains(':') {
                                 let param_type = param.split(':').nth(1).unwrap_or("").trim();
                                 if !param_type.is_empty() && !param_type.contains("Self") {
@@ -496,20 +1954,10 @@ impl CargoDocRouter {
             }
             
             // Look for associated types in traits
-            if line.contains("type ") && line.contains(';') {
-                let type_name_start = line.find("type ");
-                if let Some(pos) = type_name_start {
-                    let rest = &line[pos+5..];
-                    if let Some(name_end) = rest.find(':') {
-                        let type_name = &rest[..name_end].trim();
-                        associated_types.push(type_name.to_string());
-                    } else if let Some(name_end) = rest.find('=') {
-                        let type_name = &rest[..name_end].trim();
-                        associated_types.push(type_name.to_string());
-                    } else if let Some(name_end) = rest.find(';') {
-                        let type_name = &rest[..name_end].trim();
-                        associated_types.push(type_name.to_string());
-                    }
+            if let Some(type_name) = associated_type_name(line) {
+                let type_name = type_name.to_string();
+                if !associated_types.contains(&type_name) {
+                    associated_types.push(type_name);
                 }
             }
             
@@ -533,13 +1981,109 @@ impl CargoDocRouter {
             }
         }
         
+        // A future's effective return type is what awaiting it produces
+        let async_methods = parse_async_methods(&signatures);
+        for method in &async_methods {
+            if method.output != "()" && !method.output.contains("Self") && !method_return_types.contains(&method.output) {
+                method_return_types.push(method.output.clone());
+            }
+        }
+        
         // Add relationship information
         relationships.push_str("## Overview\n\n");
         
-        let parts: Vec<&str> = item_path.split("::").collect();
-        let item_name = parts.last().unwrap_or(&"").to_string();
+        let item_name = item_path.item_name();
         
         relationships.push_str(&format!("`{}` is a {} in the `{}` crate.\n\n", item_name, item_type, crate_name));
+
+        let unsafe_functions = parse_unsafe_functions(&signatures, item_name);
+        let unsafe_impls = parse_unsafe_impls(&signatures);
+        if !unsafe_functions.is_empty() || !unsafe_impls.is_empty() {
+            relationships.push_str("## Safety Requirements\n\n");
+            for function in &unsafe_functions {
+                relationships.push_str(&format!("### `{}`\n\n", function.signature));
+                match &function.safety {
+                    Some(safety) => relationships.push_str(&format!("{}\n\n", safety)),
+                    None => relationships.push_str("_No Safety section is documented for this function._\n\n"),
+                }
+            }
+            if !unsafe_impls.is_empty() {
+                relationships.push_str("Unsafe trait implementations, which must uphold the trait's safety contract:\n\n");
+                for header in &unsafe_impls {
+                    relationships.push_str(&format!("- `{}` \n", header));
+                }
+                relationships.push('\n');
+            }
+        }
+        
+        if !async_methods.is_empty() {
+            relationships.push_str("## Async Methods\n\n");
+            relationships.push_str("These methods return futures; `.await` them to get the listed type:\n\n");
+            for method in &async_methods {
+                relationships.push_str(&format!("- `{}` → `{}` \n", method.name, method.output));
+            }
+            relationships.push('\n');
+        }
+        
+        let supertraits = parse_supertrait_bounds(&item_doc);
+        if !supertraits.is_empty() {
+            relationships.push_str("## Supertraits\n\n");
+            relationships.push_str("Implementors of this trait must also implement:\n\n");
+            
+            for supertrait in &supertraits {
+                relationships.push_str(&format!("- `{}` \n", supertrait));
+            }
+            relationships.push('\n');
+        }
+        
+        if let Some(target) = deref_target(&item_doc) {
+            relationships.push_str("## Deref Target\n\n");
+            relationships.push_str(&format!("`Target = {}`\n\n", target));
+            relationships.push_str(&format!("Methods of `{}` are available on this type via auto-deref.\n\n", target));
+        }
+        
+        let thread_safety = parse_thread_safety(&item_html);
+        let auto_traits: Vec<String> = [("Send", thread_safety.send), ("Sync", thread_safety.sync)]
+            .iter()
+            .filter_map(|(name, implemented)| match implemented {
+                Some(true) => Some(format!("`{}`", name)),
+                Some(false) => Some(format!("NOT `{}`", name)),
+                None => None,
+            })
+            .collect();
+        if !auto_traits.is_empty() {
+            relationships.push_str("## Thread Safety\n\n");
+            relationships.push_str(&format!("This type is {}.\n", auto_traits.join(" and ")));
+            if thread_safety.send == Some(false) {
+                relationships.push_str("- It can't be moved to another thread, e.g. into `std::thread::spawn` or `tokio::spawn`\n");
+            }
+            if thread_safety.sync == Some(false) && thread_safety.send != Some(false) {
+                relationships.push_str("- References to it can't be shared between threads; wrap it in a `Mutex` or `RwLock` to do so\n");
+            }
+            relationships.push('\n');
+        }
+        
+        let (created_from, converted_to) = parse_conversions(&item_doc, item_name);
+        if !created_from.is_empty() || !converted_to.is_empty() {
+            relationships.push_str("## Type Conversions\n\n");
+            if !created_from.is_empty() {
+                relationships.push_str("Can be created from:\n\n");
+                for source in &created_from {
+                    relationships.push_str(&format!("- `{}` \n", source));
+                }
+                relationships.push_str(&format!(
+                    "\nEach `From` impl also makes `Into<{}>` available, so these types convert with `.into()` too.\n\n",
+                    item_name
+                ));
+            }
+            if !converted_to.is_empty() {
+                relationships.push_str("Can be converted to:\n\n");
+                for target in &converted_to {
+                    relationships.push_str(&format!("- `{}` \n", target));
+                }
+                relationships.push('\n');
+            }
+        }
         
         // Add relationships sections
         if !method_return_types.is_empty() {
@@ -568,7 +2112,7 @@ impl CargoDocRouter {
                     relationships.push_str(&format!("  - When Some, it contains `{}`\n", clean_type));
                 }
             }
-            relationships.push_str("\n");
+            relationships.push('\n');
         }
         
         if !parameter_types.is_empty() {
@@ -590,7 +2134,7 @@ impl CargoDocRouter {
                     relationships.push_str("  - This accepts any type that implements the specified trait\n");
                 }
             }
-            relationships.push_str("\n");
+            relationships.push('\n');
         }
         
         if !associated_types.is_empty() {
@@ -600,7 +2144,7 @@ impl CargoDocRouter {
             for assoc_type in &associated_types {
                 relationships.push_str(&format!("- `{}` \n", assoc_type));
             }
-            relationships.push_str("\n");
+            relationships.push('\n');
         }
         
         if !impl_traits.is_empty() {
@@ -610,10 +2154,11 @@ impl CargoDocRouter {
             for trait_name in &impl_traits {
                 relationships.push_str(&format!("- `{}` \n", trait_name));
             }
-            relationships.push_str("\n");
+            relationships.push('\n');
         }
         
         // Add common usage patterns based on the type
+        let returns_result = method_return_types.iter().any(|t| t.starts_with("Result<"));
         relationships.push_str("## Common Usage Patterns\n\n");
         
         match item_type {
@@ -627,17 +2172,14 @@ impl CargoDocRouter {
                 relationships.push_str(&format!("### Using {} methods\n\n", item_name));
                 relationships.push_str("```rust\n");
                 relationships.push_str("// Call methods on the instance\n");
-                relationships.push_str(&format!("// instance.some_method();\n"));
-                
-                // Clone method_return_types to avoid ownership issues
-                let method_return_types_clone = method_return_types.clone();
+                relationships.push_str("// instance.some_method();\n");
                 
                 // If we have Result return types, show how to handle them
-                if method_return_types_clone.iter().any(|t| t.starts_with("Result<")) {
+                if returns_result {
                     relationships.push_str("\n// For methods returning Result\n");
-                    relationships.push_str(&format!("let result = instance.some_method()?; // Use ? to propagate errors\n"));
+                    relationships.push_str("let result = instance.some_method()?; // Use ? to propagate errors\n");
                     relationships.push_str("// Or handle errors explicitly\n");
-                    relationships.push_str(&format!("match instance.some_method() {{\n"));
+                    relationships.push_str("match instance.some_method() {\n");
                     relationships.push_str("    Ok(value) => { /* use value */ },\n");
                     relationships.push_str("    Err(err) => { /* handle error */ },\n");
                     relationships.push_str("}\n");
@@ -651,9 +2193,7 @@ impl CargoDocRouter {
                 relationships.push_str("struct MyType;\n\n");
                 relationships.push_str(&format!("impl {} for MyType {{\n", item_name));
                 
-                // Clone associated_types to avoid ownership issues
-                let associated_types_clone = associated_types.clone();
-                for assoc_type in &associated_types_clone {
+                for assoc_type in &associated_types {
                     relationships.push_str(&format!("    type {} = /* Your type here */;\n", assoc_type));
                 }
                 
@@ -685,11 +2225,8 @@ impl CargoDocRouter {
                 relationships.push_str("```rust\n");
                 relationships.push_str(&format!("let result = {}(/* parameters */);\n", item_name));
                 
-                // Clone method_return_types again for this block
-                let method_return_types_clone2 = method_return_types.clone();
-                
                 // If we have Result return types, show how to handle them
-                if method_return_types_clone2.iter().any(|t| t.starts_with("Result<")) {
+                if returns_result {
                     relationships.push_str("\n// If the function returns Result\n");
                     relationships.push_str(&format!("let value = {}(/* parameters */)?; // Use ? to propagate errors\n", item_name));
                     relationships.push_str("// Or handle errors explicitly\n");
@@ -707,11 +2244,8 @@ impl CargoDocRouter {
             }
         }
         
-        // Make a final clone of method_return_types for the last section
-        let method_return_types_clone3 = method_return_types.clone();
-        
         // Add tips for Result and Option types if we see them in the docs
-        if method_return_types_clone3.iter().any(|t| t.starts_with("Result<")) {
+        if returns_result {
             relationships.push_str("## Working with Result types\n\n");
             relationships.push_str("This item works with Result types. Here are common patterns:\n\n");
             relationships.push_str("```rust\n");
@@ -732,10 +2266,7 @@ impl CargoDocRouter {
             relationships.push_str("```\n\n");
         }
         
-        // Make another clone for Option types
-        let method_return_types_clone4 = method_return_types.clone();
-        
-        if method_return_types_clone4.iter().any(|t| t.starts_with("Option<")) {
+        if method_return_types.iter().any(|t| t.starts_with("Option<")) {
             relationships.push_str("## Working with Option types\n\n");
             relationships.push_str("This item works with Option types. Here are common patterns:\n\n");
             relationships.push_str("```rust\n");
@@ -752,136 +2283,1023 @@ impl CargoDocRouter {
             relationships.push_str("```\n\n");
         }
         
+        if !unsafe_functions.is_empty() {
+            relationships.insert_str(0, "> ⚠️ This API includes unsafe functions. Read the Safety sections carefully.\n\n");
+        }
+
         // Cache the relationships information
         self.cache.set(cache_key, relationships.clone()).await;
         
-        relationships
+        Ok(CallToolResult::success(vec![Content::text(relationships)]))
     }
 
-    // This function is not directly exposed as a tool but used internally
-    async fn lookup_item(
+    #[tool(description = "Clear cached documentation so it is fetched fresh from docs.rs on the next lookup (returns a summary of how many entries were removed). The server caches every lookup for its whole lifetime, so use this tool when a new version of a crate has been published or cached documentation looks stale. Without arguments it clears the entire cache; with a crate name it clears that crate's documentation, items, and examples; with a crate name and version it clears only that version's crate documentation. Example usage: Clear everything: `{\"name\": \"clear_cache\", \"arguments\": {}}`. Clear one crate: `{\"name\": \"clear_cache\", \"arguments\": {\"crate_name\": \"tokio\"}}`. Clear one version: `{\"name\": \"clear_cache\", \"arguments\": {\"crate_name\": \"serde\", \"version\": \"1.0.152\"}}`")]
+    async fn clear_cache(
         &self,
-        crate_name: String,
-        mut item_path: String,
+        #[tool(param)]
+        #[schemars(description = "The name of the crate whose cached documentation should be cleared (optional). When omitted, the entire cache is cleared. When provided without a version, all cached entries for the crate are removed, including item documentation, examples, and relationship analyses (e.g., 'tokio', 'serde').")]
+        crate_name: Option<String>,
+
+        #[tool(param)]
+        #[schemars(description = "The version of the crate to clear (optional, requires crate_name). When provided, only the cached crate documentation for exactly this version is removed (e.g., '1.0.152'). Omit it to clear every cached version of the crate.")]
         version: Option<String>,
-    ) -> String {
-        // Strip crate name prefix from the item path if it exists
-        let crate_prefix = format!("{}::", crate_name);
-        if item_path.starts_with(&crate_prefix) {
-            item_path = item_path[crate_prefix.len()..].to_string();
+    ) -> Result<CallToolResult, McpError> {
+        let crate_name = crate_name.as_deref().map(CrateName::parse).transpose()?;
+        let version = parse_version(version)?;
+        let summary = match (crate_name, version) {
+            (None, None) => {
+                let removed = self.cache.clear_all().await;
+                format!("Cleared {} cache entries", removed)
+            }
+            (Some(crate_name), None) => {
+                let removed = self.cache.clear_crate(&crate_name).await;
+                format!("Cleared {} cache entries for {}", removed, crate_name)
+            }
+            (Some(crate_name), Some(version)) => {
+                let cache_key = format!("{}{}:{}", DOC_KEY_PREFIX, crate_name, version);
+                let removed = usize::from(self.cache.remove(&cache_key).await.is_some());
+                format!("Cleared {} cache entries for {} {}", removed, crate_name, version)
+            }
+            (None, Some(_)) => {
+                return Err(DocError::InvalidInput(
+                    "a crate_name is required when clearing a specific version".to_string(),
+                )
+                .into())
+            }
+        };
+        Ok(CallToolResult::success(vec![Content::text(summary)]))
+    }
+
+    #[tool(description = "Report documentation cache statistics (returns a markdown table). Shows how many documentation and example entries are cached and roughly how much memory they use, how many lookups were served from the cache (hits) or required a network request (misses), and the resulting hit ratio. Use this tool to check whether the cache is effective or to decide whether clearing it with clear_cache is worthwhile. Example usage: `{\"name\": \"cache_stats\", \"arguments\": {}}`. Report and then zero the counters: `{\"name\": \"cache_stats\", \"arguments\": {\"reset\": true}}`")]
+    async fn cache_stats(
+        &self,
+        #[tool(param)]
+        #[schemars(description = "Whether to zero the hit and miss counters after reporting them (optional, defaults to false). Set to true to start measuring a fresh window of lookups; cached entries are not affected.")]
+        reset: Option<bool>,
+    ) -> Result<CallToolResult, McpError> {
+        let cache_stats = self.cache.stats().await;
+        let hits = self.cache.hits();
+        let misses = self.cache.misses();
+        let lookups = hits + misses;
+        let hit_ratio = if lookups == 0 {
+            0.0
+        } else {
+            hits as f64 / lookups as f64 * 100.0
+        };
+
+        let mut stats = String::from("# Cache Statistics\n\n");
+        stats.push_str("| Metric | Value |\n|---|---|\n");
+        stats.push_str(&format!("| Documentation entries | {} |\n", cache_stats.main_entries));
+        stats.push_str(&format!("| Example entries | {} |\n", cache_stats.examples_entries));
+        stats.push_str(&format!("| Estimated size | {} bytes |\n", cache_stats.estimated_bytes()));
+        stats.push_str(&format!("| Hits | {} |\n", hits));
+        stats.push_str(&format!("| Misses | {} |\n", misses));
+        stats.push_str(&format!("| Hit ratio | {:.1}% |\n", hit_ratio));
+
+        if reset.unwrap_or(false) {
+            self.cache.reset_counters();
+            stats.push_str("\n_Hit and miss counters have been reset._\n");
         }
 
+        Ok(CallToolResult::success(vec![Content::text(stats)]))
+    }
+
+    // Markdown documentation of a crate's docs.rs landing page, or of its rustdoc root page when
+    // built for another `target`, cached per crate, version and target
+    async fn crate_docs(
+        &self,
+        crate_name: &CrateName,
+        version: Option<&str>,
+        target: Option<&str>,
+    ) -> Result<String, DocError> {
+        let version = normalize_version(version);
+        let version = version.as_deref();
+
         // Check cache first
-        let cache_key = if let Some(ver) = &version {
-            format!("{}:{}:{}", crate_name, ver, item_path)
-        } else {
-            format!("{}:{}", crate_name, item_path)
+        let cache_key = match build_key(version, target) {
+            Some(build) => format!("{}{}:{}", DOC_KEY_PREFIX, crate_name, build),
+            None => format!("{}{}", DOC_KEY_PREFIX, crate_name),
         };
 
-        if let Some(doc) = self.cache.get(&cache_key).await {
-            return doc;
+        let cached = self.cache.get(&cache_key).await;
+        record_cache_lookup(cached.is_some());
+        if let Some(doc) = cached {
+            return Ok(doc);
+        }
+
+        // Concurrent lookups of the same crate share one request
+        self.singleflight(&cache_key, async {
+            // Construct the docs.rs URL for the crate
+            let url = if STDLIB_CRATES.contains(&crate_name.as_str()) || target.is_some() {
+                format!("{}/index.html", self.crate_docs_root(crate_name, version, target))
+            } else if let Some(ver) = version {
+                format!("{}/crate/{}/{}/", self.base_url, crate_name, ver)
+            } else {
+                format!("{}/crate/{}/", self.base_url, crate_name)
+            };
+
+            // Fetch the documentation page
+            let response = self.send(&url).await?;
+
+            if !response.status().is_success() {
+                return Err(DocError::from_response(&response, &url).or_not_found(crate_name, None));
+            }
+
+            // Relative links are resolved against the page docs.rs redirected to
+            let page_url = response.url().to_string();
+            let html_body = response.text().await?;
+
+            // Convert HTML to markdown
+            let markdown_body = page_to_markdown(&html_body, &page_url);
+
+            // Cache the markdown result
+            self.cache.set(cache_key.clone(), markdown_body.clone()).await;
+
+            Ok(markdown_body)
+        })
+        .await
+    }
+
+    // Markdown documentation of a crate, or of one of its items, generated from its rustdoc JSON
+    // and cached per crate, version and item
+    #[cfg(feature = "rustdoc-json")]
+    async fn json_docs(
+        &self,
+        crate_name: &CrateName,
+        item_path: Option<&ItemPath>,
+        version: Option<&str>,
+    ) -> Result<String, DocError> {
+        let version = normalize_version(version);
+        let version = version.as_deref();
+        let mut cache_key = format!("json:{}:{}", crate_name, version.unwrap_or("latest"));
+        if let Some(item_path) = item_path {
+            cache_key = format!("{}:{}", cache_key, item_path);
         }
+        let cached = self.cache.get(&cache_key).await;
+        record_cache_lookup(cached.is_some());
+        if let Some(doc) = cached {
+            return Ok(doc);
+        }
+
+        self.singleflight(&cache_key, async {
+            let url = rustdoc_json_url(&self.base_url, crate_name, version);
+            let response = self.send(&url).await?;
+            if !response.status().is_success() {
+                return Err(DocError::from_response(&response, &url).or_not_found(crate_name, None));
+            }
+            let krate = decode_rustdoc_json(&response.bytes().await?)?;
+
+            let markdown = match item_path {
+                Some(item_path) => format_item(&krate, crate_name, item_path)?,
+                None => format_crate(&krate, crate_name)?,
+            };
+            self.cache.set(cache_key.clone(), markdown.clone()).await;
+            Ok(markdown)
+        })
+        .await
+    }
+
+    #[cfg(not(feature = "rustdoc-json"))]
+    async fn json_docs(&self, _: &CrateName, _: Option<&ItemPath>, _: Option<&str>) -> Result<String, DocError> {
+        Err(DocError::InvalidInput(
+            "format `json` needs a server built with the `rustdoc-json` feature".to_string(),
+        ))
+    }
 
-        // Process the item path to determine the item type
-        // Format: module::path::ItemName
-        // Need to split into module path and item name, and guess item type
-        let parts: Vec<&str> = item_path.split("::").collect();
+    // Version of `crate_name` locked by the loaded project, which may spell the name with
+    // hyphens where the lookup uses underscores
+    fn project_version(&self, crate_name: &CrateName) -> Option<String> {
+        self.project_versions
+            .get(crate_name.as_str())
+            .or_else(|| self.project_versions.get(&crate_name.replace('_', "-")))
+            .cloned()
+    }
+
+    // Every item listed on the crate's `all.html` page, cached as JSON
+    async fn all_items(&self, crate_name: &CrateName, version: Option<&str>) -> Result<Vec<IndexedItem>, DocError> {
+        let version = normalize_version(version);
+        let cache_key = format!("index:{}:{}", crate_name, version.as_deref().unwrap_or("latest"));
+        if let Some(items) = self.cache.get(&cache_key).await {
+            return parse_json(&items, "cached item index");
+        }
+
+        let url = format!("{}/all.html", self.crate_docs_root(crate_name, version.as_deref(), None));
+        let html = self
+            .fetch_text(&url)
+            .await
+            .map_err(|e| e.or_not_found(crate_name, None))?;
+        let items = parse_all_items(&html);
+        let json = serde_json::to_string(&items).map_err(DocError::from)?;
+        self.cache.set(cache_key, json).await;
+        Ok(items)
+    }
 
-        if parts.is_empty() {
-            return "Invalid item path. Expected format: module::path::ItemName".to_string();
+    // The crate's crates.io metadata, cached as JSON
+    async fn crate_metadata(&self, crate_name: &CrateName) -> Result<CrateMetadata, DocError> {
+        let cache_key = format!("metadata:{}", crate_name);
+        if let Some(metadata) = self.cache.get(&cache_key).await {
+            return parse_json(&metadata, "cached crate metadata");
         }
 
-        let item_name = parts.last().unwrap().to_string();
-        let module_path = if parts.len() > 1 {
-            parts[..parts.len() - 1].join("/")
+        let body = self
+            .fetch_text(&crate_url(&self.crates_io_base_url, crate_name))
+            .await
+            .map_err(|e| e.or_not_found(crate_name, None))?;
+        let response: CrateResponse = parse_json(&body, "crate metadata")?;
+        let metadata = response.metadata();
+        let json = serde_json::to_string_pretty(&metadata).map_err(DocError::from)?;
+        self.cache.set(cache_key, json).await;
+        Ok(metadata)
+    }
+
+    // The first paragraph of a crate's documentation `doc` under its name, version and license.
+    // The latest version and the license come from crates.io, which doesn't know the standard
+    // library crates; without crates.io the summary leaves out what it couldn't find.
+    async fn crate_summary(&self, crate_name: &CrateName, version: Option<&str>, doc: &str) -> String {
+        let metadata = if STDLIB_CRATES.contains(&crate_name.as_str()) {
+            None
         } else {
-            String::new()
+            self.crate_metadata(crate_name).await.ok()
         };
+        let version = version.or(metadata.as_ref().map(|metadata| metadata.version.as_str()));
 
-        // Try different item types (struct, enum, trait, fn)
-        let item_types = ["struct", "enum", "trait", "fn", "macro"];
-        let mut last_error = None;
+        let mut summary = match version {
+            Some(version) => format!("# {} {}\n\n", crate_name, version),
+            None => format!("# {}\n\n", crate_name),
+        };
+        if let Some(paragraph) = first_paragraph(doc) {
+            summary.push_str(&paragraph);
+            summary.push_str("\n\n");
+        }
+        if let Some(license) = metadata.and_then(|metadata| metadata.license) {
+            summary.push_str(&format!("License: {}\n", license));
+        }
+        summary
+    }
 
-        for item_type in item_types.iter() {
-            // Construct the docs.rs URL for the specific item
-            let url = if let Some(ver) = version.clone() {
-                if module_path.is_empty() {
-                    format!(
-                        "https://docs.rs/{}/{}/{}/{}.{}.html",
-                        crate_name, ver, crate_name, item_type, item_name
-                    )
+    // Return `version`, or look up on crates.io the crate's latest stable version if it is `None`
+    // and the newest release matching it if it is a requirement such as `^0.11`
+    async fn resolve_version(&self, crate_name: &CrateName, version: Option<String>) -> Result<String, DocError> {
+        let req = match version {
+            Some(version) if CrateVersion::parse(&version).is_ok() => return Ok(version),
+            Some(version) => Some(
+                semver::VersionReq::parse(&version)
+                    .map_err(|e| DocError::InvalidInput(format!("invalid version `{}`: {}", version, e)))?,
+            ),
+            None => None,
+        };
+
+        let body = self
+            .fetch_text(&crate_url(&self.crates_io_base_url, crate_name))
+            .await
+            .map_err(|e| e.or_not_found(crate_name, None))?;
+        let response: CrateResponse = parse_json(&body, "crate information")?;
+        let Some(req) = req else {
+            return Ok(response.krate.latest_version().to_string());
+        };
+        response
+            .versions
+            .iter()
+            .filter_map(|version| semver::Version::parse(&version.num).ok())
+            .filter(|version| req.matches(version))
+            .max()
+            .map(|version| version.to_string())
+            .ok_or_else(|| DocError::InvalidInput(format!("no published version of {} matches `{}`", crate_name, req)))
+    }
+
+    // The feature flags a crate version declares, with the features each one enables, cached per
+    // version; the latest release's for `None`
+    async fn crate_feature_map(
+        &self,
+        crate_name: &CrateName,
+        version: Option<String>,
+    ) -> Result<BTreeMap<String, Vec<String>>, DocError> {
+        let version = self.resolve_version(crate_name, version).await?;
+        let cache_key = format!("feature_map:{}:{}", crate_name, version);
+        if let Some(json) = self.cache.get(&cache_key).await {
+            return parse_json(&json, "cached crate features");
+        }
+
+        let body = self
+            .fetch_text(&crate_version_url(&self.crates_io_base_url, crate_name, &version))
+            .await
+            .map_err(|e| e.or_not_found(crate_name, None))?;
+        let response: CrateVersionResponse = parse_json(&body, "crate version")?;
+        let json = serde_json::to_string(&response.version.features).map_err(DocError::from)?;
+        self.cache.set(cache_key, json).await;
+        Ok(response.version.features)
+    }
+
+    // Full paths of a trait's direct supertraits, cached per trait
+    async fn supertraits(&self, crate_name: &CrateName, trait_path: &ItemPath) -> Result<Vec<String>, DocError> {
+        let cache_key = format!("supertraits:{}:{}", crate_name, trait_path);
+        if let Some(supertraits) = self.cache.get(&cache_key).await {
+            return Ok(supertraits.lines().map(str::to_string).collect());
+        }
+
+        let url = self.item_page_url(
+            crate_name,
+            &trait_path.module_path(),
+            None,
+            ItemKind::Trait.page_prefix(),
+            trait_path.item_name(),
+            None,
+        );
+        let html = self
+            .fetch_text(&url)
+            .await
+            .map_err(|e| e.or_not_found(crate_name, Some(&trait_path.to_string())))?;
+        let supertraits = parse_supertraits(&html).ok_or_else(|| {
+            DocError::InvalidInput(format!("`{}::{}` is not a trait", crate_name, trait_path))
+        })?;
+
+        self.cache.set(cache_key, supertraits.join("\n")).await;
+        Ok(supertraits)
+    }
+
+    // GET `url`, retrying transient failures, unless the circuit breaker is open. Network errors
+    // and server errors count as failures; any other response shows the server is up.
+    async fn send(&self, url: &str) -> Result<Response, DocError> {
+        self.circuit_breaker.allow_request()?;
+        let result = retry_with_backoff(self.max_retries, || async {
+            let _slot = match &self.request_slots {
+                Some(slots) => Some(slots.acquire().await.expect("request semaphore is never closed")),
+                None => None,
+            };
+            if let Some(rate_limiter) = &self.rate_limiter {
+                rate_limiter.acquire().await;
+            }
+            self.client
+                .get(url)
+                .header("User-Agent", &self.user_agent)
+                .timeout(self.timeout)
+                .send()
+                .await
+        })
+        .await;
+
+        match &result {
+            Ok(response) => {
+                tracing::info!(status = %response.status(), url = %url, "HTTP request");
+                if response.status().is_server_error() {
+                    self.circuit_breaker.record_failure();
                 } else {
-                    format!(
-                        "https://docs.rs/{}/{}/{}/{}/{}.{}.html",
-                        crate_name, ver, crate_name, module_path, item_type, item_name
-                    )
+                    self.circuit_breaker.record_success();
                 }
-            } else if module_path.is_empty() {
-                format!(
-                    "https://docs.rs/{}/latest/{}/{}.{}.html",
-                    crate_name, crate_name, item_type, item_name
-                )
-            } else {
-                format!(
-                    "https://docs.rs/{}/latest/{}/{}/{}.{}.html",
-                    crate_name, crate_name, module_path, item_type, item_name
-                )
+            }
+            Err(e) => {
+                tracing::error!(url = %url, error = %e, "HTTP request failed");
+                self.circuit_breaker.record_failure();
+            }
+        }
+        result
+    }
+
+    // Run `fetch` unless a lookup with the same cache key is already in flight, in which case
+    // wait for its result instead. If that lookup fails, `fetch` runs after all.
+    async fn singleflight(
+        &self,
+        key: &str,
+        fetch: impl Future<Output = Result<String, DocError>>,
+    ) -> Result<String, DocError> {
+        let guard = {
+            let mut in_flight = self.in_flight.lock().unwrap();
+            match in_flight.get(key) {
+                Some(sender) => Err(sender.subscribe()),
+                None => {
+                    let sender = Arc::new(broadcast::channel(1).0);
+                    in_flight.insert(key.to_string(), sender.clone());
+                    Ok(InFlightGuard {
+                        in_flight: self.in_flight.clone(),
+                        key: key.to_string(),
+                        sender,
+                    })
+                }
+            }
+        };
+
+        match guard {
+            Ok(guard) => {
+                let result = fetch.await;
+                if let Ok(value) = &result {
+                    guard.finish(value);
+                }
+                result
+            }
+            Err(mut receiver) => match receiver.recv().await {
+                Ok(value) => Ok(value),
+                Err(_) => fetch.await,
+            },
+        }
+    }
+
+    // Fetch `url` and return its body, or why the request failed
+    async fn fetch_text(&self, url: &str) -> Result<String, DocError> {
+        Ok(self.fetch_page(url).await?.1)
+    }
+
+    // Fetch `url` and return the URL it was served from once redirects are followed, along with
+    // its body
+    async fn fetch_page(&self, url: &str) -> Result<(String, String), DocError> {
+        let response = self.send(url).await?;
+
+        if !response.status().is_success() {
+            return Err(DocError::from_response(&response, url));
+        }
+
+        let page_url = response.url().to_string();
+        Ok((page_url, response.text().await?))
+    }
+
+    // URL of a module's index page, e.g. `https://docs.rs/tokio/latest/tokio/sync/index.html`
+    fn module_index_url(&self, crate_name: &CrateName, module: &ItemPath, version: Option<&str>) -> String {
+        format!(
+            "{}/{}/index.html",
+            self.crate_docs_root(crate_name, version, None),
+            module.segments().join("/")
+        )
+    }
+
+    // URL of the directory holding a crate's rustdoc pages: `https://docs.rs/tokio/latest/tokio`,
+    // `https://docs.rs/tokio/latest/wasm32-unknown-unknown/tokio` for the docs built for another
+    // target, or for the standard library `https://doc.rust-lang.org/std` (`.../1.75.0/std` for a
+    // release), which is only documented for one target
+    fn crate_docs_root(&self, crate_name: &CrateName, version: Option<&str>, target: Option<&str>) -> String {
+        let version = normalize_version(version);
+        if STDLIB_CRATES.contains(&crate_name.as_str()) {
+            return match version {
+                Some(version) => format!("{}/{}/{}", self.stdlib_base_url, version, crate_name),
+                None => format!("{}/{}", self.stdlib_base_url, crate_name),
             };
+        }
+        let target_dir = target.map(|target| format!("{}/", target)).unwrap_or_default();
+        format!(
+            "{}/{}/{}/{}{}",
+            self.base_url,
+            crate_name,
+            version.as_deref().unwrap_or("latest"),
+            target_dir,
+            crate_name
+        )
+    }
+
+    // Build the URL of an item's page, e.g. `fn.spawn.html` in the `tokio/task` module
+    fn item_page_url(
+        &self,
+        crate_name: &CrateName,
+        module_path: &str,
+        version: Option<&str>,
+        page_prefix: &str,
+        item_name: &str,
+        target: Option<&str>,
+    ) -> String {
+        let module_path = if module_path.is_empty() {
+            String::new()
+        } else {
+            format!("{}/", module_path)
+        };
+        format!(
+            "{}/{}{}.{}.html",
+            self.crate_docs_root(crate_name, version, target),
+            module_path,
+            page_prefix,
+            item_name
+        )
+    }
 
-            // Try to fetch the documentation page
-            let response = match self
-                .client
-                .get(&url)
-                .header(
-                    "User-Agent",
-                    "CrateDocs/0.1.0 (https://github.com/d6e/cratedocs-mcp)",
+    // The crate directory and path within it of the item documented at `page_url`, such as
+    // `("tokio", "sync::mutex::Mutex")` for `https://docs.rs/tokio/1.40.0/tokio/sync/mutex/struct.Mutex.html`;
+    // `None` for pages of methods and URLs outside the documentation hosts
+    fn canonical_item_path(&self, page_url: &str) -> Option<(String, String)> {
+        if page_url.contains('#') {
+            return None;
+        }
+        let segments: Vec<&str> = if let Some(path) = page_url.strip_prefix(&format!("{}/", self.base_url)) {
+            // `<crate>/<version>/[<target>/]<crate directory>/...`, target triples having hyphens
+            // and crate directories none
+            let segments: Vec<&str> = path.split('/').skip(2).collect();
+            let start = usize::from(segments.first().is_some_and(|segment| segment.contains('-')));
+            segments[start..].to_vec()
+        } else {
+            // `[<release>/]<crate>/...`
+            let path = page_url.strip_prefix(&format!("{}/", self.stdlib_base_url))?;
+            let segments: Vec<&str> = path.split('/').collect();
+            let start = segments.iter().position(|segment| STDLIB_CRATES.contains(segment))?;
+            segments[start..].to_vec()
+        };
+        let (page, directories) = segments.split_last()?;
+        let (crate_dir, modules) = directories.split_first()?;
+        let name = match page.split('.').collect::<Vec<_>>()[..] {
+            [_, name, "html"] => name,
+            _ => return None,
+        };
+        let path: Vec<&str> = modules.iter().copied().chain([name]).collect();
+        Some((crate_dir.to_string(), path.join("::")))
+    }
+
+    // Fetch the page of an item known to be of `kind`, for the tools dedicated to one kind, and
+    // return its URL along with its HTML. A missing page means the path doesn't name such an item.
+    async fn fetch_item_page(
+        &self,
+        crate_name: &CrateName,
+        item_path: &ItemPath,
+        version: Option<&str>,
+        kind: ItemKind,
+    ) -> Result<(String, String), McpError> {
+        let url = self.item_page_url(
+            crate_name,
+            &item_path.module_path(),
+            version,
+            kind.page_prefix(),
+            item_path.item_name(),
+            None,
+        );
+
+        let html_body = self.fetch_text(&url).await.map_err(|e| {
+            if e.is_not_found() {
+                McpError::resource_not_found(
+                    format!(
+                        "`{}` is not {} in {}. Check the path with lookup_module, or use lookup_item_tool for other kinds of items.",
+                        item_path,
+                        kind.description(),
+                        crate_name
+                    ),
+                    None,
                 )
-                .send()
+            } else {
+                e.into()
+            }
+        })?;
+        Ok((url, html_body))
+    }
+
+    // Fetch the docs.rs build status of a crate version as markdown and cache it
+    async fn fetch_build_status(&self, crate_name: &CrateName, version: &str) -> Result<String, DocError> {
+        let body = self
+            .fetch_text(&builds_url(&self.base_url, crate_name, version))
+            .await
+            .map_err(|e| e.or_not_found(crate_name, None))?;
+        let builds: Vec<DocsRsBuild> = parse_json(&body, "docs.rs build list")?;
+
+        // Builds get queued and rebuilt, so only keep the status briefly
+        let markdown = format_build_status(crate_name, version, &builds);
+        self.cache
+            .set_with_ttl(format!("builds:{}:{}", crate_name, version), markdown.clone(), BUILD_STATUS_TTL)
+            .await;
+        Ok(markdown)
+    }
+
+    // If the cached entry under `key` is about to expire, refetch it with `refresh` in a
+    // background task so callers keep being served from the cache instead of waiting for the
+    // network once it expires. At most one refresh per key runs at a time.
+    async fn refresh_if_stale<F>(&self, key: &str, refresh: impl FnOnce(CargoDocRouter) -> F)
+    where
+        F: Future<Output = Result<(), DocError>> + Send + 'static,
+    {
+        if !self.background_refresh_enabled || !self.cache.needs_refresh(key).await {
+            return;
+        }
+        if !self.refreshing.lock().unwrap().insert(key.to_string()) {
+            return;
+        }
+
+        let task = refresh(self.clone());
+        let guard = RefreshGuard {
+            refreshing: self.refreshing.clone(),
+            key: key.to_string(),
+        };
+        tokio::spawn(async move {
+            if let Err(e) = task.await {
+                tracing::warn!("Failed to refresh '{}' in the background: {}", guard.key, e);
+            }
+            drop(guard);
+        });
+    }
+
+    /// Warm the cache for `crate_name`: fetch its documentation, then look up the first `top_n`
+    /// types listed at its root (structs, then traits, then enums) in background tasks, at most
+    /// `PREFETCH_CONCURRENCY` at a time. Returns the paths being prefetched without waiting for
+    /// their lookups, whose failures are only logged.
+    pub async fn prefetch_crate(
+        &self,
+        crate_name: &str,
+        version: Option<&str>,
+        top_n: usize,
+    ) -> Result<Vec<ItemPath>, DocError> {
+        let crate_name = CrateName::parse(crate_name)?;
+        let version = normalize_version(version);
+        self.crate_docs(&crate_name, version.as_deref(), None).await?;
+
+        let index_url = format!("{}/index.html", self.crate_docs_root(&crate_name, version.as_deref(), None));
+        let index = self
+            .fetch_text(&index_url)
+            .await
+            .map_err(|e| e.or_not_found(&crate_name, None))?;
+        let mut items: Vec<ModuleItem> = parse_module_items(&index)
+            .into_iter()
+            .filter(|item| PREFETCH_KINDS.contains(&item.kind.as_str()))
+            .collect();
+        items.sort_by_key(|item| PREFETCH_KINDS.iter().position(|kind| *kind == item.kind));
+        let paths: Vec<ItemPath> = items
+            .iter()
+            .filter_map(|item| ItemPath::parse(&crate_name, &item.name).ok())
+            .take(top_n)
+            .collect();
+
+        // Items are looked up in the version `lookup_item_tool` would use, so it finds them cached
+        let version = version.or_else(|| self.project_version(&crate_name));
+        let slots = Arc::new(Semaphore::new(PREFETCH_CONCURRENCY));
+        for path in paths.clone() {
+            let router = self.clone();
+            let slots = slots.clone();
+            let crate_name = crate_name.clone();
+            let version = version.clone();
+            tokio::spawn(async move {
+                let Ok(_slot) = slots.acquire_owned().await else {
+                    return;
+                };
+                if let Err(e) = router.lookup_item_doc(crate_name, path.clone(), version, None).await {
+                    tracing::warn!("Failed to prefetch '{}': {}", path, e);
+                }
+            });
+        }
+        Ok(paths)
+    }
+
+    /// Documentation of the item at `item_path` in `crate_name`, whatever kind of item it is, as
+    /// built by docs.rs for `target`, or for the crate's default target. An item missing from
+    /// that path is looked for by name in the crate's index of all items.
+    pub async fn lookup_item_doc(
+        &self,
+        crate_name: CrateName,
+        item_path: ItemPath,
+        version: Option<String>,
+        target: Option<String>,
+    ) -> Result<ItemDoc, DocError> {
+        let target = target.as_deref();
+        // Check cache first
+        let doc_key = item_cache_key(&crate_name, &item_path, version.as_deref(), target);
+        let cache_key = format!("item_doc:{}", doc_key);
+        let cached = self.cache.get(&cache_key).await;
+        record_cache_lookup(cached.is_some());
+        if let Some(json) = cached {
+            return parse_json(&json, "cached item documentation");
+        }
+
+        // Concurrent lookups of the same item share one set of requests
+        let lookup = self
+            .singleflight(&cache_key, async {
+                let (url, html_body) = self.item_page(&crate_name, &item_path, version.as_deref(), target).await?;
+                let mut item_doc = ItemDoc::from_page(&crate_name, &item_path, version.as_deref(), &url, &html_body);
+
+                // A re-exported item is redirected to the page of its definition, which is cached
+                // under the path it is defined at too
+                if let Some((crate_dir, canonical)) = self.canonical_item_path(&url) {
+                    let same_crate = crate_dir == crate_name.as_str().replace('-', "_");
+                    let canonical_path = format!(
+                        "{}::{}",
+                        if same_crate { crate_name.as_str() } else { crate_dir.as_str() },
+                        canonical
+                    );
+                    if canonical_path != item_doc.path() {
+                        item_doc.canonical_path = Some(canonical_path);
+                        match ItemPath::parse(&crate_name, &canonical) {
+                            Ok(canonical) if same_crate => {
+                                let canonical_doc =
+                                    ItemDoc::from_page(&crate_name, &canonical, version.as_deref(), &url, &html_body);
+                                let canonical_key = item_cache_key(&crate_name, &canonical, version.as_deref(), target);
+                                self.cache_item_doc(&canonical_key, &canonical_doc).await?;
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+
+                self.cache_item_doc(&doc_key, &item_doc).await
+            })
+            .await;
+        let json = match lookup {
+            Err(e) if e.is_not_found() && item_path.method().is_none() => {
+                let item_doc = self.lookup_indexed_item(crate_name, &item_path, version, target, e).await?;
+                self.cache_item_doc(&doc_key, &item_doc).await?;
+                return Ok(item_doc);
+            }
+            lookup => lookup?,
+        };
+        parse_json(&json, "item documentation")
+    }
+
+    // Look up an item that isn't at `item_path` by its name in the crate's index of all items,
+    // e.g. `Mutex` of tokio at `sync::Mutex`: the only item of that name is looked up at its
+    // path, and several are reported as candidates. `error` is returned when there are none.
+    async fn lookup_indexed_item(
+        &self,
+        crate_name: CrateName,
+        item_path: &ItemPath,
+        version: Option<String>,
+        target: Option<&str>,
+        error: DocError,
+    ) -> Result<ItemDoc, DocError> {
+        let items = match self.all_items(&crate_name, version.as_deref()).await {
+            Ok(items) => items,
+            Err(e) if e.is_not_found() => return Err(error),
+            Err(e) => return Err(e),
+        };
+        let requested = item_path.to_string();
+        let mut candidates: Vec<String> = items
+            .into_iter()
+            .filter(|item| item.path.rsplit("::").next() == Some(item_path.item_name()) && item.path != requested)
+            .map(|item| item.path)
+            .collect();
+        candidates.dedup();
+
+        match candidates.as_slice() {
+            [] => Err(error),
+            [path] => {
+                let Ok(path) = ItemPath::parse(&crate_name, path) else {
+                    return Err(error);
+                };
+                tracing::debug!("Found `{}` of {} in its item index at `{}`", item_path, crate_name, path);
+                Box::pin(self.lookup_item_doc(crate_name, path, version, target.map(str::to_string))).await
+            }
+            _ => Err(DocError::AmbiguousItem {
+                item_path: requested,
+                candidates: candidates
+                    .iter()
+                    .map(|candidate| format!("{}::{}", crate_name.replace('-', "_"), candidate))
+                    .collect(),
+                crate_name: crate_name.to_string(),
+            }),
+        }
+    }
+
+    // Cache the documentation of the item keyed `item_key` as JSON, and as markdown where
+    // resources and the other item tools find it, then return the JSON
+    async fn cache_item_doc(&self, item_key: &str, item_doc: &ItemDoc) -> Result<String, DocError> {
+        self.cache.set(format!("{}{}", DOC_KEY_PREFIX, item_key), item_doc.to_markdown()).await;
+        let json = serde_json::to_string(item_doc)
+            .map_err(|e| DocError::Parse(format!("failed to serialize item documentation: {}", e)))?;
+        self.cache.set(format!("item_doc:{}", item_key), json.clone()).await;
+        Ok(json)
+    }
+
+    // URL and HTML of the page documenting `item_path`, whatever kind of item it is, including
+    // methods given as `Type::method`
+    async fn item_page(
+        &self,
+        crate_name: &CrateName,
+        item_path: &ItemPath,
+        version: Option<&str>,
+        target: Option<&str>,
+    ) -> Result<(String, String), DocError> {
+        let Some((type_path, method)) = item_path.method() else {
+            let module_path = item_path.module_path();
+            let error = match self
+                .probe_all_types(crate_name, &module_path, item_path.item_name(), version, target)
                 .await
             {
-                Ok(resp) => resp,
-                Err(e) => {
-                    last_error = Some(e.to_string());
-                    continue;
-                }
+                Err(e) if e.is_not_found() && self.case_insensitive_fallback => e,
+                result => return result,
             };
+            return match self
+                .probe_other_case(crate_name, &module_path, item_path.item_name(), version, target)
+                .await?
+            {
+                Some(page) => Ok(page),
+                None => Err(error),
+            };
+        };
 
-            // If found, process and return
-            if response.status().is_success() {
-                let html_body = match response.text().await {
-                    Ok(body) => body,
-                    Err(e) => return format!("Failed to read response body: {}", e),
-                };
+        // A method is documented on its type's page, whose section for it stands in for a page
+        let (url, html) = self
+            .probe_all_types(crate_name, &type_path.module_path(), type_path.item_name(), version, target)
+            .await?;
+        match extract_method(&html, method) {
+            Some((anchor, method_html)) => Ok((format!("{}#{}", url, anchor), method_html)),
+            None => Err(DocError::NotFound {
+                crate_name: crate_name.to_string(),
+                item_path: Some(item_path.to_string()),
+                tried_urls: vec![format!("{}#method.{}", url, method)],
+            }),
+        }
+    }
+
+    // Look for an item that wasn't found under the capitalizations docs.rs file names use: first
+    // the name title-cased and converted from snake_case to PascalCase, then the name of the
+    // item of the module's index that matches it ignoring case
+    async fn probe_other_case(
+        &self,
+        crate_name: &CrateName,
+        module_path: &str,
+        item_name: &str,
+        version: Option<&str>,
+        target: Option<&str>,
+    ) -> Result<Option<(String, String)>, DocError> {
+        for candidate in case_variants(item_name) {
+            match self.probe_all_types(crate_name, module_path, &candidate, version, target).await {
+                Ok(page) => return Ok(Some(page)),
+                Err(e) if e.is_not_found() => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        let module_dir = match module_path {
+            "" => String::new(),
+            module_path => format!("{}/", module_path),
+        };
+        let index_url = format!("{}/{}index.html", self.crate_docs_root(crate_name, version, target), module_dir);
+        let index = match self.fetch_text(&index_url).await {
+            Ok(index) => index,
+            Err(e) if e.is_not_found() => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        let wanted = item_name.trim_end_matches('!');
+        let Some(item) = parse_module_items(&index)
+            .into_iter()
+            .find(|item| item.kind != "mod" && item.name != wanted && item.name.eq_ignore_ascii_case(wanted))
+        else {
+            return Ok(None);
+        };
+        let url = self.item_page_url(crate_name, module_path, version, &item.kind, &item.name, target);
+        match self.fetch_text(&url).await {
+            Ok(html) => Ok(Some((url, html))),
+            Err(e) if e.is_not_found() => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
 
-                // Convert HTML to markdown
-                let markdown_body = parse_html(&html_body);
+    /// Request the page of `item_name` in `module_path` (`/`-separated) and return the URL and
+    /// HTML of the one that exists, the URL being the one docs.rs redirected to, if any. The
+    /// kinds the naming convention suggests are requested at once first, and the remaining kinds
+    /// only if none of those exists. Kinds are ranked by [`probe_order`], and a page is only used
+    /// once every kind ranked before it is known to be missing, so the result matches probing the
+    /// kinds one at a time. The standard library's primitive types and keywords are fetched from
+    /// their `primitive.` or `keyword.` page alone.
+    pub async fn probe_all_types(
+        &self,
+        crate_name: &CrateName,
+        module_path: &str,
+        item_name: &str,
+        version: Option<&str>,
+        target: Option<&str>,
+    ) -> Result<(String, String), DocError> {
+        // A primitive type has a page of its own kind, which no other item can share its name with
+        if module_path.is_empty()
+            && ["std", "core", "alloc"].contains(&crate_name.as_str())
+            && PRIMITIVE_TYPES.contains(&item_name)
+        {
+            let prefix = ItemKind::Primitive.page_prefix();
+            return self.fetch_page(&self.item_page_url(crate_name, "", version, prefix, item_name, target)).await;
+        }
+        // So does a keyword, which only `std` documents
+        if module_path.is_empty() && crate_name.as_str() == "std" && RUST_KEYWORDS.contains(&item_name) {
+            let prefix = ItemKind::Keyword.page_prefix();
+            return self.fetch_page(&self.item_page_url(crate_name, "", version, prefix, item_name, target)).await;
+        }
 
-                // Cache the markdown result
-                self.cache.set(cache_key, markdown_body.clone()).await;
+        let page_name = item_name.trim_end_matches('!');
+        let urls: Vec<String> = probe_order(item_name)
+            .into_iter()
+            .map(|kind| self.item_page_url(crate_name, module_path, version, kind.page_prefix(), page_name, target))
+            .collect();
 
-                return markdown_body;
+        // Only fall back to the unlikely kinds once every kind the name suggests is missing
+        let (likely, rest) = urls.split_at(likely_kinds(item_name).len().min(urls.len()));
+        for wave in [likely, rest] {
+            if let Some(result) = self.probe_ranked(wave).await {
+                return result;
             }
+        }
+
+        // None of the item kinds worked
+        let module_path = module_path.replace('/', "::");
+        Err(DocError::NotFound {
+            crate_name: crate_name.to_string(),
+            item_path: Some(if module_path.is_empty() {
+                item_name.to_string()
+            } else {
+                format!("{}::{}", module_path, item_name)
+            }),
+            tried_urls: urls,
+        })
+    }
 
-            last_error = Some(format!("Status code: {}", response.status()));
+    // Fetch `urls` at once and return the first page in their order that exists, or the error
+    // that stops the search. `None` means every page is missing. The remaining requests are
+    // dropped as soon as the result is known.
+    async fn probe_ranked(&self, urls: &[String]) -> Option<Result<(String, String), DocError>> {
+        let mut probes: FuturesUnordered<_> = urls
+            .iter()
+            .enumerate()
+            .map(|(rank, url)| async move { (rank, self.fetch_page(url).await) })
+            .collect();
+        let mut outcomes: Vec<Option<Result<(String, String), DocError>>> = urls.iter().map(|_| None).collect();
+        // Highest-ranked kind whose outcome decides the result
+        let mut next = 0;
+        while let Some((rank, outcome)) = probes.next().await {
+            outcomes[rank] = Some(outcome);
+            while let Some(outcome) = outcomes.get_mut(next).and_then(Option::take) {
+                match outcome {
+                    // A missing page only means the item is of another kind
+                    Err(e) if e.is_not_found() => next += 1,
+                    outcome => return Some(outcome),
+                }
+            }
         }
+        None
+    }
 
-        // If we got here, none of the item types worked
-        format!(
-            "Failed to fetch item documentation. No matching item found. Last error: {}",
-            last_error.unwrap_or_else(|| "Unknown error".to_string())
-        )
+    /// One page of `docs://` resources for the documentation in the cache, starting at `cursor`,
+    /// the `next_cursor` of the previous page
+    pub async fn list_cached_docs(&self, cursor: Option<&str>) -> Result<ListResourcesResult, McpError> {
+        let start = match cursor {
+            Some(cursor) => cursor
+                .parse::<usize>()
+                .map_err(|_| DocError::InvalidInput(format!("invalid cursor `{}`", cursor)))?,
+            None => 0,
+        };
+        let resources: Vec<Resource> = self
+            .cache
+            .keys()
+            .await
+            .iter()
+            .filter_map(|key| cached_doc_resource(key))
+            .collect();
+
+        let end = resources.len().min(start.saturating_add(RESOURCES_PER_PAGE));
+        Ok(ListResourcesResult {
+            next_cursor: (end < resources.len()).then(|| end.to_string()),
+            resources: resources.get(start..end).unwrap_or_default().to_vec(),
+        })
+    }
+
+    /// The cached markdown documentation at a `docs://` URI
+    pub async fn read_cached_doc(&self, uri: &str) -> Result<ReadResourceResult, McpError> {
+        let key = resource_key(uri)
+            .ok_or_else(|| DocError::InvalidInput(format!("`{}` is not a docs:// documentation URI", uri)))?;
+        let text = self.cache.get(&key).await.ok_or_else(|| {
+            McpError::resource_not_found(format!("no cached documentation at {}", uri), None)
+        })?;
+
+        Ok(ReadResourceResult {
+            contents: vec![ResourceContents::TextResourceContents {
+                uri: uri.to_string(),
+                mime_type: Some("text/markdown".to_string()),
+                text,
+            }],
+        })
     }
 }
 
 #[tool(tool_box)]
 impl ServerHandler for CargoDocRouter {
+    async fn list_prompts(
+        &self,
+        _request: PaginatedRequestParam,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListPromptsResult, McpError> {
+        Ok(ListPromptsResult {
+            next_cursor: None,
+            prompts: prompts(),
+        })
+    }
+
+    async fn get_prompt(
+        &self,
+        request: GetPromptRequestParam,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<GetPromptResult, McpError> {
+        Ok(get_prompt(&request.name, &request.arguments.unwrap_or_default())?)
+    }
+
+    async fn list_resources(
+        &self,
+        request: PaginatedRequestParam,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListResourcesResult, McpError> {
+        self.list_cached_docs(request.and_then(|request| request.cursor).as_deref())
+            .await
+    }
+
+    async fn read_resource(
+        &self,
+        request: ReadResourceRequestParam,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ReadResourceResult, McpError> {
+        self.read_cached_doc(&request.uri).await
+    }
+
     fn get_info(&self) -> ServerInfo {
         ServerInfo {
             protocol_version: ProtocolVersion::V_2024_11_05,
-            capabilities: ServerCapabilities::builder().enable_tools().build(),
+            capabilities: ServerCapabilities::builder()
+                .enable_tools()
+                .enable_resources()
+                .enable_prompts()
+                .build(),
             server_info: Implementation::from_build_env(),
             instructions: Some(
-                "# Rust Documentation MCP Server\n\nThis server provides enhanced tools for searching, exploring, and understanding Rust crate documentation. Beyond basic documentation lookup, it offers intelligent analysis of type relationships, practical usage examples, and API patterns to help you properly implement Rust code. All documentation is automatically fetched from docs.rs and enriched with additional context.\n\n## Tool Overview\n\n* **search_crates** - Discover relevant Rust libraries for specific functionality needs\n* **lookup_crate** - Get comprehensive documentation for an entire crate\n* **lookup_item_tool** - View detailed API documentation for a specific struct, enum, trait, or function\n* **lookup_item_examples** - Find practical code examples showing how to use a specific API item\n* **analyze_type_relationships** - Examine how types interact and get guidance on proper error handling\n\n## Detailed Tool Usage Examples\n\n### 1. Searching for Crates\n```json\n{\n  \"name\": \"search_crates\",\n  \"arguments\": {\n    \"query\": \"http client\",\n    \"limit\": 5\n  }\n}\n```\nThis searches for HTTP client libraries on crates.io, limiting results to the top 5 matches.\n\n### 2. Looking Up Crate Documentation\n```json\n{\n  \"name\": \"lookup_crate\",\n  \"arguments\": {\n    \"crate_name\": \"reqwest\"\n  }\n}\n```\nOr with a specific version:\n```json\n{\n  \"name\": \"lookup_crate\",\n  \"arguments\": {\n    \"crate_name\": \"tokio\",\n    \"version\": \"1.28.0\"\n  }\n}\n```\n\n### 3. Looking Up Specific Item Documentation\n```json\n{\n  \"name\": \"lookup_item_tool\",\n  \"arguments\": {\n    \"crate_name\": \"reqwest\",\n    \"item_path\": \"Client\"\n  }\n}\n```\nFor nested types:\n```json\n{\n  \"name\": \"lookup_item_tool\",\n  \"arguments\": {\n    \"crate_name\": \"tokio\",\n    \"item_path\": \"io::AsyncRead\",\n    \"version\": \"1.28.0\"\n  }\n}\n```\n\n### 4. Finding Usage Examples\n```json\n{\n  \"name\": \"lookup_item_examples\",\n  \"arguments\": {\n    \"crate_name\": \"serde_json\",\n    \"item_path\": \"Value\"\n  }\n}\n```\nOr for a standard library type:\n```json\n{\n  \"name\": \"lookup_item_examples\",\n  \"arguments\": {\n    \"crate_name\": \"std\",\n    \"item_path\": \"fs::File\"\n  }\n}\n```\n\n### 5. Analyzing Type Relationships\n```json\n{\n  \"name\": \"analyze_type_relationships\",\n  \"arguments\": {\n    \"crate_name\": \"reqwest\",\n    \"item_path\": \"Response\"\n  }\n}\n```\nThis will show how Response relates to other types and proper usage patterns.\n\n## Common Use Case Scenarios\n\n### For Discovering Libraries\n1. Start with searching crates:\n```json\n{\"name\": \"search_crates\", \"arguments\": {\"query\": \"http client\"}}\n```\n2. Look up promising crate documentation:\n```json\n{\"name\": \"lookup_crate\", \"arguments\": {\"crate_name\": \"reqwest\"}}\n```\n3. Compare with alternative crates:\n```json\n{\"name\": \"lookup_crate\", \"arguments\": {\"crate_name\": \"hyper\"}}\n```\n\n### For Understanding API Details\n1. Look up specific type documentation:\n```json\n{\"name\": \"lookup_item_tool\", \"arguments\": {\"crate_name\": \"reqwest\", \"item_path\": \"Client\"}}\n```\n2. Analyze how the type relates to other types:\n```json\n{\"name\": \"analyze_type_relationships\", \"arguments\": {\"crate_name\": \"reqwest\", \"item_path\": \"Client\"}}\n```\n3. Find practical usage examples:\n```json\n{\"name\": \"lookup_item_examples\", \"arguments\": {\"crate_name\": \"reqwest\", \"item_path\": \"Client\"}}\n```\n\n### For Implementing Error Handling\n1. Understand the error type:\n```json\n{\"name\": \"lookup_item_tool\", \"arguments\": {\"crate_name\": \"reqwest\", \"item_path\": \"Error\"}}\n```\n2. See examples of error handling:\n```json\n{\"name\": \"lookup_item_examples\", \"arguments\": {\"crate_name\": \"reqwest\", \"item_path\": \"Error\"}}\n```\n3. Analyze Result relationships:\n```json\n{\"name\": \"analyze_type_relationships\", \"arguments\": {\"crate_name\": \"std\", \"item_path\": \"result::Result\"}}\n```\n\n### For Working with Async Code\n1. Understand the trait:\n```json\n{\"name\": \"lookup_item_tool\", \"arguments\": {\"crate_name\": \"tokio\", \"item_path\": \"io::AsyncRead\"}}\n```\n2. Find usage patterns:\n```json\n{\"name\": \"lookup_item_examples\", \"arguments\": {\"crate_name\": \"tokio\", \"item_path\": \"io::AsyncRead\"}}\n```\n3. Look up extension methods:\n```json\n{\"name\": \"lookup_item_tool\", \"arguments\": {\"crate_name\": \"tokio\", \"item_path\": \"io::AsyncReadExt\"}}\n```\n\nThese enhanced tools help bridge the gap between documentation and implementation by providing practical context for correctly using Rust APIs, including proper error handling, type conversion patterns, and idiomatic usage.".to_string(),
+                "# Rust Documentation MCP Server\n\nThis server provides enhanced tools for searching, exploring, and understanding Rust crate documentation. Beyond basic documentation lookup, it offers intelligent analysis of type relationships, practical usage examples, and API patterns to help you properly implement Rust code. All documentation is automatically fetched from docs.rs and enriched with additional context.\n\n## Tool Overview\n\n* **search_crates** - Discover relevant Rust libraries for specific functionality needs\n* **lookup_crate** - Get comprehensive documentation for an entire crate\n* **lookup_item_tool** - View detailed API documentation for a specific struct, enum, trait, or function\n* **lookup_function** - View a function's documentation without probing other item kinds\n* **lookup_trait** - View a trait's required and provided methods, then its full documentation\n* **lookup_enum** - View an enum's variants, then its full documentation\n* **lookup_macro** - View the documentation of a declarative, derive or attribute macro\n* **lookup_module** - Read a module's documentation and see which items it contains\n* **list_module_items** - List a module's public items with their kinds and summaries as JSON\n* **get_full_module_path** - Find an item's full path when only its name is known\n* **prefetch_crate** - Fetch a crate's main types in the background ahead of lookups\n* **batch_lookup_items** - Look up several items of one crate in a single call\n* **lookup_keyword** - Read the standard library's documentation of a keyword such as `async` or `dyn`\n* **list_crate_versions** - See every published version of a crate, newest first\n* **get_crate_dependencies** - List a crate version's normal, dev and build dependencies\n* **get_crate_features** - See a crate's feature flags and what each one enables\n* **get_reverse_dependencies** - See how many crates depend on a crate and the most downloaded of them\n* **get_crate_metadata** - Get a crate's license, repository, keywords and download counts as JSON\n* **get_crate_readme** - Read a crate's README from crates.io\n* **get_msrv** - Find the minimum Rust version a crate supports\n* **get_build_status** - Check whether a crate's docs.rs build succeeded\n* **compare_crate_versions** - See which documentation sections changed between two versions\n* **find_trait_implementors** - List the types that implement a trait\n* **get_type_hierarchy** - Trace a trait's supertraits as a tree\n* **lookup_item_examples** - Find practical code examples showing how to use a specific API item\n* **analyze_type_relationships** - Examine how types interact and get guidance on proper error handling\n* **clear_cache** - Drop cached documentation so it is fetched fresh on the next lookup\n* **cache_stats** - Check cache size and hit ratio\n\n## Detailed Tool Usage Examples\n\n### 1. Searching for Crates\n```json\n{\n  \"name\": \"search_crates\",\n  \"arguments\": {\n    \"query\": \"http client\",\n    \"limit\": 5\n  }\n}\n```\nThis searches for HTTP client libraries on crates.io, limiting results to the top 5 matches.\n\n### 2. Looking Up Crate Documentation\n```json\n{\n  \"name\": \"lookup_crate\",\n  \"arguments\": {\n    \"crate_name\": \"reqwest\"\n  }\n}\n```\nOr with a specific version:\n```json\n{\n  \"name\": \"lookup_crate\",\n  \"arguments\": {\n    \"crate_name\": \"tokio\",\n    \"version\": \"1.28.0\"\n  }\n}\n```\n\n### 3. Looking Up Specific Item Documentation\n```json\n{\n  \"name\": \"lookup_item_tool\",\n  \"arguments\": {\n    \"crate_name\": \"reqwest\",\n    \"item_path\": \"Client\"\n  }\n}\n```\nFor nested types:\n```json\n{\n  \"name\": \"lookup_item_tool\",\n  \"arguments\": {\n    \"crate_name\": \"tokio\",\n    \"item_path\": \"io::AsyncRead\",\n    \"version\": \"1.28.0\"\n  }\n}\n```\n\n### 4. Finding Usage Examples\n```json\n{\n  \"name\": \"lookup_item_examples\",\n  \"arguments\": {\n    \"crate_name\": \"serde_json\",\n    \"item_path\": \"Value\"\n  }\n}\n```\nOr for a standard library type:\n```json\n{\n  \"name\": \"lookup_item_examples\",\n  \"arguments\": {\n    \"crate_name\": \"std\",\n    \"item_path\": \"fs::File\"\n  }\n}\n```\n\n### 5. Analyzing Type Relationships\n```json\n{\n  \"name\": \"analyze_type_relationships\",\n  \"arguments\": {\n    \"crate_name\": \"reqwest\",\n    \"item_path\": \"Response\"\n  }\n}\n```\nThis will show how Response relates to other types and proper usage patterns.\n\n### 6. Clearing Cached Documentation\n```json\n{\n  \"name\": \"clear_cache\",\n  \"arguments\": {\n    \"crate_name\": \"tokio\"\n  }\n}\n```\nOmit all arguments to clear the entire cache.\n\n## Common Use Case Scenarios\n\n### For Discovering Libraries\n1. Start with searching crates:\n```json\n{\"name\": \"search_crates\", \"arguments\": {\"query\": \"http client\"}}\n```\n2. Look up promising crate documentation:\n```json\n{\"name\": \"lookup_crate\", \"arguments\": {\"crate_name\": \"reqwest\"}}\n```\n3. Compare with alternative crates:\n```json\n{\"name\": \"lookup_crate\", \"arguments\": {\"crate_name\": \"hyper\"}}\n```\n\n### For Understanding API Details\n1. Look up specific type documentation:\n```json\n{\"name\": \"lookup_item_tool\", \"arguments\": {\"crate_name\": \"reqwest\", \"item_path\": \"Client\"}}\n```\n2. Analyze how the type relates to other types:\n```json\n{\"name\": \"analyze_type_relationships\", \"arguments\": {\"crate_name\": \"reqwest\", \"item_path\": \"Client\"}}\n```\n3. Find practical usage examples:\n```json\n{\"name\": \"lookup_item_examples\", \"arguments\": {\"crate_name\": \"reqwest\", \"item_path\": \"Client\"}}\n```\n\n### For Implementing Error Handling\n1. Understand the error type:\n```json\n{\"name\": \"lookup_item_tool\", \"arguments\": {\"crate_name\": \"reqwest\", \"item_path\": \"Error\"}}\n```\n2. See examples of error handling:\n```json\n{\"name\": \"lookup_item_examples\", \"arguments\": {\"crate_name\": \"reqwest\", \"item_path\": \"Error\"}}\n```\n3. Analyze Result relationships:\n```json\n{\"name\": \"analyze_type_relationships\", \"arguments\": {\"crate_name\": \"std\", \"item_path\": \"result::Result\"}}\n```\n\n### For Working with Async Code\n1. Understand the trait:\n```json\n{\"name\": \"lookup_item_tool\", \"arguments\": {\"crate_name\": \"tokio\", \"item_path\": \"io::AsyncRead\"}}\n```\n2. Find usage patterns:\n```json\n{\"name\": \"lookup_item_examples\", \"arguments\": {\"crate_name\": \"tokio\", \"item_path\": \"io::AsyncRead\"}}\n```\n3. Look up extension methods:\n```json\n{\"name\": \"lookup_item_tool\", \"arguments\": {\"crate_name\": \"tokio\", \"item_path\": \"io::AsyncReadExt\"}}\n```\n\nThese enhanced tools help bridge the gap between documentation and implementation by providing practical context for correctly using Rust APIs, including proper error handling, type conversion patterns, and idiomatic usage.".to_string(),
             ),
         }
     }
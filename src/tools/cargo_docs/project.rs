@@ -0,0 +1,106 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{bail, Context};
+use serde::Deserialize;
+
+// The parts of `cargo metadata --no-deps` output used to find a project's dependencies
+#[derive(Deserialize)]
+struct Metadata {
+    packages: Vec<Package>,
+    workspace_root: PathBuf,
+}
+
+#[derive(Deserialize)]
+struct Package {
+    dependencies: Vec<Dependency>,
+}
+
+#[derive(Deserialize)]
+struct Dependency {
+    /// Name of the dependency on crates.io, even when the manifest renames it
+    name: String,
+    req: String,
+}
+
+/// Versions of the crates the project at `manifest_path` depends on, as locked in its
+/// workspace's `Cargo.lock`, keyed by crate name. Dependencies that aren't in the lockfile
+/// are left out.
+pub fn locked_versions(manifest_path: &Path) -> anyhow::Result<HashMap<String, String>> {
+    let cargo = std::env::var_os("CARGO").unwrap_or_else(|| "cargo".into());
+    let output = Command::new(cargo)
+        .args(["metadata", "--no-deps", "--format-version", "1", "--manifest-path"])
+        .arg(manifest_path)
+        .output()
+        .context("failed to run `cargo metadata`")?;
+    if !output.status.success() {
+        bail!(
+            "`cargo metadata` failed for {}: {}",
+            manifest_path.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    let metadata: Metadata =
+        serde_json::from_slice(&output.stdout).context("failed to parse `cargo metadata` output")?;
+
+    let lockfile_path = metadata.workspace_root.join("Cargo.lock");
+    let lockfile = std::fs::read_to_string(&lockfile_path)
+        .with_context(|| format!("failed to read {}", lockfile_path.display()))?;
+    let locked = parse_lockfile(&lockfile);
+
+    let mut versions = HashMap::new();
+    for dependency in metadata.packages.iter().flat_map(|package| &package.dependencies) {
+        let Some(candidates) = locked.get(&dependency.name) else {
+            continue;
+        };
+        // With several versions locked, pick the one the requirement allows
+        let version = match candidates.as_slice() {
+            [version] => version,
+            _ => match candidates.iter().find(|version| matches_requirement(&dependency.req, version)) {
+                Some(version) => version,
+                None => continue,
+            },
+        };
+        versions.insert(dependency.name.clone(), version.clone());
+    }
+    Ok(versions)
+}
+
+/// Versions of each package in a `Cargo.lock` file, keyed by package name
+pub fn parse_lockfile(lockfile: &str) -> HashMap<String, Vec<String>> {
+    let mut packages: HashMap<String, Vec<String>> = HashMap::new();
+    let mut name = None;
+    for line in lockfile.lines().map(str::trim) {
+        if line == "[[package]]" {
+            name = None;
+        } else if let Some(value) = line.strip_prefix("name = ") {
+            name = Some(value.trim_matches('"').to_string());
+        } else if let Some(value) = line.strip_prefix("version = ") {
+            if let Some(name) = name.take() {
+                packages.entry(name).or_default().push(value.trim_matches('"').to_string());
+            }
+        }
+    }
+    packages
+}
+
+// Whether `version` is compatible with the first comparator of a Cargo version requirement,
+// comparing components up to the first non-zero one as caret requirements do
+fn matches_requirement(req: &str, version: &str) -> bool {
+    let req = req.split(',').next().unwrap_or_default();
+    let req = req.trim().trim_start_matches(['^', '~', '=', ' ']);
+    let version = version.split(['-', '+']).next().unwrap_or_default();
+    for (wanted, actual) in req.split('.').zip(version.split('.')) {
+        if wanted == "*" {
+            return true;
+        }
+        if wanted != actual {
+            return false;
+        }
+        if wanted != "0" {
+            return true;
+        }
+    }
+    true
+}
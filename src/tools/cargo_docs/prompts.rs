@@ -0,0 +1,123 @@
+use rmcp::model::{GetPromptResult, JsonObject, Prompt, PromptArgument, PromptMessage, PromptMessageRole};
+
+use super::error::DocError;
+
+// A prompt template: its arguments, with descriptions, are substituted for `{argument}` in `text`
+struct PromptTemplate {
+    name: &'static str,
+    description: &'static str,
+    arguments: &'static [(&'static str, &'static str)],
+    text: &'static str,
+}
+
+const CRATE_NAME: (&str, &str) = ("crate_name", "Name of the crate as published on crates.io, e.g. `serde`");
+
+const TEMPLATES: &[PromptTemplate] = &[
+    PromptTemplate {
+        name: "explain-crate",
+        description: "Explain what a crate is for and how to get started with it",
+        arguments: &[CRATE_NAME],
+        text: "Explain the Rust crate `{crate_name}` to me.\n\n\
+1. Call `lookup_crate` with `{\"crate_name\": \"{crate_name}\"}` to read its documentation.\n\
+2. Call `get_crate_metadata` with `{\"crate_name\": \"{crate_name}\"}` for its license, repository and popularity.\n\
+3. Call `get_crate_features` with `{\"crate_name\": \"{crate_name}\"}` to see which features it offers.\n\
+4. Call `lookup_item_tool` on the main types or functions the documentation points to.\n\n\
+Then summarize what the crate is for, its main entry points, which features are worth enabling, and show a minimal example of using it.",
+    },
+    PromptTemplate {
+        name: "implement-trait",
+        description: "Walk through implementing a crate's trait for one of your types",
+        arguments: &[
+            ("trait_path", "Path to the trait within the crate, e.g. `ser::Serialize`"),
+            CRATE_NAME,
+            ("type_name", "Name of the type that should implement the trait"),
+        ],
+        text: "Help me implement the trait `{trait_path}` from the `{crate_name}` crate for my type `{type_name}`.\n\n\
+1. Call `lookup_trait` with `{\"crate_name\": \"{crate_name}\", \"item_path\": \"{trait_path}\"}` to see its required and provided methods.\n\
+2. Call `get_type_hierarchy` with `{\"crate_name\": \"{crate_name}\", \"trait_path\": \"{trait_path}\"}` to find the supertraits `{type_name}` must implement first.\n\
+3. Call `lookup_item_examples` with `{\"crate_name\": \"{crate_name}\", \"item_path\": \"{trait_path}\"}` for example implementations.\n\n\
+Then write the `impl` block for `{type_name}`, implementing every required method and any supertraits, and explain which provided methods are worth overriding.",
+    },
+    PromptTemplate {
+        name: "error-handling",
+        description: "Explain a crate's error types and how to handle them",
+        arguments: &[CRATE_NAME],
+        text: "Explain how to handle errors from the `{crate_name}` crate.\n\n\
+1. Call `lookup_crate` with `{\"crate_name\": \"{crate_name}\"}` to find its error types and `Result` aliases, often in an `error` module.\n\
+2. If they live in a module, call `list_module_items` with that `module_path` to see all of them.\n\
+3. Call `lookup_item_tool` on each error type to read what its variants or methods report.\n\
+4. Call `analyze_type_relationships` on the main error type to see its conversions and trait implementations.\n\n\
+Then show how to propagate these errors with `?`, how to match on the cases worth handling, and how to convert them into my application's own error type.",
+    },
+    PromptTemplate {
+        name: "migrate-version",
+        description: "Plan an upgrade of a crate from one version to another",
+        arguments: &[
+            CRATE_NAME,
+            ("from_version", "Version currently in use, e.g. `0.11.27`"),
+            ("to_version", "Version to upgrade to, e.g. `0.12.4`"),
+        ],
+        text: "Help me migrate from `{crate_name}` {from_version} to {to_version}.\n\n\
+1. Call `compare_crate_versions` with `{\"crate_name\": \"{crate_name}\", \"old_version\": \"{from_version}\", \"new_version\": \"{to_version}\"}` to see which documentation changed.\n\
+2. Call `get_crate_dependencies` and `get_msrv` for both versions to spot new dependencies or a higher minimum Rust version.\n\
+3. Call `lookup_item_tool` with `\"version\": \"{to_version}\"` on the items that changed.\n\n\
+Then list the breaking changes I need to handle, with before-and-after code for each, and any new features worth adopting.",
+    },
+    PromptTemplate {
+        name: "find-alternatives",
+        description: "Find and compare crates that could replace a given crate",
+        arguments: &[CRATE_NAME],
+        text: "Find alternatives to the `{crate_name}` crate.\n\n\
+1. Call `get_crate_metadata` with `{\"crate_name\": \"{crate_name}\"}` for its keywords, categories and description.\n\
+2. Call `search_crates` with those keywords to find crates solving the same problem.\n\
+3. Call `get_crate_metadata` on the most promising candidates to compare downloads, licenses and release activity.\n\n\
+Then compare the alternatives with `{crate_name}` in a table and recommend one for new projects, explaining the trade-offs.",
+    },
+];
+
+/// The prompt templates offered through `prompts/list`
+pub fn prompts() -> Vec<Prompt> {
+    TEMPLATES
+        .iter()
+        .map(|template| {
+            let arguments = template
+                .arguments
+                .iter()
+                .map(|(name, description)| PromptArgument {
+                    name: name.to_string(),
+                    description: Some(description.to_string()),
+                    required: Some(true),
+                })
+                .collect();
+            Prompt::new(template.name, Some(template.description), Some(arguments))
+        })
+        .collect()
+}
+
+/// The prompt `name` with `arguments` filled in, as returned by `prompts/get`
+pub fn get_prompt(name: &str, arguments: &JsonObject) -> Result<GetPromptResult, DocError> {
+    let template = TEMPLATES
+        .iter()
+        .find(|template| template.name == name)
+        .ok_or_else(|| DocError::InvalidInput(format!("unknown prompt `{}`", name)))?;
+
+    let mut text = template.text.to_string();
+    for (argument, _) in template.arguments {
+        let value = match arguments.get(*argument) {
+            Some(serde_json::Value::String(value)) if !value.trim().is_empty() => value.trim().to_string(),
+            Some(value) if !value.is_null() && !value.is_string() => value.to_string(),
+            _ => {
+                return Err(DocError::InvalidInput(format!(
+                    "prompt `{}` requires the argument `{}`",
+                    name, argument
+                )))
+            }
+        };
+        text = text.replace(&format!("{{{}}}", argument), &value);
+    }
+
+    Ok(GetPromptResult {
+        description: Some(template.description.to_string()),
+        messages: vec![PromptMessage::new_text(PromptMessageRole::User, text)],
+    })
+}
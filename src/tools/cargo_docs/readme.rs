@@ -0,0 +1,203 @@
+/// Markup language a README is written in
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReadmeFormat {
+    Html,
+    Markdown,
+    ReStructuredText,
+}
+
+// Characters reStructuredText allows for section title adornments
+const RST_ADORNMENTS: &[char] = &['=', '-', '~', '^', '*', '+', '#', '"', '`', '\''];
+
+// Whether `line` is an RST section adornment (a run of one repeated punctuation character)
+// at least as long as the `title` it underlines
+fn is_adornment(line: &str, title: &str) -> Option<char> {
+    let line = line.trim_end();
+    let first = line.chars().next()?;
+    let title_len = title.trim().chars().count();
+    (RST_ADORNMENTS.contains(&first)
+        && line.len() >= 3
+        && title_len > 0
+        && line.chars().count() >= title_len
+        && line.chars().all(|c| c == first))
+        .then_some(first)
+}
+
+/// Guess a README's format from its content. crates.io serves rendered HTML; raw READMEs are
+/// treated as reStructuredText when they use underlined titles or RST directives and as
+/// Markdown otherwise.
+pub fn detect_readme_format(readme: &str) -> ReadmeFormat {
+    let trimmed = readme.trim_start();
+    if trimmed.starts_with('<') {
+        return ReadmeFormat::Html;
+    }
+
+    let lines: Vec<&str> = readme.lines().collect();
+    let underlined_title = lines.windows(2).any(|pair| {
+        !pair[0].trim().is_empty()
+            && !pair[0].starts_with('#')
+            && is_adornment(pair[1], pair[0]).is_some_and(|c| c == '=' || c == '-' || c == '~')
+            && pair[1].trim_end().chars().count() == pair[0].trim().chars().count()
+    });
+    let directive = lines.iter().any(|line| line.trim_start().starts_with(".. "));
+    if directive || (underlined_title && !readme.contains("```")) {
+        ReadmeFormat::ReStructuredText
+    } else {
+        ReadmeFormat::Markdown
+    }
+}
+
+// Convert inline RST markup: ``code`` to `code` and `text <url>`_ links to [text](url)
+fn convert_rst_inline(line: &str) -> String {
+    let mut output = String::with_capacity(line.len());
+    let mut rest = line;
+
+    while let Some(start) = rest.find('`') {
+        output.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        // Inline literal
+        if let Some(literal) = rest.strip_prefix("``") {
+            if let Some(end) = literal.find("``") {
+                output.push('`');
+                output.push_str(&literal[..end]);
+                output.push('`');
+                rest = &literal[end + 2..];
+                continue;
+            }
+        }
+
+        // Hyperlink reference with an embedded URL; anything else is passed through
+        let inner = &rest[1..];
+        let link = inner.find("`_").and_then(|end| {
+            let target = &inner[..end];
+            let open = target.rfind('<')?;
+            target.ends_with('>').then(|| (target[..open].trim(), &target[open + 1..target.len() - 1], end))
+        });
+        match link {
+            Some((text, url, end)) => {
+                output.push_str(&format!("[{}]({})", text, url));
+                let after = &inner[end + 2..];
+                rest = after.strip_prefix('_').unwrap_or(after);
+            }
+            None => {
+                output.push('`');
+                rest = inner;
+            }
+        }
+    }
+    output.push_str(rest);
+    output
+}
+
+/// Best-effort conversion of reStructuredText to Markdown. Handles section titles, literal
+/// blocks (`::` and `code-block` directives), inline literals and hyperlinks; other
+/// directives are dropped and the remaining text is passed through unchanged.
+pub fn rst_to_markdown(rst: &str) -> String {
+    let lines: Vec<&str> = rst.lines().collect();
+    // Heading levels are assigned in the order adornment styles first appear
+    let mut heading_styles: Vec<(char, bool)> = Vec::new();
+    let mut output: Vec<String> = Vec::new();
+    let mut i = 0;
+
+    while i < lines.len() {
+        let line = lines[i];
+
+        // Overlined title: adornment, title, adornment
+        if i + 2 < lines.len() {
+            if let (Some(over), Some(under)) =
+                (is_adornment(line, lines[i + 1]), is_adornment(lines[i + 2], lines[i + 1]))
+            {
+                if over == under && !lines[i + 1].trim().is_empty() {
+                    let level = heading_level(&mut heading_styles, (over, true));
+                    output.push(format!("{} {}", "#".repeat(level), lines[i + 1].trim()));
+                    i += 3;
+                    continue;
+                }
+            }
+        }
+
+        // Underlined title
+        if i + 1 < lines.len() && !line.trim().is_empty() && !line.starts_with(' ') {
+            if let Some(under) = is_adornment(lines[i + 1], line) {
+                let level = heading_level(&mut heading_styles, (under, false));
+                output.push(format!("{} {}", "#".repeat(level), convert_rst_inline(line.trim())));
+                i += 2;
+                continue;
+            }
+        }
+
+        // Code directives and literal blocks introduced by `::`
+        let trimmed = line.trim();
+        let code_language = if let Some(rest) = trimmed
+            .strip_prefix(".. code-block::")
+            .or_else(|| trimmed.strip_prefix(".. code::"))
+            .or_else(|| trimmed.strip_prefix(".. sourcecode::"))
+        {
+            Some(rest.trim().to_string())
+        } else if trimmed.ends_with("::") && !trimmed.starts_with("..") {
+            // `Paragraph::` keeps one colon; a bare `::` line only introduces the block
+            let text = line.trim_end().trim_end_matches(':');
+            if !text.trim().is_empty() {
+                output.push(convert_rst_inline(&format!("{}:", text)));
+            }
+            Some(String::new())
+        } else {
+            None
+        };
+
+        if let Some(language) = code_language {
+            i += 1;
+            // Skip directive options and the blank line before the indented block
+            while i < lines.len() && (lines[i].trim().is_empty() || lines[i].trim_start().starts_with(':')) {
+                i += 1;
+            }
+            let indent = lines
+                .get(i)
+                .map(|line| line.len() - line.trim_start().len())
+                .unwrap_or(0);
+            if indent == 0 {
+                continue;
+            }
+
+            output.push(String::new());
+            output.push(format!("```{}", language));
+            while i < lines.len()
+                && (lines[i].trim().is_empty() || lines[i].len() - lines[i].trim_start().len() >= indent)
+            {
+                output.push(lines[i].get(indent..).unwrap_or("").to_string());
+                i += 1;
+            }
+            while output.last().is_some_and(|line| line.is_empty()) {
+                output.pop();
+            }
+            output.push("```".to_string());
+            output.push(String::new());
+            continue;
+        }
+
+        // Drop other directives (images, badges, includes) and comments
+        if trimmed.starts_with("..") {
+            i += 1;
+            while i < lines.len() && lines[i].starts_with(' ') {
+                i += 1;
+            }
+            continue;
+        }
+
+        output.push(convert_rst_inline(line));
+        i += 1;
+    }
+
+    // Collapse the blank lines doubled up around converted blocks
+    output.dedup_by(|a, b| a.is_empty() && b.is_empty());
+    output.join("\n").trim().to_string() + "\n"
+}
+
+fn heading_level(styles: &mut Vec<(char, bool)>, style: (char, bool)) -> usize {
+    let position = styles.iter().position(|s| *s == style).unwrap_or_else(|| {
+        styles.push(style);
+        styles.len() - 1
+    });
+    (position + 1).min(6)
+}
@@ -0,0 +1,74 @@
+use rmcp::model::{Annotated, RawResource, Resource};
+
+use super::cache::DOC_KEY_PREFIX;
+
+/// Number of resources returned per page by `resources/list`
+pub const RESOURCES_PER_PAGE: usize = 20;
+
+// Versions start with a digit and requirements such as `^0.11` with a digit or an operator;
+// item paths never do
+fn is_version(text: &str) -> bool {
+    text.starts_with(|c: char| c.is_ascii_digit() || "^~=<>".contains(c))
+}
+
+/// The `docs://` URI of the documentation cached under `key`: `docs://crate/<name>/latest` or
+/// `docs://crate/<name>/<version>` for crate docs and `docs://item/<crate>/<module_path>/<item_name>`
+/// for the latest docs of an item. Keys without the `doc:` prefix of documentation (examples,
+/// relationships, crates.io data, ...), item docs of a specific version and docs built for a
+/// specific target (keyed `<version>@<target>`) have no URI.
+pub fn resource_uri(key: &str) -> Option<String> {
+    let key = key.strip_prefix(DOC_KEY_PREFIX)?;
+    if key.contains('@') {
+        return None;
+    }
+    let Some((crate_name, rest)) = key.split_once(':') else {
+        return Some(format!("docs://crate/{}/latest", key));
+    };
+    match rest.split_once(':') {
+        _ if crate_name.is_empty() || rest.is_empty() => None,
+        // `crate:version:path`
+        Some((version, _)) if is_version(version) => None,
+        None if is_version(rest) => Some(format!("docs://crate/{}/{}", crate_name, rest)),
+        _ => Some(format!("docs://item/{}/{}", crate_name, rest.replace("::", "/"))),
+    }
+}
+
+/// The cache key of the documentation at a `docs://` URI, the inverse of [`resource_uri`]
+pub fn resource_key(uri: &str) -> Option<String> {
+    if let Some(rest) = uri.strip_prefix("docs://crate/") {
+        let (crate_name, version) = rest.split_once('/')?;
+        if crate_name.is_empty() || version.is_empty() || version.contains('/') {
+            return None;
+        }
+        return Some(if version == "latest" {
+            format!("{}{}", DOC_KEY_PREFIX, crate_name)
+        } else {
+            format!("{}{}:{}", DOC_KEY_PREFIX, crate_name, version)
+        });
+    }
+
+    let (crate_name, path) = uri.strip_prefix("docs://item/")?.split_once('/')?;
+    if crate_name.is_empty() || path.split('/').any(str::is_empty) {
+        return None;
+    }
+    Some(format!("{}{}:{}", DOC_KEY_PREFIX, crate_name, path.replace('/', "::")))
+}
+
+/// The resource listed for the documentation cached under `key`, if it has a URI
+pub fn cached_doc_resource(key: &str) -> Option<Resource> {
+    let uri = resource_uri(key)?;
+    let key = key.strip_prefix(DOC_KEY_PREFIX)?;
+    let name = match key.split_once(':') {
+        None => format!("{} (latest)", key),
+        Some((crate_name, version)) if is_version(version) => format!("{} {}", crate_name, version),
+        Some((crate_name, path)) => format!("{}::{}", crate_name, path),
+    };
+    let resource = RawResource {
+        uri,
+        name,
+        description: None,
+        mime_type: Some("text/markdown".to_string()),
+        size: None,
+    };
+    Some(Annotated::new(resource, None))
+}
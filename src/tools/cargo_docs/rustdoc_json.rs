@@ -0,0 +1,212 @@
+use rmcp::schemars;
+use serde::{Deserialize, Serialize};
+
+/// Where `lookup_crate` and `lookup_item_tool` read documentation from
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "lowercase")]
+#[schemars(crate = "rmcp::schemars")]
+pub enum DocFormat {
+    /// The rendered docs.rs pages, converted to markdown
+    #[default]
+    Html,
+    /// The rustdoc JSON docs.rs publishes for each build; needs the `rustdoc-json` feature
+    Json,
+}
+
+/// URL of the gzip-compressed rustdoc JSON of a crate version on docs.rs, in the format version
+/// the linked `rustdoc-types` reads. docs.rs keeps the JSON of every format version a crate was
+/// built with, while its unversioned URL serves whichever version rustdoc emitted last.
+#[cfg(feature = "rustdoc-json")]
+pub(crate) fn rustdoc_json_url(base_url: &str, crate_name: &str, version: Option<&str>) -> String {
+    format!(
+        "{}/crate/{}/{}/json/{}.gz",
+        base_url,
+        crate_name,
+        version.unwrap_or("latest"),
+        rustdoc_types::FORMAT_VERSION
+    )
+}
+
+#[cfg(feature = "rustdoc-json")]
+pub use json::{decode_rustdoc_json, format_crate, format_item};
+
+#[cfg(feature = "rustdoc-json")]
+mod json {
+    use std::io::Read;
+
+    use flate2::read::GzDecoder;
+    use rustdoc_types::{Crate, Id, Item, ItemEnum, ItemKind, StructKind, FORMAT_VERSION};
+    use serde::Deserialize;
+
+    use super::super::error::DocError;
+    use super::super::types::ItemPath;
+
+    /// Parse the rustdoc JSON in `body` with the `rustdoc-types` schema, decompressing it first
+    /// when it is gzip-compressed as docs.rs serves it. The schema changes between format
+    /// versions, so JSON of any version but `FORMAT_VERSION` is rejected.
+    pub fn decode_rustdoc_json(body: &[u8]) -> Result<Crate, DocError> {
+        #[derive(Deserialize)]
+        struct Header {
+            format_version: u32,
+        }
+
+        let json = if body.starts_with(&[0x1f, 0x8b]) {
+            let mut json = Vec::new();
+            GzDecoder::new(body)
+                .read_to_end(&mut json)
+                .map_err(|e| DocError::Parse(format!("invalid gzip data in rustdoc JSON response: {}", e)))?;
+            json
+        } else {
+            body.to_vec()
+        };
+        serde_json::from_slice(&json).map_err(|e| match serde_json::from_slice::<Header>(&json) {
+            Ok(header) if header.format_version != FORMAT_VERSION => DocError::Parse(format!(
+                "rustdoc JSON has format version {}, but only version {} can be read",
+                header.format_version, FORMAT_VERSION
+            )),
+            _ => DocError::Parse(format!("invalid rustdoc JSON: {}", e)),
+        })
+    }
+
+    // Ids of the items documented as part of `item`: a module's items, a struct's fields, an
+    // enum's variants, a trait's items, and the items of a type's inherent impls
+    fn member_ids(krate: &Crate, item: &Item) -> Vec<Id> {
+        let (mut members, impls) = match &item.inner {
+            ItemEnum::Module(module) => (module.items.clone(), &[][..]),
+            ItemEnum::Struct(details) => {
+                let fields = match &details.kind {
+                    StructKind::Unit => Vec::new(),
+                    StructKind::Tuple(fields) => fields.iter().flatten().copied().collect(),
+                    StructKind::Plain { fields, .. } => fields.clone(),
+                };
+                (fields, &details.impls[..])
+            }
+            ItemEnum::Union(details) => (details.fields.clone(), &details.impls[..]),
+            ItemEnum::Enum(details) => (details.variants.clone(), &details.impls[..]),
+            ItemEnum::Trait(details) => (details.items.clone(), &[][..]),
+            _ => return Vec::new(),
+        };
+        for impl_id in impls {
+            if let Some(ItemEnum::Impl(impl_block)) = krate.index.get(impl_id).map(|item| &item.inner) {
+                if impl_block.trait_.is_none() {
+                    members.extend(impl_block.items.iter().copied());
+                }
+            }
+        }
+        members
+    }
+
+    // Heading of the section listing members of `kind`, in the order sections are rendered
+    const SECTIONS: &[(ItemKind, &str)] = &[
+        (ItemKind::Use, "Re-exports"),
+        (ItemKind::Module, "Modules"),
+        (ItemKind::StructField, "Fields"),
+        (ItemKind::Variant, "Variants"),
+        (ItemKind::AssocType, "Associated Types"),
+        (ItemKind::AssocConst, "Associated Constants"),
+        (ItemKind::Struct, "Structs"),
+        (ItemKind::Enum, "Enums"),
+        (ItemKind::Union, "Unions"),
+        (ItemKind::Trait, "Traits"),
+        (ItemKind::TypeAlias, "Type Aliases"),
+        (ItemKind::Function, "Functions"),
+        (ItemKind::Macro, "Macros"),
+        (ItemKind::ProcAttribute, "Attribute Macros"),
+        (ItemKind::ProcDerive, "Derive Macros"),
+        (ItemKind::Constant, "Constants"),
+        (ItemKind::Static, "Statics"),
+    ];
+
+    // First paragraph line of an item's docs
+    fn summary(item: &Item) -> &str {
+        item.docs.as_deref().and_then(|docs| docs.lines().next()).unwrap_or("").trim()
+    }
+
+    // Documentation of `item` followed by one section per kind of member
+    fn format_documented(krate: &Crate, title: String, item: &Item, is_type: bool) -> String {
+        let mut markdown = format!("# {}\n\n", title);
+        if let Some(docs) = item.docs.as_deref().filter(|docs| !docs.trim().is_empty()) {
+            markdown.push_str(docs.trim());
+            markdown.push_str("\n\n");
+        }
+
+        let members: Vec<&Item> = member_ids(krate, item)
+            .iter()
+            .filter_map(|id| krate.index.get(id))
+            .collect();
+        for (kind, heading) in SECTIONS {
+            let heading = match kind {
+                ItemKind::Function if is_type => "Methods",
+                _ => heading,
+            };
+            let mut lines: Vec<String> = members
+                .iter()
+                .filter(|member| member.inner.item_kind() == *kind)
+                .filter_map(|member| {
+                    let name = match &member.inner {
+                        ItemEnum::Use(use_item) => Some(use_item.name.as_str()),
+                        _ => member.name.as_deref(),
+                    }?;
+                    Some(match summary(member) {
+                        "" => format!("- `{}`\n", name),
+                        summary => format!("- `{}`: {}\n", name, summary),
+                    })
+                })
+                .collect();
+            lines.sort();
+            if !lines.is_empty() {
+                markdown.push_str(&format!("## {}\n\n{}\n", heading, lines.concat()));
+            }
+        }
+        markdown
+    }
+
+    /// Markdown documentation of the crate's root module
+    pub fn format_crate(krate: &Crate, crate_name: &str) -> Result<String, DocError> {
+        let root = krate
+            .index
+            .get(&krate.root)
+            .ok_or_else(|| DocError::Parse("rustdoc JSON has no root module".to_string()))?;
+        let version = krate
+            .crate_version
+            .as_deref()
+            .map(|version| format!(" {}", version))
+            .unwrap_or_default();
+        Ok(format_documented(krate, format!("Crate `{}`{}", crate_name, version), root, false))
+    }
+
+    /// Markdown documentation of the item at `item_path`
+    pub fn format_item(krate: &Crate, crate_name: &str, item_path: &ItemPath) -> Result<String, DocError> {
+        let mut wanted = vec![crate_name.replace('-', "_")];
+        wanted.extend(item_path.segments().iter().cloned());
+
+        // The canonical path, or else the shortest path ending the same way, e.g. for re-exports
+        let local = || krate.paths.iter().filter(|(_, summary)| summary.crate_id == 0);
+        let found = local().find(|(_, summary)| summary.path == wanted).or_else(|| {
+            local()
+                .filter(|(_, summary)| summary.path.ends_with(&wanted[1..]))
+                .min_by_key(|(_, summary)| (summary.path.len(), summary.path.join("::")))
+        });
+        let (id, summary) = found.ok_or_else(|| DocError::NotFound {
+            crate_name: crate_name.to_string(),
+            item_path: Some(item_path.to_string()),
+            tried_urls: Vec::new(),
+        })?;
+        let item = krate
+            .index
+            .get(id)
+            .ok_or_else(|| DocError::Parse(format!("rustdoc JSON has no item with id {}", id.0)))?;
+
+        // The kind as it is serialized, e.g. `type_alias`
+        let kind = serde_json::to_value(summary.kind)
+            .ok()
+            .and_then(|kind| kind.as_str().map(|kind| kind.replace('_', " ")))
+            .unwrap_or_default();
+        let title = format!("{} `{}`", kind, summary.path.join("::"));
+        let is_type = matches!(
+            summary.kind,
+            ItemKind::Struct | ItemKind::Enum | ItemKind::Union | ItemKind::Trait
+        );
+        Ok(format_documented(krate, title, item, is_type))
+    }
+}
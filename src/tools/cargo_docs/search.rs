@@ -0,0 +1,162 @@
+use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
+use rmcp::schemars;
+use serde::{Deserialize, Serialize};
+
+/// Result ordering supported by the crates.io search API
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+#[schemars(crate = "rmcp::schemars")]
+pub enum SearchSort {
+    Downloads,
+    RecentDownloads,
+    RecentUpdates,
+    NewCrates,
+    Alphabetical,
+}
+
+impl SearchSort {
+    /// Value of the crates.io API `sort` query parameter
+    pub fn as_query_value(self) -> &'static str {
+        match self {
+            SearchSort::Downloads => "downloads",
+            SearchSort::RecentDownloads => "recent-downloads",
+            SearchSort::RecentUpdates => "recent-updates",
+            SearchSort::NewCrates => "new",
+            SearchSort::Alphabetical => "alpha",
+        }
+    }
+}
+
+/// A single crate from a crates.io search
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CrateSearchResult {
+    pub name: String,
+    pub description: Option<String>,
+    pub downloads: u64,
+    pub recent_downloads: Option<u64>,
+    #[serde(rename(deserialize = "max_version"))]
+    pub version: String,
+    pub updated_at: String,
+    pub repository: Option<String>,
+    pub documentation: Option<String>,
+}
+
+// Body of a crates.io `/api/v1/crates` response
+#[derive(Debug, Deserialize)]
+pub(crate) struct CrateSearchResponse {
+    pub crates: Vec<CrateSearchResult>,
+    pub meta: CrateSearchMeta,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct CrateSearchMeta {
+    // Number of matching crates across all pages
+    pub total: u64,
+}
+
+// What a crates.io search request returned: search results, or an HTML page (as markdown)
+// when the response isn't JSON
+pub(crate) enum SearchResponse {
+    Results(CrateSearchResponse),
+    Page(String),
+}
+
+// Body of a crates.io API error response, e.g. `{"errors": [{"detail": "invalid page"}]}`
+#[derive(Debug, Deserialize)]
+pub(crate) struct CratesIoErrors {
+    pub errors: Vec<CratesIoErrorDetail>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct CratesIoErrorDetail {
+    pub detail: String,
+}
+
+// Whether a `Content-Type` header value names JSON: `application/json` or `text/json`,
+// optionally with parameters such as `charset`
+pub(crate) fn is_json_content_type(content_type: &str) -> bool {
+    let mime = content_type.split(';').next().unwrap_or_default().trim();
+    mime.eq_ignore_ascii_case("application/json") || mime.eq_ignore_ascii_case("text/json")
+}
+
+// Build the crates.io search API URL, percent-encoding the user-supplied query and filters
+pub(crate) fn crates_io_search_url(
+    crates_io_base_url: &str,
+    query: &str,
+    limit: u32,
+    sort: Option<SearchSort>,
+    category: Option<&str>,
+    keyword: Option<&str>,
+    page: Option<u32>,
+) -> String {
+    let mut url = format!(
+        "{}/api/v1/crates?q={}&per_page={}",
+        crates_io_base_url,
+        utf8_percent_encode(query, NON_ALPHANUMERIC),
+        limit
+    );
+    if let Some(sort) = sort {
+        url.push_str(&format!("&sort={}", sort.as_query_value()));
+    }
+    if let Some(category) = category {
+        url.push_str(&format!("&category={}", utf8_percent_encode(category, NON_ALPHANUMERIC)));
+    }
+    if let Some(keyword) = keyword {
+        url.push_str(&format!("&keyword={}", utf8_percent_encode(keyword, NON_ALPHANUMERIC)));
+    }
+    if let Some(page) = page {
+        url.push_str(&format!("&page={}", page));
+    }
+    url
+}
+
+// Render one page of search results as markdown, one section per crate.
+// `limit` is the page size the results were requested with.
+pub(crate) fn format_search_results(
+    query: &str,
+    response: &CrateSearchResponse,
+    page: u32,
+    limit: u32,
+) -> String {
+    let total = response.meta.total;
+    let pages = total.div_ceil(u64::from(limit.max(1))).max(1);
+    let summary = format!("Page {} of {} (Total: {} results)", page, pages, total);
+
+    if response.crates.is_empty() {
+        return if total == 0 {
+            format!("No crates found matching `{}`.", query)
+        } else {
+            format!(
+                "No results on page {} for `{}`; the last page is {}. _{}_",
+                page, query, pages, summary
+            )
+        };
+    }
+
+    let mut output = format!("# Crate search results for `{}`\n\n_{}_\n\n", query, summary);
+    for result in &response.crates {
+        output.push_str(&format!(
+            "## [{}](https://crates.io/crates/{}) {}\n\n",
+            result.name, result.name, result.version
+        ));
+        if let Some(description) = &result.description {
+            output.push_str(description.trim());
+            output.push_str("\n\n");
+        }
+
+        output.push_str(&format!("- {} downloads", result.downloads));
+        if let Some(recent) = result.recent_downloads {
+            output.push_str(&format!(" ({} recent)", recent));
+        }
+        output.push('\n');
+        output.push_str(&format!("- Updated: {}\n", result.updated_at));
+        if let Some(repository) = &result.repository {
+            output.push_str(&format!("- Repository: {}\n", repository));
+        }
+        if let Some(documentation) = &result.documentation {
+            output.push_str(&format!("- Documentation: {}\n", documentation));
+        }
+        output.push('\n');
+    }
+    output
+}
@@ -1,10 +1,27 @@
 use super::*;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use tokio::test;
 
+// Text of a successful tool call
+fn output(result: Result<CallToolResult, McpError>) -> String {
+    result.unwrap().content[0].as_text().unwrap().text.clone()
+}
+
+// A crate name known to be valid
+fn crate_name(name: &str) -> CrateName {
+    CrateName::parse(name).unwrap()
+}
+
+// An item path known to be valid
+fn item_path(crate_name: &str, path: &str) -> ItemPath {
+    ItemPath::parse(crate_name, path).unwrap()
+}
+
 #[test]
 async fn test_lookup_crate() {
     let router = CargoDocRouter::new();
-    let result = router.lookup_crate("lumin".to_string(), None).await;
+    let result = output(router.lookup_crate("lumin".to_string(), None, None, None, None, None).await);
     
     // Verify that the result contains expected content for the lumin crate
     assert!(result.contains("lumin"));
@@ -19,7 +36,7 @@ async fn test_lookup_crate() {
 #[test]
 async fn test_lookup_crate_with_version() {
     let router = CargoDocRouter::new();
-    let result = router.lookup_crate("lumin".to_string(), Some("0.1.0".to_string())).await;
+    let result = output(router.lookup_crate("lumin".to_string(), Some("0.1.0".to_string()), None, None, None, None).await);
     
     // Verify that the result contains expected content for the specific version
     assert!(result.contains("lumin"));
@@ -37,11 +54,20 @@ async fn test_lookup_crate_with_version() {
 #[test]
 async fn test_lookup_item() {
     let router = CargoDocRouter::new();
-    let result = router.lookup_item_tool(
-        "lumin".to_string(),
-        "core::Lumin".to_string(),
-        None,
-    ).await;
+    let result = output(
+        router.lookup_item_tool(
+            "lumin".to_string(),
+            "core::Lumin".to_string(),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        ).await,
+    );
     
     // Verify result contains the Lumin struct documentation
     assert!(!result.is_empty());
@@ -50,10 +76,215 @@ async fn test_lookup_item() {
     assert!(result.len() > 10);
 }
 
+#[test]
+async fn test_lookup_item_type_alias() {
+    let router = CargoDocRouter::new();
+    let result = output(router.lookup_item_tool("std".to_string(), "io::Result".to_string(), None, None, None, None, None, None, None, None).await);
+
+    assert!(result.contains("Result"));
+}
+
+#[test]
+async fn test_lookup_item_derive_macro() {
+    let router = CargoDocRouter::new();
+    let result = output(
+        router
+            .lookup_item_tool("serde_derive".to_string(), "Serialize".to_string(), None, None, None, None, None, None, None, None)
+            .await,
+    );
+
+    assert!(result.contains("Serialize"));
+}
+
+#[test]
+async fn test_lookup_item_constant() {
+    let router = CargoDocRouter::new();
+    let result = output(
+        router
+            .lookup_item_tool("std".to_string(), "f64::consts::PI".to_string(), None, None, None, None, None, None, None, None)
+            .await,
+    );
+
+    assert!(result.contains("PI"));
+}
+
+#[test]
+async fn test_infer_item_kind() {
+    let cases = [
+        ("HashMap", Some(ItemKind::Struct)),
+        ("Vec", Some(ItemKind::Struct)),
+        ("Client", Some(ItemKind::Struct)),
+        ("Option", Some(ItemKind::Struct)),
+        ("Ordering", Some(ItemKind::Struct)),
+        ("Serialize", Some(ItemKind::Struct)),
+        ("AsyncRead", Some(ItemKind::Struct)),
+        ("Result", Some(ItemKind::Struct)),
+        ("from_str", Some(ItemKind::Fn)),
+        ("spawn", Some(ItemKind::Fn)),
+        ("read_to_string", Some(ItemKind::Fn)),
+        ("to_string", Some(ItemKind::Fn)),
+        ("channel", Some(ItemKind::Fn)),
+        ("vec", Some(ItemKind::Fn)),
+        ("println!", Some(ItemKind::Macro)),
+        ("vec!", Some(ItemKind::Macro)),
+        ("json!", Some(ItemKind::Macro)),
+        ("select!", Some(ItemKind::Macro)),
+        ("MAX", Some(ItemKind::Constant)),
+        ("PI", Some(ItemKind::Constant)),
+        ("MAIN_SEPARATOR", Some(ItemKind::Constant)),
+        ("EPSILON", Some(ItemKind::Constant)),
+        ("UNIX_EPOCH", Some(ItemKind::Constant)),
+        ("E", Some(ItemKind::Struct)),
+        ("", None),
+        ("_private", None),
+    ];
+    for (name, expected) in cases {
+        assert_eq!(infer_item_kind(name), expected, "{}", name);
+    }
+}
+
+#[test]
+async fn test_probe_order() {
+    assert_eq!(probe_order("println!"), vec![ItemKind::Macro]);
+    assert_eq!(&probe_order("spawn")[..2], &[ItemKind::Fn, ItemKind::Macro]);
+    assert_eq!(&probe_order("MAX")[..2], &[ItemKind::Constant, ItemKind::Static]);
+    assert_eq!(probe_order("HashMap"), ItemKind::ALL.to_vec());
+
+    // Every kind is still probed for names that don't end in `!`
+    for name in ["spawn", "MAX", "HashMap", "_private"] {
+        let mut order = probe_order(name);
+        order.dedup();
+        assert_eq!(order.len(), ItemKind::ALL.len());
+    }
+}
+
+#[test]
+async fn test_module_index_url() {
+    let router = CargoDocRouter::new();
+    let tokio = CrateName::parse("tokio").unwrap();
+    let std = CrateName::parse("std").unwrap();
+    assert_eq!(
+        router.module_index_url(&tokio, &parse_module_path(&tokio, "sync").unwrap(), None),
+        "https://docs.rs/tokio/latest/tokio/sync/index.html"
+    );
+    let hash_map = parse_module_path(&std, "std::collections::hash_map").unwrap();
+    assert_eq!(
+        router.module_index_url(&std, &hash_map, Some("1.0.0")),
+        "https://doc.rust-lang.org/1.0.0/std/collections/hash_map/index.html"
+    );
+
+    // Spellings of one module share a cache key
+    for raw in ["sync", "tokio::sync", "sync/", "/sync", "sync::"] {
+        assert_eq!(parse_module_path(&tokio, raw).unwrap().to_string(), "sync");
+    }
+    assert!(parse_module_path(&tokio, "").is_err());
+    assert!(parse_module_path(&tokio, "io::::util").is_err());
+}
+
+#[test]
+async fn test_lookup_module() {
+    let router = CargoDocRouter::new();
+    let result = output(router.lookup_module("tokio".to_string(), "sync".to_string(), None).await);
+
+    assert!(result.contains("Mutex"));
+    assert!(router.cache.contains("module:tokio:sync").await);
+}
+
+#[test]
+async fn test_lookup_std_module() {
+    let router = CargoDocRouter::new();
+    let result = output(
+        router
+            .lookup_module("std".to_string(), "collections".to_string(), None)
+            .await,
+    );
+
+    assert!(result.contains("HashMap"));
+}
+
+#[test]
+async fn test_parse_module_items() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/module_index.html");
+    let html = std::fs::read_to_string(path).unwrap();
+    let items = parse_module_items(&html);
+
+    let names: Vec<&str> = items.iter().map(|item| item.name.as_str()).collect();
+    assert_eq!(
+        names,
+        vec![
+            "hash_map",
+            "BTreeMap",
+            "HashMap",
+            "HashSet",
+            "LinkedList",
+            "TryReserveError",
+            "Bound",
+            "TryReserveErrorKind",
+        ]
+    );
+    assert_eq!(
+        items[1],
+        ModuleItem {
+            name: "BTreeMap".to_string(),
+            kind: "struct".to_string(),
+            brief: "An ordered map based on a B-Tree.".to_string(),
+        }
+    );
+    assert_eq!(items[0].kind, "mod");
+    assert_eq!(items[6].kind, "enum");
+}
+
+#[test]
+async fn test_parse_module_items_legacy_layout() {
+    let html = r#"<div class="item-table">
+        <div class="item-row"><div class="item-left module-item"><a class="struct" href="struct.Mutex.html">Mutex</a></div>
+        <div class="item-right docblock-short">An asynchronous mutual exclusion primitive.</div></div>
+        <div class="item-row"><div class="item-left module-item"><a class="fn" href="fn.channel.html">channel</a></div>
+        <div class="item-right docblock-short"></div></div>
+    </div>"#;
+    let items = parse_module_items(html);
+
+    assert_eq!(items.len(), 2);
+    assert_eq!(items[0].brief, "An asynchronous mutual exclusion primitive.");
+    assert_eq!((items[1].name.as_str(), items[1].kind.as_str(), items[1].brief.as_str()), ("channel", "fn", ""));
+}
+
+#[test]
+async fn test_list_module_items() {
+    let router = CargoDocRouter::new();
+    let result = output(
+        router
+            .list_module_items("std".to_string(), "collections".to_string(), None)
+            .await,
+    );
+    let items: Vec<serde_json::Value> = serde_json::from_str(&result).unwrap();
+    let names: Vec<&str> = items.iter().filter_map(|item| item["name"].as_str()).collect();
+
+    for expected in ["HashMap", "BTreeMap", "HashSet", "LinkedList"] {
+        assert!(names.contains(&expected), "missing {}", expected);
+    }
+}
+
+#[test]
+async fn test_list_tokio_sync_items() {
+    let router = CargoDocRouter::new();
+    let result = output(
+        router
+            .list_module_items("tokio".to_string(), "sync".to_string(), None)
+            .await,
+    );
+    let items: Vec<serde_json::Value> = serde_json::from_str(&result).unwrap();
+    let names: Vec<&str> = items.iter().filter_map(|item| item["name"].as_str()).collect();
+
+    for expected in ["Mutex", "RwLock", "Semaphore"] {
+        assert!(names.contains(&expected), "missing {}", expected);
+    }
+}
+
 #[test]
 async fn test_search_crates() {
     let router = CargoDocRouter::new();
-    let result = router.search_crates("lumin".to_string(), Some(5)).await;
+    let result = output(router.search_crates("lumin".to_string(), Some(5), None, None, None, None).await);
     
     // Verify result contains search results for lumin
     assert!(result.contains("lumin"));
@@ -75,156 +306,6020 @@ async fn test_search_crates() {
 }
 
 #[test]
-async fn test_content_transformation() {
+async fn test_search_url_encodes_query() {
+    assert_eq!(
+        crates_io_search_url("https://crates.io", "http client", 10, None, None, None, None),
+        "https://crates.io/api/v1/crates?q=http%20client&per_page=10"
+    );
+    assert_eq!(
+        crates_io_search_url("https://crates.io", "a&b=c+d", 5, None, None, None, None),
+        "https://crates.io/api/v1/crates?q=a%26b%3Dc%2Bd&per_page=5"
+    );
+    assert_eq!(
+        crates_io_search_url("https://crates.io", "日本語", 5, None, None, None, None),
+        "https://crates.io/api/v1/crates?q=%E6%97%A5%E6%9C%AC%E8%AA%9E&per_page=5"
+    );
+
+    // The encoded URL must parse and round-trip the original query
+    let url = reqwest::Url::parse(&crates_io_search_url("https://crates.io", "serde & json = fast+", 5, None, None, None, None)).unwrap();
+    let (_, query) = url.query_pairs().find(|(key, _)| key == "q").unwrap();
+    assert_eq!(query, "serde & json = fast+");
+}
+
+#[test]
+async fn test_search_crates_multi_word_query() {
     let router = CargoDocRouter::new();
-    
-    // Test the transformation from HTML to markdown
-    let serde_result = router.lookup_crate("serde".to_string(), None).await;
-    
-    // Verify we got substantial content
-    assert!(!serde_result.is_empty());
-    assert!(serde_result.len() > 100);
-    
-    // Basic check for HTML to markdown conversion
-    // Just check that we don't have obvious HTML tags
-    assert!(!serde_result.contains("<html>"));
+    let result = output(router.search_crates("http client".to_string(), Some(5), None, None, None, None).await);
+
+    // Verify crates.io accepted the encoded query and returned search results
+    assert!(result.contains("## ["));
 }
 
 #[test]
-async fn test_doc_cache() {
-    let cache = DocCache::new();
-    let key = "test_key";
-    let value = "test_value".to_string();
-    
-    // Initially the key should not exist
-    assert_eq!(cache.get(key).await, None);
-    
-    // Set a value
-    cache.set(key.to_string(), value.clone()).await;
-    
-    // Now we should get the value back
-    assert_eq!(cache.get(key).await, Some(value));
+async fn test_search_url_sort() {
+    let cases = [
+        (SearchSort::Downloads, "downloads"),
+        (SearchSort::RecentDownloads, "recent-downloads"),
+        (SearchSort::RecentUpdates, "recent-updates"),
+        (SearchSort::NewCrates, "new"),
+        (SearchSort::Alphabetical, "alpha"),
+    ];
+    for (sort, value) in cases {
+        assert_eq!(
+            crates_io_search_url("https://crates.io", "json", 10, Some(sort), None, None, None),
+            format!("https://crates.io/api/v1/crates?q=json&per_page=10&sort={}", value)
+        );
+    }
+
+    // Tool arguments use the kebab-case variant names
+    let sort: SearchSort = serde_json::from_str("\"recent-downloads\"").unwrap();
+    assert_eq!(sort, SearchSort::RecentDownloads);
 }
 
 #[test]
-async fn test_cache_in_lookup() {
+async fn test_search_url_filters() {
+    assert_eq!(
+        crates_io_search_url("https://crates.io", "client", 10, None, Some("web-programming::http-client"), None, None),
+        "https://crates.io/api/v1/crates?q=client&per_page=10&category=web%2Dprogramming%3A%3Ahttp%2Dclient"
+    );
+    assert_eq!(
+        crates_io_search_url("https://crates.io", "", 5, Some(SearchSort::Downloads), Some("parsing"), Some("json"), None),
+        "https://crates.io/api/v1/crates?q=&per_page=5&sort=downloads&category=parsing&keyword=json"
+    );
+}
+
+#[test]
+async fn test_search_crates_by_category() {
     let router = CargoDocRouter::new();
-    
-    // First lookup to populate the cache
-    let first_result = router.lookup_crate("regex".to_string(), None).await;
-    assert!(!first_result.is_empty());
-    
-    // Second lookup should use the cache
-    let second_result = router.lookup_crate("regex".to_string(), None).await;
-    
-    // Results should be identical when pulled from cache
-    assert_eq!(first_result, second_result);
+    let result = output(
+        router
+            .search_crates(
+                "http".to_string(),
+                Some(10),
+                Some(SearchSort::Downloads),
+                Some("web-programming".to_string()),
+                None,
+                None,
+            )
+            .await,
+    );
+
+    assert!(result.contains("## ["));
+    assert!(result.contains("hyper") || result.contains("reqwest"));
 }
 
 #[test]
-async fn test_lookup_item_examples() {
+async fn test_search_crates_sort_order() {
     let router = CargoDocRouter::new();
-    
-    // Test lookup examples for a well-documented item that should have examples
-    let examples = router.lookup_item_examples(
-        "std".to_string(),
-        "vec::Vec".to_string(),
-        None
-    ).await;
-    
-    // Verify we got substantial content
-    assert!(!examples.is_empty());
-    assert!(examples.contains("Usage Examples"));
-    
-    // Should include either real examples or generated examples
-    assert!(examples.contains("```rust") || examples.contains("```"));
+    let by_downloads = output(
+        router
+            .search_crates("json".to_string(), Some(10), Some(SearchSort::Downloads), None, None, None)
+            .await,
+    );
+    let by_newest = output(
+        router
+            .search_crates("json".to_string(), Some(10), Some(SearchSort::NewCrates), None, None, None)
+            .await,
+    );
+
+    assert!(by_downloads.contains("## ["));
+    assert!(by_newest.contains("## ["));
+    assert_ne!(by_downloads, by_newest);
 }
 
 #[test]
-async fn test_analyze_type_relationships() {
+async fn test_format_search_results() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/crates_io_search.json");
+    let json = std::fs::read_to_string(path).unwrap();
+    let response: search::CrateSearchResponse = serde_json::from_str(&json).unwrap();
+
+    let serde = &response.crates[0];
+    assert_eq!(serde.version, "1.0.219");
+    assert_eq!(serde.recent_downloads, Some(87654321));
+    assert_eq!(response.crates[1].description, None);
+
+    let markdown = format_search_results("serde", &response, 1, 10);
+    assert!(markdown.starts_with("# Crate search results for `serde`"));
+    assert!(markdown.contains("_Page 1 of 1 (Total: 2 results)_"));
+    assert!(markdown.contains("## [serde](https://crates.io/crates/serde) 1.0.219"));
+    assert!(markdown.contains("- 512345678 downloads (87654321 recent)"));
+    assert!(markdown.contains("- Repository: https://github.com/serde-rs/serde"));
+    assert!(markdown.contains("- 42 downloads\n"));
+
+    let empty = search::CrateSearchResponse {
+        crates: Vec::new(),
+        meta: search::CrateSearchMeta { total: 0 },
+    };
+    assert_eq!(format_search_results("nothing", &empty, 1, 10), "No crates found matching `nothing`.");
+}
+
+#[test]
+async fn test_format_search_results_pages() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/crates_io_search.json");
+    let json = std::fs::read_to_string(path).unwrap();
+    let mut response: search::CrateSearchResponse = serde_json::from_str(&json).unwrap();
+    response.meta.total = 45;
+
+    let markdown = format_search_results("serde", &response, 2, 10);
+    assert!(markdown.contains("_Page 2 of 5 (Total: 45 results)_"));
+
+    response.crates.clear();
+    assert_eq!(
+        format_search_results("serde", &response, 7, 10),
+        "No results on page 7 for `serde`; the last page is 5. _Page 7 of 5 (Total: 45 results)_"
+    );
+}
+
+#[test]
+async fn test_search_url_page() {
+    assert_eq!(
+        crates_io_search_url("https://crates.io", "web", 20, None, None, None, Some(3)),
+        "https://crates.io/api/v1/crates?q=web&per_page=20&page=3"
+    );
+}
+
+#[test]
+async fn test_search_crates_pagination() {
     let router = CargoDocRouter::new();
-    
-    // Test analysis for a standard type that should have well-defined relationships
-    let relationships = router.analyze_type_relationships(
-        "std".to_string(),
-        "result::Result".to_string(),
-        None
-    ).await;
-    
-    // Verify we got substantial content
-    assert!(!relationships.is_empty());
-    assert!(relationships.contains("Type Relationships"));
-    
-    // Should include some guidance about Result usage patterns
-    assert!(relationships.contains("Usage Patterns") || 
-            relationships.contains("Working with Result types") ||
-            relationships.contains("impl") ||
-            relationships.contains("match"));
+    let first = output(
+        router
+            .search_crates("web".to_string(), Some(10), None, None, None, Some(1))
+            .await,
+    );
+    let second = output(
+        router
+            .search_crates("web".to_string(), Some(10), None, None, None, Some(2))
+            .await,
+    );
+    assert!(first.contains("_Page 1 of "));
+    assert!(second.contains("_Page 2 of "));
+    assert_ne!(first, second);
+
+    // crates.io may refuse a page this far out instead of returning no results
+    let beyond = router
+        .search_crates("web".to_string(), Some(10), None, None, None, Some(100_000))
+        .await;
+    if let Ok(beyond) = beyond {
+        assert!(output(Ok(beyond)).starts_with("No results on page 100000"));
+    }
 }
 
 #[test]
-async fn test_examples_cache() {
-    let cache = DocCache::new();
-    let examples = vec![
-        CodeExample {
-            title: "Test Example".to_string(),
-            code: "fn main() {}".to_string(),
-            description: "A test example".to_string(),
-        }
-    ];
-    let key = "examples:test";
-    
-    // Initially the key should not exist
-    assert_eq!(cache.get_examples(key).await, None);
-    
-    // Set a value
-    cache.set_examples(key.to_string(), examples.clone()).await;
-    
-    // Now we should get the value back
-    assert_eq!(cache.get_examples(key).await.unwrap()[0].title, "Test Example");
-    assert_eq!(cache.get_examples(key).await.unwrap()[0].code, "fn main() {}");
+async fn test_search_crates_structured() {
+    let router = CargoDocRouter::new();
+    let results = router.search_crates_structured("serde", 5).await.unwrap();
+
+    let serde = results.iter().find(|result| result.name == "serde").unwrap();
+    assert!(serde.description.is_some());
+    assert!(serde.downloads > 0);
+    assert!(serde.recent_downloads.is_some());
+    assert!(!serde.version.is_empty());
+    assert!(!serde.updated_at.is_empty());
+    assert!(serde.repository.is_some());
+    assert!(serde.documentation.is_some());
 }
 
 #[test]
-async fn test_generated_examples() {
+async fn test_format_crate_versions() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/crates_io_versions.json");
+    let json = std::fs::read_to_string(path).unwrap();
+    let response: crates_io::CrateVersionsResponse = serde_json::from_str(&json).unwrap();
+
+    let versions = filter_versions(response.versions.clone(), false);
+    let numbers: Vec<&str> = versions.iter().map(|v| v.version.as_str()).collect();
+    assert_eq!(numbers, vec!["1.0.1", "1.0.0", "1.0.0-rc.1"]);
+
+    let table = format_versions("serde", &versions);
+    assert!(table.contains("| Version | Published | Yanked |"));
+    assert!(table.contains("| 1.0.0 | 2017-04-20 | no |"));
+    assert!(table.contains("| 1.0.0-rc.1 | 2017-04-10 | yes |"));
+
+    let stable = filter_versions(response.versions, true);
+    assert_eq!(stable.len(), 2);
+    assert!(!format_versions("serde", &stable).contains("rc.1"));
+}
+
+#[test]
+async fn test_crate_version_prerelease() {
+    let version = |num: &str| PublishedVersion {
+        version: num.to_string(),
+        published_at: String::new(),
+        yanked: false,
+    };
+    assert!(version("0.5.0-alpha.1").is_prerelease());
+    assert!(!version("1.0.0").is_prerelease());
+    assert!(!version("1.0.0+build-5").is_prerelease());
+}
+
+#[test]
+async fn test_list_crate_versions() {
     let router = CargoDocRouter::new();
-    
-    // Test generation of synthetic examples for an item that likely won't have explicit examples
-    // but can be inferred from its type (struct, enum, trait, etc.)
-    let examples = router.lookup_item_examples(
-        "lumin".to_string(),
-        "core::Lumin".to_string(),
-        None
-    ).await;
-    
-    // Verify we got content
-    assert!(!examples.is_empty());
-    
-    // Should include the phrases indicating generated examples
-    assert!(examples.contains("Usage Examples"));
+    let result = output(router.list_crate_versions("serde".to_string(), None).await);
+
+    assert!(result.contains("| Version | Published | Yanked |"));
+    assert!(result.contains("| 1.0.0 |"));
+
+    let stable = output(router.list_crate_versions("serde".to_string(), Some(true)).await);
+    assert!(stable.contains("| 1.0.0 |"));
+    assert!(!stable.contains("-rc") && !stable.contains("-alpha") && !stable.contains("-beta"));
 }
 
 #[test]
-async fn test_relationship_analysis_impl() {
+async fn test_format_crate_dependencies() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/crates_io_dependencies.json");
+    let json = std::fs::read_to_string(path).unwrap();
+    let response: crates_io::CrateDependenciesResponse = serde_json::from_str(&json).unwrap();
+
+    let markdown = format_dependencies("reqwest", "0.12.0", &response.dependencies);
+    assert!(markdown.starts_with("# Dependencies of `reqwest` 0.12.0"));
+    assert!(markdown.contains("| hyper | `^1.1` | no | yes |\n| serde_json | `^1.0` | yes | yes |\n| tokio | `^1.0` | no | no |"));
+    assert!(markdown.contains("## Dev dependencies\n\n| Name | Requirement | Optional | Default features |\n|---|---|---|---|\n| env_logger |"));
+    assert!(markdown.ends_with("## Build dependencies\n\n_None_\n"));
+}
+
+#[test]
+async fn test_get_crate_dependencies() {
     let router = CargoDocRouter::new();
-    
-    // Test the implementation details of type relationship analysis
-    let relationships = router.analyze_type_relationships(
-        "serde".to_string(),
-        "Serialize".to_string(),
-        None
-    ).await;
-    
-    // Verify we got substantial content
-    assert!(!relationships.is_empty());
-    
-    // Should include sections that analyze the type
-    assert!(relationships.contains("Type Relationships") || 
-            relationships.contains("trait") ||
-            relationships.contains("impl"));
-    
-    // Should include guidance on usage patterns
-    assert!(relationships.contains("Usage Patterns") || 
-            relationships.contains("Implementing") ||
-            relationships.contains("Common"));
-}
\ No newline at end of file
+    let result = output(router.get_crate_dependencies("reqwest".to_string(), None).await);
+
+    let normal = result
+        .split("## Normal dependencies")
+        .nth(1)
+        .and_then(|rest| rest.split("## Dev dependencies").next())
+        .unwrap();
+    assert!(normal.contains("| hyper |"));
+    assert!(normal.contains("| tokio |"));
+}
+
+#[test]
+async fn test_format_crate_features() {
+    let json = r#"{"version": {"num": "1.0.0", "features": {
+        "full": ["macros", "rt"],
+        "default": [],
+        "rt": [],
+        "macros": ["dep:tokio-macros"]
+    }}}"#;
+    let response: crates_io::CrateVersionResponse = serde_json::from_str(json).unwrap();
+
+    let markdown = format_features("tokio", "1.0.0", &response.version.features);
+    assert_eq!(
+        markdown,
+        "# Features of `tokio` 1.0.0\n\n| Feature | Enables |\n|---|---|\n\
+         | **default** | _nothing_ |\n\
+         | `full` | `macros`, `rt` |\n\
+         | `macros` | `dep:tokio-macros` |\n\
+         | `rt` | _nothing_ |\n"
+    );
+
+    let markdown = format_features("anyhow", "1.0.0", &Default::default());
+    assert!(markdown.contains("declares no feature flags"));
+}
+
+#[test]
+async fn test_get_crate_features() {
+    let router = CargoDocRouter::new();
+    let result = output(router.get_crate_features("tokio".to_string(), None).await);
+    for feature in ["`full`", "`rt`", "`io-util`", "`macros`"] {
+        assert!(result.contains(feature), "missing {}", feature);
+    }
+
+    let result = output(router.get_crate_features("serde".to_string(), None).await);
+    assert!(result.contains("| **default** |"));
+    assert!(result.contains("| `derive` |"));
+}
+
+#[test]
+async fn test_detect_readme_format() {
+    assert_eq!(detect_readme_format("<h1>serde</h1>"), ReadmeFormat::Html);
+    assert_eq!(detect_readme_format("# serde\n\nSerialization."), ReadmeFormat::Markdown);
+    assert_eq!(
+        detect_readme_format("Title\n-----\n\n```rust\nfn main() {}\n```\n"),
+        ReadmeFormat::Markdown
+    );
+    assert_eq!(detect_readme_format("pyo3\n====\n\nBindings."), ReadmeFormat::ReStructuredText);
+    assert_eq!(
+        detect_readme_format("Intro\n\n.. image:: badge.svg\n"),
+        ReadmeFormat::ReStructuredText
+    );
+}
+
+#[test]
+async fn test_rst_to_markdown() {
+    let rst = "\
+=====
+Title
+=====
+
+Intro with ``inline code`` and a `link <https://example.com>`_.
+
+Usage
+-----
+
+Example::
+
+    let x = 1;
+    let y = 2;
+
+.. code-block:: rust
+
+    fn main() {}
+
+.. image:: https://example.com/badge.svg
+   :alt: badge
+
+Details
+~~~~~~~
+
+Done.
+";
+    assert_eq!(
+        rst_to_markdown(rst),
+        "\
+# Title
+
+Intro with `inline code` and a [link](https://example.com).
+
+## Usage
+
+Example:
+
+```
+let x = 1;
+let y = 2;
+```
+
+```rust
+fn main() {}
+```
+
+### Details
+
+Done.
+"
+    );
+}
+
+#[test]
+async fn test_get_crate_readme() {
+    let router = CargoDocRouter::new();
+    for crate_name in ["serde", "clap"] {
+        let result = output(router.get_crate_readme(crate_name.to_string(), None).await);
+        assert!(result.len() > 500, "{} README too short", crate_name);
+    }
+}
+
+#[test]
+async fn test_format_failed_build_status() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/docs_rs_builds_failed.json");
+    let json = std::fs::read_to_string(path).unwrap();
+    let builds: Vec<DocsRsBuild> = serde_json::from_str(&json).unwrap();
+    assert_eq!(builds[1].build_status, BuildStatus::Success);
+    assert_eq!(builds[0].duration_secs(), Some(192));
+
+    let markdown = format_build_status("broken", "0.1.0", &builds);
+    assert!(markdown.contains("- Status: failure"));
+    assert!(markdown.contains("- Toolchain: rustc 1.80.0-nightly (72fdf913c 2024-06-05)"));
+    assert!(markdown.contains("- Duration: 3m 12s"));
+    assert!(markdown.contains("- Build log: https://docs.rs/crate/broken/0.1.0/builds/1234568"));
+
+    assert_eq!(
+        format_build_status("none", "0.1.0", &[]),
+        "No docs.rs builds found for none 0.1.0."
+    );
+}
+
+#[test]
+async fn test_get_build_status() {
+    let router = CargoDocRouter::new();
+    let result = output(
+        router
+            .get_build_status("serde".to_string(), Some("1.0.200".to_string()))
+            .await,
+    );
+
+    assert!(result.contains("- Status: success"));
+    assert!(!result.contains("Build log"));
+    assert!(router.cache.contains("builds:serde:1.0.200").await);
+}
+
+#[test]
+async fn test_build_status_refreshed_in_background() {
+    let mut server = mockito::Server::new_async().await;
+    let failed = server
+        .mock("GET", "/crate/broken/0.1.0/builds.json")
+        .with_body(fixture("docs_rs_builds_failed.json"))
+        .expect(1)
+        .create_async()
+        .await;
+    let rebuilt = server
+        .mock("GET", "/crate/broken/0.1.0/builds.json")
+        .with_body("[]")
+        .expect(1)
+        .create_async()
+        .await;
+    let router = CargoDocRouter::new().with_base_url(server.url());
+    let status = || router.get_build_status("broken".to_string(), Some("0.1.0".to_string()));
+    assert!(output(status().await).contains("- Status: failure"));
+    assert!(!router.cache.needs_refresh("builds:broken:0.1.0").await);
+
+    // Close to expiring, the cached status is still returned while one refresh runs
+    tokio::time::pause();
+    tokio::time::advance(BUILD_STATUS_TTL.mul_f64(0.95)).await;
+    assert!(router.cache.needs_refresh("builds:broken:0.1.0").await);
+    for _ in 0..2 {
+        assert!(output(status().await).contains("- Status: failure"));
+    }
+    tokio::time::resume();
+
+    for _ in 0..100 {
+        if !router.cache.needs_refresh("builds:broken:0.1.0").await {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert_eq!(output(status().await), "No docs.rs builds found for broken 0.1.0.");
+    failed.assert_async().await;
+    rebuilt.assert_async().await;
+    assert!(router.refreshing.lock().unwrap().is_empty());
+}
+
+#[test]
+async fn test_panicking_refresh_can_run_again() {
+    let router = CargoDocRouter::new();
+    router.cache.set_with_ttl("builds:broken:0.1.0".to_string(), "cached".to_string(), BUILD_STATUS_TTL).await;
+    tokio::time::pause();
+    tokio::time::advance(BUILD_STATUS_TTL.mul_f64(0.95)).await;
+    tokio::time::resume();
+
+    let panicked = Arc::new(AtomicUsize::new(0));
+    for _ in 0..2 {
+        let panicked = panicked.clone();
+        router
+            .refresh_if_stale("builds:broken:0.1.0", |_| async move {
+                if panicked.fetch_add(1, Ordering::SeqCst) < 2 {
+                    panic!("refresh failed");
+                }
+                Ok(())
+            })
+            .await;
+        for _ in 0..100 {
+            if router.refreshing.lock().unwrap().is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert!(router.refreshing.lock().unwrap().is_empty());
+    }
+    assert_eq!(panicked.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+async fn test_background_refresh_disabled() {
+    let mut server = mockito::Server::new_async().await;
+    let builds = server
+        .mock("GET", "/crate/broken/0.1.0/builds.json")
+        .with_body(fixture("docs_rs_builds_failed.json"))
+        .expect(1)
+        .create_async()
+        .await;
+    let router = CargoDocRouter::builder()
+        .with_base_url(server.url())
+        .with_background_refresh(false)
+        .build()
+        .unwrap();
+    let status = || router.get_build_status("broken".to_string(), Some("0.1.0".to_string()));
+    output(status().await);
+
+    tokio::time::pause();
+    tokio::time::advance(BUILD_STATUS_TTL.mul_f64(0.95)).await;
+    assert!(output(status().await).contains("- Status: failure"));
+    tokio::time::resume();
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    builds.assert_async().await;
+    assert!(router.cache.needs_refresh("builds:broken:0.1.0").await);
+}
+
+#[test]
+async fn test_doc_cache_ttl() {
+    let cache = DocCache::new();
+    cache
+        .set_with_ttl("builds:serde:latest".to_string(), "status".to_string(), std::time::Duration::from_millis(50))
+        .await;
+    assert!(cache.contains("builds:serde:latest").await);
+    assert_eq!(cache.get("builds:serde:latest").await, Some("status".to_string()));
+
+    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    assert!(!cache.contains("builds:serde:latest").await);
+    assert_eq!(cache.get("builds:serde:latest").await, None);
+    assert_eq!(cache.stats().await.main_entries, 0);
+
+    // A plain set clears an earlier TTL
+    cache
+        .set_with_ttl("serde".to_string(), "old".to_string(), std::time::Duration::from_millis(50))
+        .await;
+    cache.set("serde".to_string(), "docs".to_string()).await;
+    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    assert_eq!(cache.get("serde").await, Some("docs".to_string()));
+}
+
+#[test]
+async fn test_diff_documentation() {
+    let old = "\
+# widget 0.1.0
+
+A widget library.
+
+## Structs
+
+- Widget
+- Builder
+
+## Functions
+
+- build
+
+## Deprecated
+
+- old_build
+";
+    let new = "\
+# widget 0.2.0
+
+A widget library.
+
+Structs
+-------
+
+- Widget
+- WidgetBuilder
+
+## Functions
+
+- build
+
+## Traits
+
+- Render
+";
+    let diff = diff_documentation("widget", "0.1.0", "0.2.0", old, new);
+
+    assert!(diff.contains("**Before:** `widget` 0.1.0"));
+    assert!(diff.contains("**After:** `widget` 0.2.0"));
+    assert!(diff.contains("not a semantic API diff"));
+
+    let section = |title: &str| {
+        let start = diff.find(&format!("## {}\n", title)).unwrap();
+        let rest = &diff[start + title.len() + 4..];
+        rest[..rest.find("\n## ").unwrap_or(rest.len())].to_string()
+    };
+    assert!(section("Added sections").contains("- Traits"));
+    assert!(section("Added sections").contains("- widget 0.2.0"));
+    assert!(section("Removed sections").contains("- Deprecated"));
+    assert!(section("Removed sections").contains("- widget 0.1.0"));
+
+    // The setext heading in the new version matches the ATX heading in the old one
+    let changed = section("Changed sections");
+    assert!(changed.contains("### Structs"));
+    assert!(changed.contains("-- Builder"));
+    assert!(changed.contains("+- WidgetBuilder"));
+    assert!(!changed.contains("### Functions"));
+
+    let unchanged = diff_documentation("widget", "0.1.0", "0.1.1", old, old);
+    assert!(unchanged.contains("No documentation differences found."));
+}
+
+#[test]
+async fn test_compare_crate_versions() {
+    let router = CargoDocRouter::new();
+    let diff = output(
+        router
+            .compare_crate_versions("reqwest".to_string(), "0.11.27".to_string(), "0.12.0".to_string())
+            .await,
+    );
+
+    assert!(diff.contains("# Documentation changes in `reqwest`: 0.11.27 → 0.12.0"));
+    assert!(diff.contains("## Changed sections"));
+    assert!(router.cache.contains("doc:reqwest:0.11.27").await);
+    assert!(router.cache.contains("doc:reqwest:0.12.0").await);
+}
+
+#[test]
+async fn test_extract_section() {
+    let markdown = html_to_markdown(
+        "<h1>Trait Display</h1><p>Format trait for an empty format.</p>\
+         <h2 id=\"required-methods\">Required Methods<a href=\"#required-methods\">§</a></h2>\
+         <h4>fn fmt(&amp;self) -&gt; Result</h4><p>Formats the value.</p>\
+         <h2 id=\"implementors\">Implementors<a href=\"#implementors\">§</a></h2>\
+         <h3>impl Display for str</h3>",
+    );
+
+    let methods = extract_section(&markdown, "required methods", false).unwrap();
+    assert!(methods.contains("Formats the value."));
+    assert!(!methods.contains("impl Display"));
+    assert_eq!(extract_section(&markdown, "Implementors", false).unwrap(), "### impl Display for str ###");
+    assert_eq!(extract_section(&markdown, "Provided Methods", false), None);
+
+    assert_eq!(plain_text(r"impl\<T\> [Display](trait.Display.html) for [[T]](primitive.slice.html)"), "impl<T> Display for [T]");
+}
+
+#[test]
+async fn test_extract_section_levels() {
+    let markdown = "# Crate\n\nIntro\n\n## Examples\n\n```rust\n# use std::io;\n## not a heading\n```\n\n### Sync\n\nBlocking.\n\n### Async\n\nAwaited.\n\n## Features\n\nNone.\n\n# Other\n";
+
+    let examples = extract_section(markdown, "examples", false).unwrap();
+    assert!(examples.starts_with("```rust\n# use std::io;\n## not a heading\n```"));
+    assert!(examples.contains("### Async\n\nAwaited."));
+    assert!(!examples.contains("Features"));
+    assert_eq!(extract_section(markdown, "Sync", false).unwrap(), "Blocking.");
+    assert!(extract_section(markdown, "Crate", false).unwrap().ends_with("None."));
+    assert_eq!(extract_section(markdown, "Other", false).unwrap(), "");
+    assert_eq!(extract_section(markdown, "examples", true), None);
+    assert!(extract_section(markdown, "Examples", true).is_some());
+    assert_eq!(extract_section(markdown, "not a heading", false), None);
+}
+
+#[test]
+async fn test_list_sections() {
+    let markdown = "Crate\n=====\n\n## [§](#examples)Examples\n\n```rust\n# hidden\n```\n\n### Sync ###\n\nSetext\n---\n\n## Examples\n";
+    assert_eq!(
+        list_sections(markdown),
+        vec![
+            (1, "Crate".to_string()),
+            (2, "Examples".to_string()),
+            (3, "Sync".to_string()),
+            (2, "Setext".to_string()),
+            (2, "Examples".to_string()),
+        ]
+    );
+}
+
+#[test]
+async fn test_parse_implementors() {
+    let markdown = html_to_markdown(
+        "<h2 id=\"implementors\">Implementors<a href=\"#implementors\">§</a></h2>\
+         <div id=\"implementors-list\">\
+         <section class=\"impl\"><h3 class=\"code-header\">impl <a href=\"trait.Display.html\">Display</a> for <a href=\"primitive.str.html\">str</a></h3></section>\
+         <section class=\"impl\"><h3 class=\"code-header\">impl <a href=\"trait.Display.html\">Display</a> for <a href=\"primitive.i32.html\">i32</a></h3></section>\
+         <section class=\"impl\"><h3 class=\"code-header\">impl&lt;T: ?Sized + <a href=\"trait.Display.html\">Display</a>&gt; <a href=\"trait.Display.html\">Display</a> for &amp;T</h3></section>\
+         <section class=\"impl\"><h3 class=\"code-header\">impl&lt;T, F: Fn() -&gt; T&gt; <a href=\"trait.Display.html\">Display</a> for Lazy&lt;T, F&gt;<div class=\"where\">where T: Display</div></h3></section>\
+         <section class=\"impl\"><h3 class=\"code-header\">impl <a href=\"trait.Display.html\">Display</a> for <a href=\"primitive.str.html\">str</a></h3></section>\
+         </div>",
+    );
+    let section = extract_section(&markdown, "Implementors", false).unwrap();
+
+    assert_eq!(
+        parse_implementors(&section),
+        vec!["str", "i32", "&T", "Lazy<T, F>"]
+    );
+    assert_eq!(implementing_type("impl String"), None);
+    assert_eq!(implementing_type("unsafe impl Send for Token"), Some("Token".to_string()));
+}
+
+#[test]
+async fn test_find_trait_implementors() {
+    let router = CargoDocRouter::new();
+    let result = output(
+        router
+            .find_trait_implementors("std".to_string(), "fmt::Display".to_string(), None)
+            .await,
+    );
+
+    assert!(result.starts_with("# Implementors of `std::fmt::Display`"));
+    assert!(result.contains("- `str`\n"));
+    assert!(result.contains("- `String`\n"));
+    assert!(result.contains("- `i32`\n"));
+}
+
+#[test]
+async fn test_parse_supertraits() {
+    let error = r##"<pre class="rust item-decl"><code>pub trait Error: <a class="trait" href="../fmt/trait.Debug.html" title="trait core::fmt::Debug">Debug</a> + <a class="trait" href="../fmt/trait.Display.html" title="trait core::fmt::Display">Display</a> {
+    // Provided methods
+    fn <a href="#method.source" class="fn">source</a>(&amp;self) -&gt; <a class="enum" href="../option/enum.Option.html" title="enum core::option::Option">Option</a>&lt;&amp;(dyn <a class="trait" href="trait.Error.html" title="trait core::error::Error">Error</a> + 'static)&gt; { ... }
+}</code></pre>"##;
+    assert_eq!(
+        parse_supertraits(error),
+        Some(vec!["core::fmt::Debug".to_string(), "core::fmt::Display".to_string()])
+    );
+
+    let display = r##"<pre class="rust item-decl"><code>pub trait Display {
+    // Required method
+    fn <a href="#tymethod.fmt" class="fn">fmt</a>(&amp;self, f: &amp;mut <a class="struct" href="struct.Formatter.html" title="struct core::fmt::Formatter">Formatter</a>&lt;'_&gt;) -&gt; <a class="type" href="type.Result.html" title="type core::fmt::Result">Result</a>;
+}</code></pre>"##;
+    assert_eq!(parse_supertraits(display), Some(vec![]));
+
+    // Bounds on generic parameters and in where clauses aren't supertraits
+    let generic = r##"<pre class="rust item-decl"><code>pub trait Extend&lt;A: <a class="trait" href="trait.Clone.html" title="trait core::clone::Clone">Clone</a>&gt;: <a class="trait" href="trait.Sized.html" title="trait core::marker::Sized">Sized</a>
+where A: <a class="trait" href="trait.Send.html" title="trait core::marker::Send">Send</a>
+{ }</code></pre>"##;
+    assert_eq!(parse_supertraits(generic), Some(vec!["core::marker::Sized".to_string()]));
+
+    assert_eq!(parse_supertraits("<pre class=\"rust item-decl\"><code>pub struct String { }</code></pre>"), None);
+}
+
+#[test]
+async fn test_get_type_hierarchy() {
+    let router = CargoDocRouter::new();
+
+    let display = output(
+        router
+            .get_type_hierarchy("std".to_string(), "fmt::Display".to_string(), None)
+            .await,
+    );
+    assert!(display.contains("This trait has no supertraits."));
+
+    let error = output(
+        router
+            .get_type_hierarchy("std".to_string(), "std::error::Error".to_string(), None)
+            .await,
+    );
+    assert!(error.starts_with("# Supertraits of `std::error::Error`"));
+    assert!(error.contains("  - `core::fmt::Debug`\n"));
+    assert!(error.contains("  - `core::fmt::Display`\n"));
+    assert!(router.cache.contains("supertraits:std:error::Error").await);
+    assert!(router.cache.contains("supertraits:core:fmt::Debug").await);
+
+    let iterator = output(
+        router
+            .get_type_hierarchy("std".to_string(), "iter::Iterator".to_string(), Some(5))
+            .await,
+    );
+    assert!(iterator.contains("This trait has no supertraits."));
+}
+
+#[test]
+async fn test_get_type_hierarchy_stdlib_base_url() {
+    let mut server = mockito::Server::new_async().await;
+    let error = server
+        .mock("GET", "/std/error/trait.Error.html")
+        .with_body(
+            r##"<pre class="rust item-decl"><code>pub trait Error: <a class="trait" href="../fmt/trait.Debug.html" title="trait core::fmt::Debug">Debug</a> { }</code></pre>"##,
+        )
+        .expect(1)
+        .create_async()
+        .await;
+    let debug = server
+        .mock("GET", "/core/fmt/trait.Debug.html")
+        .with_body(r##"<pre class="rust item-decl"><code>pub trait Debug { }</code></pre>"##)
+        .expect(1)
+        .create_async()
+        .await;
+    server
+        .mock("GET", mockito::Matcher::Any)
+        .with_status(404)
+        .expect(0)
+        .create_async()
+        .await;
+
+    // Standard library traits are read from the stdlib documentation, never from docs.rs
+    let router = CargoDocRouter::new()
+        .with_base_url("http://docs.rs.invalid")
+        .with_stdlib_base_url(server.url());
+    let hierarchy = output(
+        router
+            .get_type_hierarchy("std".to_string(), "error::Error".to_string(), None)
+            .await,
+    );
+
+    assert!(hierarchy.contains("  - `core::fmt::Debug`\n"));
+    error.assert_async().await;
+    debug.assert_async().await;
+}
+
+#[test]
+async fn test_crate_metadata_from_response() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/crates_io_crate.json");
+    let json = std::fs::read_to_string(path).unwrap();
+    let response: CrateResponse = serde_json::from_str(&json).unwrap();
+    let metadata = response.metadata();
+
+    assert_eq!(metadata.name, "serde");
+    assert_eq!(metadata.version, "1.0.219");
+    // The license comes from the latest version, not an older one
+    assert_eq!(metadata.license.as_deref(), Some("MIT OR Apache-2.0"));
+    assert_eq!(metadata.repository.as_deref(), Some("https://github.com/serde-rs/serde"));
+    assert_eq!(metadata.keywords, vec!["serde", "serialization", "no_std"]);
+    assert_eq!(metadata.categories[0], "encoding");
+    assert_eq!(metadata.recent_downloads, Some(98765432));
+    assert_eq!(metadata.documentation_url, "https://docs.rs/serde");
+}
+
+#[test]
+async fn test_get_crate_metadata() {
+    let router = CargoDocRouter::new();
+    let json = output(router.get_crate_metadata("serde".to_string()).await);
+    let metadata: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+    assert_eq!(metadata["name"], "serde");
+    assert!(metadata["license"].as_str().unwrap().contains("MIT OR Apache-2.0"));
+    assert!(metadata["downloads"].as_u64().unwrap() > 0);
+    assert!(router.cache.contains("metadata:serde").await);
+}
+
+#[test]
+async fn test_format_msrv() {
+    let declared: CrateVersionResponse =
+        serde_json::from_str(r#"{"version": {"num": "4.5.0", "rust_version": "1.74", "edition": "2021"}}"#).unwrap();
+    assert_eq!(format_msrv(&declared.version), "Minimum Rust version: 1.74 (edition 2021)");
+
+    let without_edition: CrateVersionResponse =
+        serde_json::from_str(r#"{"version": {"num": "1.0.0", "rust_version": "1.56.0", "edition": null}}"#).unwrap();
+    assert_eq!(format_msrv(&without_edition.version), "Minimum Rust version: 1.56.0");
+
+    let undeclared: CrateVersionResponse =
+        serde_json::from_str(r#"{"version": {"num": "2.33.0", "rust_version": null}}"#).unwrap();
+    assert_eq!(format_msrv(&undeclared.version), "MSRV not declared in Cargo.toml");
+}
+
+#[test]
+async fn test_get_msrv() {
+    let router = CargoDocRouter::new();
+
+    let clap4 = output(router.get_msrv("clap".to_string(), Some("4.5.0".to_string())).await);
+    assert!(clap4.starts_with("Minimum Rust version: 1.74"));
+    assert!(router.cache.contains("msrv:clap:4.5.0").await);
+
+    // Published before Cargo supported `rust-version`
+    let clap2 = output(router.get_msrv("clap".to_string(), Some("2.33.0".to_string())).await);
+    assert_eq!(clap2, "MSRV not declared in Cargo.toml");
+}
+
+const FUNCTION_PAGE: &str = "<html><body><h1>Function mycrate::Bezier</h1>\
+    <pre class=\"rust item-decl\"><code>pub fn Bezier(t: f64) -&gt; f64</code></pre>\
+    <p>Evaluates the curve at t.</p></body></html>";
+
+#[test]
+async fn test_lookup_function_requests() {
+    // lookup_function goes straight to the fn page
+    let mut server = mockito::Server::new_async().await;
+    let page = server
+        .mock("GET", "/mycrate/latest/mycrate/fn.Bezier.html")
+        .with_body(FUNCTION_PAGE)
+        .expect(1)
+        .create_async()
+        .await;
+    let not_found = server
+        .mock("GET", mockito::Matcher::Any)
+        .with_status(404)
+        .expect(0)
+        .create_async()
+        .await;
+
+    let router = CargoDocRouter::new().with_base_url(server.url());
+    let doc = output(
+        router
+            .lookup_function("mycrate".to_string(), "Bezier".to_string(), None)
+            .await,
+    );
+    assert!(doc.contains("Evaluates the curve at t."));
+    assert!(!doc.contains("Note:"));
+    page.assert_async().await;
+    not_found.assert_async().await;
+
+    // lookup_item_tool requests the pages of every item kind at once
+    let mut server = mockito::Server::new_async().await;
+    let page = server
+        .mock("GET", "/mycrate/latest/mycrate/fn.Bezier.html")
+        .with_body(FUNCTION_PAGE)
+        .expect(1)
+        .create_async()
+        .await;
+    let not_found = server
+        .mock("GET", "/mycrate/latest/mycrate/struct.Bezier.html")
+        .with_status(404)
+        .expect(1)
+        .create_async()
+        .await;
+    server
+        .mock("GET", mockito::Matcher::Any)
+        .with_status(404)
+        .create_async()
+        .await;
+
+    let router = CargoDocRouter::new().with_base_url(server.url());
+    let doc = output(
+        router
+            .lookup_item_tool("mycrate".to_string(), "Bezier".to_string(), None, None, None, None, None, None, None, None)
+            .await,
+    );
+    assert!(doc.contains("Evaluates the curve at t."));
+    page.assert_async().await;
+    not_found.assert_async().await;
+}
+
+#[test]
+async fn test_lookup_function_fallback() {
+    let mut server = mockito::Server::new_async().await;
+    let page = server
+        .mock("GET", "/mycrate/1.0.0/mycrate/macros/macro.curve.html")
+        .with_body("<html><body><h1>Macro mycrate::macros::curve</h1><p>Builds a curve.</p></body></html>")
+        .expect(1)
+        .create_async()
+        .await;
+    server
+        .mock("GET", mockito::Matcher::Any)
+        .with_status(404)
+        .create_async()
+        .await;
+
+    let router = CargoDocRouter::new().with_base_url(format!("{}/", server.url()));
+    let doc = output(
+        router
+            .lookup_function(
+                "mycrate".to_string(),
+                "mycrate::macros::curve".to_string(),
+                Some("1.0.0".to_string()),
+            )
+            .await,
+    );
+
+    assert!(doc.starts_with("> Note: `macros::curve` is a macro, not a function"));
+    assert!(doc.contains("Builds a curve."));
+    page.assert_async().await;
+    // Cached under the same key lookup_item_tool uses
+    assert!(router.cache.contains("doc:mycrate:1.0.0:macros::curve").await);
+}
+
+#[test]
+async fn test_lookup_function_shares_item_cache() {
+    let mut server = mockito::Server::new_async().await;
+    let page = server
+        .mock("GET", "/mycrate/latest/mycrate/struct.Bezier.html")
+        .with_body("<html><body><h1>Struct mycrate::Bezier</h1><p>A cubic curve.</p></body></html>")
+        .expect(1)
+        .create_async()
+        .await;
+    server
+        .mock("GET", mockito::Matcher::Any)
+        .with_status(404)
+        .create_async()
+        .await;
+
+    let router = CargoDocRouter::new().with_base_url(server.url());
+    let item = output(
+        router
+            .lookup_item_tool("mycrate".to_string(), "Bezier".to_string(), None, None, None, None, None, None, None, None)
+            .await,
+    );
+    // Served from the entry lookup_item_tool cached, in the same format and with the note
+    let function = output(
+        router
+            .lookup_function("mycrate".to_string(), "Bezier".to_string(), None)
+            .await,
+    );
+
+    assert_eq!(
+        function,
+        format!("> Note: `Bezier` is a struct, not a function, so other item kinds were tried instead.\n\n{}", item)
+    );
+    assert_eq!(router.cache.get("doc:mycrate:Bezier").await, Some(item));
+    page.assert_async().await;
+}
+
+const TRAIT_PAGE: &str = r##"<html><body><section id="main-content">
+<h1>Trait <span class="trait">Display</span></h1>
+<pre class="rust item-decl"><code>pub trait Display {
+    // Required method
+    fn <a href="#tymethod.fmt" class="fn">fmt</a>(&amp;self, f: &amp;mut <a class="struct" href="struct.Formatter.html">Formatter</a>&lt;'_&gt;) -&gt; <a class="type" href="type.Result.html">Result</a>;
+}</code></pre>
+<h2 id="required-methods" class="section-header">Required Methods<a href="#required-methods" class="anchor">§</a></h2>
+<div class="methods">
+<details class="toggle method-toggle" open><summary><section id="tymethod.fmt" class="method"><a class="src rightside" href="../../src/core/fmt/mod.rs.html#1">Source</a><h4 class="code-header">fn <a href="#tymethod.fmt" class="fn">fmt</a>(&amp;self, f: &amp;mut <a class="struct" href="struct.Formatter.html">Formatter</a>&lt;'_&gt;) -&gt; <a class="type" href="type.Result.html">Result</a></h4></section></summary><div class="docblock"><p>Formats the value using the given formatter.</p><h5>Errors</h5><p>Only fails when the formatter does.</p></div></details>
+</div>
+<h2 id="provided-methods" class="section-header">Provided Methods<a href="#provided-methods" class="anchor">§</a></h2>
+<div class="methods">
+<section id="method.describe" class="method"><h4 class="code-header">fn <a href="#method.describe" class="fn">describe</a>(&amp;self) -&gt; String</h4></section>
+</div>
+<h2 id="implementors" class="section-header">Implementors<a href="#implementors" class="anchor">§</a></h2>
+<div id="implementors-list"><section id="impl-Display-for-str" class="impl"><h3 class="code-header">impl Display for str</h3></section></div>
+</section></body></html>"##;
+
+#[test]
+async fn test_parse_trait_methods() {
+    let (required, provided) = parse_trait_methods(TRAIT_PAGE);
+
+    assert_eq!(
+        required,
+        vec![TraitMethod {
+            signature: "fn fmt(&self, f: &mut Formatter<'_>) -> Result".to_string(),
+            summary: "Formats the value using the given formatter.".to_string(),
+        }]
+    );
+    assert_eq!(provided.len(), 1);
+    assert_eq!(provided[0].signature, "fn describe(&self) -> String");
+    assert_eq!(provided[0].summary, "");
+
+    assert_eq!(parse_trait_methods("<html><body><p>Not a trait</p></body></html>"), (vec![], vec![]));
+}
+
+#[test]
+async fn test_lookup_trait_mocked() {
+    let mut server = mockito::Server::new_async().await;
+    server
+        .mock("GET", "/mycrate/latest/mycrate/fmt/trait.Display.html")
+        .with_body(TRAIT_PAGE)
+        .create_async()
+        .await;
+    server
+        .mock("GET", mockito::Matcher::Any)
+        .with_status(404)
+        .create_async()
+        .await;
+    let router = CargoDocRouter::new().with_base_url(server.url());
+
+    let result = router
+        .lookup_trait("mycrate".to_string(), "fmt::Display".to_string(), None)
+        .await
+        .unwrap();
+    let doc = &result.content[0].as_text().unwrap().text;
+    let overview_end = doc.find("---").unwrap();
+    assert!(doc.starts_with("# Trait `mycrate::fmt::Display`"));
+    assert!(doc[..overview_end].contains("## Required Methods\n\n- `fn fmt(&self, f: &mut Formatter<'_>) -> Result`: Formats the value"));
+    assert!(doc[..overview_end].contains("## Provided Methods\n\n- `fn describe(&self) -> String`\n"));
+    assert!(doc[overview_end..].contains("Implementors"));
+
+    let error = router
+        .lookup_trait("mycrate".to_string(), "String".to_string(), None)
+        .await
+        .unwrap_err();
+    assert_eq!(error.code, ErrorCode::RESOURCE_NOT_FOUND);
+    assert!(error.message.contains("`String` is not a trait in mycrate"));
+}
+
+#[test]
+async fn test_lookup_trait_cache_key() {
+    let mut server = mockito::Server::new_async().await;
+    let locked = server
+        .mock("GET", "/mycrate/1.2.0/mycrate/fmt/trait.Display.html")
+        .with_body(TRAIT_PAGE)
+        .expect(1)
+        .create_async()
+        .await;
+    let latest = server
+        .mock("GET", "/mycrate/latest/mycrate/fmt/trait.Display.html")
+        .with_body(TRAIT_PAGE)
+        .expect(1)
+        .create_async()
+        .await;
+    let mut router = CargoDocRouter::new().with_base_url(server.url());
+
+    // `latest`, `*` and both spellings of the path share one entry
+    let doc = output(router.lookup_trait("mycrate".to_string(), "fmt::Display".to_string(), None).await);
+    for version in ["latest", "*"] {
+        let again = output(router.lookup_trait("mycrate".to_string(), "mycrate::fmt::Display".to_string(), Some(version.to_string())).await);
+        assert_eq!(again, doc);
+    }
+    assert!(router.cache.contains("trait:mycrate:fmt::Display").await);
+    latest.assert_async().await;
+
+    // Without a version the locked one is read
+    router.project_versions.insert("mycrate".to_string(), "1.2.0".to_string());
+    output(router.lookup_trait("mycrate".to_string(), "fmt::Display".to_string(), None).await);
+    assert!(router.cache.contains("trait:mycrate:1.2.0:fmt::Display").await);
+    locked.assert_async().await;
+}
+
+#[test]
+async fn test_lookup_trait() {
+    let router = CargoDocRouter::new();
+
+    let serialize = router
+        .lookup_trait("serde".to_string(), "Serialize".to_string(), None)
+        .await
+        .unwrap();
+    let serialize = &serialize.content[0].as_text().unwrap().text;
+    let required = extract_section(serialize, "Required Methods", false).unwrap();
+    assert!(required.contains("fn serialize<S>"));
+
+    let display = router
+        .lookup_trait("std".to_string(), "fmt::Display".to_string(), None)
+        .await
+        .unwrap();
+    let display = &display.content[0].as_text().unwrap().text;
+    let required = extract_section(display, "Required Methods", false).unwrap();
+    assert!(required.contains("fn fmt(&self"));
+}
+
+const ENUM_PAGE: &str = r##"<html><body><section id="main-content">
+<h1>Enum <span class="enum">Shape</span></h1>
+<h2 id="variants" class="variants section-header">Variants<a href="#variants" class="anchor">§</a></h2>
+<div class="variants">
+<section id="variant.Empty" class="variant"><a href="#variant.Empty" class="anchor">§</a><h3 class="code-header">Empty</h3></section>
+<div class="docblock"><p>Nothing at all.</p><p>More detail.</p></div>
+<section id="variant.Circle" class="variant"><a href="#variant.Circle" class="anchor">§</a><h3 class="code-header">Circle(<a class="primitive" href="primitive.f64.html">f64</a>)</h3></section>
+<section id="variant.Rect" class="variant"><a href="#variant.Rect" class="anchor">§</a><h3 class="code-header">Rect</h3></section>
+<div class="docblock"><p>An axis-aligned rectangle.</p></div>
+<div class="sub-variant" id="variant.Rect.fields"><h4>Fields</h4>
+<div class="sub-variant-field"><span id="variant.Rect.field.width" class="section-header"><a href="#variant.Rect.field.width" class="anchor field">§</a><code>width: <a class="primitive" href="primitive.f64.html">f64</a></code></span></div>
+<div class="sub-variant-field"><span id="variant.Rect.field.height" class="section-header"><a href="#variant.Rect.field.height" class="anchor field">§</a><code>height: <a class="primitive" href="primitive.f64.html">f64</a></code></span></div>
+</div>
+</div>
+<h2 id="implementations" class="section-header">Implementations</h2>
+<section id="method.area" class="method"><h4 class="code-header">pub fn area(&amp;self) -&gt; f64</h4></section>
+</section></body></html>"##;
+
+#[test]
+async fn test_parse_enum_variants() {
+    let variants = parse_enum_variants(ENUM_PAGE);
+
+    assert_eq!(variants.len(), 3);
+    assert_eq!(variants[0].name, "Empty");
+    assert_eq!(variants[0].summary, "Nothing at all.");
+    assert_eq!(variants[1].signature, "Circle(f64)");
+    assert_eq!(variants[1].summary, "");
+    assert_eq!(variants[2].summary, "An axis-aligned rectangle.");
+    assert_eq!(variants[2].fields, vec!["width: f64", "height: f64"]);
+
+    // Older rustdoc marked variants up with a `variant` class and a nested `code`
+    let legacy = r##"<div id="variant.NotFound" class="variant small-section-header"><a href="#variant.NotFound" class="anchor field"></a><code>NotFound</code></div><div class="docblock"><p>An entity was not found.</p></div>"##;
+    let variants = parse_enum_variants(legacy);
+    assert_eq!(variants[0].signature, "NotFound");
+    assert_eq!(variants[0].summary, "An entity was not found.");
+}
+
+#[test]
+async fn test_lookup_enum_mocked() {
+    let mut server = mockito::Server::new_async().await;
+    server
+        .mock("GET", "/shapes/0.3.0/shapes/enum.Shape.html")
+        .with_body(ENUM_PAGE)
+        .create_async()
+        .await;
+    server
+        .mock("GET", mockito::Matcher::Any)
+        .with_status(404)
+        .create_async()
+        .await;
+    let router = CargoDocRouter::new().with_base_url(server.url());
+
+    let result = router
+        .lookup_enum("shapes".to_string(), "shapes::Shape".to_string(), Some("0.3.0".to_string()))
+        .await
+        .unwrap();
+    let doc = &result.content[0].as_text().unwrap().text;
+    let variants = extract_section(doc, "Variants", false).unwrap();
+    assert!(variants.contains("- `Rect`: An axis-aligned rectangle.\n  - field `width: f64`"));
+    assert!(!variants.contains("area"));
+
+    let error = router
+        .lookup_enum("shapes".to_string(), "Circle".to_string(), Some("0.3.0".to_string()))
+        .await
+        .unwrap_err();
+    assert_eq!(error.code, ErrorCode::RESOURCE_NOT_FOUND);
+    assert!(error.message.contains("`Circle` is not an enum in shapes"));
+}
+
+#[test]
+async fn test_lookup_enum_cache_key() {
+    let mut server = mockito::Server::new_async().await;
+    let page = server
+        .mock("GET", "/shapes/0.3.0/shapes/enum.Shape.html")
+        .with_body(ENUM_PAGE)
+        .expect(1)
+        .create_async()
+        .await;
+    let mut router = CargoDocRouter::new().with_base_url(server.url());
+    router.project_versions.insert("shapes".to_string(), "0.3.0".to_string());
+
+    // The locked version is read, and an explicit one with another spelling shares its entry
+    let doc = output(router.lookup_enum("shapes".to_string(), "Shape".to_string(), None).await);
+    let again = output(router.lookup_enum("shapes".to_string(), "shapes::Shape".to_string(), Some("=0.3.0".to_string())).await);
+    assert_eq!(again, doc);
+    assert!(router.cache.contains("enum:shapes:0.3.0:Shape").await);
+    page.assert_async().await;
+}
+
+#[test]
+async fn test_lookup_enum() {
+    let router = CargoDocRouter::new();
+    let result = router
+        .lookup_enum("std".to_string(), "io::ErrorKind".to_string(), None)
+        .await
+        .unwrap();
+    let doc = &result.content[0].as_text().unwrap().text;
+    let variants = extract_section(doc, "Variants", false).unwrap();
+
+    for variant in ["NotFound", "PermissionDenied", "ConnectionRefused", "AlreadyExists", "TimedOut", "Other"] {
+        assert!(variants.contains(&format!("- `{}`", variant)), "missing variant {}", variant);
+    }
+}
+
+#[test]
+async fn test_lookup_macro_mocked() {
+    let mut server = mockito::Server::new_async().await;
+    server
+        .mock("GET", "/mycrate/latest/mycrate/macro.select.html")
+        .with_body("<html><body><p>Waits on multiple branches.</p></body></html>")
+        .create_async()
+        .await;
+    server
+        .mock("GET", "/mycrate/latest/mycrate/derive.Builder.html")
+        .with_body("<html><body><p>Derives a builder.</p></body></html>")
+        .create_async()
+        .await;
+    server
+        .mock("GET", "/mycrate/latest/mycrate/attr.main.html")
+        .with_body("<html><body><p>Marks the async entry point.</p></body></html>")
+        .create_async()
+        .await;
+    server
+        .mock("GET", mockito::Matcher::Any)
+        .with_status(404)
+        .create_async()
+        .await;
+    let router = CargoDocRouter::new().with_base_url(server.url());
+    let text = |result: CallToolResult| result.content[0].as_text().unwrap().text.clone();
+
+    let select = router
+        .lookup_macro("mycrate".to_string(), "mycrate::select!".to_string(), None)
+        .await
+        .unwrap();
+    assert_eq!(text(select), "# Macro `mycrate::select`\n\nWaits on multiple branches.");
+
+    let builder = router
+        .lookup_macro("mycrate".to_string(), "Builder".to_string(), None)
+        .await
+        .unwrap();
+    assert!(text(builder).starts_with("# Derive macro `mycrate::Builder`"));
+
+    let main = router
+        .lookup_macro("mycrate".to_string(), "main".to_string(), None)
+        .await
+        .unwrap();
+    assert!(text(main).starts_with("# Attribute macro `mycrate::main`"));
+
+    let error = router
+        .lookup_macro("mycrate".to_string(), "missing".to_string(), None)
+        .await
+        .unwrap_err();
+    assert_eq!(error.code, ErrorCode::RESOURCE_NOT_FOUND);
+}
+
+#[test]
+async fn test_lookup_macro_cache_key() {
+    let mut server = mockito::Server::new_async().await;
+    let page = server
+        .mock("GET", "/mycrate/2.0.1/mycrate/macro.select.html")
+        .with_body("<html><body><p>Waits on multiple branches.</p></body></html>")
+        .expect(1)
+        .create_async()
+        .await;
+    let mut router = CargoDocRouter::new().with_base_url(server.url());
+    router.project_versions.insert("mycrate".to_string(), "2.0.1".to_string());
+
+    // The locked version is read, and every spelling of the macro shares its entry
+    let doc = output(router.lookup_macro("mycrate".to_string(), "select!".to_string(), None).await);
+    for path in ["select", "mycrate::select!"] {
+        assert_eq!(output(router.lookup_macro("mycrate".to_string(), path.to_string(), None).await), doc);
+    }
+    assert!(router.cache.contains("macro:mycrate:2.0.1:select").await);
+    page.assert_async().await;
+}
+
+#[test]
+async fn test_lookup_macro() {
+    let router = CargoDocRouter::new();
+
+    for (crate_name, path) in [("std", "vec!"), ("serde", "Serialize"), ("tokio", "select!")] {
+        let result = router
+            .lookup_macro(crate_name.to_string(), path.to_string(), None)
+            .await
+            .unwrap();
+        let doc = &result.content[0].as_text().unwrap().text;
+        assert!(doc.len() > 100, "{}::{} returned too little", crate_name, path);
+    }
+}
+
+#[test]
+async fn test_content_transformation() {
+    let router = CargoDocRouter::new();
+    
+    // Test the transformation from HTML to markdown
+    let serde_result = output(router.lookup_crate("serde".to_string(), None, None, None, None, None).await);
+    
+    // Verify we got substantial content
+    assert!(!serde_result.is_empty());
+    assert!(serde_result.len() > 100);
+    
+    // Basic check for HTML to markdown conversion
+    // Just check that we don't have obvious HTML tags
+    assert!(!serde_result.contains("<html>"));
+}
+
+#[test]
+async fn test_doc_cache() {
+    let cache = DocCache::new();
+    let key = "test_key";
+    let value = "test_value".to_string();
+    
+    // Initially the key should not exist
+    assert_eq!(cache.get(key).await, None);
+    
+    // Set a value
+    cache.set(key.to_string(), value.clone()).await;
+    
+    // Now we should get the value back
+    assert_eq!(cache.get(key).await, Some(value));
+}
+
+#[test]
+async fn test_cache_in_lookup() {
+    let router = CargoDocRouter::new();
+    
+    // First lookup to populate the cache
+    let first_result = output(router.lookup_crate("regex".to_string(), None, None, None, None, None).await);
+    assert!(!first_result.is_empty());
+    
+    // Second lookup should use the cache
+    let second_result = output(router.lookup_crate("regex".to_string(), None, None, None, None, None).await);
+    
+    // Results should be identical when pulled from cache
+    assert_eq!(first_result, second_result);
+}
+
+#[test]
+async fn test_lookup_item_examples() {
+    let router = CargoDocRouter::new();
+    
+    // Test lookup examples for a well-documented item that should have examples
+    let examples = output(
+        router.lookup_item_examples(
+            "std".to_string(),
+            "vec::Vec".to_string(),
+            None,
+            None
+        ).await,
+    );
+    
+    // Verify we got substantial content
+    assert!(!examples.is_empty());
+    assert!(examples.contains("Usage Examples"));
+    
+    // Should include either real examples or generated examples
+    assert!(examples.contains("```rust") || examples.contains("```"));
+}
+
+#[test]
+async fn test_analyze_type_relationships() {
+    let router = CargoDocRouter::new();
+    
+    // Test analysis for a standard type that should have well-defined relationships
+    let relationships = output(
+        router.analyze_type_relationships(
+            "std".to_string(),
+            "result::Result".to_string(),
+            None
+        ).await,
+    );
+    
+    // Verify we got substantial content
+    assert!(!relationships.is_empty());
+    assert!(relationships.contains("Type Relationships"));
+    
+    // Should include some guidance about Result usage patterns
+    assert!(relationships.contains("Usage Patterns") || 
+            relationships.contains("Working with Result types") ||
+            relationships.contains("impl") ||
+            relationships.contains("match"));
+}
+
+#[test]
+async fn test_examples_cache() {
+    let cache = DocCache::new();
+    let examples = vec![
+        CodeExample {
+            title: "Test Example".to_string(),
+            code: "fn main() {}".to_string(),
+            description: "A test example".to_string(),
+            fence_info: String::new(),
+            generated: false,
+        }
+    ];
+    let key = "examples:test";
+    
+    // Initially the key should not exist
+    assert_eq!(cache.get_examples(key).await, None);
+    
+    // Set a value
+    cache.set_examples(key.to_string(), examples.clone()).await;
+    
+    // Now we should get the value back
+    assert_eq!(cache.get_examples(key).await.unwrap()[0].title, "Test Example");
+    assert_eq!(cache.get_examples(key).await.unwrap()[0].code, "fn main() {}");
+}
+
+const ADD_PAGE: &str = r##"<html><body><section id="main-content">
+<h1>Trait <span class="trait">Add</span></h1>
+<pre class="rust item-decl"><code>pub trait Add&lt;Rhs = Self&gt; {
+    type <a href="#associatedtype.Output" class="associatedtype">Output</a>;
+
+    // Required method
+    fn <a href="#tymethod.add" class="fn">add</a>(self, rhs: Rhs) -&gt; Self::Output;
+}</code></pre>
+<p>The addition operator <code>+</code>.</p>
+<pre class="rust rust-example-rendered"><code>impl Add for Point {
+    type Output = Self;
+
+    fn add(self, other: Self) -&gt; Self { other }
+}
+impl&lt;T&gt; Add for Wrapper&lt;T&gt; {
+    type Output = &lt;Vec&lt;T&gt; as IntoIterator&gt;::Item;
+}</code></pre>
+</section></body></html>"##;
+
+const ITERATOR_PAGE: &str = r##"<html><body><section id="main-content">
+<h1>Trait <span class="trait">Iterator</span></h1>
+<pre class="rust item-decl"><code>pub trait Iterator {
+    type <a href="#associatedtype.Item" class="associatedtype">Item</a>;
+
+    // Required method
+    fn <a href="#tymethod.next" class="fn">next</a>(&amp;mut self) -&gt; Option&lt;Self::Item&gt;;
+}</code></pre>
+<p>The type of the elements being iterated over; see <code>next</code>.</p>
+</section></body></html>"##;
+
+// Analyze `item_path` of the standard library against a server serving `page` for it
+async fn analyze_mocked_std_item(item_path: &str, page_path: &str, page: &str) -> String {
+    let mut server = mockito::Server::new_async().await;
+    server
+        .mock("GET", format!("/std/{}", page_path).as_str())
+        .with_body(page)
+        .create_async()
+        .await;
+    server
+        .mock("GET", mockito::Matcher::Any)
+        .with_status(404)
+        .create_async()
+        .await;
+    let router = CargoDocRouter::new().with_stdlib_base_url(server.url());
+
+    output(
+        router
+            .analyze_type_relationships("std".to_string(), item_path.to_string(), None)
+            .await,
+    )
+}
+
+#[test]
+async fn test_associated_type_name() {
+    assert_eq!(associated_type_name("    type Item;"), Some("Item"));
+    assert_eq!(associated_type_name("type Item: Clone + Send;"), Some("Item"));
+    assert_eq!(associated_type_name("    type Output = Vec<T>;"), Some("Output"));
+    assert_eq!(associated_type_name("type Output = <Vec<T> as IntoIterator>::Item;"), Some("Output"));
+    assert_eq!(associated_type_name("type Iter<'a> where Self: 'a;"), Some("Iter"));
+    assert_eq!(associated_type_name("pub type Result<T> = Result<T, Error>;"), None);
+    assert_eq!(associated_type_name("The type of the elements; see next."), None);
+    assert_eq!(associated_type_name("type Output"), None);
+}
+
+#[test]
+async fn test_analyze_associated_types() {
+    let add = analyze_mocked_std_item("ops::Add", "ops/trait.Add.html", ADD_PAGE).await;
+    assert!(add.contains("## Associated Types\n\nThis trait has the following associated types that implementors must define:\n\n- `Output` \n\n"));
+
+    let iterator = analyze_mocked_std_item("iter::Iterator", "iter/trait.Iterator.html", ITERATOR_PAGE).await;
+    assert!(iterator.contains("## Associated Types\n\nThis trait has the following associated types that implementors must define:\n\n- `Item` \n\n"));
+}
+
+const PARSE_PAGE: &str = r##"<html><body><section id="main-content">
+<h1>Function <span class="fn">parse</span></h1>
+<pre class="rust item-decl"><code>pub fn parse(input: &amp;str) -&gt; Option&lt;u32&gt;</code></pre>
+<p>Parses a number.</p>
+<h2 id="examples">Examples</h2>
+<pre class="rust rust-example-rendered"><code>fn main() -&gt; Result&lt;(), Box&lt;dyn Error&gt;&gt; {
+    parse("1");
+    Ok(())
+}</code></pre>
+</section></body></html>"##;
+
+#[test]
+async fn test_analyze_ignores_example_code() {
+    let parse = analyze_mocked_std_item("str::parse", "str/fn.parse.html", PARSE_PAGE).await;
+    assert!(parse.contains("Option<u32>"), "{}", parse);
+    assert!(!parse.contains("Box<dyn Error>"), "{}", parse);
+}
+
+const TOKIO_FILE_PAGE: &str = r##"<html><body><section id="main-content">
+<div class="main-heading"><h1>Struct <span class="struct">File</span></h1></div>
+<pre class="rust item-decl"><code>pub struct File { /* private fields */ }</code></pre>
+<details class="toggle top-doc" open><summary>Expand description</summary><div class="docblock"><p>A reference to an open file on the filesystem.</p></div></details>
+<h2 id="implementations" class="section-header">Implementations</h2>
+<details class="toggle implementors-toggle" open><summary><section id="impl-File" class="impl"><h3 class="code-header">impl <a class="struct" href="struct.File.html">File</a></h3></section></summary><div class="impl-items">
+<details class="toggle method-toggle" open><summary><section id="method.open" class="method"><h4 class="code-header">pub async fn <a href="#method.open" class="fn">open</a>(path: impl <a class="trait" href="https://doc.rust-lang.org/nightly/core/convert/trait.AsRef.html">AsRef</a>&lt;<a class="struct" href="https://doc.rust-lang.org/nightly/std/path/struct.Path.html">Path</a>&gt;) -&gt; <a class="type" href="https://doc.rust-lang.org/nightly/std/io/error/type.Result.html">Result</a>&lt;<a class="struct" href="struct.File.html">File</a>&gt;</h4></section></summary><div class="docblock"><p>Attempts to open a file in read-only mode.</p></div></details>
+<details class="toggle method-toggle" open><summary><section id="method.sync_all" class="method"><h4 class="code-header">pub async fn <a href="#method.sync_all" class="fn">sync_all</a>(&amp;self) -&gt; <a class="type" href="https://doc.rust-lang.org/nightly/std/io/error/type.Result.html">Result</a>&lt;()&gt;</h4></section></summary></details>
+<details class="toggle method-toggle" open><summary><section id="method.set_max_buf_size" class="method"><h4 class="code-header">pub fn <a href="#method.set_max_buf_size" class="fn">set_max_buf_size</a>(&amp;mut self, max_buf_size: <a class="primitive" href="https://doc.rust-lang.org/nightly/std/primitive.usize.html">usize</a>)</h4></section></summary></details>
+</div></details>
+</section></body></html>"##;
+
+const REQWEST_CLIENT_PAGE: &str = r##"<html><body><section id="main-content">
+<div class="main-heading"><h1>Struct <span class="struct">Client</span></h1></div>
+<pre class="rust item-decl"><code>pub struct Client { /* private fields */ }</code></pre>
+<details class="toggle top-doc" open><summary>Expand description</summary><div class="docblock"><p>An asynchronous <code>Client</code> to make Requests with.</p></div></details>
+<h2 id="implementations" class="section-header">Implementations</h2>
+<details class="toggle implementors-toggle" open><summary><section id="impl-Client" class="impl"><h3 class="code-header">impl <a class="struct" href="struct.Client.html">Client</a></h3></section></summary><div class="impl-items">
+<details class="toggle method-toggle" open><summary><section id="method.get" class="method"><h4 class="code-header">pub fn <a href="#method.get" class="fn">get</a>&lt;U: <a class="trait" href="trait.IntoUrl.html">IntoUrl</a>&gt;(&amp;self, url: U) -&gt; <a class="struct" href="struct.RequestBuilder.html">RequestBuilder</a></h4></section></summary><div class="docblock"><p>Convenience method to make a <code>GET</code> request to a URL.</p></div></details>
+<details class="toggle method-toggle" open><summary><section id="method.execute" class="method"><h4 class="code-header">pub fn <a href="#method.execute" class="fn">execute</a>(&amp;self, request: <a class="struct" href="struct.Request.html">Request</a>) -&gt; impl <a class="trait" href="https://doc.rust-lang.org/nightly/core/future/future/trait.Future.html">Future</a>&lt;Output = <a class="type" href="type.Result.html">Result</a>&lt;<a class="struct" href="struct.Response.html">Response</a>, <a class="struct" href="struct.Error.html">Error</a>&gt;&gt;</h4></section></summary><div class="docblock"><p>Executes a <code>Request</code>.</p></div></details>
+</div></details>
+</section></body></html>"##;
+
+#[test]
+async fn test_async_method() {
+    assert_eq!(
+        async_method("pub async fn open(path: impl AsRef<Path>) -> Result<File>"),
+        Some(AsyncMethod { name: "open".to_string(), output: "Result<File>".to_string() })
+    );
+    assert_eq!(
+        async_method("pub async fn shutdown(&mut self)"),
+        Some(AsyncMethod { name: "shutdown".to_string(), output: "()".to_string() })
+    );
+    assert_eq!(
+        async_method("pub fn execute(&self, request: Request) -> impl Future<Output = Result<Response, Error>> + Send"),
+        Some(AsyncMethod { name: "execute".to_string(), output: "Result<Response, Error>".to_string() })
+    );
+    assert_eq!(
+        async_method("fn call<'a>(&'a self) -> impl Future<Output = Vec<u8>> + 'a where Self: Sync"),
+        Some(AsyncMethod { name: "call".to_string(), output: "Vec<u8>".to_string() })
+    );
+    assert_eq!(async_method("pub fn get<U: IntoUrl>(&self, url: U) -> RequestBuilder"), None);
+    assert_eq!(async_method("pub fn poll(f: impl Future<Output = ()>) -> Poll<()>"), None);
+}
+
+#[test]
+async fn test_analyze_async_methods() {
+    let mut server = mockito::Server::new_async().await;
+    server
+        .mock("GET", "/tokio/latest/tokio/fs/struct.File.html")
+        .with_body(TOKIO_FILE_PAGE)
+        .create_async()
+        .await;
+    server
+        .mock("GET", "/reqwest/latest/reqwest/struct.Client.html")
+        .with_body(REQWEST_CLIENT_PAGE)
+        .create_async()
+        .await;
+    server
+        .mock("GET", mockito::Matcher::Any)
+        .with_status(404)
+        .create_async()
+        .await;
+    let router = CargoDocRouter::new().with_base_url(server.url());
+
+    let file = output(
+        router
+            .analyze_type_relationships("tokio".to_string(), "fs::File".to_string(), None)
+            .await,
+    );
+    assert!(file.contains("## Async Methods\n\nThese methods return futures; `.await` them to get the listed type:\n\n- `open` → `Result<File>` \n- `sync_all` → `Result<()>` \n\n"), "{}", file);
+    assert!(!file.contains("`set_max_buf_size` →"));
+    let return_types = file.split("## Return Types\n\n").nth(1).unwrap();
+    assert!(return_types.contains("- `Result<File>` \n"), "{}", return_types);
+
+    let client = output(
+        router
+            .analyze_type_relationships("reqwest".to_string(), "Client".to_string(), None)
+            .await,
+    );
+    assert!(client.contains("## Async Methods\n\nThese methods return futures; `.await` them to get the listed type:\n\n- `execute` → `Result<Response, Error>` \n\n"), "{}", client);
+    assert!(!client.contains("`get` →"));
+    let return_types = client.split("## Return Types\n\n").nth(1).unwrap();
+    assert!(return_types.contains("- `Result<Response, Error>` \n"), "{}", return_types);
+    assert!(!return_types.contains("impl Future"), "{}", return_types);
+}
+
+#[test]
+async fn test_extract_code_blocks() {
+    let markdown = "Intro\n\n````markdown\n```rust\nlet x = 1;\n```\n````\n\n```rust,no_run\nconnect();\n```\n\n```rust,should_panic,edition2021\npanic!();\n```\n\n```compile_fail\nlet x: u8 = \"\";\n```\n\n```\nunclosed();";
+    let blocks = extract_code_blocks(markdown);
+    assert_eq!(blocks.len(), 5);
+
+    assert_eq!(blocks[0].language.as_deref(), Some("markdown"));
+    assert_eq!(blocks[0].content, "```rust\nlet x = 1;\n```");
+    assert_eq!(blocks[0].start_line, 2);
+
+    assert_eq!(blocks[1].language.as_deref(), Some("rust"));
+    assert_eq!(blocks[1].fence_annotation, "rust,no_run");
+    assert!(!blocks[1].is_runnable);
+
+    assert_eq!(blocks[2].language.as_deref(), Some("rust"));
+    assert!(blocks[2].is_runnable);
+
+    assert_eq!(blocks[3].language, None);
+    assert!(!blocks[3].is_runnable);
+
+    assert_eq!(blocks[4].language, None);
+    assert_eq!(blocks[4].content, "unclosed();");
+    assert!(blocks[4].is_runnable);
+}
+
+const READ_UNALIGNED_PAGE: &str = r##"<html><body><section id="main-content">
+<div class="main-heading"><h1>Function <span class="fn">read_unaligned</span></h1></div>
+<pre class="rust item-decl"><code>pub const unsafe fn read_unaligned&lt;T&gt;(src: <a class="primitive" href="../primitive.pointer.html">*const T</a>) -&gt; T</code></pre>
+<details class="toggle top-doc" open><summary>Expand description</summary><div class="docblock"><p>Reads the value from <code>src</code> without moving it.</p>
+<h2 id="safety"><a class="doc-anchor" href="#safety">§</a>Safety</h2>
+<p>Behavior is undefined if any of the following conditions are violated:</p>
+<ul><li><code>src</code> must be valid for reads.</li></ul>
+<h2 id="examples"><a class="doc-anchor" href="#examples">§</a>Examples</h2>
+<pre class="rust rust-example-rendered"><code>unsafe fn first(p: *const u8) -&gt; u8 { ptr::read_unaligned(p) }</code></pre>
+</div></details></section></body></html>"##;
+
+const GLOBAL_ALLOC_PAGE: &str = r##"<html><body><section id="main-content">
+<div class="main-heading"><h1>Trait <span class="trait">GlobalAlloc</span></h1></div>
+<pre class="rust item-decl"><code>pub unsafe trait GlobalAlloc {
+    // Required methods
+    unsafe fn <a href="#tymethod.alloc" class="fn">alloc</a>(&amp;self, layout: <a class="struct" href="struct.Layout.html">Layout</a>) -&gt; *mut <a class="primitive" href="../primitive.u8.html">u8</a>;
+    unsafe fn <a href="#tymethod.dealloc" class="fn">dealloc</a>(&amp;self, ptr: *mut <a class="primitive" href="../primitive.u8.html">u8</a>, layout: <a class="struct" href="struct.Layout.html">Layout</a>);
+}</code></pre>
+<details class="toggle top-doc" open><summary>Expand description</summary><div class="docblock"><p>A memory allocator that can be registered as the standard library’s default.</p>
+<h2 id="safety"><a class="doc-anchor" href="#safety">§</a>Safety</h2>
+<p>The <code>GlobalAlloc</code> trait is an <code>unsafe</code> trait for a number of reasons.</p>
+</div></details>
+<h2 id="required-methods" class="section-header">Required Methods</h2><div class="methods">
+<details class="toggle method-toggle" open><summary><section id="tymethod.alloc" class="method"><h4 class="code-header">unsafe fn <a href="#tymethod.alloc" class="fn">alloc</a>(&amp;self, layout: <a class="struct" href="struct.Layout.html">Layout</a>) -&gt; *mut <a class="primitive" href="../primitive.u8.html">u8</a></h4></section></summary><div class="docblock"><p>Allocates memory as described by the given <code>layout</code>.</p>
+<h5 id="safety-1"><a class="doc-anchor" href="#safety-1">§</a>Safety</h5><p><code>layout</code> must have non-zero size.</p>
+<h5 id="errors"><a class="doc-anchor" href="#errors">§</a>Errors</h5><p>Returning a null pointer indicates that memory is exhausted.</p></div></details>
+<details class="toggle method-toggle" open><summary><section id="tymethod.dealloc" class="method"><h4 class="code-header">unsafe fn <a href="#tymethod.dealloc" class="fn">dealloc</a>(&amp;self, ptr: *mut <a class="primitive" href="../primitive.u8.html">u8</a>, layout: <a class="struct" href="struct.Layout.html">Layout</a>)</h4></section></summary><div class="docblock"><p>Deallocates the block of memory at <code>ptr</code>.</p></div></details>
+</div>
+<h2 id="implementors" class="section-header">Implementors</h2><div id="implementors-list">
+<section id="impl-GlobalAlloc-for-System" class="impl"><h3 class="code-header">unsafe impl <a class="trait" href="trait.GlobalAlloc.html">GlobalAlloc</a> for <a class="struct" href="../../std/alloc/struct.System.html">System</a></h3></section>
+</div></section></body></html>"##;
+
+#[test]
+async fn test_analyze_safety_requirements() {
+    let read = analyze_mocked_std_item("ptr::read_unaligned", "ptr/fn.read_unaligned.html", READ_UNALIGNED_PAGE).await;
+    assert!(read.starts_with("> ⚠️ This API includes unsafe functions. Read the Safety sections carefully.\n\n# Type Relationships"), "{}", read);
+    assert!(read.contains("## Safety Requirements\n\n### `pub const unsafe fn read_unaligned<T>(src: *const T) -> T`\n\nBehavior is undefined if any of the following conditions are violated:"), "{}", read);
+    assert!(read.contains("must be valid for reads."));
+    // The example's `unsafe fn first` isn't part of the API
+    assert!(!read.contains("fn first"));
+
+    let alloc = analyze_mocked_std_item("alloc::GlobalAlloc", "alloc/trait.GlobalAlloc.html", GLOBAL_ALLOC_PAGE).await;
+    assert!(alloc.starts_with("> ⚠️ This API includes unsafe functions."));
+    let safety = alloc.split("## Safety Requirements\n\n").nth(1).unwrap();
+    assert!(safety.starts_with("### `unsafe fn alloc(&self, layout: Layout) -> *mut u8`\n\n"), "{}", safety);
+    assert!(safety.contains("must have non-zero size."));
+    assert!(!safety.split("### `unsafe fn dealloc").next().unwrap().contains("memory is exhausted"));
+    assert!(safety.contains("### `unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout)`\n\n_No Safety section is documented for this function._"));
+    assert!(safety.contains("- `unsafe impl GlobalAlloc for System` \n"));
+    assert!(!safety.contains("unsafe` trait for a number of reasons"));
+
+    let error = analyze_mocked_std_item("error::Error", "error/trait.Error.html", ERROR_PAGE).await;
+    assert!(!error.contains("Safety Requirements"));
+    assert!(error.starts_with("# Type Relationships"));
+}
+
+const ERROR_PAGE: &str = r##"<html><body><section id="main-content">
+<h1>Trait <span class="trait">Error</span></h1>
+<pre class="rust item-decl"><code>pub trait Error: <a class="trait" href="../fmt/trait.Debug.html" title="trait core::fmt::Debug">Debug</a> + <a class="trait" href="../fmt/trait.Display.html" title="trait core::fmt::Display">Display</a> {
+    // Provided methods
+    fn <a href="#method.source" class="fn">source</a>(&amp;self) -&gt; <a class="enum" href="../option/enum.Option.html">Option</a>&lt;&amp;(dyn Error + 'static)&gt; { ... }
+}</code></pre>
+<p><code>Error</code> is a trait representing the basic expectations for error values.</p>
+</section></body></html>"##;
+
+const READ_PAGE: &str = r##"<html><body><section id="main-content">
+<h1>Trait <span class="trait">Read</span></h1>
+<pre class="rust item-decl"><code>pub trait Read {
+    // Required method
+    fn <a href="#tymethod.read" class="fn">read</a>(&amp;mut self, buf: &amp;mut [u8]) -&gt; <a class="type" href="type.Result.html">Result</a>&lt;usize&gt;;
+}</code></pre>
+<p>The <code>Read</code> trait allows for reading bytes from a source.</p>
+</section></body></html>"##;
+
+#[test]
+async fn test_parse_supertrait_bounds() {
+    assert_eq!(parse_supertrait_bounds("```rust\npub trait Error: Debug + Display {\n}\n```"), vec!["Debug", "Display"]);
+    assert_eq!(parse_supertrait_bounds("pub trait Read {\n    fn read(&mut self) -> usize;\n}"), Vec::<String>::new());
+    assert_eq!(
+        parse_supertrait_bounds("pub unsafe trait Extend<A: Clone>: Sized + fmt::Debug + 'static\nwhere\n    A: Send,\n{"),
+        vec!["Sized", "fmt::Debug"]
+    );
+    assert_eq!(
+        parse_supertrait_bounds("pub trait Service<Request>:\n    Clone\n    + Send\n{"),
+        vec!["Clone", "Send"]
+    );
+    assert_eq!(parse_supertrait_bounds("pub trait Iterator { type Item: Debug; }"), Vec::<String>::new());
+}
+
+#[test]
+async fn test_analyze_supertraits() {
+    let error = analyze_mocked_std_item("error::Error", "error/trait.Error.html", ERROR_PAGE).await;
+    assert!(error.contains("## Supertraits\n\nImplementors of this trait must also implement:\n\n- `Debug` \n- `Display` \n\n"));
+
+    let read = analyze_mocked_std_item("io::Read", "io/trait.Read.html", READ_PAGE).await;
+    assert!(!read.contains("## Supertraits"));
+}
+
+const STRING_PAGE: &str = r##"<html><body><section id="main-content">
+<h1>Struct <span class="struct">String</span></h1>
+<pre class="rust item-decl"><code>pub struct String { /* private fields */ }</code></pre>
+<details class="toggle implementors-toggle" open><summary><section id="impl-Deref-for-String" class="impl"><a href="#impl-Deref-for-String" class="anchor">§</a><h3 class="code-header">impl <a class="trait" href="../ops/trait.Deref.html" title="trait core::ops::Deref">Deref</a> for <a class="struct" href="struct.String.html" title="struct alloc::string::String">String</a></h3></section></summary><div class="impl-items"><details class="toggle" open><summary><section id="associatedtype.Target" class="associatedtype trait-impl"><h4 class="code-header">type <a href="../ops/trait.Deref.html#associatedtype.Target" class="associatedtype">Target</a> = <a class="primitive" href="../primitive.str.html">str</a></h4></section></summary><div class="docblock">The resulting type after dereferencing.</div></details></div></details>
+<details class="toggle implementors-toggle" open><summary><section id="impl-Extend%3Cchar%3E-for-String" class="impl"><h3 class="code-header">impl <a class="trait" href="../iter/trait.Extend.html" title="trait core::iter::Extend">Extend</a>&lt;<a class="primitive" href="../primitive.char.html">char</a>&gt; for <a class="struct" href="struct.String.html">String</a></h3></section></summary></details>
+<details class="toggle implementors-toggle" open><summary><section id="impl-From%3C%26str%3E-for-String" class="impl"><h3 class="code-header">impl <a class="trait" href="../convert/trait.From.html" title="trait core::convert::From">From</a>&lt;&amp;<a class="primitive" href="../primitive.str.html">str</a>&gt; for <a class="struct" href="struct.String.html">String</a></h3></section></summary></details>
+<details class="toggle implementors-toggle" open><summary><section id="impl-From%3CCow%3C'a,+str%3E%3E-for-String" class="impl"><h3 class="code-header">impl&lt;'a&gt; <a class="trait" href="../convert/trait.From.html" title="trait core::convert::From">From</a>&lt;<a class="enum" href="../borrow/enum.Cow.html">Cow</a>&lt;'a, <a class="primitive" href="../primitive.str.html">str</a>&gt;&gt; for <a class="struct" href="struct.String.html">String</a></h3></section></summary></details>
+<details class="toggle implementors-toggle" open><summary><section id="impl-From%3CString%3E-for-Vec%3Cu8%3E" class="impl"><h3 class="code-header">impl <a class="trait" href="../convert/trait.From.html" title="trait core::convert::From">From</a>&lt;<a class="struct" href="struct.String.html">String</a>&gt; for <a class="struct" href="../vec/struct.Vec.html">Vec</a>&lt;<a class="primitive" href="../primitive.u8.html">u8</a>&gt;</h3></section></summary></details>
+<details class="toggle implementors-toggle" open><summary><section id="impl-From%3C%26str%3E-for-String-1" class="impl"><h3 class="code-header">impl <a class="trait" href="../convert/trait.From.html" title="trait core::convert::From">From</a>&lt;&amp;<a class="primitive" href="../primitive.str.html">str</a>&gt; for <a class="struct" href="struct.String.html">String</a></h3></section></summary></details>
+</section></body></html>"##;
+
+const VEC_PAGE: &str = r##"<html><body><section id="main-content">
+<h1>Struct <span class="struct">Vec</span></h1>
+<pre class="rust item-decl"><code>pub struct Vec&lt;T, A = Global&gt; { /* private fields */ }</code></pre>
+<details class="toggle implementors-toggle" open><summary><section id="impl-DerefMut-for-Vec%3CT,+A%3E" class="impl"><h3 class="code-header">impl&lt;T, A: <a class="trait" href="../alloc/trait.Allocator.html" title="trait core::alloc::Allocator">Allocator</a>&gt; <a class="trait" href="../ops/trait.DerefMut.html" title="trait core::ops::DerefMut">DerefMut</a> for <a class="struct" href="struct.Vec.html">Vec</a>&lt;T, A&gt;</h3></section></summary></details>
+<details class="toggle implementors-toggle" open><summary><section id="impl-Deref-for-Vec%3CT,+A%3E" class="impl"><h3 class="code-header">impl&lt;T, A: <a class="trait" href="../alloc/trait.Allocator.html" title="trait core::alloc::Allocator">Allocator</a>&gt; <a class="trait" href="../ops/trait.Deref.html" title="trait core::ops::Deref">Deref</a> for <a class="struct" href="struct.Vec.html">Vec</a>&lt;T, A&gt;</h3></section></summary><div class="impl-items"><details class="toggle" open><summary><section id="associatedtype.Target-1" class="associatedtype trait-impl"><h4 class="code-header">type <a href="../ops/trait.Deref.html#associatedtype.Target" class="associatedtype">Target</a> = <a class="primitive" href="../primitive.slice.html">[T]</a></h4></section></summary></details></div></details>
+</section></body></html>"##;
+
+#[test]
+async fn test_analyze_deref_target() {
+    let string = analyze_mocked_std_item("string::String", "string/struct.String.html", STRING_PAGE).await;
+    assert!(string.contains("## Deref Target\n\n`Target = str`\n\nMethods of `str` are available on this type via auto-deref.\n"));
+
+    let vec = analyze_mocked_std_item("vec::Vec", "vec/struct.Vec.html", VEC_PAGE).await;
+    assert!(vec.contains("## Deref Target\n\n`Target = [T]`\n\nMethods of `[T]` are available on this type via auto-deref.\n"));
+
+    let read = analyze_mocked_std_item("io::Read", "io/trait.Read.html", READ_PAGE).await;
+    assert!(!read.contains("## Deref Target"));
+}
+
+const PATHBUF_PAGE: &str = r##"<html><body><section id="main-content">
+<h1>Struct <span class="struct">PathBuf</span></h1>
+<pre class="rust item-decl"><code>pub struct PathBuf { /* private fields */ }</code></pre>
+<details class="toggle implementors-toggle" open><summary><section id="impl-From%3CString%3E-for-PathBuf" class="impl"><h3 class="code-header">impl <a class="trait" href="../convert/trait.From.html" title="trait core::convert::From">From</a>&lt;<a class="struct" href="../string/struct.String.html">String</a>&gt; for <a class="struct" href="struct.PathBuf.html">PathBuf</a></h3></section></summary></details>
+<details class="toggle implementors-toggle" open><summary><section id="impl-Into%3COsString%3E-for-PathBuf" class="impl"><h3 class="code-header">impl <a class="trait" href="../convert/trait.Into.html" title="trait core::convert::Into">Into</a>&lt;<a class="struct" href="../ffi/struct.OsString.html">OsString</a>&gt; for <a class="struct" href="struct.PathBuf.html">PathBuf</a></h3></section></summary></details>
+</section></body></html>"##;
+
+#[test]
+async fn test_analyze_type_conversions() {
+    let string = analyze_mocked_std_item("string::String", "string/struct.String.html", STRING_PAGE).await;
+    assert!(string.contains("## Type Conversions\n\nCan be created from:\n\n- `&str` \n- `Cow<'a, str>` \n\n"));
+    assert!(string.contains("`Into<String>` available"));
+    assert!(string.contains("Can be converted to:\n\n- `Vec<u8>` \n\n"));
+
+    let path_buf = analyze_mocked_std_item("path::PathBuf", "path/struct.PathBuf.html", PATHBUF_PAGE).await;
+    assert!(path_buf.contains("Can be created from:\n\n- `String` \n\n"));
+    assert!(path_buf.contains("Can be converted to:\n\n- `OsString` \n\n"));
+
+    let read = analyze_mocked_std_item("io::Read", "io/trait.Read.html", READ_PAGE).await;
+    assert!(!read.contains("## Type Conversions"));
+}
+
+const ARC_PAGE: &str = r##"<html><body><section id="main-content">
+<h1>Struct <span class="struct">Arc</span></h1>
+<pre class="rust item-decl"><code>pub struct Arc&lt;T: ?Sized, A: Allocator = Global&gt; { /* private fields */ }</code></pre>
+<h2 id="trait-implementations" class="section-header">Trait Implementations</h2>
+<div id="trait-implementations-list">
+<details class="toggle implementors-toggle" open><summary><section id="impl-Send-for-Arc%3CT,+A%3E" class="impl"><h3 class="code-header">impl&lt;T: ?<a class="trait" href="../../marker/trait.Sized.html">Sized</a> + <a class="trait" href="../../marker/trait.Sync.html">Sync</a> + <a class="trait" href="../../marker/trait.Send.html">Send</a>, A: <a class="trait" href="../../alloc/trait.Allocator.html">Allocator</a> + <a class="trait" href="../../marker/trait.Send.html">Send</a>&gt; <a class="trait" href="../../marker/trait.Send.html">Send</a> for <a class="struct" href="struct.Arc.html">Arc</a>&lt;T, A&gt;</h3></section></summary></details>
+<details class="toggle implementors-toggle" open><summary><section id="impl-Sync-for-Arc%3CT,+A%3E" class="impl"><h3 class="code-header">impl&lt;T: ?<a class="trait" href="../../marker/trait.Sized.html">Sized</a> + <a class="trait" href="../../marker/trait.Sync.html">Sync</a> + <a class="trait" href="../../marker/trait.Send.html">Send</a>, A: <a class="trait" href="../../alloc/trait.Allocator.html">Allocator</a> + <a class="trait" href="../../marker/trait.Sync.html">Sync</a>&gt; <a class="trait" href="../../marker/trait.Sync.html">Sync</a> for <a class="struct" href="struct.Arc.html">Arc</a>&lt;T, A&gt;</h3></section></summary></details>
+</div>
+</section></body></html>"##;
+
+const REFCELL_PAGE: &str = r##"<html><body><section id="main-content">
+<h1>Struct <span class="struct">RefCell</span></h1>
+<pre class="rust item-decl"><code>pub struct RefCell&lt;T: ?Sized&gt; { /* private fields */ }</code></pre>
+<h2 id="trait-implementations" class="section-header">Trait Implementations</h2>
+<div id="trait-implementations-list">
+<details class="toggle implementors-toggle" open><summary><section id="impl-Send-for-RefCell%3CT%3E" class="impl"><h3 class="code-header">impl&lt;T: ?<a class="trait" href="../marker/trait.Sized.html">Sized</a> + <a class="trait" href="../marker/trait.Send.html">Send</a>&gt; <a class="trait" href="../marker/trait.Send.html">Send</a> for <a class="struct" href="struct.RefCell.html">RefCell</a>&lt;T&gt;</h3></section></summary></details>
+<details class="toggle implementors-toggle" open><summary><section id="impl-Sync-for-RefCell%3CT%3E" class="impl"><h3 class="code-header">impl&lt;T: ?<a class="trait" href="../marker/trait.Sized.html">Sized</a>&gt; !<a class="trait" href="../marker/trait.Sync.html">Sync</a> for <a class="struct" href="struct.RefCell.html">RefCell</a>&lt;T&gt;</h3></section></summary></details>
+</div>
+<h2 id="synthetic-implementations" class="section-header">Auto Trait Implementations</h2>
+<div id="synthetic-implementations-list">
+<section id="impl-Unpin-for-RefCell%3CT%3E" class="impl"><h3 class="code-header">impl&lt;T&gt; <a class="trait" href="../marker/trait.Unpin.html">Unpin</a> for <a class="struct" href="struct.RefCell.html">RefCell</a>&lt;T&gt;</h3></section>
+</div>
+</section></body></html>"##;
+
+#[test]
+async fn test_parse_thread_safety() {
+    assert_eq!(parse_thread_safety(ARC_PAGE), ThreadSafety { send: Some(true), sync: Some(true) });
+    assert_eq!(parse_thread_safety(REFCELL_PAGE), ThreadSafety { send: Some(true), sync: Some(false) });
+
+    let rc = r##"<div id="synthetic-implementations-list"><section class="impl"><h3 class="code-header">impl&lt;T&gt; !Send for Rc&lt;T&gt;</h3></section><section class="impl"><h3 class="code-header">impl&lt;T&gt; !Sync for Rc&lt;T&gt;</h3></section></div>"##;
+    assert_eq!(parse_thread_safety(rc), ThreadSafety { send: Some(false), sync: Some(false) });
+
+    // Implementors listed on a trait's own page aren't impls of the documented type
+    let send = r##"<div id="implementors-list"><section class="impl"><h3 class="code-header">impl Send for String</h3></section></div>"##;
+    assert_eq!(parse_thread_safety(send), ThreadSafety::default());
+}
+
+#[test]
+async fn test_analyze_thread_safety() {
+    let arc = analyze_mocked_std_item("sync::Arc", "sync/struct.Arc.html", ARC_PAGE).await;
+    assert!(arc.contains("## Thread Safety\n\nThis type is `Send` and `Sync`.\n\n"));
+
+    let ref_cell = analyze_mocked_std_item("cell::RefCell", "cell/struct.RefCell.html", REFCELL_PAGE).await;
+    assert!(ref_cell.contains("## Thread Safety\n\nThis type is `Send` and NOT `Sync`.\n"));
+    assert!(ref_cell.contains("wrap it in a `Mutex` or `RwLock`"));
+
+    let read = analyze_mocked_std_item("io::Read", "io/trait.Read.html", READ_PAGE).await;
+    assert!(!read.contains("## Thread Safety"));
+}
+
+const EXAMPLES_PAGE: &str = r##"<html><body><h1>Struct mycrate::Client</h1>
+<div class="docblock"><p>An HTTP client.</p>
+<h2 id="examples"><a class="doc-anchor" href="#examples">§</a>Examples</h2>
+<p>Send a <a href="struct.Request.html">request</a>:</p>
+<div class="example-wrap"><pre class="rust rust-example-rendered"><code>let client = Client::new();
+client.get("https://example.com");</code></pre></div>
+<p>Then drop it:</p>
+<div class="example-wrap"><pre class="rust rust-example-rendered"><code>drop(client);</code></pre></div>
+<h2 id="panics"><a class="doc-anchor" href="#panics">§</a>Panics</h2>
+<div class="example-wrap"><pre class="rust rust-example-rendered"><code>unreachable!();</code></pre></div>
+</div></body></html>"##;
+
+#[test]
+async fn test_extract_examples() {
+    let doc = "\
+Client
+======
+
+An HTTP client.
+
+```rust
+let client = Client::new();
+```
+
+Examples
+--------
+
+Send a [request](struct.Request.html)
+to a server:
+
+```rust
+client.get(url);
+```
+
+```rust
+drop(client);
+```
+
+Panics
+------
+
+```rust
+unreachable!();
+```
+";
+    let examples = extract_examples(doc);
+    assert_eq!(
+        examples,
+        vec![
+            CodeExample {
+                title: "Example 1".to_string(),
+                code: "client.get(url);".to_string(),
+                description: "Send a request to a server:".to_string(),
+                fence_info: "rust".to_string(),
+                generated: false,
+            },
+            CodeExample {
+                title: "Example 2".to_string(),
+                code: "drop(client);".to_string(),
+                description: String::new(),
+                fence_info: "rust".to_string(),
+                generated: false,
+            },
+        ]
+    );
+
+    // Without an Examples section every code block is an example
+    let doc = "A client.\n\n```rust\nlet client = Client::new();\n```\n";
+    let examples = extract_examples(doc);
+    assert_eq!(examples.len(), 1);
+    assert_eq!(examples[0].description, "A client.");
+
+    let example = generated_example("mycrate", &item_path("mycrate", "Client"), "pub struct Client");
+    assert_eq!(example.title, "Creating and using a Client instance");
+    assert!(example.code.starts_with("use mycrate::Client;"));
+    let markdown = format_examples(&[example], DEFAULT_PLAYGROUND_EDITION);
+    assert!(markdown.contains("## Creating and using a Client instance\n\nThis is a generated example."));
+    // Generated templates don't compile, so they aren't linked to the Playground
+    assert!(!markdown.contains("Run in Playground"));
+}
+
+#[test]
+async fn test_examples_end_at_next_section() {
+    let doc = "# Client\n\n## Examples\n\n### Basic usage\n\n```rust\nclient.get(url);\n```\n\n## Panics\n\nWhen closed:\n\n```rust\nclient.close();\nclient.get(url);\n```\n\n## Safety\n\n```rust\nunsafe { client.reset() };\n```\n\n## Notes\n\nReuse clients.\n";
+    let examples = extract_examples(doc);
+    assert_eq!(examples.len(), 1);
+    assert_eq!(examples[0].code, "client.get(url);");
+    assert_eq!(examples[0].description, "");
+}
+
+#[test]
+async fn test_code_example_to_markdown() {
+    let example = CodeExample {
+        title: "Example 1".to_string(),
+        code: "drop(client);".to_string(),
+        description: "Then drop it:".to_string(),
+        fence_info: "rust,no_run".to_string(),
+        generated: false,
+    };
+    assert_eq!(
+        example.to_markdown("2018"),
+        "## Example 1\n\nThen drop it:\n\n```rust\ndrop(client);\n```\n\
+         [▶ Run in Playground](https://play.rust-lang.org/?version=stable&edition=2018&code=fn%20main%28%29%20%7B%0A%20%20%20%20drop%28client%29%3B%0A%7D)\n\n\
+         _Note: This example is marked as non-runnable in the official documentation._\n"
+    );
+    assert_eq!(
+        format_examples(&[example.clone(), example.clone()], "2018"),
+        format!("# Usage Examples\n\n{}\n{}", example.to_markdown("2018"), example.to_markdown("2018"))
+    );
+}
+
+#[test]
+async fn test_non_runnable_examples() {
+    let html = r##"<html><body><h1>Struct mycrate::Client</h1>
+<div class="docblock"><h2 id="examples">Examples</h2>
+<div class="example-wrap"><pre class="rust rust-example-rendered"><code>let client = Client::new();</code></pre></div>
+<div class="example-wrap ignore"><a href="#" class="tooltip" title="This example is not tested">ⓘ</a><pre class="rust rust-example-rendered"><code>client.send();</code></pre></div>
+<div class="example-wrap compile_fail"><a href="#" class="tooltip" title="This example deliberately fails to compile">ⓘ</a><pre class="rust rust-example-rendered"><code>let client: Client = 1;</code></pre></div>
+<pre class="rust rust-example-rendered ignore"><code>client.close();</code></pre>
+</div></body></html>"##;
+    let markdown = html_to_markdown(html);
+    assert!(markdown.contains("```rust\nlet client = Client::new();"));
+    assert!(markdown.contains("```rust,ignore\nclient.send();"));
+    assert!(markdown.contains("```rust,compile_fail\nlet client: Client = 1;"));
+    assert!(markdown.contains("```rust,ignore\nclient.close();"));
+
+    let mut server = mockito::Server::new_async().await;
+    server
+        .mock("GET", "/mycrate/latest/mycrate/struct.Client.html")
+        .with_body(html)
+        .create_async()
+        .await;
+    let router = CargoDocRouter::new().with_base_url(server.url());
+    let examples = output(
+        router
+            .lookup_item_examples("mycrate".to_string(), "Client".to_string(), None, None)
+            .await,
+    );
+    let note = "_Note: This example is marked as non-runnable in the official documentation._";
+    let sections: Vec<&str> = examples.split("\n## ").collect();
+    assert_eq!(sections.len(), 5);
+    assert!(!sections[1].contains(note));
+    assert!(sections[2].contains(note));
+    assert!(!sections[2].contains("Expected to fail compilation"));
+    assert!(sections[3].contains(note));
+    assert!(sections[3].contains(&format!("{}\n\n_Expected to fail compilation._", note)));
+    assert!(sections[4].contains(note));
+
+    // rustdoc doesn't render `no_run`, so it is only seen in raw markdown
+    let examples = extract_examples("```rust,no_run\nserver.listen();\n```\n");
+    assert_eq!(examples[0].fence_info, "rust,no_run");
+    assert!(format_examples(&examples, DEFAULT_PLAYGROUND_EDITION).contains(note));
+}
+
+#[test]
+async fn test_playground_links() {
+    let url = playground_url("let x = vec![1, 2];\nprintln!(\"{:?}\", x);", "2021");
+    assert_eq!(
+        url,
+        "https://play.rust-lang.org/?version=stable&edition=2021&code=let%20x%20%3D%20vec%21%5B1%2C%202%5D%3B%0Aprintln%21%28%22%7B%3A%3F%7D%22%2C%20x%29%3B"
+    );
+
+    // Like rustdoc, the Playground wraps code without a `fn main` in one, after its crate attributes
+    let linked = |code: &str| {
+        let example = CodeExample {
+            title: "Example 1".to_string(),
+            code: code.to_string(),
+            description: String::new(),
+            fence_info: "rust".to_string(),
+            generated: false,
+        };
+        example.to_markdown("2021")
+    };
+    let wrapped = "#![allow(unused)]\nfn main() {\n    let x = 1;\n\n    assert_eq!(x, 1);\n}";
+    assert!(linked("#![allow(unused)]\nlet x = 1;\n\nassert_eq!(x, 1);").contains(&playground_url(wrapped, "2021")));
+    let program = "fn main() {\n    println!(\"hi\");\n}";
+    assert!(linked(program).contains(&playground_url(program, "2021")));
+
+    let mut server = mockito::Server::new_async().await;
+    server
+        .mock("GET", "/mycrate/latest/mycrate/struct.Client.html")
+        .with_body(EXAMPLES_PAGE)
+        .create_async()
+        .await;
+    let router = CargoDocRouter::new().with_base_url(server.url());
+    let examples = output(
+        router
+            .lookup_item_examples("mycrate".to_string(), "Client".to_string(), None, None)
+            .await,
+    );
+    assert!(examples.contains(
+        "drop(client);\n```\n[▶ Run in Playground](https://play.rust-lang.org/?version=stable&edition=2021&code=fn%20main%28%29%20%7B%0A%20%20%20%20drop%28client%29%3B%0A%7D)\n"
+    ));
+
+    // Cached examples are linked with the edition of each call
+    let examples = output(
+        router
+            .lookup_item_examples("mycrate".to_string(), "Client".to_string(), None, Some("2024".to_string()))
+            .await,
+    );
+    assert!(examples.contains("edition=2024&code=fn%20main%28%29%20%7B%0A%20%20%20%20drop%28client%29%3B%0A%7D"));
+    assert!(!examples.contains("edition=2021"));
+
+    let error = router
+        .lookup_item_examples("mycrate".to_string(), "Client".to_string(), None, Some("2022".to_string()))
+        .await
+        .unwrap_err();
+    assert_eq!(error.code, ErrorCode::INVALID_PARAMS);
+}
+
+#[test]
+async fn test_lookup_item_examples_uses_examples_cache() {
+    let mut server = mockito::Server::new_async().await;
+    let page = server
+        .mock("GET", "/mycrate/latest/mycrate/struct.Client.html")
+        .with_body(EXAMPLES_PAGE)
+        .expect(1)
+        .create_async()
+        .await;
+    server
+        .mock("GET", mockito::Matcher::Any)
+        .with_status(404)
+        .create_async()
+        .await;
+    let router = CargoDocRouter::new().with_base_url(server.url());
+
+    let markdown = output(
+        router
+            .lookup_item_examples("mycrate".to_string(), "Client".to_string(), None, None)
+            .await,
+    );
+    assert!(markdown.contains("## Example 1\n\nSend a request:\n\n```rust\nlet client = Client::new();"));
+    assert!(markdown.contains("## Example 2\n\nThen drop it:\n\n```rust\ndrop(client);\n```"));
+    assert!(!markdown.contains("unreachable!()"));
+    let examples = router.cache.get_examples("examples:mycrate:Client").await.unwrap();
+    assert_eq!(examples.len(), 2);
+    assert!(router.cache.get("examples:mycrate:Client").await.is_none());
+
+    // Served from the examples cache the second time
+    let cached = output(
+        router
+            .lookup_item_examples("mycrate".to_string(), "Client".to_string(), None, None)
+            .await,
+    );
+    assert_eq!(cached, markdown);
+    page.assert_async().await;
+}
+
+#[test]
+async fn test_generated_examples() {
+    let router = CargoDocRouter::new();
+    
+    // Test generation of synthetic examples for an item that likely won't have explicit examples
+    // but can be inferred from its type (struct, enum, trait, etc.)
+    let examples = output(
+        router.lookup_item_examples(
+            "lumin".to_string(),
+            "core::Lumin".to_string(),
+            None,
+            None
+        ).await,
+    );
+    
+    // Verify we got content
+    assert!(!examples.is_empty());
+    
+    // Should include the phrases indicating generated examples
+    assert!(examples.contains("Usage Examples"));
+}
+
+#[test]
+async fn test_relationship_analysis_impl() {
+    let router = CargoDocRouter::new();
+    
+    // Test the implementation details of type relationship analysis
+    let relationships = output(
+        router.analyze_type_relationships(
+            "serde".to_string(),
+            "Serialize".to_string(),
+            None
+        ).await,
+    );
+    
+    // Verify we got substantial content
+    assert!(!relationships.is_empty());
+    
+    // Should include sections that analyze the type
+    assert!(relationships.contains("Type Relationships") || 
+            relationships.contains("trait") ||
+            relationships.contains("impl"));
+    
+    // Should include guidance on usage patterns
+    assert!(relationships.contains("Usage Patterns") || 
+            relationships.contains("Implementing") ||
+            relationships.contains("Common"));
+}
+#[test]
+async fn test_doc_cache_lru_eviction() {
+    let cache = DocCache::new_with_capacity(3);
+
+    cache.set("a".to_string(), "A".to_string()).await;
+    cache.set("b".to_string(), "B".to_string()).await;
+    cache.set("c".to_string(), "C".to_string()).await;
+
+    // Touch "a" so that "b" becomes the least recently used entry
+    assert_eq!(cache.get("a").await, Some("A".to_string()));
+
+    // Inserting a fourth entry exceeds the capacity and evicts "b"
+    cache.set("d".to_string(), "D".to_string()).await;
+
+    assert_eq!(cache.get("b").await, None);
+    assert_eq!(cache.get("a").await, Some("A".to_string()));
+    assert_eq!(cache.get("c").await, Some("C".to_string()));
+    assert_eq!(cache.get("d").await, Some("D".to_string()));
+}
+
+#[test]
+async fn test_examples_cache_lru_eviction() {
+    let cache = DocCache::new_with_capacity(2);
+    let example = |title: &str| vec![CodeExample {
+        title: title.to_string(),
+        code: "fn main() {}".to_string(),
+        description: String::new(),
+        fence_info: String::new(),
+        generated: false,
+    }];
+
+    cache.set_examples("first".to_string(), example("first")).await;
+    cache.set_examples("second".to_string(), example("second")).await;
+    cache.set_examples("third".to_string(), example("third")).await;
+
+    assert_eq!(cache.get_examples("first").await, None);
+    assert_eq!(cache.get_examples("second").await.unwrap()[0].title, "second");
+    assert_eq!(cache.get_examples("third").await.unwrap()[0].title, "third");
+}
+
+#[test]
+async fn test_persistent_cache_roundtrip() {
+    let dir = temp_dir::TempDir::new().unwrap();
+    let path = dir.child("cache");
+    let examples = vec![CodeExample {
+        title: "Persisted Example".to_string(),
+        code: "fn main() {}".to_string(),
+        description: "Survives a restart".to_string(),
+        fence_info: String::new(),
+        generated: false,
+    }];
+
+    {
+        let cache = PersistentDocCache::open(&path).unwrap();
+        assert_eq!(cache.get("serde").await, None);
+        cache.set("serde".to_string(), "# serde docs".to_string()).await;
+        cache.set_examples("examples:serde:Serialize".to_string(), examples.clone()).await;
+    }
+
+    // Reopen the database after the first handle has been dropped
+    let cache = PersistentDocCache::open(&path).unwrap();
+    assert_eq!(cache.get("serde").await, Some("# serde docs".to_string()));
+    assert_eq!(cache.get_examples("examples:serde:Serialize").await, Some(examples));
+}
+
+#[test]
+async fn test_router_with_persistent_cache() {
+    let dir = temp_dir::TempDir::new().unwrap();
+    let path = dir.child("cache");
+
+    {
+        let router = CargoDocRouter::new_with_persistent_cache(&path).unwrap();
+        router.cache.set("tokio:1.0.0".to_string(), "# tokio docs".to_string()).await;
+    }
+
+    // A fresh router starts with an empty in-memory cache but reads through to disk
+    let router = CargoDocRouter::new_with_persistent_cache(&path).unwrap();
+    assert_eq!(router.cache.get("tokio:1.0.0").await, Some("# tokio docs".to_string()));
+}
+
+#[test]
+async fn test_doc_cache_clear_crate() {
+    let cache = DocCache::new();
+    cache.set("doc:tokio".to_string(), "crate".to_string()).await;
+    cache.set("doc:tokio:1.0.0".to_string(), "versioned".to_string()).await;
+    cache.set("doc:tokio:sync::Mutex".to_string(), "item".to_string()).await;
+    cache.set("relationships:tokio:sync::Mutex".to_string(), "analysis".to_string()).await;
+    cache.set("module:tokio:sync".to_string(), "module".to_string()).await;
+    cache.set("doc:tokio-util".to_string(), "other crate".to_string()).await;
+    cache.set_examples("examples:tokio:sync::Mutex".to_string(), Vec::new()).await;
+
+    assert_eq!(cache.clear_crate("tokio").await, 6);
+
+    assert_eq!(cache.get("doc:tokio").await, None);
+    assert_eq!(cache.get("doc:tokio:sync::Mutex").await, None);
+    assert_eq!(cache.get_examples("examples:tokio:sync::Mutex").await, None);
+    // Crates sharing a name prefix are left alone
+    assert_eq!(cache.get("doc:tokio-util").await, Some("other crate".to_string()));
+
+    // Crates named like a key prefix only lose their own entries
+    cache.set("doc:json".to_string(), "crate".to_string()).await;
+    cache.set("doc:json:0.12.4".to_string(), "versioned".to_string()).await;
+    cache.set("doc:json:JsonValue".to_string(), "item".to_string()).await;
+    cache.set("json:serde:latest".to_string(), "rustdoc json".to_string()).await;
+    cache.set("json:json:latest".to_string(), "rustdoc json".to_string()).await;
+    cache.set("doc:versions:serde".to_string(), "item".to_string()).await;
+    cache.set("versions:serde".to_string(), "versions".to_string()).await;
+    assert_eq!(cache.clear_crate("json").await, 4);
+    assert_eq!(cache.get("json:serde:latest").await, Some("rustdoc json".to_string()));
+    assert_eq!(cache.clear_crate("versions").await, 1);
+    assert_eq!(cache.get("versions:serde").await, Some("versions".to_string()));
+}
+
+#[test]
+async fn test_doc_cache_clear_all() {
+    let cache = DocCache::new();
+    cache.set("serde".to_string(), "crate".to_string()).await;
+    cache.set("regex".to_string(), "crate".to_string()).await;
+    cache.set_examples("examples:serde:Serialize".to_string(), Vec::new()).await;
+
+    assert_eq!(cache.clear_all().await, 3);
+    assert_eq!(cache.get("serde").await, None);
+    assert_eq!(cache.clear_all().await, 0);
+}
+
+#[test]
+async fn test_clear_cache_persistent() {
+    let dir = temp_dir::TempDir::new().unwrap();
+    let router = CargoDocRouter::new_with_persistent_cache(&dir.child("cache")).unwrap();
+    router.cache.set("doc:serde".to_string(), "crate".to_string()).await;
+
+    // Cleared entries must not be promoted back from disk
+    assert_eq!(router.cache.clear_crate("serde").await, 1);
+    assert_eq!(router.cache.get("doc:serde").await, None);
+}
+
+#[test]
+async fn test_clear_cache_tool() {
+    let router = CargoDocRouter::new();
+    router.cache.set("doc:serde".to_string(), "latest".to_string()).await;
+    router.cache.set("doc:serde:1.0.0".to_string(), "old".to_string()).await;
+    router.cache.set("doc:tokio".to_string(), "latest".to_string()).await;
+
+    let result = output(router.clear_cache(Some("serde".to_string()), Some("1.0.0".to_string())).await);
+    assert_eq!(result, "Cleared 1 cache entries for serde 1.0.0");
+    assert_eq!(router.cache.get("doc:serde").await, Some("latest".to_string()));
+
+    let result = output(router.clear_cache(Some("serde".to_string()), None).await);
+    assert_eq!(result, "Cleared 1 cache entries for serde");
+
+    let result = output(router.clear_cache(None, None).await);
+    assert_eq!(result, "Cleared 1 cache entries");
+
+    let error = router.clear_cache(None, Some("1.0.0".to_string())).await.unwrap_err();
+    assert_eq!(error.code, ErrorCode::INVALID_PARAMS);
+}
+
+#[test]
+async fn test_cache_hit_miss_counters() {
+    let cache = DocCache::new();
+    assert_eq!((cache.hits(), cache.misses()), (0, 0));
+
+    assert_eq!(cache.get("serde").await, None);
+    assert_eq!((cache.hits(), cache.misses()), (0, 1));
+
+    cache.set("serde".to_string(), "docs".to_string()).await;
+    cache.get("serde").await;
+    cache.get("serde").await;
+    assert_eq!((cache.hits(), cache.misses()), (2, 1));
+
+    // Clones share the same counters
+    cache.clone().get("tokio").await;
+    assert_eq!(cache.misses(), 2);
+
+    cache.reset_counters();
+    assert_eq!((cache.hits(), cache.misses()), (0, 0));
+}
+
+#[test]
+async fn test_cache_stats_tool() {
+    let router = CargoDocRouter::new();
+    router.cache.set("serde".to_string(), "docs".to_string()).await;
+    router.cache.get("serde").await;
+    router.cache.get("serde").await;
+    router.cache.get("serde").await;
+    router.cache.get("tokio").await;
+
+    let stats = output(router.cache_stats(Some(true)).await);
+    assert!(stats.contains("| Documentation entries | 1 |"));
+    assert!(stats.contains("| Estimated size | 9 bytes |"));
+    assert!(stats.contains("| Hits | 3 |"));
+    assert!(stats.contains("| Misses | 1 |"));
+    assert!(stats.contains("| Hit ratio | 75.0% |"));
+    assert_eq!((router.cache.hits(), router.cache.misses()), (0, 0));
+}
+
+#[test]
+async fn test_cache_stats_bytes() {
+    let cache = DocCache::new();
+    assert_eq!(cache.stats().await, CacheStats::default());
+
+    cache.set("serde".to_string(), "docs".to_string()).await;
+    let stats = cache.stats().await;
+    assert_eq!((stats.main_entries, stats.main_bytes), (1, 9));
+
+    let example = CodeExample {
+        title: "Basic".to_string(),
+        code: "let x = 1;".to_string(),
+        description: String::new(),
+        fence_info: String::new(),
+        generated: false,
+    };
+    cache.set_examples("examples:serde:Value".to_string(), vec![example]).await;
+    let stats = cache.stats().await;
+    assert_eq!((stats.examples_entries, stats.examples_bytes), (1, 35));
+    assert_eq!(cache.entry_count().await, 2);
+    assert_eq!(cache.estimated_bytes().await, 44);
+    assert_eq!(
+        stats.to_string(),
+        "1 documentation entries (9 bytes), 1 example entries (35 bytes)"
+    );
+
+    cache.remove("serde").await;
+    cache.remove_examples("examples:serde:Value").await;
+    assert_eq!(cache.estimated_bytes().await, 0);
+}
+
+#[test]
+async fn test_doc_cache_export_import() {
+    let cache = DocCache::new();
+    cache.set("tokio".to_string(), "tokio docs".to_string()).await;
+    cache.set("serde".to_string(), "serde docs".to_string()).await;
+
+    let entries = cache.export().await;
+    assert_eq!(
+        entries,
+        vec![
+            ("serde".to_string(), "serde docs".to_string()),
+            ("tokio".to_string(), "tokio docs".to_string()),
+        ]
+    );
+
+    let imported = DocCache::new();
+    imported.import(entries.clone()).await;
+    assert_eq!(imported.export().await, entries);
+}
+
+#[test]
+async fn test_doc_cache_file_roundtrip() {
+    let dir = temp_dir::TempDir::new().unwrap();
+    let path = dir.child("cache.json");
+
+    let cache = DocCache::new();
+    cache.set("serde".to_string(), "serde docs".to_string()).await;
+    cache.set("serde:Serialize".to_string(), "trait docs".to_string()).await;
+    cache.save_to_file(&path).await.unwrap();
+
+    let loaded = DocCache::load_from_file(&path).await.unwrap();
+    assert_eq!(loaded.export().await, cache.export().await);
+
+    assert!(DocCache::load_from_file(&dir.child("missing.json")).await.is_err());
+}
+
+#[test]
+async fn test_lookup_from_fixture() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/doc_cache.json");
+    let fixture = DocCache::load_from_file(&path).await.unwrap().export().await;
+
+    // Served from the imported fixture, so no network access is needed
+    let router = CargoDocRouter::new();
+    router.cache.import(fixture).await;
+    let doc = output(router.lookup_crate("serde".to_string(), None, None, None, None, None).await);
+    assert!(doc.starts_with("# serde"));
+}
+
+#[test]
+async fn test_doc_cache_contains_and_remove() {
+    let cache = DocCache::new();
+    cache.set("serde".to_string(), "docs".to_string()).await;
+
+    assert!(cache.contains("serde").await);
+    assert!(!cache.contains("tokio").await);
+    // contains() is not a lookup and must not move the counters
+    assert_eq!((cache.hits(), cache.misses()), (0, 0));
+
+    assert_eq!(cache.remove("serde").await, Some("docs".to_string()));
+    assert!(!cache.contains("serde").await);
+    assert_eq!(cache.get("serde").await, None);
+    assert_eq!(cache.remove("serde").await, None);
+}
+
+#[test]
+async fn test_doc_cache_remove_examples() {
+    let cache = DocCache::new();
+    let examples = vec![CodeExample {
+        title: "Example 1".to_string(),
+        code: "fn main() {}".to_string(),
+        description: String::new(),
+        fence_info: String::new(),
+        generated: false,
+    }];
+    cache.set_examples("examples:serde:Serialize".to_string(), examples.clone()).await;
+
+    assert!(cache.contains_examples("examples:serde:Serialize").await);
+    assert_eq!(cache.remove_examples("examples:serde:Serialize").await, Some(examples));
+    assert!(!cache.contains_examples("examples:serde:Serialize").await);
+    assert_eq!(cache.get_examples("examples:serde:Serialize").await, None);
+}
+
+#[test]
+async fn test_doc_cache_remove_persistent() {
+    let dir = temp_dir::TempDir::new().unwrap();
+    let persistent = PersistentDocCache::open(&dir.child("cache")).unwrap();
+    let cache = DocCache::new().with_persistent_cache(persistent);
+    cache.set("serde".to_string(), "docs".to_string()).await;
+
+    assert_eq!(cache.remove("serde").await, Some("docs".to_string()));
+    // The entry must be gone from disk too, not just from memory
+    assert_eq!(cache.get("serde").await, None);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_doc_cache_concurrent_reads() {
+    let cache = DocCache::new();
+    for i in 0..10 {
+        cache.set(format!("crate-{}", i), format!("docs-{}", i)).await;
+    }
+
+    let handles: Vec<_> = (0..50)
+        .map(|i| {
+            let cache = cache.clone();
+            tokio::spawn(async move {
+                let key = format!("crate-{}", i % 10);
+                cache.get(&key).await
+            })
+        })
+        .collect();
+
+    let results = tokio::time::timeout(
+        std::time::Duration::from_secs(5),
+        futures::future::join_all(handles),
+    )
+    .await
+    .expect("concurrent reads deadlocked");
+
+    for (i, result) in results.into_iter().enumerate() {
+        assert_eq!(result.unwrap(), Some(format!("docs-{}", i % 10)));
+    }
+    assert_eq!(cache.hits(), 50);
+}
+
+#[test]
+async fn test_in_memory_backend() {
+    let backend = InMemoryBackend::new(10);
+    backend.set("serde".to_string(), "docs".to_string()).await;
+    backend.set("tokio".to_string(), "docs".to_string()).await;
+
+    assert_eq!(backend.get("serde").await, Some("docs".to_string()));
+    let mut keys = backend.keys().await;
+    keys.sort();
+    assert_eq!(keys, vec!["serde".to_string(), "tokio".to_string()]);
+
+    assert!(backend.remove("serde").await);
+    assert!(!backend.remove("serde").await);
+
+    backend.clear().await;
+    assert!(backend.is_empty().await);
+}
+
+#[test]
+async fn test_null_backend_disables_caching() {
+    let router = CargoDocRouter::with_cache_backend(NullBackend);
+    router.cache.set("serde".to_string(), "docs".to_string()).await;
+
+    assert_eq!(router.cache.get("serde").await, None);
+    assert!(!router.cache.contains("serde").await);
+    assert_eq!(router.cache.misses(), 1);
+}
+
+// Test double that records every write
+#[derive(Clone, Default)]
+struct RecordingBackend {
+    inner: InMemoryBackend,
+    writes: std::sync::Arc<std::sync::Mutex<Vec<String>>>,
+}
+
+#[async_trait::async_trait]
+impl CacheBackend for RecordingBackend {
+    async fn get(&self, key: &str) -> Option<String> {
+        self.inner.get(key).await
+    }
+
+    async fn set(&self, key: String, value: String) {
+        self.writes.lock().unwrap().push(key.clone());
+        self.inner.set(key, value).await;
+    }
+
+    async fn remove(&self, key: &str) -> bool {
+        self.inner.remove(key).await
+    }
+
+    async fn clear(&self) {
+        self.inner.clear().await;
+    }
+
+    async fn keys(&self) -> Vec<String> {
+        self.inner.keys().await
+    }
+}
+
+#[test]
+async fn test_custom_cache_backend() {
+    let backend = RecordingBackend::default();
+    let cache = DocCache::with_backend(backend.clone());
+
+    cache.set("doc:serde".to_string(), "docs".to_string()).await;
+    cache.set("doc:serde:sde::Serialize".to_string(), "item".to_string()).await;
+    assert_eq!(cache.get("doc:serde").await, Some("docs".to_string()));
+    assert_eq!(backend.writes.lock().unwrap().len(), 2);
+
+    assert_eq!(cache.clear_crate("serde").await, 2);
+    assert!(backend.keys().await.is_empty());
+}
+
+fn docs_rs_struct_page() -> String {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/docs_rs_struct_page.html");
+    std::fs::read_to_string(path).unwrap()
+}
+
+#[test]
+async fn test_extract_doc_content() {
+    let html = docs_rs_struct_page();
+    let full = html_to_markdown(&html);
+    let content = html_to_markdown(&extract_doc_content(&html));
+
+    assert!(content.contains("An asynchronous `Client` to make Requests with."));
+    assert!(content.contains("Convenience method to make a `GET` request to a URL."));
+    // docs.rs menu, rustdoc sidebar and footer are gone
+    for noise in ["The Cargo Guide", "Privacy policy", "Auto Trait Implementations", "Report an issue"] {
+        assert!(full.contains(noise), "fixture should contain {:?}", noise);
+        assert!(!content.contains(noise), "{:?} should have been stripped", noise);
+    }
+    assert!(
+        content.len() * 10 < full.len() * 7,
+        "expected at least 30% less output, got {} of {} bytes",
+        content.len(),
+        full.len()
+    );
+}
+
+#[test]
+async fn test_extract_doc_content_fallbacks() {
+    // Older rustdoc versions wrapped the documentation in `<div id="main">`
+    let legacy = r#"<html><body><nav class="sidebar">Sidebar</nav>
+        <div id="main"><h1>Struct Legacy</h1><div class="docblock"><p>Old layout.</p></div></div>
+        <footer>Footer</footer></body></html>"#;
+    let content = extract_doc_content(legacy);
+    assert!(content.contains("Old layout."));
+    assert!(!content.contains("Sidebar") && !content.contains("Footer"));
+
+    // Without a main container, top-level docblocks are kept, each once
+    let docblocks = r#"<html><body><nav>Menu</nav>
+        <div class="docblock"><p>First.</p><div class="docblock"><p>Nested.</p></div></div>
+        <div class="docblock"><p>Second.</p></div></body></html>"#;
+    let content = extract_doc_content(docblocks);
+    assert!(!content.contains("Menu"));
+    assert_eq!(content.matches("Nested.").count(), 1);
+    assert!(content.contains("First.") && content.contains("Second."));
+
+    // Anything else is left alone
+    let plain = "<html><body><p>Nothing to strip.</p></body></html>";
+    assert_eq!(extract_doc_content(plain), plain);
+}
+
+#[test]
+async fn test_lookup_item_strips_navigation() {
+    let mut server = mockito::Server::new_async().await;
+    let page = server
+        .mock("GET", "/reqwest/latest/reqwest/struct.Client.html")
+        .with_body(docs_rs_struct_page())
+        .expect(1)
+        .create_async()
+        .await;
+    server
+        .mock("GET", mockito::Matcher::Any)
+        .with_status(404)
+        .create_async()
+        .await;
+
+    let router = CargoDocRouter::new().with_base_url(server.url());
+    let doc = output(
+        router
+            .lookup_item_tool("reqwest".to_string(), "Client".to_string(), None, None, None, None, None, None, None, None)
+            .await,
+    );
+    assert!(doc.contains("An asynchronous `Client` to make Requests with."));
+    assert!(!doc.contains("The Cargo Guide"));
+    assert!(!doc.contains("Report an issue"));
+    page.assert_async().await;
+}
+
+#[test]
+async fn test_code_block_languages() {
+    // rustdoc's markup for a doc example, a TOML snippet and a shell snippet
+    let markdown = html_to_markdown(
+        r#"<div class="docblock"><p>Add the dependency:</p>
+        <div class="example-wrap"><pre class="language-toml"><code>[dependencies]
+serde = "1"</code></pre></div>
+        <p>Then run <code>cargo build</code>:</p>
+        <div class="example-wrap"><pre class="language-sh"><code>cargo build --release</code></pre></div>
+        <div class="example-wrap"><pre class="rust rust-example-rendered"><code><span class="kw">let </span>x = <span class="number">1</span>;</code></pre></div>
+        <pre class="language-text"><code>plain output</code></pre></div>"#,
+    );
+
+    assert!(markdown.contains("```toml\n[dependencies]\nserde = \"1\"\n```"));
+    assert!(markdown.contains("```sh\ncargo build --release\n```"));
+    assert!(markdown.contains("```rust\nlet x = 1;\n```"));
+    assert!(markdown.contains("```\nplain output\n```"));
+    assert!(markdown.contains("Then run `cargo build`:"));
+
+    // Rendered READMEs put the language on the inner `<code>`
+    let readme = html_to_markdown(
+        r#"<pre><code class="language-rust">fn main() {}</code></pre><pre><code class="language-bash">cargo add tokio</code></pre>"#,
+    );
+    assert!(readme.contains("```rust\nfn main() {}\n```"));
+    assert!(readme.contains("```bash\ncargo add tokio\n```"));
+}
+
+// Whether every line of `table` is a pipe table row with `columns` cells, the second one
+// being the header separator
+fn assert_gfm_table(table: &str, columns: usize) {
+    let lines: Vec<&str> = table.lines().collect();
+    assert!(lines.len() >= 2, "not a table: {:?}", table);
+    assert_eq!(lines[1], format!("|{}", "---|".repeat(columns)));
+    for line in lines {
+        assert!(line.starts_with('|') && line.ends_with('|'), "bad row {:?}", line);
+        let separators = line.matches('|').count() - line.matches("\\|").count();
+        assert_eq!(separators, columns + 1, "wrong cell count in {:?}", line);
+    }
+}
+
+#[test]
+async fn test_tables_as_gfm() {
+    // A method table from a docblock, as rustdoc renders markdown tables
+    let markdown = html_to_markdown(
+        r##"<div class="docblock"><p>Choosing a method:</p>
+        <table><thead><tr><th>Method</th><th>Returns</th><th>Notes</th></tr></thead>
+        <tbody>
+        <tr><td><a href="#method.get"><code>get</code></a></td><td><code>Option&lt;&amp;T&gt;</code></td><td>Never panics</td></tr>
+        <tr><td><a href="#method.index"><code>index</code></a></td><td><code>&amp;T</code></td><td></td></tr>
+        <tr><td><code>split</code></td><td><code>Result&lt;T, E&gt;</code> or <code>a | b</code></td><td><p>First line.</p><p>Second line.</p></td></tr>
+        </tbody></table>
+        <p>After the table.</p></div>"##,
+    );
+
+    let start = markdown.find("| Method |").expect("table header");
+    let table = markdown[start..]
+        .lines()
+        .take_while(|line| line.starts_with('|'))
+        .collect::<Vec<_>>()
+        .join("\n");
+    assert_gfm_table(&table, 3);
+    assert_eq!(table.lines().count(), 5);
+    assert!(table.contains("| [`get`](#method.get) | `Option<&T>` | Never panics |"));
+    assert!(table.contains("| [`index`](#method.index) | `&T` |  |"));
+    assert!(table.contains("`a \\| b`"));
+    assert!(table.contains("| First line.<br>Second line. |"));
+    assert!(markdown.contains("Choosing a method:\n\n| Method"));
+    assert!(markdown.contains("\n\nAfter the table."));
+}
+
+#[test]
+async fn test_tables_without_header_or_with_colspan() {
+    let markdown = html_to_markdown(
+        "<table><tr><th colspan=\"2\">Variable</th><th>Default</th></tr>\
+         <tr><td>RUST_LOG</td><td>filter</td><td>error</td></tr>\
+         <tr><td>NO_COLOR</td></tr></table>",
+    );
+    assert_gfm_table(&markdown, 3);
+    assert!(markdown.starts_with("| Variable |  | Default |"));
+    assert!(markdown.contains("| NO_COLOR |  |  |"));
+
+    // GFM requires a header row, so data-only tables get an empty one
+    let markdown = html_to_markdown("<table><tr><td>a</td><td>b</td></tr></table>");
+    assert_eq!(markdown, "|  |  |\n|---|---|\n| a | b |");
+}
+
+#[test]
+async fn test_html_entities_decoded() {
+    let markdown = html_to_markdown(
+        "<h4 class=\"code-header\">pub fn debug&amp;lt;T: Clone + Debug&amp;gt;(value: &amp;amp;T)</h4>\
+         <p>Needs <code>T: Clone</code><sup>&lt;T: Clone + Debug&gt;</sup></p>\
+         <pre class=\"rust\"><code>let s = &quot;it&#39;s&quot;;</code></pre>",
+    );
+    assert!(markdown.contains("<T: Clone + Debug>"));
+    assert!(markdown.contains("(value: &T)"));
+    assert!(markdown.contains("let s = \"it's\";"));
+    assert!(!markdown.contains("&lt;") && !markdown.contains("&gt;") && !markdown.contains("&amp;"));
+}
+
+#[test]
+async fn test_scripts_and_styles_dropped() {
+    let html = r#"<html><head><style>.docblock { color: red; }</style>
+        <script>var ALIASES = {"serde": {}};</script></head>
+        <body><section id="main-content"><h1>Struct serde::de::Error</h1>
+        <script type="text/javascript">window.searchIndex = JSON.parse('{}');</script>
+        <style type="text/css">pre.rust { tab-size: 4; }</style>
+        <div class="docblock"><p>Errors during deserialization.</p></div></section></body></html>"#;
+
+    let markdown = html_to_markdown(html);
+    assert!(markdown.contains("Errors during deserialization."));
+    for script_or_css in ["ALIASES", "searchIndex", "JSON.parse", "color: red", "tab-size"] {
+        assert!(!markdown.contains(script_or_css), "{:?} leaked into {:?}", script_or_css, markdown);
+    }
+    assert!(!html_to_markdown(&extract_doc_content(html)).contains("searchIndex"));
+}
+
+#[test]
+async fn test_definition_lists() {
+    // The error cases of `std::env::var`, written as a definition list
+    let markdown = html_to_markdown(
+        r#"<div class="docblock"><h2 id="errors">Errors</h2>
+        <p>This function will return an error if:</p>
+        <dl>
+        <dt><a href="enum.VarError.html#variant.NotPresent"><code>NotPresent</code></a></dt>
+        <dd>The environment variable isn't set.</dd>
+        <dt><code>NotUnicode</code></dt>
+        <dd><p>The environment variable's name contains an equal sign or a NUL character.</p>
+        <p>Use <code>var_os</code> to read it anyway.</p></dd>
+        <dd>This is the only other case.</dd>
+        </dl>
+        <p>Examples follow.</p></div>"#,
+    );
+
+    assert!(markdown.contains(
+        "This function will return an error if:\n\n\
+         **[`NotPresent`](enum.VarError.html#variant.NotPresent)**:\n  The environment variable isn't set.\n\n\
+         **`NotUnicode`**:\n  The environment variable's name contains an equal sign or a NUL character.\n\n  \
+         Use `var_os` to read it anyway.\n\n  This is the only other case.\n\nExamples follow."
+    ), "{}", markdown);
+}
+
+#[test]
+async fn test_page_links_made_absolute() {
+    let html = r##"<html><body><section id="main-content">
+        <h2 id="implementations" class="section-header">Implementations<a href="#implementations" class="anchor">§</a></h2>
+        <div class="docblock"><p>Built with <a href="struct.ClientBuilder.html" title="struct reqwest::ClientBuilder"><code>ClientBuilder</code></a>,
+        see also <a href="../http/header/index.html">the header module</a>,
+        <a href="https://docs.rs/url/latest/url/struct.Url.html"><code>Url</code></a> and <a href="#method.get">get</a>.</p>
+        <a name="legacy-anchor">Named only</a></div></section></body></html>"##;
+
+    let markdown = page_to_markdown(html, "https://docs.rs/reqwest/0.12.9/reqwest/struct.Client.html");
+    assert!(markdown.contains(
+        "[`ClientBuilder`](https://docs.rs/reqwest/0.12.9/reqwest/struct.ClientBuilder.html)"
+    ));
+    assert!(markdown.contains("[the header module](https://docs.rs/reqwest/0.12.9/http/header/index.html)"));
+    assert!(markdown.contains("[`Url`](https://docs.rs/url/latest/url/struct.Url.html)"));
+    assert!(markdown.contains("[get](https://docs.rs/reqwest/0.12.9/reqwest/struct.Client.html#method.get)"));
+    assert!(markdown.contains("Named only") && !markdown.contains("[Named only]"));
+    // Section anchors are dropped rather than linked
+    assert!(!markdown.contains('§'));
+
+    // Without a page URL, links are kept as written
+    assert!(html_to_markdown(html).contains("[`ClientBuilder`](struct.ClientBuilder.html)"));
+}
+
+#[test]
+async fn test_doc_error_display_and_mcp_codes() {
+    let not_found = DocError::NotFound {
+        crate_name: "serde".to_string(),
+        item_path: Some("Serializer".to_string()),
+        tried_urls: vec!["https://docs.rs/a.html".to_string(), "https://docs.rs/b.html".to_string()],
+    };
+    assert_eq!(
+        not_found.to_string(),
+        "No documentation found for `Serializer` in serde (tried https://docs.rs/a.html, https://docs.rs/b.html)"
+    );
+    assert_eq!(McpError::from(not_found).code, ErrorCode::RESOURCE_NOT_FOUND);
+
+    let rate_limited = DocError::RateLimited {
+        retry_after: Some(Duration::from_secs(30)),
+    };
+    assert_eq!(rate_limited.to_string(), "Rate limited, retry after 30 seconds");
+    assert_eq!(McpError::from(rate_limited).code, ErrorCode::INTERNAL_ERROR);
+
+    let invalid = DocError::InvalidInput("empty item path".to_string());
+    assert_eq!(McpError::from(invalid).code, ErrorCode::INVALID_PARAMS);
+
+    let server_error = DocError::Http {
+        status: 500,
+        url: "https://docs.rs/serde".to_string(),
+    };
+    assert_eq!(server_error.to_string(), "Request to https://docs.rs/serde failed with status 500");
+    assert_eq!(McpError::from(server_error).code, ErrorCode::INTERNAL_ERROR);
+}
+
+#[test]
+async fn test_lookup_item_not_found_lists_tried_urls() {
+    let mut server = mockito::Server::new_async().await;
+    server
+        .mock("GET", mockito::Matcher::Any)
+        .with_status(404)
+        .create_async()
+        .await;
+    let router = CargoDocRouter::new().with_base_url(server.url());
+
+    let error = router
+        .lookup_item_doc(crate_name("mycrate"), item_path("mycrate", "sync::Missing"), None, None)
+        .await
+        .unwrap_err();
+    let DocError::NotFound { item_path, tried_urls, .. } = &error else {
+        panic!("expected NotFound, got {:?}", error);
+    };
+    assert_eq!(item_path.as_deref(), Some("sync::Missing"));
+    assert!(tried_urls.contains(&format!("{}/mycrate/latest/mycrate/sync/struct.Missing.html", server.url())));
+    assert!(tried_urls.contains(&format!("{}/mycrate/latest/mycrate/sync/trait.Missing.html", server.url())));
+
+    let error = router
+        .lookup_item_tool("mycrate".to_string(), "sync::Missing".to_string(), None, None, None, None, None, None, None, None)
+        .await
+        .unwrap_err();
+    assert_eq!(error.code, ErrorCode::RESOURCE_NOT_FOUND);
+    assert!(error.message.contains("struct.Missing.html"));
+
+    let error = router
+        .lookup_item_tool("mycrate".to_string(), "sync::".to_string(), None, None, None, None, None, None, None, None)
+        .await
+        .unwrap_err();
+    assert_eq!(error.code, ErrorCode::INVALID_PARAMS);
+}
+
+#[test]
+async fn test_probe_all_types() {
+    let mut server = mockito::Server::new_async().await;
+    // Both pages exist; the kind ranked first by the naming convention wins
+    let function = server
+        .mock("GET", "/mycrate/1.0.0/mycrate/fmt/fn.format.html")
+        .with_body("<html><body><p>A function.</p></body></html>")
+        .create_async()
+        .await;
+    server
+        .mock("GET", "/mycrate/1.0.0/mycrate/fmt/macro.format.html")
+        .with_body("<html><body><p>A macro.</p></body></html>")
+        .create_async()
+        .await;
+    let write_macro = server
+        .mock("GET", "/mycrate/1.0.0/mycrate/fmt/macro.write.html")
+        .with_body("<html><body><p>A macro.</p></body></html>")
+        .expect(1)
+        .create_async()
+        .await;
+    server
+        .mock("GET", mockito::Matcher::Any)
+        .with_status(404)
+        .create_async()
+        .await;
+    let router = CargoDocRouter::new().with_base_url(server.url());
+
+    let (url, body) = router
+        .probe_all_types(&crate_name("mycrate"), "fmt", "format", Some("1.0.0"), None)
+        .await
+        .unwrap();
+    assert_eq!(url, format!("{}/mycrate/1.0.0/mycrate/fmt/fn.format.html", server.url()));
+    assert!(body.contains("A function."));
+    function.assert_async().await;
+
+    // Names ending in `!` are only probed as macros
+    let (url, _) = router
+        .probe_all_types(&crate_name("mycrate"), "fmt", "write!", Some("1.0.0"), None)
+        .await
+        .unwrap();
+    assert!(url.ends_with("/fmt/macro.write.html"));
+    write_macro.assert_async().await;
+
+    let error = router
+        .probe_all_types(&crate_name("mycrate"), "fmt", "Missing", Some("1.0.0"), None)
+        .await
+        .unwrap_err();
+    let DocError::NotFound { item_path, tried_urls, .. } = &error else {
+        panic!("expected NotFound, got {:?}", error);
+    };
+    assert_eq!(item_path.as_deref(), Some("fmt::Missing"));
+    assert_eq!(tried_urls.len(), ItemKind::ALL.len());
+}
+
+#[test]
+async fn test_probe_all_types_tries_likely_kinds_first() {
+    let mut server = mockito::Server::new_async().await;
+    let client = server
+        .mock("GET", "/mycrate/1.0.0/mycrate/struct.Client.html")
+        .with_body("<html><body><p>A struct.</p></body></html>")
+        .expect(1)
+        .create_async()
+        .await;
+    let alias = server
+        .mock("GET", "/mycrate/1.0.0/mycrate/type.Result.html")
+        .with_body("<html><body><p>A type alias.</p></body></html>")
+        .expect(1)
+        .create_async()
+        .await;
+    // At most the enum and trait pages of `Client`, then every kind of `Result` but the alias
+    let missing = server
+        .mock("GET", mockito::Matcher::Any)
+        .with_status(404)
+        .expect_at_most(2 + ItemKind::ALL.len() - 1)
+        .create_async()
+        .await;
+    let router = CargoDocRouter::new().with_base_url(server.url());
+
+    let (url, _) = router
+        .probe_all_types(&crate_name("mycrate"), "", "Client", Some("1.0.0"), None)
+        .await
+        .unwrap();
+    assert!(url.ends_with("/struct.Client.html"));
+    // A kind the name doesn't suggest is still found once the likely ones miss
+    let (url, _) = router
+        .probe_all_types(&crate_name("mycrate"), "", "Result", Some("1.0.0"), None)
+        .await
+        .unwrap();
+    assert!(url.ends_with("/type.Result.html"));
+
+    client.assert_async().await;
+    alias.assert_async().await;
+    missing.assert_async().await;
+}
+
+#[test]
+async fn test_server_errors_are_not_reported_as_missing() {
+    let mut server = mockito::Server::new_async().await;
+    server
+        .mock("GET", mockito::Matcher::Any)
+        .with_status(503)
+        .create_async()
+        .await;
+    let router = CargoDocRouter::new().with_base_url(server.url());
+
+    let error = router
+        .lookup_item_doc(crate_name("mycrate"), item_path("mycrate", "Client"), None, None)
+        .await
+        .unwrap_err();
+    assert!(matches!(error, DocError::Http { status: 503, .. }));
+}
+
+#[test]
+async fn test_backoff_delay() {
+    for attempt in 0..4 {
+        let full = Duration::from_millis(100 * 2u64.pow(attempt));
+        let delay = backoff_delay(attempt);
+        assert!(delay >= full / 2 && delay <= full, "attempt {}: {:?}", attempt, delay);
+    }
+    assert!(backoff_delay(20) <= Duration::from_secs(30));
+    assert!(backoff_delay(u32::MAX) <= Duration::from_secs(30));
+}
+
+#[test]
+async fn test_retry_transient_failures() {
+    let mut server = mockito::Server::new_async().await;
+    let unavailable = server
+        .mock("GET", "/mycrate/latest/mycrate/struct.Client.html")
+        .with_status(503)
+        .expect(2)
+        .create_async()
+        .await;
+    let available = server
+        .mock("GET", "/mycrate/latest/mycrate/struct.Client.html")
+        .with_body("<html><body><p>An HTTP client.</p></body></html>")
+        .expect(1)
+        .create_async()
+        .await;
+    let router = CargoDocRouter::new().with_base_url(server.url());
+
+    let doc = router
+        .lookup_item_doc(crate_name("mycrate"), item_path("mycrate", "Client"), None, None)
+        .await
+        .unwrap()
+        .to_markdown();
+    assert!(doc.contains("An HTTP client."));
+    unavailable.assert_async().await;
+    available.assert_async().await;
+}
+
+#[test]
+async fn test_retry_gives_up() {
+    let mut server = mockito::Server::new_async().await;
+    let unavailable = server
+        .mock("GET", "/crate/builds.json")
+        .with_status(502)
+        .expect(2)
+        .create_async()
+        .await;
+    let missing = server
+        .mock("GET", "/crate/missing.json")
+        .with_status(404)
+        .expect(1)
+        .create_async()
+        .await;
+    let router = CargoDocRouter::new().with_max_retries(1);
+
+    let error = router
+        .fetch_text(&format!("{}/crate/builds.json", server.url()))
+        .await
+        .unwrap_err();
+    assert!(matches!(error, DocError::Http { status: 502, .. }));
+    unavailable.assert_async().await;
+
+    // Missing pages are not retried
+    let error = router
+        .fetch_text(&format!("{}/crate/missing.json", server.url()))
+        .await
+        .unwrap_err();
+    assert!(error.is_not_found());
+    missing.assert_async().await;
+}
+
+#[test]
+async fn test_parse_retry_after() {
+    let now = std::time::UNIX_EPOCH + Duration::from_secs(784_111_777); // Sun, 06 Nov 1994 08:49:37 GMT
+
+    assert_eq!(parse_retry_after("5", now), Some(Duration::from_secs(5)));
+    assert_eq!(parse_retry_after(" 120 ", now), Some(Duration::from_secs(120)));
+    assert_eq!(
+        parse_retry_after("Sun, 06 Nov 1994 08:50:07 GMT", now),
+        Some(Duration::from_secs(30))
+    );
+    assert_eq!(parse_retry_after("Sun, 06 Nov 1994 08:00:00 GMT", now), Some(Duration::ZERO));
+    assert_eq!(parse_retry_after("soon", now), None);
+    assert_eq!(parse_retry_after("-1", now), None);
+}
+
+#[test]
+async fn test_rate_limited_retry_waits_for_retry_after() {
+    let mut server = mockito::Server::new_async().await;
+    let limited = server
+        .mock("GET", "/api/v1/crates/serde")
+        .with_status(429)
+        .with_header("Retry-After", "5")
+        .expect(1)
+        .create_async()
+        .await;
+    let available = server
+        .mock("GET", "/api/v1/crates/serde")
+        .with_body("{}")
+        .expect(1)
+        .create_async()
+        .await;
+    let router = CargoDocRouter::new();
+
+    let started = std::time::Instant::now();
+    let body = router
+        .fetch_text(&format!("{}/api/v1/crates/serde", server.url()))
+        .await
+        .unwrap();
+    assert_eq!(body, "{}");
+    assert!(started.elapsed() >= Duration::from_secs(5));
+    limited.assert_async().await;
+    available.assert_async().await;
+}
+
+#[test]
+async fn test_rate_limited_error() {
+    let mut server = mockito::Server::new_async().await;
+    let limited = server
+        .mock("GET", "/api/v1/crates/serde")
+        .with_status(429)
+        .with_header("Retry-After", "120")
+        .expect(1)
+        .create_async()
+        .await;
+    let router = CargoDocRouter::new();
+
+    // A wait longer than the maximum backoff is left to the caller
+    let error = router
+        .fetch_text(&format!("{}/api/v1/crates/serde", server.url()))
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        error,
+        DocError::RateLimited { retry_after: Some(delay) } if delay == Duration::from_secs(120)
+    ));
+    limited.assert_async().await;
+}
+
+#[test]
+async fn test_request_timeout() {
+    // A server that accepts connections but takes far longer than the timeout to answer
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        while let Ok((socket, _)) = listener.accept().await {
+            tokio::spawn(async move {
+                tokio::time::sleep(Duration::from_secs(10)).await;
+                drop(socket);
+            });
+        }
+    });
+    let router = CargoDocRouter::new()
+        .with_timeout(Duration::from_millis(200))
+        .with_max_retries(0);
+    assert_eq!(router.timeout, Duration::from_millis(200));
+    assert_eq!(CargoDocRouter::new().timeout, DEFAULT_TIMEOUT);
+
+    let started = std::time::Instant::now();
+    let error = router
+        .fetch_text(&format!("http://{}/serde/latest/serde/", address))
+        .await
+        .unwrap_err();
+    assert!(matches!(&error, DocError::Network(e) if e.is_timeout()), "{:?}", error);
+    assert!(started.elapsed() < Duration::from_secs(5));
+}
+
+#[test]
+async fn test_circuit_breaker_states() {
+    let breaker = CircuitBreaker::new(2, Duration::from_millis(50));
+    assert_eq!(breaker.state(), CircuitState::Closed);
+
+    // A success resets the count of consecutive failures
+    breaker.record_failure();
+    breaker.record_success();
+    breaker.record_failure();
+    assert!(breaker.allow_request().is_ok());
+    breaker.record_failure();
+    assert!(matches!(breaker.state(), CircuitState::Open { .. }));
+    assert!(matches!(
+        breaker.allow_request(),
+        Err(DocError::ServiceUnavailable { retry_after }) if retry_after <= Duration::from_millis(50)
+    ));
+
+    // After the recovery timeout one probe goes through; a failed probe reopens the circuit
+    tokio::time::sleep(Duration::from_millis(60)).await;
+    assert!(breaker.allow_request().is_ok());
+    assert_eq!(breaker.state(), CircuitState::HalfOpen);
+    assert!(breaker.allow_request().is_err());
+    breaker.record_failure();
+    assert!(matches!(breaker.state(), CircuitState::Open { .. }));
+
+    // A successful probe closes it
+    tokio::time::sleep(Duration::from_millis(60)).await;
+    assert!(breaker.allow_request().is_ok());
+    breaker.record_success();
+    assert_eq!(breaker.state(), CircuitState::Closed);
+    assert!(breaker.allow_request().is_ok());
+}
+
+#[test]
+async fn test_circuit_opens_after_repeated_failures() {
+    let mut server = mockito::Server::new_async().await;
+    let failing = server
+        .mock("GET", "/api/v1/crates/serde")
+        .with_status(500)
+        .expect(DEFAULT_FAILURE_THRESHOLD as usize)
+        .create_async()
+        .await;
+    let router = CargoDocRouter::new().with_max_retries(0);
+    let url = format!("{}/api/v1/crates/serde", server.url());
+
+    for _ in 0..DEFAULT_FAILURE_THRESHOLD {
+        let error = router.fetch_text(&url).await.unwrap_err();
+        assert!(matches!(error, DocError::Http { status: 500, .. }));
+    }
+    assert!(matches!(router.circuit_breaker.state(), CircuitState::Open { .. }));
+
+    // Further requests fail without reaching the server
+    let error = router.fetch_text(&url).await.unwrap_err();
+    assert!(matches!(error, DocError::ServiceUnavailable { .. }));
+    assert_eq!(McpError::from(error).code, ErrorCode::INTERNAL_ERROR);
+    failing.assert_async().await;
+}
+
+#[test]
+async fn test_missing_pages_keep_circuit_closed() {
+    let mut server = mockito::Server::new_async().await;
+    server
+        .mock("GET", mockito::Matcher::Any)
+        .with_status(404)
+        .create_async()
+        .await;
+    let router = CargoDocRouter::new()
+        .with_base_url(server.url())
+        .with_circuit_breaker(CircuitBreaker::new(1, DEFAULT_RECOVERY_TIMEOUT));
+
+    // Probing every item kind means many 404s in a row
+    let error = router
+        .lookup_item_doc(crate_name("mycrate"), item_path("mycrate", "Missing"), None, None)
+        .await
+        .unwrap_err();
+    assert!(error.is_not_found());
+    assert_eq!(router.circuit_breaker.state(), CircuitState::Closed);
+}
+
+#[test]
+async fn test_concurrent_lookups_share_one_request() {
+    let mut server = mockito::Server::new_async().await;
+    let page = server
+        .mock("GET", "/mycrate/latest/mycrate/struct.Client.html")
+        .with_body("<html><body><p>An HTTP client.</p></body></html>")
+        .expect(1)
+        .create_async()
+        .await;
+    let router = CargoDocRouter::new().with_base_url(server.url());
+
+    let lookups = (0..20).map(|_| {
+        let router = router.clone();
+        tokio::spawn(async move {
+            router
+                .lookup_item_doc(crate_name("mycrate"), item_path("mycrate", "Client"), None, None)
+                .await
+        })
+    });
+    for doc in futures::future::join_all(lookups).await {
+        assert!(doc.unwrap().unwrap().to_markdown().contains("An HTTP client."));
+    }
+    page.assert_async().await;
+    assert!(router.in_flight.lock().unwrap().is_empty());
+}
+
+#[test]
+async fn test_waiting_lookups_retry_after_failure() {
+    let mut server = mockito::Server::new_async().await;
+    server
+        .mock("GET", mockito::Matcher::Any)
+        .with_status(404)
+        .create_async()
+        .await;
+    let router = CargoDocRouter::new().with_base_url(server.url());
+
+    let (first, second) = tokio::join!(
+        router.lookup_item_doc(crate_name("mycrate"), item_path("mycrate", "Missing"), None, None),
+        router.lookup_item_doc(crate_name("mycrate"), item_path("mycrate", "Missing"), None, None),
+    );
+    assert!(first.unwrap_err().is_not_found());
+    assert!(second.unwrap_err().is_not_found());
+    assert!(router.in_flight.lock().unwrap().is_empty());
+}
+
+#[test]
+async fn test_builder_defaults() {
+    let router = CargoDocRouter::builder().build().unwrap();
+    assert_eq!(router.timeout, DEFAULT_TIMEOUT);
+    assert_eq!(router.max_retries, DEFAULT_MAX_RETRIES);
+    assert_eq!(router.base_url, "https://docs.rs");
+    assert_eq!(router.user_agent, DEFAULT_USER_AGENT);
+    assert!(router.rate_limiter.is_none());
+    assert!(router.request_slots.is_none());
+}
+
+#[test]
+async fn test_default_user_agent() {
+    let expected = format!("{}/{}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
+    assert!(DEFAULT_USER_AGENT.starts_with(&expected));
+
+    let mut server = mockito::Server::new_async().await;
+    let page = server
+        .mock("GET", "/api/v1/crates/serde")
+        .match_header("user-agent", mockito::Matcher::Regex(format!("^{} ", expected.replace('.', "\\."))))
+        .with_body("{}")
+        .create_async()
+        .await;
+    let router = CargoDocRouter::new();
+
+    router
+        .fetch_text(&format!("{}/api/v1/crates/serde", server.url()))
+        .await
+        .unwrap();
+    page.assert_async().await;
+}
+
+#[test]
+async fn test_builder_timeout() {
+    let router = CargoDocRouter::builder()
+        .with_timeout(Duration::from_secs(5))
+        .build()
+        .unwrap();
+    assert_eq!(router.timeout, Duration::from_secs(5));
+
+    assert!(CargoDocRouter::builder().with_timeout(Duration::ZERO).build().is_err());
+}
+
+#[test]
+async fn test_builder_cache() {
+    let cache = DocCache::new();
+    cache.set("serde".to_string(), "# serde".to_string()).await;
+    let router = CargoDocRouter::builder().with_cache(cache.clone()).build().unwrap();
+    assert_eq!(router.cache.get("serde").await, Some("# serde".to_string()));
+
+    let router = CargoDocRouter::builder().with_max_cache_size(2).build().unwrap();
+    for name in ["serde", "tokio", "reqwest"] {
+        router.cache.set(name.to_string(), "docs".to_string()).await;
+    }
+    assert_eq!(router.cache.entry_count().await, 2);
+
+    let router = CargoDocRouter::builder().with_cache_backend(NullBackend).build().unwrap();
+    router.cache.set("serde".to_string(), "docs".to_string()).await;
+    assert_eq!(router.cache.get("serde").await, None);
+
+    assert!(CargoDocRouter::builder().with_max_cache_size(0).build().is_err());
+    assert!(CargoDocRouter::builder()
+        .with_cache(cache)
+        .with_max_cache_size(10)
+        .build()
+        .is_err());
+}
+
+#[test]
+async fn test_builder_client_and_user_agent() {
+    let mut server = mockito::Server::new_async().await;
+    let page = server
+        .mock("GET", "/api/v1/crates/serde")
+        .match_header("user-agent", "my-agent/1.0")
+        .match_header("x-extra", "from-client")
+        .with_body("{}")
+        .create_async()
+        .await;
+    let mut headers = reqwest::header::HeaderMap::new();
+    headers.insert("x-extra", "from-client".parse().unwrap());
+    let client = Client::builder().default_headers(headers).build().unwrap();
+    let router = CargoDocRouter::builder()
+        .with_client(client)
+        .with_user_agent("my-agent/1.0")
+        .build()
+        .unwrap();
+
+    let body = router
+        .fetch_text(&format!("{}/api/v1/crates/serde", server.url()))
+        .await
+        .unwrap();
+    assert_eq!(body, "{}");
+    page.assert_async().await;
+
+    assert!(CargoDocRouter::builder().with_user_agent("bad\nagent").build().is_err());
+}
+
+#[test]
+async fn test_builder_base_url() {
+    let router = CargoDocRouter::builder()
+        .with_base_url("http://localhost:8080/")
+        .build()
+        .unwrap();
+    assert_eq!(router.base_url, "http://localhost:8080");
+
+    assert!(CargoDocRouter::builder().with_base_url("not a url").build().is_err());
+    assert!(CargoDocRouter::builder().with_base_url("ftp://docs.rs").build().is_err());
+}
+
+#[test]
+async fn test_builder_retry_count() {
+    let router = CargoDocRouter::builder().with_retry_count(0).build().unwrap();
+    assert_eq!(router.max_retries, 0);
+}
+
+#[test]
+async fn test_builder_rate_limit() {
+    let mut server = mockito::Server::new_async().await;
+    server
+        .mock("GET", "/api/v1/crates/serde")
+        .with_body("{}")
+        .expect(3)
+        .create_async()
+        .await;
+    let router = CargoDocRouter::builder().with_rate_limit_rps(10.0).build().unwrap();
+    let url = format!("{}/api/v1/crates/serde", server.url());
+
+    // The first request goes out immediately, the next two 100ms apart
+    let started = std::time::Instant::now();
+    for _ in 0..3 {
+        router.fetch_text(&url).await.unwrap();
+    }
+    assert!(started.elapsed() >= Duration::from_millis(200));
+
+    for rps in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+        assert!(CargoDocRouter::builder().with_rate_limit_rps(rps).build().is_err());
+    }
+}
+
+#[test]
+async fn test_builder_max_concurrent_requests() {
+    let router = CargoDocRouter::builder()
+        .with_max_concurrent_requests(4)
+        .build()
+        .unwrap();
+    assert_eq!(router.request_slots.as_ref().unwrap().available_permits(), 4);
+
+    assert!(CargoDocRouter::builder().with_max_concurrent_requests(0).build().is_err());
+}
+
+// Forward proxy that sends every request it receives to `upstream`; returns its URL and the
+// number of requests forwarded so far
+fn start_forward_proxy(upstream: String) -> (String, Arc<AtomicUsize>) {
+    use hyper::service::{make_service_fn, service_fn};
+
+    let forwarded = Arc::new(AtomicUsize::new(0));
+    let counter = forwarded.clone();
+    let make_service = make_service_fn(move |_| {
+        let upstream = upstream.clone();
+        let counter = counter.clone();
+        async move {
+            Ok::<_, hyper::Error>(service_fn(move |mut request: hyper::Request<hyper::Body>| {
+                counter.fetch_add(1, Ordering::SeqCst);
+                // Proxied requests carry the absolute URL of the target; keep its path and query
+                let path = request.uri().path_and_query().map_or("/", |path| path.as_str());
+                *request.uri_mut() = format!("{}{}", upstream, path).parse().unwrap();
+                request.headers_mut().remove(hyper::header::HOST);
+                hyper::Client::new().request(request)
+            }))
+        }
+    });
+    let server = hyper::Server::bind(&([127, 0, 0, 1], 0).into()).serve(make_service);
+    let url = format!("http://{}", server.local_addr());
+    tokio::spawn(server);
+    (url, forwarded)
+}
+
+#[test]
+async fn test_builder_proxy_url() {
+    let mut server = mockito::Server::new_async().await;
+    let page = server
+        .mock("GET", "/api/v1/crates/serde")
+        .with_body("{}")
+        .expect(2)
+        .create_async()
+        .await;
+    let (proxy_url, forwarded) = start_forward_proxy(server.url());
+    let router = CargoDocRouter::builder()
+        .with_proxy_url(proxy_url)
+        .with_retry_count(0)
+        .build()
+        .unwrap();
+
+    // The host doesn't resolve, so only the proxy can reach it
+    let url = "http://crates.example.invalid/api/v1/crates/serde";
+    assert_eq!(router.fetch_text(url).await.unwrap(), "{}");
+    // Changing the timeout rebuilds the client without dropping the proxy
+    let router = router.with_timeout(Duration::from_secs(5));
+    assert_eq!(router.fetch_text(url).await.unwrap(), "{}");
+    assert_eq!(forwarded.load(Ordering::SeqCst), 2);
+    page.assert_async().await;
+
+    assert!(CargoDocRouter::builder().with_proxy_url("not a url").build().is_err());
+    assert!(CargoDocRouter::builder()
+        .with_client(Client::new())
+        .with_proxy_url("http://localhost:3128")
+        .build()
+        .is_err());
+}
+
+#[test]
+async fn test_builder_combined_options() {
+    let mut server = mockito::Server::new_async().await;
+    let page = server
+        .mock("GET", "/mycrate/1.0.0/mycrate/struct.Client.html")
+        .match_header("user-agent", "combined/1.0")
+        .with_body("<html><body><p>An HTTP client.</p></body></html>")
+        .expect(1)
+        .create_async()
+        .await;
+    let router = CargoDocRouter::builder()
+        .with_timeout(Duration::from_secs(5))
+        .with_client(Client::new())
+        .with_base_url(server.url())
+        .with_user_agent("combined/1.0")
+        .with_retry_count(1)
+        .with_max_cache_size(10)
+        .with_rate_limit_rps(50.0)
+        .with_max_concurrent_requests(2)
+        .build()
+        .unwrap();
+    assert_eq!(router.timeout, Duration::from_secs(5));
+    assert_eq!(router.max_retries, 1);
+
+    // The second lookup is served from the cache
+    for _ in 0..2 {
+        let doc = router
+            .lookup_item_doc(crate_name("mycrate"), item_path("mycrate", "Client"), Some("1.0.0".to_string()), None)
+            .await
+            .unwrap()
+            .to_markdown();
+        assert!(doc.contains("An HTTP client."));
+    }
+    page.assert_async().await;
+    assert_eq!(router.request_slots.as_ref().unwrap().available_permits(), 2);
+}
+
+fn fixture(name: &str) -> String {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures").join(name);
+    std::fs::read_to_string(path).unwrap()
+}
+
+#[test]
+async fn test_base_url_overrides() {
+    let mut docs = mockito::Server::new_async().await;
+    let mut crates_io = mockito::Server::new_async().await;
+    let docs_mocks = [
+        docs.mock("GET", "/crate/serde/1.0.0/")
+            .with_body("<html><body><p>Serialization framework.</p></body></html>")
+            .create_async()
+            .await,
+        docs.mock("GET", "/serde/latest/serde/de/index.html")
+            .with_body(fixture("module_index.html"))
+            .create_async()
+            .await,
+        docs.mock("GET", "/serde/latest/serde/trait.Serialize.html")
+            .with_body(r#"<pre class="rust item-decl"><code>pub trait Serialize { }</code></pre>"#)
+            .create_async()
+            .await,
+        docs.mock("GET", "/crate/serde/1.0.0/builds.json")
+            .with_body(fixture("docs_rs_builds_failed.json"))
+            .create_async()
+            .await,
+    ];
+    let crates_io_mocks = [
+        crates_io
+            .mock("GET", "/api/v1/crates")
+            .match_query(mockito::Matcher::UrlEncoded("q".to_string(), "serde".to_string()))
+            .with_body(fixture("crates_io_search.json"))
+            .create_async()
+            .await,
+        crates_io
+            .mock("GET", "/api/v1/crates/serde/versions")
+            .with_body(fixture("crates_io_versions.json"))
+            .create_async()
+            .await,
+        crates_io
+            .mock("GET", "/api/v1/crates/serde")
+            .with_body(fixture("crates_io_crate.json"))
+            .expect(2)
+            .create_async()
+            .await,
+        crates_io
+            .mock("GET", "/api/v1/crates/serde/1.0.219/dependencies")
+            .with_body(fixture("crates_io_dependencies.json"))
+            .create_async()
+            .await,
+        crates_io
+            .mock("GET", "/api/v1/crates/serde/1.0.0/readme")
+            .with_body("<p>Serde README</p>")
+            .create_async()
+            .await,
+    ];
+    let router = CargoDocRouter::new()
+        .with_base_url(docs.url())
+        .with_crates_io_base_url(format!("{}/", crates_io.url()));
+    let version = || Some("1.0.0".to_string());
+
+    assert!(output(router.lookup_crate("serde".to_string(), version(), None, None, None, None).await).contains("Serialization framework."));
+    output(router.lookup_module("serde".to_string(), "de".to_string(), None).await);
+    output(router.get_type_hierarchy("serde".to_string(), "Serialize".to_string(), None).await);
+    output(router.get_build_status("serde".to_string(), version()).await);
+    output(router.search_crates("serde".to_string(), None, None, None, None, None).await);
+    output(router.list_crate_versions("serde".to_string(), None).await);
+    output(router.get_crate_metadata("serde".to_string()).await);
+    // Resolves the latest version through crates.io again first
+    output(router.get_crate_dependencies("serde".to_string(), None).await);
+    assert!(output(router.get_crate_readme("serde".to_string(), version()).await).contains("Serde README"));
+
+    for mock in docs_mocks.iter().chain(&crates_io_mocks) {
+        mock.assert_async().await;
+    }
+}
+
+#[test]
+async fn test_resource_uris() {
+    assert_eq!(resource_uri("doc:serde").as_deref(), Some("docs://crate/serde/latest"));
+    assert_eq!(resource_uri("doc:serde:1.0.200").as_deref(), Some("docs://crate/serde/1.0.200"));
+    assert_eq!(resource_uri("doc:serde:de::Deserialize").as_deref(), Some("docs://item/serde/de/Deserialize"));
+    assert_eq!(resource_uri("doc:reqwest:Client").as_deref(), Some("docs://item/reqwest/Client"));
+    assert_eq!(resource_uri("doc:serde:1.0.200:de::Deserialize"), None);
+    assert_eq!(resource_uri("examples:serde:de::Deserialize"), None);
+    assert_eq!(resource_uri("versions:serde"), None);
+    assert_eq!(resource_uri("feature_map:tokio:1.40.0"), None);
+    // Without the prefix a key is never documentation, even for a crate named like a prefix
+    assert_eq!(resource_uri("serde"), None);
+    assert_eq!(resource_uri("doc:json:JsonValue").as_deref(), Some("docs://item/json/JsonValue"));
+
+    for key in ["doc:serde", "doc:serde:1.0.200", "doc:serde:de::Deserialize", "doc:reqwest:Client"] {
+        assert_eq!(resource_key(&resource_uri(key).unwrap()).as_deref(), Some(key));
+    }
+    assert_eq!(resource_key("docs://crate/serde"), None);
+    assert_eq!(resource_key("docs://item/serde//Deserialize"), None);
+    assert_eq!(resource_key("https://docs.rs/serde"), None);
+}
+
+#[test]
+async fn test_cached_docs_as_resources() {
+    let router = CargoDocRouter::new();
+    router.cache.set("doc:serde".to_string(), "# serde".to_string()).await;
+    router.cache.set("doc:serde:1.0.200".to_string(), "# serde 1.0.200".to_string()).await;
+    router.cache.set("doc:serde:de::Deserialize".to_string(), "# Deserialize".to_string()).await;
+    router.cache.set("examples:serde:de::Deserialize".to_string(), "derived".to_string()).await;
+    for i in 0..20 {
+        router.cache.set(format!("doc:crate{:02}", i), format!("# crate{:02}", i)).await;
+    }
+
+    let first = router.list_cached_docs(None).await.unwrap();
+    assert_eq!(first.resources.len(), RESOURCES_PER_PAGE);
+    assert_eq!(first.resources[0].raw.uri, "docs://crate/crate00/latest");
+    assert_eq!(first.resources[0].raw.mime_type.as_deref(), Some("text/markdown"));
+    assert_eq!(first.next_cursor.as_deref(), Some("20"));
+
+    let second = router.list_cached_docs(first.next_cursor.as_deref()).await.unwrap();
+    let uris: Vec<&str> = second.resources.iter().map(|resource| resource.raw.uri.as_str()).collect();
+    assert_eq!(
+        uris,
+        vec!["docs://crate/serde/latest", "docs://crate/serde/1.0.200", "docs://item/serde/de/Deserialize"]
+    );
+    assert_eq!(second.resources[2].raw.name, "serde::de::Deserialize");
+    assert_eq!(second.next_cursor, None);
+
+    let error = router.list_cached_docs(Some("page two")).await.unwrap_err();
+    assert_eq!(error.code, ErrorCode::INVALID_PARAMS);
+
+    let read = router.read_cached_doc("docs://item/serde/de/Deserialize").await.unwrap();
+    assert_eq!(
+        read.contents,
+        vec![ResourceContents::TextResourceContents {
+            uri: "docs://item/serde/de/Deserialize".to_string(),
+            mime_type: Some("text/markdown".to_string()),
+            text: "# Deserialize".to_string(),
+        }]
+    );
+    let read = router.read_cached_doc("docs://crate/serde/1.0.200").await.unwrap();
+    assert!(matches!(&read.contents[0], ResourceContents::TextResourceContents { text, .. } if text == "# serde 1.0.200"));
+
+    let error = router.read_cached_doc("docs://crate/tokio/latest").await.unwrap_err();
+    assert_eq!(error.code, ErrorCode::RESOURCE_NOT_FOUND);
+    let error = router.read_cached_doc("file:///etc/passwd").await.unwrap_err();
+    assert_eq!(error.code, ErrorCode::INVALID_PARAMS);
+}
+
+// Text of the single message of a prompt
+fn prompt_text(name: &str, arguments: serde_json::Value) -> String {
+    let arguments = arguments.as_object().cloned().unwrap_or_default();
+    let prompt = get_prompt(name, &arguments).unwrap();
+    assert_eq!(prompt.messages.len(), 1);
+    assert_eq!(prompt.messages[0].role, PromptMessageRole::User);
+    match &prompt.messages[0].content {
+        PromptMessageContent::Text { text } => text.clone(),
+        content => panic!("expected a text message, got {:?}", content),
+    }
+}
+
+#[test]
+async fn test_prompts() {
+    let names: Vec<String> = prompts().into_iter().map(|prompt| prompt.name).collect();
+    assert_eq!(
+        names,
+        vec!["explain-crate", "implement-trait", "error-handling", "migrate-version", "find-alternatives"]
+    );
+    let implement_trait = &prompts()[1];
+    let arguments: Vec<&str> = implement_trait
+        .arguments
+        .iter()
+        .flatten()
+        .map(|argument| argument.name.as_str())
+        .collect();
+    assert_eq!(arguments, vec!["trait_path", "crate_name", "type_name"]);
+
+    let text = prompt_text("explain-crate", serde_json::json!({"crate_name": "serde"}));
+    assert!(text.contains("`lookup_crate` with `{\"crate_name\": \"serde\"}`"));
+
+    let text = prompt_text(
+        "implement-trait",
+        serde_json::json!({"trait_path": "ser::Serialize", "crate_name": "serde", "type_name": "Point"}),
+    );
+    assert!(text.contains("`lookup_trait` with `{\"crate_name\": \"serde\", \"item_path\": \"ser::Serialize\"}`"));
+    assert!(text.contains("the `impl` block for `Point`"));
+
+    let text = prompt_text("error-handling", serde_json::json!({"crate_name": "reqwest"}));
+    assert!(text.contains("errors from the `reqwest` crate"));
+
+    let text = prompt_text(
+        "migrate-version",
+        serde_json::json!({"crate_name": "reqwest", "from_version": "0.11.27", "to_version": "0.12.4"}),
+    );
+    assert!(text.contains("\"old_version\": \"0.11.27\", \"new_version\": \"0.12.4\""));
+
+    let text = prompt_text("find-alternatives", serde_json::json!({"crate_name": "chrono"}));
+    assert!(text.contains("alternatives with `chrono`"));
+
+    for text in [
+        prompt_text("explain-crate", serde_json::json!({"crate_name": "serde"})),
+        prompt_text("migrate-version", serde_json::json!({"crate_name": "a", "from_version": "1", "to_version": "2"})),
+    ] {
+        assert!(!text.contains("{crate_name}") && !text.contains("_version}"));
+    }
+
+    let missing = get_prompt("migrate-version", &serde_json::Map::new()).unwrap_err();
+    assert!(matches!(missing, DocError::InvalidInput(message) if message.contains("`crate_name`")));
+    let unknown = get_prompt("write-my-code", &serde_json::Map::new()).unwrap_err();
+    assert!(matches!(unknown, DocError::InvalidInput(message) if message.contains("unknown prompt")));
+}
+
+#[test]
+#[cfg(not(feature = "rustdoc-json"))]
+async fn test_json_format_needs_feature() {
+    let router = CargoDocRouter::new().with_base_url("http://127.0.0.1:9");
+    let error = router
+        .lookup_crate("serde".to_string(), None, Some(DocFormat::Json), None, None, None)
+        .await
+        .unwrap_err();
+    assert_eq!(error.code, ErrorCode::INVALID_PARAMS);
+    assert!(error.message.contains("rustdoc-json"));
+}
+
+// Rustdoc JSON of a small crate: a module with a documented struct, its field and an inherent
+// method, plus a trait impl whose methods aren't the struct's own
+#[cfg(feature = "rustdoc-json")]
+fn rustdoc_json() -> String {
+    use rustdoc_types::*;
+
+    let item = |id: u32, name: Option<&str>, docs: Option<&str>, inner: ItemEnum| Item {
+        id: Id(id),
+        crate_id: 0,
+        name: name.map(str::to_string),
+        span: None,
+        visibility: Visibility::Public,
+        docs: docs.map(str::to_string),
+        links: Default::default(),
+        attrs: Vec::new(),
+        deprecation: None,
+        stability: None,
+        const_stability: None,
+        inner,
+    };
+    let generics = || Generics {
+        params: Vec::new(),
+        where_predicates: Vec::new(),
+    };
+    let function = || {
+        ItemEnum::Function(Function {
+            sig: FunctionSignature {
+                inputs: Vec::new(),
+                output: None,
+                is_c_variadic: false,
+            },
+            generics: generics(),
+            header: FunctionHeader {
+                is_const: false,
+                is_unsafe: false,
+                is_async: false,
+                abi: Abi::Rust,
+            },
+            has_body: true,
+            default_unstable: None,
+        })
+    };
+    let path = |path: &str, id: u32| Path {
+        path: path.to_string(),
+        id: Id(id),
+        args: None,
+    };
+    let impl_block = |trait_: Option<Path>, items: Vec<Id>| {
+        ItemEnum::Impl(Impl {
+            is_unsafe: false,
+            generics: generics(),
+            provided_trait_methods: Vec::new(),
+            trait_,
+            for_: Type::ResolvedPath(path("Circle", 2)),
+            items,
+            is_negative: false,
+            is_synthetic: false,
+            blanket_impl: None,
+        })
+    };
+    let module = |is_crate: bool, items: Vec<Id>| {
+        ItemEnum::Module(Module {
+            is_crate,
+            items,
+            is_stripped: false,
+        })
+    };
+    let summary = |crate_id: u32, path: &[&str], kind: ItemKind| ItemSummary {
+        crate_id,
+        path: path.iter().map(|segment| segment.to_string()).collect(),
+        kind,
+    };
+
+    let index = [
+        item(0, Some("mycrate"), Some("Geometry helpers.\n\nMore details."), module(true, vec![Id(1), Id(5)])),
+        item(1, Some("shapes"), Some("Shapes."), module(false, vec![Id(2)])),
+        item(
+            2,
+            Some("Circle"),
+            Some("A circle.\n\nCentered on the origin."),
+            ItemEnum::Struct(Struct {
+                kind: StructKind::Plain {
+                    fields: vec![Id(3)],
+                    has_stripped_fields: false,
+                },
+                generics: generics(),
+                impls: vec![Id(4), Id(6)],
+            }),
+        ),
+        item(3, Some("radius"), Some("Radius in meters."), ItemEnum::StructField(Type::Primitive("f64".to_string()))),
+        item(4, None, None, impl_block(None, vec![Id(7)])),
+        item(
+            5,
+            None,
+            None,
+            ItemEnum::Use(Use {
+                source: "shapes::Circle".to_string(),
+                name: "Circle".to_string(),
+                id: Some(Id(2)),
+                is_glob: false,
+            }),
+        ),
+        item(6, None, None, impl_block(Some(path("Clone", 100)), vec![Id(8)])),
+        item(7, Some("area"), Some("Area of the circle."), function()),
+        item(8, Some("clone"), None, function()),
+    ];
+    let paths = [
+        (0, summary(0, &["mycrate"], ItemKind::Module)),
+        (1, summary(0, &["mycrate", "shapes"], ItemKind::Module)),
+        (2, summary(0, &["mycrate", "shapes", "Circle"], ItemKind::Struct)),
+        (100, summary(1, &["core", "clone", "Clone"], ItemKind::Trait)),
+    ];
+    serde_json::to_string(&Crate {
+        root: Id(0),
+        crate_version: Some("0.3.1".to_string()),
+        includes_private: false,
+        index: index.into_iter().map(|item| (item.id, item)).collect(),
+        paths: paths.into_iter().map(|(id, summary)| (Id(id), summary)).collect(),
+        external_crates: Default::default(),
+        target: Target {
+            triple: "x86_64-unknown-linux-gnu".to_string(),
+            target_features: Vec::new(),
+        },
+        format_version: FORMAT_VERSION,
+    })
+    .unwrap()
+}
+
+// `json` gzip-compressed, as docs.rs serves it
+#[cfg(feature = "rustdoc-json")]
+fn gzip(json: &str) -> Vec<u8> {
+    use std::io::Write;
+
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(json.as_bytes()).unwrap();
+    encoder.finish().unwrap()
+}
+
+#[test]
+#[cfg(feature = "rustdoc-json")]
+async fn test_lookup_with_rustdoc_json() {
+    let mut server = mockito::Server::new_async().await;
+    let json = server
+        .mock("GET", format!("/crate/mycrate/latest/json/{}.gz", rustdoc_types::FORMAT_VERSION).as_str())
+        .with_body(gzip(&rustdoc_json()))
+        .expect(2)
+        .create_async()
+        .await;
+    server
+        .mock("GET", format!("/crate/mycrate/0.1.0/json/{}.gz", rustdoc_types::FORMAT_VERSION).as_str())
+        .with_status(404)
+        .create_async()
+        .await;
+    let router = CargoDocRouter::new().with_base_url(server.url());
+
+    let doc = output(router.lookup_crate("mycrate".to_string(), None, Some(DocFormat::Json), None, None, None).await);
+    assert!(doc.starts_with("# Crate `mycrate` 0.3.1\n\nGeometry helpers.\n\nMore details.\n\n"));
+    assert!(doc.contains("## Re-exports\n\n- `Circle`\n"));
+    assert!(doc.contains("## Modules\n\n- `shapes`: Shapes.\n"));
+
+    let doc = output(
+        router
+            .lookup_item_tool("mycrate".to_string(), "mycrate::shapes::Circle".to_string(), None, Some(DocFormat::Json), None, None, None, None, None, None)
+            .await,
+    );
+    assert!(doc.starts_with("# struct `mycrate::shapes::Circle`\n\nA circle.\n\nCentered on the origin.\n\n"));
+    assert!(doc.contains("## Fields\n\n- `radius`: Radius in meters.\n"));
+    assert!(doc.contains("## Methods\n\n- `area`: Area of the circle.\n"));
+    assert!(!doc.contains("clone"));
+
+    // Served from the cache, so docs.rs is asked only once per lookup above
+    let cached = output(
+        router
+            .lookup_item_tool("mycrate".to_string(), "shapes::Circle".to_string(), None, Some(DocFormat::Json), None, None, None, None, None, None)
+            .await,
+    );
+    assert_eq!(cached, doc);
+    json.assert_async().await;
+
+    // A path that only matches the end of the canonical one
+    let doc = output(
+        router
+            .lookup_item_tool("mycrate".to_string(), "Circle".to_string(), None, Some(DocFormat::Json), None, None, None, None, None, None)
+            .await,
+    );
+    assert!(doc.starts_with("# struct `mycrate::shapes::Circle`"));
+
+    let error = router
+        .lookup_item_tool("mycrate".to_string(), "shapes::Square".to_string(), None, Some(DocFormat::Json), None, None, None, None, None, None)
+        .await
+        .unwrap_err();
+    assert_eq!(error.code, ErrorCode::RESOURCE_NOT_FOUND);
+    let error = router
+        .lookup_crate("mycrate".to_string(), Some("0.1.0".to_string()), Some(DocFormat::Json), None, None, None)
+        .await
+        .unwrap_err();
+    assert_eq!(error.code, ErrorCode::RESOURCE_NOT_FOUND);
+}
+
+#[test]
+#[cfg(feature = "rustdoc-json")]
+async fn test_decode_rustdoc_json() {
+    let json = rustdoc_json();
+    let plain = decode_rustdoc_json(json.as_bytes()).unwrap();
+    assert_eq!(plain.format_version, rustdoc_types::FORMAT_VERSION);
+    assert_eq!(plain.index.len(), 9);
+
+    // A gzip header naming the file
+    let mut encoder = flate2::GzBuilder::new()
+        .filename("mycrate.json")
+        .write(Vec::new(), flate2::Compression::default());
+    std::io::Write::write_all(&mut encoder, json.as_bytes()).unwrap();
+    let named = encoder.finish().unwrap();
+    let krate = decode_rustdoc_json(&named).unwrap();
+    assert!(format_crate(&krate, "mycrate").unwrap().starts_with("# Crate `mycrate` 0.3.1"));
+
+    // The schema of another format version, here one without `includes_private`, can't be read
+    let older = json
+        .replace(&format!("\"format_version\":{}", rustdoc_types::FORMAT_VERSION), "\"format_version\":39")
+        .replacen("\"includes_private\":false,", "", 1);
+    let error = decode_rustdoc_json(older.as_bytes()).unwrap_err();
+    assert!(matches!(&error, DocError::Parse(message) if message.contains("format version 39")), "{:?}", error);
+
+    assert!(matches!(decode_rustdoc_json(&[0x1f, 0x8b, 8, 0]), Err(DocError::Parse(_))));
+    assert!(matches!(decode_rustdoc_json(b"<html>"), Err(DocError::Parse(_))));
+}
+
+#[test]
+async fn test_parse_lockfile() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/project/Cargo.lock");
+    let packages = parse_lockfile(&std::fs::read_to_string(path).unwrap());
+    assert_eq!(packages["serde"], vec!["1.0.203"]);
+    assert_eq!(packages["rand"], vec!["0.7.3", "0.8.5"]);
+    assert_eq!(packages.len(), 6);
+}
+
+#[test]
+async fn test_load_project_versions() {
+    let manifest = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/project/Cargo.toml");
+    let mut router = CargoDocRouter::new();
+    router.load_project_versions(&manifest).unwrap();
+
+    let versions = &router.project_versions;
+    assert_eq!(versions["serde"], "1.0.203");
+    assert_eq!(versions["serde_json"], "1.0.117");
+    // Keyed by the crates.io name, not the name the manifest gives the dependency
+    assert_eq!(versions["tokio"], "1.38.0");
+    assert!(!versions.contains_key("runtime"));
+    // Of several locked versions, the one the requirement allows
+    assert_eq!(versions["rand"], "0.8.5");
+    assert_eq!(versions["mockito"], "1.2.0");
+    assert!(!versions.contains_key("fixture-project"));
+
+    let missing = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/missing/Cargo.toml");
+    assert!(router.load_project_versions(&missing).is_err());
+}
+
+#[test]
+async fn test_lookup_item_uses_project_versions() {
+    let mut server = mockito::Server::new_async().await;
+    let locked = server
+        .mock("GET", "/my_widgets/1.4.2/my_widgets/struct.Widget.html")
+        .with_body("<h1>Struct Widget</h1><p>Locked widget docs.</p>")
+        .create_async()
+        .await;
+    let latest = server
+        .mock("GET", "/my_widgets/latest/my_widgets/struct.Widget.html")
+        .with_body("<h1>Struct Widget</h1><p>Latest widget docs.</p>")
+        .create_async()
+        .await;
+    server
+        .mock("GET", mockito::Matcher::Any)
+        .with_status(404)
+        .create_async()
+        .await;
+    let mut router = CargoDocRouter::new().with_base_url(server.url());
+    // Cargo.lock spells the name with a hyphen
+    router.project_versions.insert("my-widgets".to_string(), "1.4.2".to_string());
+
+    let doc = output(router.lookup_item_tool("my_widgets".to_string(), "Widget".to_string(), None, None, None, None, None, None, None, None).await);
+    assert!(doc.contains("Locked widget docs."));
+    locked.assert_async().await;
+
+    // An explicit version wins over the locked one
+    let error = router
+        .lookup_item_tool("my_widgets".to_string(), "Widget".to_string(), Some("9.9.9".to_string()), None, None, None, None, None, None, None)
+        .await
+        .unwrap_err();
+    assert_eq!(error.code, ErrorCode::RESOURCE_NOT_FOUND);
+    latest.expect(0).assert_async().await;
+}
+
+#[test]
+async fn test_stdlib_crates_use_doc_rust_lang_org() {
+    let mut docs_rs = mockito::Server::new_async().await;
+    let docs_rs_requests = docs_rs
+        .mock("GET", mockito::Matcher::Any)
+        .expect(0)
+        .create_async()
+        .await;
+    let mut stdlib = mockito::Server::new_async().await;
+    let pages = [
+        ("std", "fs::File", "/std/fs/struct.File.html"),
+        ("core", "mem::swap", "/core/mem/fn.swap.html"),
+        ("alloc", "vec::Vec", "/alloc/vec/struct.Vec.html"),
+        ("test", "black_box", "/test/fn.black_box.html"),
+        ("proc_macro", "TokenStream", "/proc_macro/struct.TokenStream.html"),
+    ];
+    for (index, (_, _, page)) in pages.iter().enumerate() {
+        stdlib
+            .mock("GET", *page)
+            .with_body(format!("<p>Page {}</p>", index))
+            .create_async()
+            .await;
+    }
+    stdlib
+        .mock("GET", "/1.75.0/std/fs/struct.File.html")
+        .with_body("<p>File of Rust 1.75.0</p>")
+        .create_async()
+        .await;
+    stdlib
+        .mock("GET", "/core/index.html")
+        .with_body("<h1>Crate core</h1>")
+        .create_async()
+        .await;
+    stdlib
+        .mock("GET", mockito::Matcher::Any)
+        .with_status(404)
+        .create_async()
+        .await;
+    let router = CargoDocRouter::builder()
+        .with_base_url(docs_rs.url())
+        .with_stdlib_base_url(stdlib.url())
+        .build()
+        .unwrap();
+
+    for (index, (crate_name, item_path, _)) in pages.iter().enumerate() {
+        let doc = output(
+            router
+                .lookup_item_tool(crate_name.to_string(), item_path.to_string(), None, None, None, None, None, None, None, None)
+                .await,
+        );
+        assert!(doc.contains(&format!("Page {}", index)), "{}", doc);
+    }
+
+    let doc = output(
+        router
+            .lookup_item_tool("std".to_string(), "fs::File".to_string(), Some("1.75.0".to_string()), None, None, None, None, None, None, None)
+            .await,
+    );
+    assert!(doc.contains("File of Rust 1.75.0"));
+
+    let doc = output(router.lookup_crate("core".to_string(), None, None, None, None, None).await);
+    assert!(doc.contains("Crate core"));
+
+    let error = router
+        .lookup_item_tool("std".to_string(), "fs::Missing".to_string(), None, None, None, None, None, None, None, None)
+        .await
+        .unwrap_err();
+    assert!(error.message.contains(&format!("{}/std/fs/struct.Missing.html", stdlib.url())));
+    docs_rs_requests.assert_async().await;
+}
+
+#[test]
+async fn test_item_doc_from_page() {
+    let url = "https://docs.rs/reqwest/0.12.4/reqwest/struct.Client.html";
+    let path = item_path("reqwest", "Client");
+    let doc = ItemDoc::from_page("reqwest", &path, Some("0.12.4"), url, &docs_rs_struct_page());
+
+    assert_eq!(doc.name, "Client");
+    assert_eq!(doc.kind, ItemKind::Struct);
+    assert_eq!(doc.module_path, "");
+    assert_eq!(doc.path(), "reqwest::Client");
+    assert_eq!(doc.version.as_deref(), Some("0.12.4"));
+    assert_eq!(doc.summary, "An asynchronous Client to make Requests with.");
+    assert_eq!(
+        doc.source_url.as_deref(),
+        Some("https://docs.rs/reqwest/0.12.4/src/reqwest/async_impl/client.rs.html#72-74")
+    );
+    assert_eq!(doc.since, None);
+    let titles: Vec<&str> = doc.sections.keys().map(String::as_str).collect();
+    assert_eq!(titles[..2], [DESCRIPTION_SECTION, "Implementations"]);
+    assert!(doc.sections[DESCRIPTION_SECTION].contains("pub struct Client"));
+    assert!(doc.sections["Implementations"].contains("Convenience method to make a `GET` request"));
+
+    let markdown = doc.to_markdown();
+    assert!(markdown.starts_with("# Struct `reqwest::Client`\n\n"));
+    assert!(markdown.contains("\n## Implementations\n\n"));
+    assert!(!markdown.contains("## Description"));
+}
+
+#[test]
+async fn test_item_doc_of_std_function() {
+    let html = r##"<html><body><main><div id="main-content">
+        <div class="main-heading"><h1>Function <a href="../index.html">std</a>::<a href="index.html">mem</a>::<span class="fn">swap</span></h1>
+        <span class="out-of-band"><span class="since" title="Stable since Rust version 1.0.0">1.0.0</span> · <a class="src" href="../../src/core/mem/mod.rs.html#727">Source</a></span></div>
+        <pre class="rust item-decl"><code>pub fn swap&lt;T&gt;(x: &amp;mut T, y: &amp;mut T)</code></pre>
+        <details class="toggle top-doc" open><summary>Expand description</summary><div class="docblock">
+        <p>Swaps the values at two mutable locations, without deinitializing either one.</p>
+        <h2 id="examples"><a class="doc-anchor" href="#examples">§</a>Examples</h2>
+        <pre class="rust rust-example-rendered"><code>let mut x = 5;
+let mut y = 42;
+std::mem::swap(&amp;mut x, &amp;mut y);</code></pre>
+        </div></details></div></main></body></html>"##;
+    let url = "https://doc.rust-lang.org/std/mem/fn.swap.html";
+    let doc = ItemDoc::from_page("std", &item_path("std", "mem::swap"), None, url, html);
+
+    assert_eq!(doc.kind, ItemKind::Fn);
+    assert_eq!(doc.module_path, "mem");
+    assert_eq!(doc.since.as_deref(), Some("1.0.0"));
+    assert_eq!(doc.source_url.as_deref(), Some("https://doc.rust-lang.org/src/core/mem/mod.rs.html#727"));
+    assert_eq!(doc.summary, "Swaps the values at two mutable locations, without deinitializing either one.");
+    assert!(doc.sections.contains_key("Examples"));
+    assert_eq!(doc.examples.len(), 1);
+    assert!(doc.examples[0].code.contains("std::mem::swap(&mut x, &mut y);"));
+
+    let markdown = doc.to_markdown();
+    assert!(markdown.starts_with("# Function `std::mem::swap`\n\n_Stable since Rust 1.0.0_\n\n"));
+    assert!(markdown.contains("## Examples"));
+}
+
+// A standard library item page with `item_info` between its declaration and documentation,
+// and an implementation with a deprecated `call_once` method
+fn std_item_info_page(heading: &str, declaration: &str, item_info: &str) -> String {
+    format!(
+        r##"<html><body><main><div id="main-content">
+        <div class="main-heading"><h1>{}</h1></div>
+        <pre class="rust item-decl"><code>{}</code></pre>
+        {}
+        <details class="toggle top-doc" open><summary>Expand description</summary><div class="docblock"><p>Old API.</p></div></details>
+        <div id="implementations-list"><details class="toggle implementors-toggle" open><summary><section id="impl-Once" class="impl"><h3 class="code-header">impl Once</h3></section></summary>
+        <div class="impl-items"><details class="toggle method-toggle" open><summary><section id="method.call_once" class="method"><h4 class="code-header">pub fn call_once(&amp;self)</h4></section></summary>
+        <span class="item-info"><div class="stab deprecated"><span class="emoji">👎</span><span>Deprecated since 1.2.0: method notice</span></div></span><div class="docblock"><p>Calls once.</p></div></details></div></details></div>
+        </div></main></body></html>"##,
+        heading, declaration, item_info
+    )
+}
+
+#[test]
+async fn test_deprecated_item_doc() {
+    let once_init = std_item_info_page(
+        "Constant std::sync::ONCE_INIT",
+        "pub const ONCE_INIT: Once;",
+        r#"<span class="item-info"><div class="stab deprecated"><span class="emoji">👎</span><span>Deprecated since 1.38.0: the <code>Once::new()</code> function is now preferred</span></div></span>"#,
+    );
+    assert_eq!(
+        parse_deprecation(&once_init).as_deref(),
+        Some("since 1.38.0: the `Once::new()` function is now preferred")
+    );
+    let url = "https://doc.rust-lang.org/std/sync/constant.ONCE_INIT.html";
+    let doc = ItemDoc::from_page("std", &item_path("std", "sync::ONCE_INIT"), None, url, &once_init);
+    assert!(doc.to_markdown().starts_with(
+        "> ⚠️ **Deprecated**: since 1.38.0: the `Once::new()` function is now preferred\n\n# Constant `std::sync::ONCE_INIT`\n\n"
+    ));
+
+    let uninitialized = std_item_info_page(
+        "Function std::mem::uninitialized",
+        "pub unsafe fn uninitialized&lt;T&gt;() -&gt; T",
+        r#"<div class="item-info"><div class="stab deprecated">Deprecated since 1.39.0: use <code>mem::MaybeUninit</code> instead</div></div>"#,
+    );
+    assert_eq!(
+        parse_deprecation(&uninitialized).as_deref(),
+        Some("since 1.39.0: use `mem::MaybeUninit` instead")
+    );
+    let bare = std_item_info_page(
+        "Function std::mem::old",
+        "pub fn old()",
+        r#"<span class="item-info"><span class="stab deprecated">Deprecated</span></span>"#,
+    );
+    assert_eq!(parse_deprecation(&bare).as_deref(), Some(""));
+    let url = "https://doc.rust-lang.org/std/mem/fn.old.html";
+    let doc = ItemDoc::from_page("std", &item_path("std", "mem::old"), None, url, &bare);
+    assert!(doc.to_markdown().starts_with("> ⚠️ **Deprecated**\n\n# Function"));
+
+    // Deprecated methods don't make their type deprecated, but are found when looked up
+    let once = std_item_info_page("Struct std::sync::Once", "pub struct Once { /* private fields */ }", "");
+    assert_eq!(parse_deprecation(&once), None);
+    let url = "https://doc.rust-lang.org/std/sync/struct.Once.html";
+    assert!(ItemDoc::from_page("std", &item_path("std", "sync::Once"), None, url, &once)
+        .to_markdown()
+        .starts_with("# Struct"));
+    let (_, method) = extract_method(&once, "call_once").unwrap();
+    assert_eq!(parse_deprecation(&method).as_deref(), Some("since 1.2.0: method notice"));
+}
+
+#[test]
+async fn test_item_doc_portability() {
+    let note = |item_info: &str| {
+        let html = std_item_info_page("Trait std::os::unix::fs::MetadataExt", "pub trait MetadataExt {}", item_info);
+        parse_portability(&html)
+    };
+    assert_eq!(note(r#"<span class="item-info"><div class="stab portability">Available on <strong>Unix</strong> only.</div></span>"#).as_deref(), Some("Unix"));
+    assert_eq!(
+        note(r#"<span class="item-info"><div class="stab portability">Available on <strong>crate feature <code>fs</code></strong> only.</div></span>"#).as_deref(),
+        Some("crate feature `fs`")
+    );
+    assert_eq!(note(""), None);
+
+    let metadata_ext = std_item_info_page(
+        "Trait std::os::unix::fs::MetadataExt",
+        "pub trait MetadataExt {}",
+        r#"<span class="item-info"><div class="stab portability">Available on <strong>Unix</strong> only.</div></span>"#,
+    );
+    let url = "https://doc.rust-lang.org/std/os/unix/fs/trait.MetadataExt.html";
+    let doc = ItemDoc::from_page("std", &item_path("std", "os::unix::fs::MetadataExt"), None, url, &metadata_ext);
+    assert!(doc.to_markdown().starts_with("# Trait `std::os::unix::fs::MetadataExt`\n\n_Available on: Unix only_\n\n"));
+
+    let mut doc = doc;
+    doc.portability = Some("crate feature `fs`".to_string());
+    assert!(doc.to_markdown().contains("\n\n_Requires feature: `fs`_\n\n"));
+    doc.portability = Some("crate features `rt` and `macros`".to_string());
+    assert!(doc.to_markdown().contains("\n\n_Requires features: `rt` and `macros`_\n\n"));
+    doc.portability = Some("crate feature `net` and Windows".to_string());
+    assert!(doc.to_markdown().contains("\n\n_Available on: crate feature `net` and Windows only_\n\n"));
+}
+
+#[test]
+async fn test_lookup_item_doc() {
+    let mut server = mockito::Server::new_async().await;
+    let page = server
+        .mock("GET", "/mycrate/latest/mycrate/io/trait.Read.html")
+        .with_body("<html><body><h1>Trait Read</h1><p>Reads bytes.</p></body></html>")
+        .expect(1)
+        .create_async()
+        .await;
+    server
+        .mock("GET", mockito::Matcher::Any)
+        .with_status(404)
+        .create_async()
+        .await;
+    let router = CargoDocRouter::new().with_base_url(server.url());
+
+    let doc = router
+        .lookup_item_doc(crate_name("mycrate"), item_path("mycrate", "mycrate::io::Read"), None, None)
+        .await
+        .unwrap();
+    assert_eq!(doc.kind, ItemKind::Trait);
+    assert_eq!(doc.path(), "mycrate::io::Read");
+    assert_eq!(doc.summary, "Reads bytes.");
+
+    // Served from the cache, with the markdown also cached under the item's key
+    let cached = router
+        .lookup_item_doc(crate_name("mycrate"), item_path("mycrate", "io::Read"), None, None)
+        .await
+        .unwrap();
+    assert_eq!(cached, doc);
+    assert_eq!(router.cache.get("doc:mycrate:io::Read").await, Some(doc.to_markdown()));
+    let tool_output = output(
+        router
+            .lookup_item_tool("mycrate".to_string(), "io::Read".to_string(), None, None, None, None, None, None, None, None)
+            .await,
+    );
+    assert_eq!(tool_output, doc.to_markdown());
+    page.assert_async().await;
+}
+
+#[test]
+async fn test_crate_name_parse() {
+    for name in ["serde", "serde_json", "tokio-util", "proc_macro", "7z", "a", &"x".repeat(64)] {
+        assert_eq!(CrateName::parse(name).unwrap().as_str(), name);
+    }
+    for (name, reason) in [
+        ("", "empty"),
+        (&"x".repeat(65), "longer than 64"),
+        ("Serde", "lowercase"),
+        ("serde json", "lowercase"),
+        ("serde::de", "lowercase"),
+        ("-serde", "start with"),
+        ("_serde", "start with"),
+        ("tokio--util", "consecutive hyphens"),
+    ] {
+        let error = CrateName::parse(name).unwrap_err();
+        assert!(error.to_string().contains(reason), "{:?}: {}", name, error);
+    }
+
+    let parsed: CrateName = serde_json::from_str("\"tokio\"").unwrap();
+    assert_eq!(serde_json::to_string(&parsed).unwrap(), "\"tokio\"");
+    assert!(serde_json::from_str::<CrateName>("\"Tokio\"").is_err());
+}
+
+#[test]
+async fn test_invalid_crate_name_sends_no_request() {
+    let mut server = mockito::Server::new_async().await;
+    let requests = server
+        .mock("GET", mockito::Matcher::Any)
+        .expect(0)
+        .create_async()
+        .await;
+    let router = CargoDocRouter::new()
+        .with_base_url(server.url())
+        .with_crates_io_base_url(server.url());
+
+    let error = router.lookup_crate("my crate".to_string(), None, None, None, None, None).await.unwrap_err();
+    assert_eq!(error.code, ErrorCode::INVALID_PARAMS);
+    assert!(error.message.contains("invalid crate name `my crate`"));
+    let error = router
+        .lookup_item_tool("".to_string(), "Client".to_string(), None, None, None, None, None, None, None, None)
+        .await
+        .unwrap_err();
+    assert_eq!(error.code, ErrorCode::INVALID_PARAMS);
+    let error = router.get_crate_metadata("Serde".to_string()).await.unwrap_err();
+    assert_eq!(error.code, ErrorCode::INVALID_PARAMS);
+    let error = router.clear_cache(Some("a--b".to_string()), None).await.unwrap_err();
+    assert_eq!(error.code, ErrorCode::INVALID_PARAMS);
+    requests.assert_async().await;
+}
+
+#[test]
+async fn test_item_path_parse() {
+    for raw in ["tokio::io::AsyncRead", "io::AsyncRead", "tokio/io/AsyncRead", "io/AsyncRead", "::io::AsyncRead", "crate::io::AsyncRead"] {
+        let path = ItemPath::parse("tokio", raw).unwrap();
+        assert_eq!(path.to_string(), "io::AsyncRead", "{}", raw);
+        assert_eq!(path.module_path(), "io");
+        assert_eq!(path.item_name(), "AsyncRead");
+    }
+
+    let path = ItemPath::parse("tokio", "AsyncRead").unwrap();
+    assert_eq!(path.module_path(), "");
+    assert_eq!(path.item_name(), "AsyncRead");
+    let path = ItemPath::parse("tokio-util", "tokio_util::io::sync::SyncIoBridge").unwrap();
+    assert_eq!(path.segments(), ["io", "sync", "SyncIoBridge"]);
+    assert_eq!(path.module_path(), "io/sync");
+    // An item named like its crate
+    assert_eq!(ItemPath::parse("bytes", "bytes").unwrap().item_name(), "bytes");
+
+    for raw in ["", "::", "io::", "io::::AsyncRead", "io//AsyncRead", "io:AsyncRead", "io::Async Read"] {
+        let error = ItemPath::parse("tokio", raw).unwrap_err();
+        assert!(error.to_string().starts_with(&format!("invalid item path `{}`", raw)), "{}", error);
+    }
+}
+
+#[test]
+async fn test_lookup_trait_with_slash_separated_path() {
+    let mut server = mockito::Server::new_async().await;
+    let page = server
+        .mock("GET", "/mycrate/latest/mycrate/io/trait.Read.html")
+        .with_body("<h1>Trait Read</h1><p>Reads bytes.</p>")
+        .create_async()
+        .await;
+    let router = CargoDocRouter::new().with_base_url(server.url());
+
+    let result = router
+        .lookup_trait("mycrate".to_string(), "mycrate/io/Read".to_string(), None)
+        .await;
+    assert!(output(result).starts_with("# Trait `mycrate::io::Read`"));
+    page.assert_async().await;
+
+    let error = router
+        .lookup_trait("mycrate".to_string(), "io::::Read".to_string(), None)
+        .await
+        .unwrap_err();
+    assert_eq!(error.code, ErrorCode::INVALID_PARAMS);
+}
+
+#[test]
+async fn test_crate_version_parse() {
+    let version = CrateVersion::parse("1.0.200").unwrap();
+    assert_eq!(version.to_string(), "1.0.200");
+    assert!(!version.is_prerelease());
+
+    let version = CrateVersion::parse("0.2.0-alpha.1+build.5").unwrap();
+    assert_eq!(version.to_string(), "0.2.0-alpha.1+build.5");
+    assert!(version.is_prerelease());
+    assert!(CrateVersion::parse("1.0.0-rc.1").unwrap() < CrateVersion::parse("1.0.0").unwrap());
+
+    for invalid in ["foo", "1.2.3.4.5", "1.0", "^1.0.0", "latest", "01.0.0", ""] {
+        assert!(CrateVersion::parse(invalid).is_err(), "{}", invalid);
+    }
+}
+
+#[test]
+async fn test_invalid_version_is_rejected() {
+    let mut server = mockito::Server::new_async().await;
+    let requests = server
+        .mock("GET", mockito::Matcher::Any)
+        .expect(0)
+        .create_async()
+        .await;
+    let router = CargoDocRouter::new()
+        .with_base_url(server.url())
+        .with_crates_io_base_url(server.url());
+
+    let error = router
+        .lookup_crate("serde".to_string(), Some("foo".to_string()), None, None, None, None)
+        .await
+        .unwrap_err();
+    assert_eq!(error.code, ErrorCode::INVALID_PARAMS);
+    assert!(error.message.starts_with("Invalid version: `foo`"), "{}", error.message);
+    let error = router
+        .lookup_item_tool("serde".to_string(), "Serialize".to_string(), Some("1.2.3.4.5".to_string()), None, None, None, None, None, None, None)
+        .await
+        .unwrap_err();
+    assert_eq!(error.code, ErrorCode::INVALID_PARAMS);
+    let error = router
+        .compare_crate_versions("serde".to_string(), "1.0.0.0".to_string(), "1.0.200".to_string())
+        .await
+        .unwrap_err();
+    assert_eq!(error.code, ErrorCode::INVALID_PARAMS);
+    requests.assert_async().await;
+}
+
+#[test]
+async fn test_latest_and_prerelease_versions() {
+    let mut server = mockito::Server::new_async().await;
+    let latest = server
+        .mock("GET", "/crate/mycrate/")
+        .with_body("<p>Latest docs</p>")
+        .create_async()
+        .await;
+    let prerelease = server
+        .mock("GET", "/crate/mycrate/2.0.0-beta.1/")
+        .with_body("<p>Beta docs</p>")
+        .create_async()
+        .await;
+    let router = CargoDocRouter::new().with_base_url(server.url());
+
+    let doc = output(router.lookup_crate("mycrate".to_string(), Some("latest".to_string()), None, None, None, None).await);
+    assert!(doc.contains("Latest docs"));
+    let doc = output(router.lookup_crate("mycrate".to_string(), Some("2.0.0-beta.1".to_string()), None, None, None, None).await);
+    assert!(doc.contains("Beta docs"));
+    latest.assert_async().await;
+    prerelease.assert_async().await;
+}
+
+#[test]
+async fn test_normalize_version() {
+    for latest in [None, Some("latest"), Some(" * ")] {
+        assert_eq!(normalize_version(latest), None, "{:?}", latest);
+    }
+    assert_eq!(normalize_version(Some("1.0.200")), Some("1.0.200".to_string()));
+    assert_eq!(normalize_version(Some(" 2.0.0-beta.1 ")), Some("2.0.0-beta.1".to_string()));
+    assert_eq!(normalize_version(Some("=1.2.3")), Some("1.2.3".to_string()));
+    // Requirements are kept for docs.rs to resolve, in one spelling
+    for (req, normalized) in [("0.11", "^0.11"), ("^1.2", "^1.2"), ("~1.0.3", "~1.0.3"), ("=1.2", "=1.2"), (">=0.11, <0.13", ">=0.11,<0.13")] {
+        assert_eq!(normalize_version(Some(req)), Some(normalized.to_string()), "{}", req);
+    }
+    // Neither a version nor a requirement, left for `CrateVersion::parse` to reject
+    assert_eq!(normalize_version(Some("foo")), Some("foo".to_string()));
+}
+
+#[test]
+async fn test_latest_version_shares_cache_entry() {
+    let mut server = mockito::Server::new_async().await;
+    let page = server
+        .mock("GET", "/crate/serde/")
+        .with_body("<p>Serde docs</p>")
+        .expect(1)
+        .create_async()
+        .await;
+    let router = CargoDocRouter::new().with_base_url(server.url());
+
+    let first = output(router.lookup_crate("serde".to_string(), None, None, None, None, None).await);
+    for version in ["latest", "*"] {
+        let doc = output(router.lookup_crate("serde".to_string(), Some(version.to_string()), None, None, None, None).await);
+        assert_eq!(doc, first);
+    }
+    assert!(first.contains("Serde docs"));
+    assert!(router.cache.get("doc:serde").await.is_some());
+    page.assert_async().await;
+}
+
+#[test]
+async fn test_version_requirement() {
+    let mut docs = mockito::Server::new_async().await;
+    let mut crates_io = mockito::Server::new_async().await;
+    let page = docs
+        .mock("GET", "/crate/serde/^0.9/")
+        .with_body("<p>Serde 0.9 docs</p>")
+        .expect(1)
+        .create_async()
+        .await;
+    crates_io
+        .mock("GET", "/api/v1/crates/serde")
+        .with_body(fixture("crates_io_crate.json"))
+        .create_async()
+        .await;
+    let msrv = crates_io
+        .mock("GET", "/api/v1/crates/serde/1.0.219")
+        .with_body(r#"{"version": {"num": "1.0.219", "rust_version": "1.31"}}"#)
+        .create_async()
+        .await;
+    let router = CargoDocRouter::new()
+        .with_base_url(docs.url())
+        .with_crates_io_base_url(crates_io.url());
+
+    // docs.rs resolves the requirement, which keys the cache in one spelling
+    let doc = output(router.lookup_crate("serde".to_string(), Some("0.9".to_string()), None, None, None, None).await);
+    assert!(doc.contains("Serde 0.9 docs"));
+    let again = output(router.lookup_crate("serde".to_string(), Some("^0.9".to_string()), None, None, None, None).await);
+    assert_eq!(again, doc);
+    assert!(router.cache.contains("doc:serde:^0.9").await);
+    assert_eq!(resource_uri("doc:serde:^0.9"), Some("docs://crate/serde/^0.9".to_string()));
+    page.assert_async().await;
+
+    // crates.io tools resolve it to the newest release matching it
+    output(router.get_msrv("serde".to_string(), Some("1".to_string())).await);
+    assert!(router.cache.contains("msrv:serde:1.0.219").await);
+    msrv.assert_async().await;
+    let error = router.get_msrv("serde".to_string(), Some("^2".to_string())).await.unwrap_err();
+    assert_eq!(error.code, ErrorCode::INVALID_PARAMS);
+    assert!(error.message.contains("no published version of serde matches `^2`"), "{}", error.message);
+}
+
+#[test]
+async fn test_search_crates_by_content_type() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/crates_io_search.json");
+    let json = std::fs::read_to_string(path).unwrap();
+    let mut server = mockito::Server::new_async().await;
+    let router = CargoDocRouter::new().with_crates_io_base_url(server.url());
+    let search = |query: &str| {
+        router.search_crates(query.to_string(), Some(10), None, None, None, None)
+    };
+
+    for (query, content_type) in [
+        ("json", "application/json"),
+        ("charset", "application/json; charset=utf-8"),
+        ("text", "text/json"),
+    ] {
+        let mock = server
+            .mock("GET", "/api/v1/crates")
+            .match_query(mockito::Matcher::UrlEncoded("q".into(), query.into()))
+            .with_header("content-type", content_type)
+            .with_body(&json)
+            .create_async()
+            .await;
+        let result = output(search(query).await);
+        assert!(result.starts_with(&format!("# Crate search results for `{}`", query)), "{}", content_type);
+        mock.remove_async().await;
+    }
+
+    // Any other content is returned as markdown
+    let mock = server
+        .mock("GET", "/api/v1/crates")
+        .match_query(mockito::Matcher::UrlEncoded("q".into(), "html".into()))
+        .with_header("content-type", "text/html; charset=utf-8")
+        .with_body("<html><body><h1>Search results</h1><p>{\"crates\": []}</p></body></html>")
+        .create_async()
+        .await;
+    let result = output(search("html").await);
+    assert!(result.contains("Search results"));
+    assert!(router.search_crates_structured("html", 10).await.is_err());
+    mock.remove_async().await;
+
+    // JSON error objects explain why crates.io rejected the request
+    server
+        .mock("GET", "/api/v1/crates")
+        .match_query(mockito::Matcher::UrlEncoded("q".into(), "bad".into()))
+        .with_status(400)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"errors": [{"detail": "invalid category"}]}"#)
+        .create_async()
+        .await;
+    let error = search("bad").await.unwrap_err();
+    assert_eq!(error.code, ErrorCode::INVALID_PARAMS);
+    assert!(error.message.contains("crates.io rejected the search: invalid category"), "{}", error.message);
+
+    server
+        .mock("GET", "/api/v1/crates")
+        .match_query(mockito::Matcher::UrlEncoded("q".into(), "down".into()))
+        .with_status(500)
+        .with_header("content-type", "text/html")
+        .with_body("<h1>Internal Server Error</h1>")
+        .create_async()
+        .await;
+    let error = search("down").await.unwrap_err();
+    assert_eq!(error.code, ErrorCode::INTERNAL_ERROR);
+    assert!(error.message.contains("failed with status 500"));
+}
+
+#[test]
+async fn test_is_json_content_type() {
+    assert!(search::is_json_content_type("application/json"));
+    assert!(search::is_json_content_type("Application/JSON; charset=utf-8"));
+    assert!(search::is_json_content_type("text/json"));
+    assert!(!search::is_json_content_type("text/html; charset=utf-8"));
+    assert!(!search::is_json_content_type("application/jsonp"));
+    assert!(!search::is_json_content_type(""));
+}
+
+// Collects what a `tracing_subscriber::fmt` subscriber writes
+#[derive(Clone, Default)]
+struct LogBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl std::io::Write for LogBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl LogBuffer {
+    fn contents(&self) -> String {
+        String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+    }
+}
+
+#[test]
+async fn test_tool_tracing_events() {
+    let logs = LogBuffer::default();
+    let writer = logs.clone();
+    let subscriber = tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::DEBUG)
+        .with_ansi(false)
+        .with_writer(move || writer.clone())
+        .finish();
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    let mut server = mockito::Server::new_async().await;
+    server
+        .mock("GET", "/crate/serde/")
+        .with_body("<p>Serde docs</p>")
+        .create_async()
+        .await;
+    server
+        .mock("GET", mockito::Matcher::Any)
+        .with_status(404)
+        .create_async()
+        .await;
+    let router = CargoDocRouter::new().with_base_url(server.url());
+
+    router.lookup_crate("serde".to_string(), None, None, None, None, None).await.unwrap();
+    let first = logs.contents();
+    assert!(first.contains("lookup_crate{"), "{}", first);
+    assert!(first.contains("cache miss"), "{}", first);
+    assert!(first.contains(&format!("status=200 OK url={}/crate/serde/", server.url())), "{}", first);
+
+    router.lookup_crate("serde".to_string(), None, None, None, None, None).await.unwrap();
+    let second = logs.contents()[first.len()..].to_string();
+    assert!(second.contains("cache_hit=true"), "{}", second);
+    assert!(second.contains("cache hit"), "{}", second);
+    assert!(!second.contains("HTTP request"), "{}", second);
+
+    router
+        .lookup_item_tool("serde".to_string(), "Missing".to_string(), None, None, None, None, None, None, None, None)
+        .await
+        .unwrap_err();
+    let third = logs.contents()[first.len() + second.len()..].to_string();
+    assert!(third.contains("status=404 Not Found"), "{}", third);
+    assert!(third.contains("ERROR") && third.contains("lookup_item_tool{"), "{}", third);
+}
+
+// A trimmed-down doc.rust-lang.org page for `std::string::String`
+const STD_STRING_PAGE: &str = r##"<html><body><main><section id="main-content" class="content"><div class="main-heading"><h1>Struct <span class="struct">String</span></h1></div>
+<pre class="rust item-decl"><code>pub struct String { /* private fields */ }</code></pre>
+<details class="toggle top-doc" open><summary>Expand description</summary><div class="docblock"><p>A UTF-8–encoded, growable string.</p>
+<h2 id="examples"><a class="doc-anchor" href="#examples">§</a>Examples</h2>
+<p>You can create a <code>String</code> from a literal string with <code>String::from</code>:</p>
+<div class="example-wrap"><pre class="rust rust-example-rendered"><code>let hello = String::from("Hello, world!");</code></pre></div>
+</div></details>
+<h2 id="implementations" class="section-header">Implementations<a href="#implementations" class="anchor">§</a></h2>
+<div id="implementations-list"><details class="toggle implementors-toggle" open><summary><section id="impl-String" class="impl"><h3 class="code-header">impl <a class="struct" href="struct.String.html">String</a></h3></section></summary><div class="impl-items">
+<details class="toggle method-toggle" open><summary><section id="method.new" class="method"><h4 class="code-header">pub const fn <a href="#method.new" class="fn">new</a>() -&gt; <a class="struct" href="struct.String.html">String</a></h4></section></summary><div class="docblock"><p>Creates a new empty <code>String</code>.</p></div></details>
+<details class="toggle method-toggle" open><summary><section id="method.push_str" class="method"><h4 class="code-header">pub fn <a href="#method.push_str" class="fn">push_str</a>(&amp;mut self, string: &amp;<a class="primitive" href="../primitive.str.html">str</a>)</h4></section></summary><div class="docblock"><p>Appends a given string slice onto the end of this <code>String</code>.</p></div></details>
+</div></details></div>
+<h2 id="trait-implementations" class="section-header">Trait Implementations<a href="#trait-implementations" class="anchor">§</a></h2>
+<div id="trait-implementations-list"><details class="toggle implementors-toggle" open><summary><section id="impl-Clone-for-String" class="impl"><h3 class="code-header">impl <a class="trait" href="../clone/trait.Clone.html">Clone</a> for <a class="struct" href="struct.String.html">String</a></h3></section></summary></details></div>
+</section></main></body></html>"##;
+
+#[test]
+async fn test_lookup_item_section() {
+    let mut server = mockito::Server::new_async().await;
+    server
+        .mock("GET", "/std/string/struct.String.html")
+        .with_body(STD_STRING_PAGE)
+        .create_async()
+        .await;
+    server
+        .mock("GET", mockito::Matcher::Any)
+        .with_status(404)
+        .create_async()
+        .await;
+    let router = CargoDocRouter::new().with_stdlib_base_url(server.url());
+    let lookup = |section: &str| {
+        router.lookup_item_tool(
+            "std".to_string(),
+            "string::String".to_string(),
+            None,
+            None,
+            Some(section.to_string()),
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+    };
+
+    let examples = output(lookup("examples").await);
+    assert!(examples.contains("let hello = String::from(\"Hello, world!\");"), "{}", examples);
+    assert!(!examples.contains("pub const fn"));
+
+    let methods = output(lookup("Methods").await);
+    assert!(methods.contains("pub const fn [new]("), "{}", methods);
+    assert!(methods.contains("push"));
+    assert!(!methods.contains("Hello, world!"));
+    assert!(!methods.contains("Clone"));
+
+    let traits = output(lookup("Trait Implementations").await);
+    assert!(traits.contains("Clone"));
+
+    let error = lookup("Fields").await.unwrap_err();
+    assert_eq!(error.code, ErrorCode::INVALID_PARAMS);
+    assert!(error.message.contains("No section `Fields`"), "{}", error.message);
+    assert!(error.message.contains("`Examples`, `Implementations`"), "{}", error.message);
+    assert!(error.message.contains("`Trait Implementations`"), "{}", error.message);
+}
+
+#[test]
+async fn test_headings() {
+    let markdown = "# Struct `String`\n\nText\n\n## [§](#examples)Examples\n\n```rust\n# hidden\n```\n\nSetext\n---\n\n## Examples\n";
+    assert_eq!(headings(markdown), ["Struct `String`", "Examples", "Setext"]);
+}
+
+#[test]
+async fn test_max_length_truncates_output() {
+    let paragraphs = (0..200).fold(String::new(), |mut html, i| {
+        let _ = write!(html, "<p>Paragraph {} of the crate documentation.</p>", i);
+        html
+    });
+    let mut server = mockito::Server::new_async().await;
+    server
+        .mock("GET", "/crate/mycrate/")
+        .with_body(format!("<section id=\"main-content\">{}</section>", paragraphs))
+        .create_async()
+        .await;
+    server
+        .mock("GET", "/std/string/struct.String.html")
+        .with_body(STD_STRING_PAGE)
+        .create_async()
+        .await;
+    server
+        .mock("GET", mockito::Matcher::Any)
+        .with_status(404)
+        .create_async()
+        .await;
+    let router = CargoDocRouter::new()
+        .with_base_url(server.url())
+        .with_stdlib_base_url(server.url());
+    let note = "_[Output truncated at 500 characters. Use the `section` parameter to access specific sections.]_";
+
+    let full = output(router.lookup_crate("mycrate".to_string(), None, None, None, None, None).await);
+    let doc = output(router.lookup_crate("mycrate".to_string(), None, None, Some(500), None, None).await);
+    assert!(full.chars().count() > 500);
+    assert!(doc.chars().count() <= 500 + 200, "{}", doc);
+    assert!(doc.ends_with(note), "{}", doc);
+    let kept = doc.strip_suffix(note).unwrap().trim_end();
+    assert!(full.starts_with(kept));
+    assert!(kept.ends_with("documentation."), "{}", kept);
+
+    let item = |max_length| {
+        router.lookup_item_tool("std".to_string(), "string::String".to_string(), None, None, None, max_length, None, None, None, None)
+    };
+    let full = output(item(None).await);
+    let doc = output(item(Some(500)).await);
+    assert!(full.chars().count() > 500);
+    assert!(doc.chars().count() <= 500 + 200, "{}", doc);
+    assert!(doc.ends_with(note), "{}", doc);
+    assert_eq!(output(item(Some(full.chars().count())).await), full);
+}
+
+#[test]
+async fn test_truncate_markdown() {
+    let markdown = "First paragraph.\n\nSecond paragraph.\n\nThird paragraph.";
+    assert_eq!(truncate_markdown(markdown, 100), markdown);
+    let truncated = truncate_markdown(markdown, 30);
+    assert!(truncated.starts_with("First paragraph.\n\n_[Output truncated at 30 characters."), "{}", truncated);
+    // Without a paragraph break, the text is cut at the limit
+    assert!(truncate_markdown("ééééé", 3).starts_with("ééé\n\n"));
+}
+
+#[test]
+async fn test_lookup_crate_summary_only() {
+    let mut docs = mockito::Server::new_async().await;
+    let mut crates_io = mockito::Server::new_async().await;
+    let details = (0..50).fold(String::new(), |mut html, i| {
+        let _ = write!(html, "<p>Details of the data model, part {}.</p>", i);
+        html
+    });
+    docs.mock("GET", "/crate/serde/")
+        .with_body(format!(
+            "<section id=\"main-content\"><h1>Crate serde</h1><pre><code>use serde::Serialize;</code></pre>\
+             <p><strong>Serde is a framework for <em>ser</em>ializing and <em>de</em>serializing Rust data \
+             structures efficiently and generically.</strong></p><h2>Design</h2>{}</section>",
+            details
+        ))
+        .create_async()
+        .await;
+    let metadata = crates_io
+        .mock("GET", "/api/v1/crates/serde")
+        .with_body(fixture("crates_io_crate.json"))
+        .create_async()
+        .await;
+    let router = CargoDocRouter::new()
+        .with_base_url(docs.url())
+        .with_crates_io_base_url(format!("{}/", crates_io.url()));
+
+    let full = output(router.lookup_crate("serde".to_string(), None, None, None, None, None).await);
+    let summary = output(router.lookup_crate("serde".to_string(), None, None, None, Some(true), None).await);
+    assert!(summary.starts_with("# serde 1.0.219\n\n"), "{}", summary);
+    assert!(summary.ends_with("License: MIT OR Apache-2.0\n"), "{}", summary);
+    assert!(summary.contains("Serde is a framework"));
+    assert!(!summary.contains("use serde::Serialize") && !summary.contains("Design"));
+    assert!(summary.chars().count() < 500, "{}", summary);
+    assert!(summary.chars().count() < full.chars().count());
+    let paragraph = first_paragraph(&full).unwrap();
+    assert!(summary.contains(&paragraph) && full.contains(&paragraph));
+    metadata.assert_async().await;
+
+    // An explicit version is reported as is
+    docs.mock("GET", "/crate/serde/1.0.0/")
+        .with_body("<p>Serialization framework.</p>")
+        .create_async()
+        .await;
+    let summary = output(
+        router
+            .lookup_crate("serde".to_string(), Some("1.0.0".to_string()), None, None, Some(true), None)
+            .await,
+    );
+    assert!(summary.starts_with("# serde 1.0.0\n\nSerialization framework.\n\n"), "{}", summary);
+}
+
+#[test]
+async fn test_first_paragraph() {
+    let markdown = "# Title\n\n```rust\nlet x = 1;\n\nlet y = 2;\n```\n\n[§](#a)\n\nFirst\nparagraph.\n\nSecond.";
+    assert_eq!(first_paragraph(markdown).as_deref(), Some("First\nparagraph."));
+    assert_eq!(first_paragraph("Setext\n===\n\nText"), Some("Text".to_string()));
+    assert_eq!(first_paragraph("## Only a heading"), None);
+}
+
+const TOKIO_ALL_ITEMS_PAGE: &str = r#"<html><body><section id="main-content" class="content">
+<h1>List of all items</h1>
+<h3 id="structs">Structs</h3><ul class="all-items">
+<li><a href="runtime/struct.Runtime.html">runtime::Runtime</a></li>
+<li><a href="sync/struct.Mutex.html">sync::Mutex</a></li>
+<li><a href="sync/struct.MutexGuard.html">sync::MutexGuard</a></li>
+</ul>
+<h3 id="functions">Functions</h3><ul class="all-items"><li><a href="fn.spawn.html">spawn</a></li><li><a href="task/fn.spawn.html">task::spawn</a></li></ul>
+<h3 id="macros">Macros</h3><ul class="all-items"><li><a href="macro.select.html">select</a></li></ul>
+</section></body></html>"#;
+
+#[test]
+async fn test_get_full_module_path() {
+    let mut server = mockito::Server::new_async().await;
+    let tokio_index = server
+        .mock("GET", "/tokio/latest/tokio/all.html")
+        .with_body(TOKIO_ALL_ITEMS_PAGE)
+        .expect(1)
+        .create_async()
+        .await;
+    server
+        .mock("GET", "/std/all.html")
+        .with_body(r#"<ul class="all-items"><li><a href="string/struct.String.html">string::String</a></li><li><a href="string/trait.ToString.html">string::ToString</a></li></ul>"#)
+        .create_async()
+        .await;
+    server
+        .mock("GET", mockito::Matcher::Any)
+        .with_status(404)
+        .create_async()
+        .await;
+    let router = CargoDocRouter::new()
+        .with_base_url(server.url())
+        .with_stdlib_base_url(server.url());
+    let find = |crate_name: &str, item_name: &str| {
+        router.get_full_module_path(crate_name.to_string(), item_name.to_string(), None)
+    };
+    let matches = |result| serde_json::from_str::<Vec<IndexedItem>>(&output(result)).unwrap();
+    let item = |path: &str, kind: &str| IndexedItem {
+        path: path.to_string(),
+        kind: kind.to_string(),
+    };
+
+    assert_eq!(matches(find("tokio", "Mutex").await), [item("tokio::sync::Mutex", "struct")]);
+    assert_eq!(matches(find("tokio", "tokio::sync::Mutex").await), [item("tokio::sync::Mutex", "struct")]);
+    assert_eq!(
+        matches(find("tokio", "spawn").await),
+        [item("tokio::spawn", "fn"), item("tokio::task::spawn", "fn")]
+    );
+    assert_eq!(matches(find("std", "String").await), [item("std::string::String", "struct")]);
+
+    let error = find("tokio", "Mutexx").await.unwrap_err();
+    assert_eq!(error.code, ErrorCode::RESOURCE_NOT_FOUND);
+    // The index is fetched once per crate version
+    tokio_index.assert_async().await;
+    assert!(router.cache.contains("index:tokio:latest").await);
+}
+
+#[test]
+async fn test_normalize_item_path() {
+    let async_read = ("io".to_string(), "AsyncRead".to_string());
+    assert_eq!(normalize_item_path("tokio", "tokio::io::AsyncRead"), async_read);
+    assert_eq!(normalize_item_path("tokio", "io::AsyncRead"), async_read);
+    assert_eq!(normalize_item_path("tokio", "io/AsyncRead"), async_read);
+    assert_eq!(normalize_item_path("tokio", "tokio/io/AsyncRead"), async_read);
+    assert_eq!(normalize_item_path("tokio", "::tokio::io::AsyncRead"), async_read);
+    assert_eq!(normalize_item_path("tokio", "AsyncRead"), (String::new(), "AsyncRead".to_string()));
+    assert_eq!(normalize_item_path("tokio-util", "tokio_util::codec::Decoder"), ("codec".to_string(), "Decoder".to_string()));
+    assert_eq!(normalize_item_path("tokio", "io::util::"), ("io".to_string(), "util".to_string()));
+    assert_eq!(normalize_item_path("tokio", "io::Async Read"), (String::new(), "io::Async Read".to_string()));
+}
+
+#[test]
+async fn test_parse_all_items() {
+    let items = parse_all_items(TOKIO_ALL_ITEMS_PAGE);
+    assert_eq!(items.len(), 6);
+    assert_eq!(items[0].path, "runtime::Runtime");
+    assert_eq!(items[5].path, "select");
+    assert_eq!(items[5].kind, "macro");
+}
+
+// A standard library type page documenting `methods`, each with a one-line description
+fn std_type_page(kind: &str, name: &str, methods: &[&str]) -> String {
+    let methods = methods.iter().fold(String::new(), |mut html, method| {
+        let _ = write!(
+            html,
+            "<details class=\"toggle method-toggle\" open><summary><section id=\"method.{method}\" class=\"method\">\
+             <span class=\"since rightside\">1.0.0</span><a class=\"src rightside\" href=\"../../src/{method}.rs.html#1\">Source</a>\
+             <h4 class=\"code-header\">pub fn <a href=\"#method.{method}\" class=\"fn\">{method}</a>(&amp;self)</h4></section></summary>\
+             <div class=\"docblock\"><p>Documentation of {name}::{method}.</p></div></details>",
+            method = method,
+            name = name
+        );
+        html
+    });
+    format!(
+        "<html><body><section id=\"main-content\" class=\"content\"><div class=\"main-heading\"><h1>{} {}</h1></div>\
+         <details class=\"toggle top-doc\" open><div class=\"docblock\"><p>The {} type.</p></div></details>\
+         <h2 id=\"implementations\">Implementations</h2><div id=\"implementations-list\"><details class=\"toggle implementors-toggle\" open>\
+         <summary><section class=\"impl\"><h3 class=\"code-header\">impl {}</h3></section></summary><div class=\"impl-items\">{}</div></details></div>\
+         </section></body></html>",
+        kind, name, name, name, methods
+    )
+}
+
+#[test]
+async fn test_lookup_method() {
+    let mut server = mockito::Server::new_async().await;
+    // The Vec page is fetched again for the missing method
+    for (path, kind, name, methods, hits) in [
+        ("/std/vec/struct.Vec.html", "Struct", "Vec", &["new", "push"][..], 2),
+        ("/std/string/struct.String.html", "Struct", "String", &["from_utf8", "push_str"][..], 1),
+        ("/std/collections/struct.HashMap.html", "Struct", "HashMap", &["get", "insert"][..], 1),
+        ("/std/fs/struct.File.html", "Struct", "File", &["create", "open"][..], 1),
+    ] {
+        server
+            .mock("GET", path)
+            .with_body(std_type_page(kind, name, methods))
+            .expect(hits)
+            .create_async()
+            .await;
+    }
+    server
+        .mock("GET", mockito::Matcher::Any)
+        .with_status(404)
+        .create_async()
+        .await;
+    let router = CargoDocRouter::new().with_stdlib_base_url(server.url());
+
+    for (path, type_name, method) in [
+        ("vec::Vec::push", "Vec", "push"),
+        ("std::string::String::from_utf8", "String", "from_utf8"),
+        ("collections::HashMap::insert", "HashMap", "insert"),
+        ("fs/File/open", "File", "open"),
+    ] {
+        let doc = router
+            .lookup_item_doc(CrateName::parse("std").unwrap(), item_path("std", path), None, None)
+            .await
+            .unwrap();
+        assert_eq!(doc.kind, ItemKind::Fn);
+        assert_eq!(doc.name, method);
+        assert_eq!(doc.summary, format!("Documentation of {}::{}.", type_name, method));
+        assert_eq!(doc.since.as_deref(), Some("1.0.0"));
+        assert!(doc.source_url.unwrap().ends_with(&format!("/src/{}.rs.html#1", method)));
+
+        let markdown = output(router.lookup_item_tool("std".to_string(), path.to_string(), None, None, None, None, None, None, None, None).await);
+        assert!(markdown.contains(&format!("struct.{}.html#method.{})(&self)", type_name, method)), "{}", markdown);
+        assert!(!markdown.contains(&format!("The {} type.", type_name)), "{}", markdown);
+    }
+
+    let error = router
+        .lookup_item_tool("std".to_string(), "vec::Vec::pop".to_string(), None, None, None, None, None, None, None, None)
+        .await
+        .unwrap_err();
+    assert_eq!(error.code, ErrorCode::RESOURCE_NOT_FOUND);
+    assert!(error.message.contains("/std/vec/struct.Vec.html#method.pop"), "{}", error.message);
+}
+
+#[test]
+async fn test_item_path_method() {
+    let path = item_path("std", "vec::Vec::push");
+    let (type_path, method) = path.method().unwrap();
+    assert_eq!(type_path, item_path("std", "vec::Vec"));
+    assert_eq!(method, "push");
+    assert_eq!(item_path("std", "cmp::Ordering::Less").method(), None);
+    assert_eq!(item_path("std", "vec::Vec").method(), None);
+    assert_eq!(item_path("std", "push").method(), None);
+}
+
+const TOKIO_IO_INDEX_PAGE: &str = r#"<html><body><section id="main-content" class="content"><h1>Module io</h1>
+<h2 id="traits" class="section-header">Traits</h2><dl class="item-table">
+<dt><a class="trait" href="trait.AsyncBufRead.html" title="trait tokio::io::AsyncBufRead">AsyncBufRead</a></dt><dd>Reads bytes asynchronously.</dd>
+<dt><a class="trait" href="trait.AsyncRead.html" title="trait tokio::io::AsyncRead">AsyncRead</a></dt><dd>Reads bytes from a source.</dd>
+</dl></section></body></html>"#;
+
+#[test]
+async fn test_lookup_item_case_insensitive_fallback() {
+    let mut server = mockito::Server::new_async().await;
+    server
+        .mock("GET", "/tokio/latest/tokio/io/trait.AsyncRead.html")
+        .with_body(r#"<section id="main-content"><div class="main-heading"><h1>Trait AsyncRead</h1></div><div class="docblock"><p>Reads bytes from a source.</p></div></section>"#)
+        .expect(2)
+        .create_async()
+        .await;
+    server
+        .mock("GET", "/tokio/latest/tokio/io/index.html")
+        .with_body(TOKIO_IO_INDEX_PAGE)
+        .create_async()
+        .await;
+    server
+        .mock("GET", mockito::Matcher::Any)
+        .with_status(404)
+        .create_async()
+        .await;
+    let router = CargoDocRouter::new().with_base_url(server.url());
+
+    // Without word boundaries the name is only found through the module index
+    let doc = router
+        .lookup_item_doc(crate_name("tokio"), item_path("tokio", "io::asyncread"), None, None)
+        .await
+        .unwrap();
+    assert_eq!(doc.name, "AsyncRead");
+    assert_eq!(doc.kind, ItemKind::Trait);
+    assert_eq!(doc.summary, "Reads bytes from a source.");
+
+    // A snake_case name is converted to PascalCase
+    let doc = router
+        .lookup_item_doc(crate_name("tokio"), item_path("tokio", "io::async_read"), None, None)
+        .await
+        .unwrap();
+    assert_eq!(doc.path(), "tokio::io::AsyncRead");
+
+    let router = CargoDocRouter::builder()
+        .with_base_url(server.url())
+        .with_case_insensitive_fallback(false)
+        .build()
+        .unwrap();
+    let error = router
+        .lookup_item_doc(crate_name("tokio"), item_path("tokio", "io::asyncread"), None, None)
+        .await
+        .unwrap_err();
+    assert!(error.is_not_found());
+}
+
+#[test]
+async fn test_case_variants() {
+    assert_eq!(case_variants("asyncread"), ["Asyncread"]);
+    assert_eq!(case_variants("async_read"), ["Async_read", "AsyncRead"]);
+    assert_eq!(case_variants("AsyncRead"), Vec::<String>::new());
+}
+
+const MYCRATE_INDEX_PAGE: &str = r#"<html><body><section id="main-content" class="content"><h1>Crate mycrate</h1>
+<h2 id="modules" class="section-header">Modules</h2><dl class="item-table">
+<dt><a class="mod" href="sync/index.html">sync</a></dt><dd>Synchronization.</dd></dl>
+<h2 id="enums" class="section-header">Enums</h2><dl class="item-table">
+<dt><a class="enum" href="enum.Error.html">Error</a></dt><dd>Errors.</dd></dl>
+<h2 id="structs" class="section-header">Structs</h2><dl class="item-table">
+<dt><a class="struct" href="struct.Client.html">Client</a></dt><dd>A client.</dd></dl>
+<h2 id="traits" class="section-header">Traits</h2><dl class="item-table">
+<dt><a class="trait" href="trait.Service.html">Service</a></dt><dd>A service.</dd></dl>
+<h2 id="functions" class="section-header">Functions</h2><dl class="item-table">
+<dt><a class="fn" href="fn.get.html">get</a></dt><dd>Sends a GET request.</dd></dl>
+</section></body></html>"#;
+
+#[test]
+async fn test_prefetch_crate() {
+    let mut server = mockito::Server::new_async().await;
+    server
+        .mock("GET", "/crate/mycrate/")
+        .with_body(MYCRATE_INDEX_PAGE)
+        .create_async()
+        .await;
+    server
+        .mock("GET", "/mycrate/latest/mycrate/index.html")
+        .with_body(MYCRATE_INDEX_PAGE)
+        .expect(2)
+        .create_async()
+        .await;
+    let client = server
+        .mock("GET", "/mycrate/latest/mycrate/struct.Client.html")
+        .with_body(r#"<section id="main-content"><h1>Struct Client</h1><div class="docblock"><p>A client.</p></div></section>"#)
+        .expect(1)
+        .create_async()
+        .await;
+    let service = server
+        .mock("GET", "/mycrate/latest/mycrate/trait.Service.html")
+        .with_body(r#"<section id="main-content"><h1>Trait Service</h1><div class="docblock"><p>A service.</p></div></section>"#)
+        .expect(1)
+        .create_async()
+        .await;
+    let error = server
+        .mock("GET", "/mycrate/latest/mycrate/enum.Error.html")
+        .expect(0)
+        .create_async()
+        .await;
+    server
+        .mock("GET", mockito::Matcher::Any)
+        .with_status(404)
+        .create_async()
+        .await;
+    let router = CargoDocRouter::new().with_base_url(server.url());
+
+    let paths = router.prefetch_crate("mycrate", None, 2).await.unwrap();
+    assert_eq!(paths, [item_path("mycrate", "Client"), item_path("mycrate", "Service")]);
+
+    // The lookups run in the background
+    for _ in 0..100 {
+        if router.cache.contains("item_doc:mycrate:Client").await
+            && router.cache.contains("item_doc:mycrate:Service").await
+        {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+
+    let doc = output(
+        router
+            .lookup_item_tool("mycrate".to_string(), "Client".to_string(), None, None, None, None, None, None, None, None)
+            .await,
+    );
+    assert!(doc.contains("A client."));
+    output(
+        router
+            .lookup_item_tool("mycrate".to_string(), "mycrate::Service".to_string(), None, None, None, None, None, None, None, None)
+            .await,
+    );
+    client.assert_async().await;
+    service.assert_async().await;
+    error.assert_async().await;
+
+    let result = output(router.prefetch_crate_tool("mycrate".to_string(), None, Some(1)).await);
+    assert_eq!(result, "Prefetching the documentation of these `mycrate` items in the background:\n\n- `Client`\n");
+}
+
+fn std_trait_page(name: &str, implementors: &[&str]) -> String {
+    let implementors = implementors.iter().fold(String::new(), |mut html, ty| {
+        let _ = write!(html, r#"<section class="impl"><h3 class="code-header">impl {} for {}</h3></section>"#, name, ty);
+        html
+    });
+    format!(
+        r##"<html><body><section id="main-content">
+<h1>Trait <span class="trait">{name}</span></h1>
+<pre class="rust item-decl"><code>pub trait {name} {{ }}</code></pre>
+<div class="docblock"><p>The {name} trait.</p></div>
+<h2 id="implementors" class="section-header">Implementors<a href="#implementors" class="anchor">§</a></h2>
+<div id="implementors-list">{implementors}</div>
+</section></body></html>"##
+    )
+}
+
+#[test]
+async fn test_lookup_item_include_implementors() {
+    let mut server = mockito::Server::new_async().await;
+    server
+        .mock("GET", "/std/fmt/trait.Display.html")
+        .with_body(std_trait_page("Display", &["str", "String", "i32", "bool"]))
+        .create_async()
+        .await;
+    server
+        .mock("GET", "/std/iter/trait.IntoIterator.html")
+        .with_body(std_trait_page("IntoIterator", &["Vec&lt;T&gt;", "Option&lt;T&gt;", "HashMap&lt;K, V&gt;"]))
+        .create_async()
+        .await;
+    server
+        .mock("GET", mockito::Matcher::Any)
+        .with_status(404)
+        .create_async()
+        .await;
+    let router = CargoDocRouter::new().with_stdlib_base_url(server.url());
+    let lookup = |path: &str, include: Option<bool>, max: Option<u32>| {
+        router.lookup_item_tool("std".to_string(), path.to_string(), None, None, None, None, include, max, None, None)
+    };
+
+    let display = output(lookup("fmt::Display", Some(true), None).await);
+    assert!(display.contains("- `str`\n- `String`\n- `i32`\n- `bool`"), "{}", display);
+    assert!(!display.contains("Showing"));
+
+    let into_iter = output(lookup("iter::IntoIterator", Some(true), Some(2)).await);
+    assert!(into_iter.contains("- `Vec<T>`\n- `Option<T>`\n"), "{}", into_iter);
+    assert!(!into_iter.contains("- `HashMap<K, V>`"));
+    assert!(into_iter.contains("_Showing 2 of 3 implementors; use `find_trait_implementors` for the full list._"));
+
+    // Left as rendered by rustdoc without the flag
+    let plain = output(lookup("fmt::Display", None, None).await);
+    assert!(plain.contains("impl Display for str"), "{}", plain);
+    assert!(!plain.contains("- `str`"));
+}
+
+#[test]
+async fn test_implementor_list() {
+    assert_eq!(implementor_list("Nothing here", 20), "_No implementors listed._");
+}
+
+#[test]
+async fn test_batch_lookup_items() {
+    let mut server = mockito::Server::new_async().await;
+    for name in ["Client", "ClientBuilder"] {
+        server
+            .mock("GET", format!("/mycrate/latest/mycrate/struct.{}.html", name).as_str())
+            .with_body(std_type_page("Struct", name, &["build"]))
+            .create_async()
+            .await;
+    }
+    server
+        .mock("GET", mockito::Matcher::Any)
+        .with_status(404)
+        .create_async()
+        .await;
+    let router = CargoDocRouter::new().with_base_url(server.url());
+    let paths = |paths: &[&str]| paths.iter().map(|path| path.to_string()).collect::<Vec<_>>();
+
+    let result = output(
+        router
+            .batch_lookup_items("mycrate".to_string(), paths(&["Client", "mycrate::ClientBuilder", "Missing"]), None)
+            .await,
+    );
+    let sections: Vec<&str> = result.split("\n\n---\n\n").collect();
+    assert_eq!(sections.len(), 3, "{}", result);
+    assert!(sections[0].starts_with("# `mycrate::Client`\n\n"), "{}", sections[0]);
+    assert!(sections[0].contains("The Client type."));
+    assert!(sections[1].starts_with("# `mycrate::ClientBuilder`\n\n"), "{}", sections[1]);
+    assert!(sections[1].contains("The ClientBuilder type."));
+    assert!(sections[2].starts_with("# `mycrate::Missing`\n\n_Lookup failed: "), "{}", sections[2]);
+
+    let too_many = router
+        .batch_lookup_items("mycrate".to_string(), vec!["Client".to_string(); 11], None)
+        .await
+        .unwrap_err();
+    assert!(too_many.message.contains("between 1 and 10"), "{}", too_many.message);
+    assert!(router.batch_lookup_items("mycrate".to_string(), vec![], None).await.is_err());
+}
+
+#[test]
+async fn test_batch_lookup_items_concurrent() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    // Counts the item pages being served at once; each takes a while to answer, so pages
+    // fetched one after another would never overlap
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    let in_flight = Arc::new(AtomicUsize::new(0));
+    let max_in_flight = Arc::new(AtomicUsize::new(0));
+    let served = Arc::new(AtomicUsize::new(0));
+    let (in_flight_count, max_count, served_count) = (in_flight.clone(), max_in_flight.clone(), served.clone());
+    tokio::spawn(async move {
+        while let Ok((mut socket, _)) = listener.accept().await {
+            let (in_flight, max_in_flight, served) = (in_flight_count.clone(), max_count.clone(), served_count.clone());
+            tokio::spawn(async move {
+                let mut request = Vec::new();
+                let mut buffer = [0; 1024];
+                while !request.windows(4).any(|window| window == b"\r\n\r\n") {
+                    match socket.read(&mut buffer).await {
+                        Ok(0) | Err(_) => return,
+                        Ok(n) => request.extend_from_slice(&buffer[..n]),
+                    }
+                }
+                let request = String::from_utf8_lossy(&request);
+                let path = request.split_whitespace().nth(1).unwrap_or_default();
+                let name = path
+                    .strip_prefix("/mycrate/latest/mycrate/struct.")
+                    .and_then(|rest| rest.strip_suffix(".html"));
+                let response = match name {
+                    Some(name) => {
+                        let current = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                        max_in_flight.fetch_max(current, Ordering::SeqCst);
+                        tokio::time::sleep(Duration::from_millis(300)).await;
+                        in_flight.fetch_sub(1, Ordering::SeqCst);
+                        served.fetch_add(1, Ordering::SeqCst);
+                        let body = std_type_page("Struct", name, &[]);
+                        format!(
+                            "HTTP/1.1 200 OK\r\nContent-Type: text/html\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                            body.len(),
+                            body
+                        )
+                    }
+                    None => "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string(),
+                };
+                let _ = socket.write_all(response.as_bytes()).await;
+            });
+        }
+    });
+    let router = CargoDocRouter::new().with_base_url(format!("http://{}", address));
+
+    let names = ["Client", "Request", "Response"];
+    let result = output(
+        router
+            .batch_lookup_items("mycrate".to_string(), names.iter().map(|name| name.to_string()).collect(), None)
+            .await,
+    );
+    for name in names {
+        assert!(result.contains(&format!("# `mycrate::{}`", name)), "{}", result);
+        assert!(result.contains(&format!("The {} type.", name)), "{}", result);
+    }
+    assert_eq!(served.load(Ordering::SeqCst), 3);
+    assert_eq!(max_in_flight.load(Ordering::SeqCst), 3);
+}
+
+#[test]
+async fn test_lookup_item_follows_redirect() {
+    let mut server = mockito::Server::new_async().await;
+    let redirect = server
+        .mock("GET", "/mycrate/latest/mycrate/sync/struct.Mutex.html")
+        .with_status(302)
+        .with_header(
+            "Location",
+            &format!("{}/mycrate/1.2.0/mycrate/sync/mutex/struct.Mutex.html", server.url()),
+        )
+        .create_async()
+        .await;
+    let page = server
+        .mock("GET", "/mycrate/1.2.0/mycrate/sync/mutex/struct.Mutex.html")
+        .with_body(std_type_page("Struct", "Mutex", &["lock"]))
+        .expect(1)
+        .create_async()
+        .await;
+    server
+        .mock("GET", mockito::Matcher::Any)
+        .with_status(404)
+        .create_async()
+        .await;
+    let router = CargoDocRouter::new().with_base_url(server.url());
+    let lookup = |path: &str| {
+        router.lookup_item_tool("mycrate".to_string(), path.to_string(), None, None, None, None, None, None, None, None)
+    };
+
+    let doc = output(lookup("sync::Mutex").await);
+    assert!(doc.starts_with("# Struct `mycrate::sync::Mutex`\n\n_Canonical path: mycrate::sync::mutex::Mutex_\n\n"), "{}", doc);
+    assert!(doc.contains("The Mutex type."));
+    assert!(router.cache.contains("item_doc:mycrate:sync::mutex::Mutex").await);
+
+    // The path it was redirected to is answered from the cache
+    let canonical = output(lookup("sync::mutex::Mutex").await);
+    assert!(canonical.starts_with("# Struct `mycrate::sync::mutex::Mutex`\n\n"), "{}", canonical);
+    assert!(!canonical.contains("_Canonical path"));
+    redirect.assert_async().await;
+    page.assert_async().await;
+}
+
+#[test]
+async fn test_canonical_item_path() {
+    let router = CargoDocRouter::new()
+        .with_base_url("https://docs.rs")
+        .with_stdlib_base_url("https://doc.rust-lang.org");
+    let canonical = |url: &str| router.canonical_item_path(url);
+
+    assert_eq!(
+        canonical("https://docs.rs/tokio/1.40.0/tokio/sync/mutex/struct.Mutex.html"),
+        Some(("tokio".to_string(), "sync::mutex::Mutex".to_string()))
+    );
+    assert_eq!(
+        canonical("https://docs.rs/serde-json/latest/serde_json/fn.to_string.html"),
+        Some(("serde_json".to_string(), "to_string".to_string()))
+    );
+    assert_eq!(
+        canonical("https://doc.rust-lang.org/1.75.0/core/option/enum.Option.html"),
+        Some(("core".to_string(), "option::Option".to_string()))
+    );
+    assert_eq!(canonical("https://docs.rs/tokio/latest/tokio/sync/struct.Mutex.html#method.lock"), None);
+    assert_eq!(canonical("https://docs.rs/tokio/latest/tokio/index.html"), None);
+    assert_eq!(canonical("https://example.com/tokio/latest/tokio/struct.Mutex.html"), None);
+}
+
+#[test]
+async fn test_required_features() {
+    let url = "https://docs.rs/tokio/latest/tokio/runtime/struct.Runtime.html";
+    let mut doc = ItemDoc::from_page("tokio", &item_path("tokio", "runtime::Runtime"), None, url, "");
+    assert_eq!(doc.required_features(), None);
+    let required = |doc: &mut ItemDoc, condition: &str| {
+        doc.portability = Some(condition.to_string());
+        doc.required_features()
+    };
+    assert_eq!(required(&mut doc, "crate feature `rt`"), Some((vec!["rt".to_string()], false)));
+    assert_eq!(
+        required(&mut doc, "crate features `rt` and `macros`"),
+        Some((vec!["rt".to_string(), "macros".to_string()], false))
+    );
+    assert_eq!(
+        required(&mut doc, "crate features `fs` or `net`"),
+        Some((vec!["fs".to_string(), "net".to_string()], true))
+    );
+    assert_eq!(
+        required(&mut doc, "Unix and crate feature `net` and target feature `sse2`"),
+        Some((vec!["net".to_string()], false))
+    );
+    assert_eq!(required(&mut doc, "Unix"), None);
+
+    let features: BTreeMap<String, Vec<String>> = [
+        ("default", vec![]),
+        ("full", vec!["rt", "net", "dep:bytes"]),
+        ("net", vec!["libc/net"]),
+        ("rt", vec![]),
+        ("fs", vec![]),
+    ]
+    .into_iter()
+    .map(|(name, enables)| (name.to_string(), enables.into_iter().map(str::to_string).collect()))
+    .collect();
+    let enabled: Vec<String> = enabled_features(&["full".to_string()], &features).into_iter().collect();
+    assert_eq!(enabled, ["default", "full", "net", "rt"]);
+}
+
+#[test]
+async fn test_lookup_item_features() {
+    let mut server = mockito::Server::new_async().await;
+    let page = |name: &str, condition: &str| {
+        std_item_info_page(
+            &format!("Struct tokio::runtime::{}", name),
+            &format!("pub struct {} {{ }}", name),
+            &format!(r#"<span class="item-info"><div class="stab portability">Available on <strong>{}</strong> only.</div></span>"#, condition),
+        )
+    };
+    server
+        .mock("GET", "/tokio/1.40.0/tokio/runtime/struct.Runtime.html")
+        .with_body(page("Runtime", "crate feature <code>rt</code>"))
+        .create_async()
+        .await;
+    server
+        .mock("GET", "/tokio/1.40.0/tokio/runtime/struct.Handle.html")
+        .with_body(page("Handle", "crate features <code>fs</code> or <code>io-util</code>"))
+        .create_async()
+        .await;
+    let features = server
+        .mock("GET", "/api/v1/crates/tokio/1.40.0")
+        .with_body(r#"{"version": {"features": {"default": [], "full": ["rt", "fs"], "rt": [], "fs": [], "io-util": []}}}"#)
+        .expect(1)
+        .create_async()
+        .await;
+    server
+        .mock("GET", mockito::Matcher::Any)
+        .with_status(404)
+        .create_async()
+        .await;
+    let router = CargoDocRouter::new()
+        .with_base_url(server.url())
+        .with_crates_io_base_url(server.url());
+    let lookup = |path: &str, features: Option<&[&str]>| {
+        router.lookup_item_tool(
+            "tokio".to_string(),
+            path.to_string(),
+            Some("1.40.0".to_string()),
+            None,
+            None,
+            None,
+            None,
+            None,
+            features.map(|features| features.iter().map(|feature| feature.to_string()).collect()),
+            None,
+        )
+    };
+
+    let full = output(lookup("runtime::Runtime", Some(&["full"])).await);
+    assert!(full.contains("_Requires feature: `rt`_\n\n_Available with the requested features_\n\n"), "{}", full);
+
+    let io_util = output(lookup("runtime::Runtime", Some(&["io-util"])).await);
+    assert!(io_util.contains("_Not available with the requested features: needs `rt`_"), "{}", io_util);
+
+    let handle = output(lookup("runtime::Handle", Some(&["full"])).await);
+    assert!(handle.contains("_Available with the requested features_"), "{}", handle);
+    let handle = output(lookup("runtime::Handle", Some(&["rt"])).await);
+    assert!(handle.contains("_Not available with the requested features: needs one of `fs`, `io-util`_"), "{}", handle);
+
+    // Without features, no note and no request to crates.io
+    let plain = output(lookup("runtime::Runtime", None).await);
+    assert!(!plain.contains("requested features"));
+    features.assert_async().await;
+}
+
+#[test]
+async fn test_parse_target() {
+    let tokio = CrateName::parse("tokio").unwrap();
+    for target in ["wasm32-unknown-unknown", "thumbv7em-none-eabihf", "x86_64-pc-windows-msvc", "aarch64-apple-ios-macabi"] {
+        assert_eq!(parse_target(&tokio, Some(target.to_string())).unwrap().as_deref(), Some(target));
+    }
+    assert_eq!(parse_target(&tokio, None).unwrap(), None);
+    assert_eq!(parse_target(&tokio, Some(" ".to_string())).unwrap(), None);
+    for target in ["linux", "x86_64-linux", "x86_64--linux-gnu", "x86_64-unknown-linux-gnu-extra", "X86_64-pc-windows-msvc", "../../etc-a-b"] {
+        let error = parse_target(&tokio, Some(target.to_string())).unwrap_err();
+        assert_eq!(error.code, ErrorCode::INVALID_PARAMS, "{}", target);
+        assert!(error.message.contains("is not a target triple"), "{}", error.message);
+    }
+
+    let std = CrateName::parse("std").unwrap();
+    let error = parse_target(&std, Some("wasm32-unknown-unknown".to_string())).unwrap_err();
+    assert!(error.message.contains("only published for one target"), "{}", error.message);
+}
+
+#[test]
+async fn test_lookup_with_target() {
+    let mut server = mockito::Server::new_async().await;
+    let item = server
+        .mock("GET", "/mycrate/latest/wasm32-unknown-unknown/mycrate/struct.Canvas.html")
+        .with_body(std_type_page("Struct", "Canvas", &["draw"]))
+        .expect(1)
+        .create_async()
+        .await;
+    let index = server
+        .mock("GET", "/mycrate/1.0.0/thumbv7em-none-eabihf/mycrate/index.html")
+        .with_body("<html><body><section id=\"main-content\"><h1>Crate mycrate</h1><p>Embedded build.</p></section></body></html>")
+        .expect(1)
+        .create_async()
+        .await;
+    server
+        .mock("GET", mockito::Matcher::Any)
+        .with_status(404)
+        .create_async()
+        .await;
+    let router = CargoDocRouter::new().with_base_url(server.url());
+    let lookup_item = |target: Option<&str>| {
+        router.lookup_item_tool(
+            "mycrate".to_string(),
+            "Canvas".to_string(),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            target.map(str::to_string),
+        )
+    };
+
+    let doc = output(lookup_item(Some("wasm32-unknown-unknown")).await);
+    assert!(doc.starts_with("# Struct `mycrate::Canvas`"), "{}", doc);
+    assert!(!doc.contains("_Canonical path"));
+    assert!(router.cache.contains("doc:mycrate:latest@wasm32-unknown-unknown:Canvas").await);
+    assert!(!router.cache.contains("doc:mycrate:Canvas").await);
+    // Cached per target: the default build is requested separately, and isn't found here
+    assert!(lookup_item(None).await.is_err());
+    output(lookup_item(Some("wasm32-unknown-unknown")).await);
+    item.assert_async().await;
+
+    let crate_doc = output(
+        router
+            .lookup_crate(
+                "mycrate".to_string(),
+                Some("1.0.0".to_string()),
+                None,
+                None,
+                None,
+                Some("thumbv7em-none-eabihf".to_string()),
+            )
+            .await,
+    );
+    assert!(crate_doc.contains("Embedded build."), "{}", crate_doc);
+    assert!(router.cache.contains("doc:mycrate:1.0.0@thumbv7em-none-eabihf").await);
+    index.assert_async().await;
+
+    assert_eq!(resource_uri("doc:mycrate:1.0.0@thumbv7em-none-eabihf"), None);
+    assert_eq!(resource_uri("doc:mycrate:latest@wasm32-unknown-unknown:Canvas"), None);
+    assert_eq!(
+        router.canonical_item_path(&format!(
+            "{}/mycrate/latest/wasm32-unknown-unknown/mycrate/web/struct.Canvas.html",
+            server.url()
+        )),
+        Some(("mycrate".to_string(), "web::Canvas".to_string()))
+    );
+}
+
+const CLONE_PAGE: &str = r##"<html><body><div id="rustdoc-vars" data-rustdoc-version="1.84.0 (9fc6b4312 2025-01-07)"></div><section id="main-content">
+<h1>Trait <span class="trait">Clone</span></h1>
+<pre class="rust item-decl"><code>pub trait Clone: <a class="trait" href="../marker/trait.Sized.html" title="trait core::marker::Sized">Sized</a> {
+    // Required method
+    fn <a href="#tymethod.clone" class="fn">clone</a>(&amp;self) -&gt; Self;
+
+    // Provided method
+    fn <a href="#method.clone_from" class="fn">clone_from</a>(&amp;mut self, source: &amp;Self) { ... }
+}</code></pre>
+<details class="toggle top-doc" open><div class="docblock"><p>A common trait for the ability to explicitly duplicate an object.</p></div></details>
+<h2 id="dyn-compatibility" class="section-header">Dyn Compatibility<a href="#dyn-compatibility" class="anchor">§</a></h2>
+<div class="dyn-compatibility-info"><p>This trait is <b>not</b> <a href="https://doc.rust-lang.org/nightly/reference/items/traits.html#dyn-compatibility">dyn compatible</a>.</p></div>
+<h2 id="implementors" class="section-header">Implementors<a href="#implementors" class="anchor">§</a></h2>
+<div id="implementors-list"><section class="impl"><h3 class="code-header">impl Clone for String</h3></section></div>
+</section></body></html>"##;
+
+const READ_TRAIT_PAGE: &str = r##"<html><body><div id="rustdoc-vars" data-rustdoc-version="1.84.0 (9fc6b4312 2025-01-07)"></div><section id="main-content">
+<h1>Trait <span class="trait">Read</span></h1>
+<pre class="rust item-decl"><code>pub trait Read {
+    // Required method
+    fn <a href="#tymethod.read" class="fn">read</a>(&amp;mut self, buf: &amp;mut [<a class="primitive" href="../primitive.u8.html">u8</a>]) -&gt; <a class="type" href="type.Result.html">Result</a>&lt;<a class="primitive" href="../primitive.usize.html">usize</a>&gt;;
+
+    // Provided methods
+<details class="toggle type-contents-toggle"><summary class="hideme"><span>Show 3 methods</span></summary>    fn <a href="#method.read_to_end" class="fn">read_to_end</a>(&amp;mut self, buf: &amp;mut <a class="struct" href="../vec/struct.Vec.html">Vec</a>&lt;<a class="primitive" href="../primitive.u8.html">u8</a>&gt;) -&gt; <a class="type" href="type.Result.html">Result</a>&lt;<a class="primitive" href="../primitive.usize.html">usize</a>&gt; { ... }
+    fn <a href="#method.by_ref" class="fn">by_ref</a>(&amp;mut self) -&gt; &amp;mut Self
+       <span class="where">where Self: <a class="trait" href="../marker/trait.Sized.html">Sized</a></span> { ... }
+    fn <a href="#method.chain" class="fn">chain</a>&lt;R: <a class="trait" href="trait.Read.html">Read</a>&gt;(self, next: R) -&gt; <a class="struct" href="struct.Chain.html">Chain</a>&lt;Self, R&gt;
+       <span class="where">where Self: <a class="trait" href="../marker/trait.Sized.html">Sized</a></span> { ... }
+</details>}</code></pre>
+<details class="toggle top-doc" open><div class="docblock"><p>The <code>Read</code> trait allows for reading bytes from a source.</p></div></details>
+</section></body></html>"##;
+
+#[test]
+async fn test_object_safety() {
+    let mut server = mockito::Server::new_async().await;
+    for (path, body) in [
+        ("/std/fmt/trait.Display.html", TRAIT_PAGE),
+        ("/std/clone/trait.Clone.html", CLONE_PAGE),
+        ("/std/io/trait.Read.html", READ_TRAIT_PAGE),
+    ] {
+        server.mock("GET", path).with_body(body).create_async().await;
+    }
+    server
+        .mock("GET", mockito::Matcher::Any)
+        .with_status(404)
+        .create_async()
+        .await;
+    let router = CargoDocRouter::new().with_stdlib_base_url(server.url());
+    let lookup = |path: &str| {
+        router.lookup_item_tool("std".to_string(), path.to_string(), None, None, None, None, None, None, None, None)
+    };
+
+    let display = output(lookup("fmt::Display").await);
+    assert!(
+        display.contains("## Object Safety\n\n✓ This trait is object safe (can be used as `dyn Display`).\n\n## Required Methods"),
+        "{}",
+        display
+    );
+
+    let clone = output(lookup("clone::Clone").await);
+    assert!(
+        clone.contains("## Object Safety\n\n✗ This trait is NOT object safe because: `Sized` is a supertrait; the method `clone` returns `Self`; the method `clone_from` takes `Self` as a parameter.\n\n"),
+        "{}",
+        clone
+    );
+    assert!(!clone.contains("Dyn Compatibility"));
+    assert!(clone.find("## Object Safety").unwrap() < clone.find("## Implementors").unwrap());
+
+    let read = output(lookup("io::Read").await);
+    assert!(read.contains("✓ This trait is object safe (can be used as `dyn Read`)."), "{}", read);
+
+    // Only traits have the section
+    let url = "https://doc.rust-lang.org/std/string/struct.String.html";
+    let page = std_type_page("Struct", "String", &["len"]);
+    let string = ItemDoc::from_page("std", &item_path("std", "string::String"), None, url, &page);
+    assert!(!string.sections.contains_key("Object Safety"));
+}
+
+#[test]
+async fn test_object_safety_violations() {
+    let declaration = |body: &str| format!("pub trait Shape {{\n{}\n}}", body);
+    assert!(object_safety_violations(&declaration("fn area(&self) -> f64;\nfn scale(self: Box<Self>, by: f64) -> Box<dyn Shape>;")).is_empty());
+    assert_eq!(
+        object_safety_violations(&declaration(
+            "const SIDES: usize;\ntype Point<'a>;\nfn new() -> Self;\nfn map<F: Fn(f64) -> f64>(&self, f: F) { ... }\nfn draw(&self, on: impl Canvas);\nfn same(&self, other: &Self) -> bool;"
+        )),
+        [
+            "it has the associated constant `SIDES`",
+            "it has the generic associated type `Point`",
+            "the associated function `new` has no `self` receiver",
+            "the method `map` has type parameters",
+            "the method `draw` has type parameters",
+            "the method `same` takes `Self` as a parameter",
+        ]
+    );
+    // Items for sized types only don't count, nor do associated types of `Self`
+    assert!(object_safety_violations(&declaration(
+        "type Item;\nfn new() -> Self where Self: Sized;\nfn next(&mut self) -> Option<Self::Item>;\nfn with<'a>(&'a self, other: &'a str);"
+    ))
+    .is_empty());
+
+    // Pages of older rustdoc releases don't mark traits, so the declaration decides
+    let old_page = |declaration: &str| {
+        format!(
+            r#"<html><body><div id="rustdoc-vars" data-rustdoc-version="1.60.0"></div><pre class="rust item-decl"><code>{}</code></pre></body></html>"#,
+            declaration
+        )
+    };
+    assert_eq!(
+        parse_object_safety(&old_page("pub trait Shape { fn new() -&gt; Self; }")),
+        Some(ObjectSafety {
+            object_safe: false,
+            violations: vec!["the associated function `new` has no `self` receiver".to_string()],
+        })
+    );
+    assert_eq!(
+        parse_object_safety(&old_page("pub trait Shape { fn area(&amp;self) -&gt; f64; }")).map(|safety| safety.object_safe),
+        Some(true)
+    );
+    assert_eq!(parse_object_safety(&old_page("pub struct Shape { }")), None);
+}
+
+#[test]
+async fn test_lookup_primitive_types() {
+    let mut server = mockito::Server::new_async().await;
+    let mut pages = Vec::new();
+    for (crate_name, name) in [("std", "u32"), ("std", "str"), ("std", "bool"), ("core", "f64")] {
+        let page = server
+            .mock("GET", format!("/{}/primitive.{}.html", crate_name, name).as_str())
+            .with_body(std_type_page("Primitive Type", name, &["to_string"]))
+            .expect(1)
+            .create_async()
+            .await;
+        pages.push(page);
+    }
+    // Primitive types aren't probed for as other kinds of items
+    let not_found = server
+        .mock("GET", mockito::Matcher::Any)
+        .with_status(404)
+        .expect(0)
+        .create_async()
+        .await;
+    let router = CargoDocRouter::new().with_stdlib_base_url(server.url());
+    let lookup_item = |crate_name: &str, item_path: &str| {
+        router.lookup_item_tool(
+            crate_name.to_string(),
+            item_path.to_string(),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+    };
+
+    for (crate_name, name) in [("std", "u32"), ("std", "str"), ("std", "bool"), ("core", "f64")] {
+        let doc = output(lookup_item(crate_name, name).await);
+        assert!(doc.starts_with(&format!("# Primitive Type `{}::{}`", crate_name, name)), "{}", doc);
+        assert!(doc.contains(&format!("The {} type.", name)), "{}", doc);
+    }
+
+    for page in pages {
+        page.assert_async().await;
+    }
+    not_found.assert_async().await;
+}
+
+// A keyword page of the standard library's documentation
+fn keyword_page(keyword: &str) -> String {
+    format!(
+        "<html><body><section id=\"main-content\" class=\"content\"><div class=\"main-heading\"><h1>Keyword <span>{keyword}</span></h1></div>\
+         <details class=\"toggle top-doc\" open><div class=\"docblock\"><p>Documentation of the <code>{keyword}</code> keyword.</p></div></details>\
+         </section></body></html>",
+        keyword = keyword
+    )
+}
+
+#[test]
+async fn test_lookup_keyword() {
+    let mut server = mockito::Server::new_async().await;
+    let mut pages = Vec::new();
+    for keyword in ["async", "impl", "dyn", "unsafe", "match", "while"] {
+        let page = server
+            .mock("GET", format!("/std/keyword.{}.html", keyword).as_str())
+            .with_body(keyword_page(keyword))
+            .expect(1)
+            .create_async()
+            .await;
+        pages.push(page);
+    }
+    // Keywords aren't probed for as other kinds of items
+    let not_found = server
+        .mock("GET", mockito::Matcher::Any)
+        .with_status(404)
+        .expect(0)
+        .create_async()
+        .await;
+    let router = CargoDocRouter::new().with_stdlib_base_url(server.url());
+
+    for keyword in ["async", "impl", "dyn", "unsafe", "match"] {
+        let doc = output(router.lookup_keyword(keyword.to_string(), None).await);
+        assert!(doc.starts_with(&format!("# Keyword `std::{}`", keyword)), "{}", doc);
+        assert!(doc.contains(&format!("Documentation of the `{}` keyword.", keyword)), "{}", doc);
+    }
+    // Served from the cache the second time
+    output(router.lookup_keyword("dyn".to_string(), None).await);
+
+    // lookup_item routes keywords of `std` to their pages too
+    let doc = output(
+        router
+            .lookup_item_tool("std".to_string(), "while".to_string(), None, None, None, None, None, None, None, None)
+            .await,
+    );
+    assert!(doc.contains("Documentation of the `while` keyword."), "{}", doc);
+
+    let error = router.lookup_keyword("loops".to_string(), None).await.unwrap_err();
+    assert!(error.message.contains("`loops` is not a keyword documented by the standard library"), "{}", error.message);
+
+    for page in pages {
+        page.assert_async().await;
+    }
+    not_found.assert_async().await;
+}
+
+#[test]
+async fn test_warm_from_list() {
+    let mut server = mockito::Server::new_async().await;
+    let serde = server
+        .mock("GET", "/crate/serde/")
+        .with_body("<html><body><h1>Crate serde</h1><p>A serialization framework.</p></body></html>")
+        .expect(1)
+        .create_async()
+        .await;
+    let anyhow = server
+        .mock("GET", "/crate/anyhow/1.0.0/")
+        .with_body("<html><body><h1>Crate anyhow</h1><p>Flexible error handling.</p></body></html>")
+        .expect(1)
+        .create_async()
+        .await;
+    server
+        .mock("GET", mockito::Matcher::Any)
+        .with_status(404)
+        .create_async()
+        .await;
+    let router = CargoDocRouter::new().with_base_url(server.url());
+
+    let crates = [("serde", None), ("anyhow", Some("1.0.0")), ("nosuchcrate", None)];
+    let result = DocCache::warm_from_list(&crates, &router).await;
+    assert_eq!(result.succeeded, 2);
+    assert_eq!(result.failed, 1);
+    assert_eq!(result.errors.len(), 1);
+    assert!(result.errors[0].starts_with("nosuchcrate: "), "{:?}", result.errors);
+    assert!(router.cache.get("doc:serde").await.unwrap().contains("A serialization framework."));
+    assert!(router.cache.contains("doc:anyhow:1.0.0").await);
+
+    // Warm crates are served from the cache
+    let result = DocCache::warm_from_list(&crates[..2], &router).await;
+    assert_eq!(result, WarmupResult { succeeded: 2, failed: 0, errors: Vec::new() });
+    serde.assert_async().await;
+    anyhow.assert_async().await;
+
+    assert!(DocCache::DEFAULT_WARM_LIST.contains(&("tokio", None)));
+}
+
+#[test]
+async fn test_lookup_no_std_crates() {
+    let mut server = mockito::Server::new_async().await;
+    let mut pages = Vec::new();
+    for (page_path, kind, name) in [
+        ("core/option/enum.Option.html", "Enum", "Option"),
+        ("core/fmt/trait.Write.html", "Trait", "Write"),
+        ("alloc/string/struct.String.html", "Struct", "String"),
+        ("alloc/vec/struct.Vec.html", "Struct", "Vec"),
+    ] {
+        let page = server
+            .mock("GET", format!("/{}", page_path).as_str())
+            .with_body(std_type_page(kind, name, &["len"]))
+            .expect(1)
+            .create_async()
+            .await;
+        pages.push(page);
+    }
+    server
+        .mock("GET", mockito::Matcher::Any)
+        .with_status(404)
+        .create_async()
+        .await;
+    // docs.rs doesn't document the distribution's crates
+    let router = CargoDocRouter::new()
+        .with_base_url("http://docs.rs.invalid".to_string())
+        .with_stdlib_base_url(server.url());
+
+    for (crate_name, item_path, expected) in [
+        ("core", "core::option::Option", "# Enum `core::option::Option`"),
+        ("core", "fmt::Write", "# Trait `core::fmt::Write`"),
+        ("alloc", "alloc::string::String", "# Struct `alloc::string::String`"),
+        ("alloc", "vec::Vec", "# Struct `alloc::vec::Vec`"),
+    ] {
+        let doc = output(
+            router
+                .lookup_item_tool(crate_name.to_string(), item_path.to_string(), None, None, None, None, None, None, None, None)
+                .await,
+        );
+        assert!(doc.starts_with(expected), "{}", doc);
+        assert!(doc.contains("::len."), "{}", doc);
+    }
+    for page in pages {
+        page.assert_async().await;
+    }
+}
+
+#[test]
+async fn test_get_reverse_dependencies() {
+    let mut server = mockito::Server::new_async().await;
+    let serde = server
+        .mock("GET", "/api/v1/crates/serde/reverse_dependencies")
+        .match_query(mockito::Matcher::AllOf(vec![
+            mockito::Matcher::UrlEncoded("page".into(), "1".into()),
+            mockito::Matcher::UrlEncoded("per_page".into(), "10".into()),
+        ]))
+        .with_body(fixture("crates_io_reverse_dependencies.json"))
+        .expect(1)
+        .create_async()
+        .await;
+    let tokio = server
+        .mock("GET", "/api/v1/crates/tokio/reverse_dependencies")
+        .match_query(mockito::Matcher::AllOf(vec![
+            mockito::Matcher::UrlEncoded("page".into(), "2".into()),
+            mockito::Matcher::UrlEncoded("per_page".into(), "1".into()),
+        ]))
+        .with_body(r#"{"dependencies": [{"version_id": 1, "req": "^1.28"}], "versions": [{"id": 1, "crate": "axum", "downloads": 1000}], "meta": {"total": 31000}}"#)
+        .create_async()
+        .await;
+    server
+        .mock("GET", mockito::Matcher::Any)
+        .with_status(404)
+        .create_async()
+        .await;
+    let router = CargoDocRouter::new().with_crates_io_base_url(server.url());
+
+    let dependents = output(router.get_reverse_dependencies("serde".to_string(), None, None).await);
+    assert!(dependents.starts_with("# Reverse dependencies of `serde`\n\n48212 crates depend on `serde`. Page 1 of 4822, most downloaded first:\n\n"), "{}", dependents);
+    // Most downloaded first
+    assert!(dependents.contains(
+        "| Crate | Requirement | Downloads |\n|---|---|---|\n| chrono | `^1.0.100` | 48211092 |\n| serde_json | `^1` | 39004512 |\n| toml | `^1.0.194` | 21893417 |\n"
+    ), "{}", dependents);
+    // Served from the cache the second time
+    assert_eq!(output(router.get_reverse_dependencies("serde".to_string(), None, None).await), dependents);
+    serde.assert_async().await;
+
+    let dependents = output(router.get_reverse_dependencies("tokio".to_string(), Some(2), Some(1)).await);
+    assert!(dependents.contains("31000 crates depend on `tokio`. Page 2 of 31000"), "{}", dependents);
+    assert!(dependents.contains("| axum | `^1.28` | 1000 |"), "{}", dependents);
+    tokio.assert_async().await;
+
+    assert!(router.get_reverse_dependencies("nosuchcrate".to_string(), None, None).await.is_err());
+}
+
+#[test]
+async fn test_lookup_item_resolves_short_names() {
+    let mut server = mockito::Server::new_async().await;
+    let index = server
+        .mock("GET", "/tokio/latest/tokio/all.html")
+        .with_body(TOKIO_ALL_ITEMS_PAGE)
+        .expect(1)
+        .create_async()
+        .await;
+    let mutex = server
+        .mock("GET", "/tokio/latest/tokio/sync/struct.Mutex.html")
+        .with_body(std_type_page("Struct", "Mutex", &["lock"]))
+        .expect(1)
+        .create_async()
+        .await;
+    server
+        .mock("GET", mockito::Matcher::Any)
+        .with_status(404)
+        .create_async()
+        .await;
+    let router = CargoDocRouter::new().with_base_url(server.url());
+    let lookup_item = |item_path: &str| {
+        router.lookup_item_tool(
+            "tokio".to_string(),
+            item_path.to_string(),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+    };
+
+    // The only `Mutex` in the index is looked up at its full path
+    let doc = output(lookup_item("tokio::Mutex").await);
+    assert!(doc.starts_with("# Struct `tokio::sync::Mutex`"), "{}", doc);
+    assert!(doc.contains("Documentation of Mutex::lock."));
+    assert!(router.cache.contains("doc:tokio:Mutex").await);
+    assert!(router.cache.contains("doc:tokio:sync::Mutex").await);
+    assert!(router.cache.contains("index:tokio:latest").await);
+    // Both paths are then served from the cache
+    assert_eq!(output(lookup_item("Mutex").await), doc);
+    output(lookup_item("sync::Mutex").await);
+
+    // Several items of the name are suggested
+    let error = lookup_item("rt::spawn").await.unwrap_err();
+    assert_eq!(error.code, ErrorCode::RESOURCE_NOT_FOUND);
+    assert!(
+        error.message.contains("No documentation found for `rt::spawn` in tokio; items of that name are at `tokio::spawn`, `tokio::task::spawn`"),
+        "{}",
+        error.message
+    );
+
+    // Names missing from the index keep the original error
+    let error = lookup_item("Mutexx").await.unwrap_err();
+    assert!(error.message.contains("No documentation found for `Mutexx` in tokio (tried "), "{}", error.message);
+
+    index.assert_async().await;
+    mutex.assert_async().await;
+}
@@ -0,0 +1,509 @@
+use super::markdown_utils::{extract_section, plain_text};
+
+// Byte offset of the first occurrence of `needle` outside any `<>`, `()` or `[]` nesting.
+// The `>` of `->` in function types doesn't close anything.
+fn find_top_level(text: &str, needle: &str) -> Option<usize> {
+    let mut depth = 0i32;
+    let mut previous = None;
+    for (i, c) in text.char_indices() {
+        match c {
+            '<' | '(' | '[' => depth += 1,
+            '>' if previous == Some('-') => {}
+            '>' | ')' | ']' => depth -= 1,
+            _ if depth == 0 && text[i..].starts_with(needle) => return Some(i),
+            _ => {}
+        }
+        previous = Some(c);
+    }
+    None
+}
+
+// The trait (`Deref`, `From<&str>`, `!Sync`) and the implementing type of a trait impl header
+pub(crate) fn impl_header_parts(header: &str) -> Option<(&str, &str)> {
+    let header = header.trim();
+    let header = header.strip_prefix("unsafe ").unwrap_or(header);
+    let rest = header.strip_prefix("impl")?;
+    if !(rest.starts_with('<') || rest.starts_with(' ')) {
+        return None;
+    }
+
+    let for_start = find_top_level(rest, " for ")?;
+    let mut trait_path = rest[..for_start].trim();
+    if trait_path.starts_with('<') {
+        trait_path = trait_path[find_top_level(trait_path, " ")?..].trim();
+    }
+    let rest = &rest[for_start + " for ".len()..];
+    let ty = match find_top_level(rest, " where") {
+        Some(end) => &rest[..end],
+        None => rest,
+    };
+    Some((trait_path, ty.trim()))
+}
+
+// Name of a trait in an impl header without its path and generic arguments: `From` for
+// `convert::From<&str>`
+pub(crate) fn trait_name(trait_path: &str) -> &str {
+    let end = trait_path.find('<').unwrap_or(trait_path.len());
+    let path = &trait_path[..end];
+    path.rsplit("::").next().unwrap_or(path)
+}
+
+// Plain text of the impl header lines of an item's markdown documentation, with the trait and
+// implementing type of each, in page order. The other lines come through as `None`.
+fn impl_headers(doc: &str) -> impl Iterator<Item = (String, Option<(String, String)>)> + '_ {
+    doc.lines().map(|line| {
+        let text = plain_text(line.trim_start_matches('#').trim_end_matches('#'));
+        let parts = impl_header_parts(&text).map(|(trait_path, ty)| (trait_path.to_string(), ty.to_string()));
+        (text, parts)
+    })
+}
+
+/// The implementing type of an impl header such as `impl<T: Display> Display for Wrapper<T>`,
+/// or `None` for inherent impls and lines that aren't impl headers.
+pub fn implementing_type(header: &str) -> Option<String> {
+    impl_header_parts(header).map(|(_, ty)| ty.to_string())
+}
+
+/// Conversions implemented in an item's markdown documentation for the type named `type_name`:
+/// the types it can be created from (`impl From<X> for Type`) and the types it can be converted to
+/// (`impl Into<Y> for Type` or `impl From<Type> for Y`), in page order and without duplicates.
+pub fn parse_conversions(doc: &str, type_name: &str) -> (Vec<String>, Vec<String>) {
+    let is_item = |ty: &str| ty.split('<').next() == Some(type_name);
+    let mut from = Vec::new();
+    let mut into = Vec::new();
+    for (_, parts) in impl_headers(doc) {
+        let Some((trait_path, ty)) = parts else {
+            continue;
+        };
+        let Some(argument) = trait_path
+            .split_once('<')
+            .and_then(|(_, rest)| rest.strip_suffix('>'))
+            .map(str::trim)
+        else {
+            continue;
+        };
+        let (list, other) = match trait_name(&trait_path) {
+            "From" if is_item(&ty) => (&mut from, argument),
+            "From" if is_item(argument) => (&mut into, ty.as_str()),
+            "Into" if is_item(&ty) => (&mut into, argument),
+            _ => continue,
+        };
+        if !list.iter().any(|known| known == other) {
+            list.push(other.to_string());
+        }
+    }
+    (from, into)
+}
+
+/// The `Target` type of the `Deref` implementation in an item's markdown documentation, e.g.
+/// `str` for `String`.
+pub fn deref_target(doc: &str) -> Option<String> {
+    let mut in_deref_impl = false;
+    for (text, parts) in impl_headers(doc) {
+        if let Some((trait_path, _)) = parts {
+            in_deref_impl = trait_name(&trait_path) == "Deref";
+        } else if in_deref_impl {
+            if let Some(target) = text.strip_prefix("type Target =") {
+                return Some(target.trim().to_string());
+            }
+        }
+    }
+    None
+}
+
+/// Types listed in the Implementors section of a trait's documentation, in page order and
+/// without duplicates.
+pub fn parse_implementors(section: &str) -> Vec<String> {
+    let mut implementors: Vec<String> = Vec::new();
+    for line in section.lines() {
+        let text = plain_text(line.trim_start_matches('#').trim_end_matches('#'));
+        if let Some(ty) = implementing_type(&text) {
+            if !implementors.contains(&ty) {
+                implementors.push(ty);
+            }
+        }
+    }
+    implementors
+}
+
+/// The name declared by an associated type line of a trait declaration: `Item` for
+/// `type Item;`, `type Item: Clone;`, `type Item = u8;` or `type Item<'a> where Self: 'a;`.
+pub fn associated_type_name(line: &str) -> Option<&str> {
+    let rest = line.trim().strip_prefix("type ")?.trim_start();
+    let end = rest
+        .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .unwrap_or(rest.len());
+    let (name, after) = rest.split_at(end);
+    let declaration = after.trim_start();
+    let terminated = [":", "=", ";", "<", "where"]
+        .iter()
+        .any(|delimiter| declaration.starts_with(delimiter));
+    (!name.is_empty() && terminated && line.contains(';')).then_some(name)
+}
+
+/// Supertraits in the trait declaration of an item's markdown documentation, as written
+/// (`Debug`, `Display` for `pub trait Error: Debug + Display {`). Lifetime bounds are left out,
+/// and bounds of generic parameters and where clauses aren't supertraits.
+pub fn parse_supertrait_bounds(doc: &str) -> Vec<String> {
+    let mut lines = doc.lines().map(str::trim);
+    let Some(start) = lines.by_ref().find_map(trait_declaration) else {
+        return Vec::new();
+    };
+
+    // Declarations with long bounds are wrapped over several lines
+    let mut declaration = start.to_string();
+    for line in lines {
+        if find_top_level(&declaration, "{").is_some() || find_top_level(&declaration, ";").is_some() {
+            break;
+        }
+        declaration.push(' ');
+        declaration.push_str(line);
+    }
+
+    let Some(colon) = find_top_level(&declaration, ":") else {
+        return Vec::new();
+    };
+    let name = &declaration[..colon];
+    if name.contains(['{', ';']) || name.contains(" where") {
+        return Vec::new();
+    }
+    let bounds = &declaration[colon + 1..];
+    let end = [" where", "{", ";"]
+        .iter()
+        .filter_map(|delimiter| find_top_level(bounds, delimiter))
+        .min()
+        .unwrap_or(bounds.len());
+
+    let mut supertraits = Vec::new();
+    let mut rest = &bounds[..end];
+    loop {
+        let (bound, remaining) = match find_top_level(rest, "+") {
+            Some(plus) => (&rest[..plus], Some(&rest[plus + 1..])),
+            None => (rest, None),
+        };
+        let bound = bound.trim();
+        if !bound.is_empty() && !bound.starts_with('\'') {
+            supertraits.push(bound.to_string());
+        }
+        match remaining {
+            Some(remaining) => rest = remaining,
+            None => return supertraits,
+        }
+    }
+}
+
+// The part of a trait declaration line after `trait `, e.g. `Error: Debug + Display {` for
+// `pub unsafe trait Error: Debug + Display {`
+fn trait_declaration(line: &str) -> Option<&str> {
+    let mut rest = line;
+    for qualifier in ["pub(crate) ", "pub ", "unsafe ", "auto "] {
+        rest = rest.strip_prefix(qualifier).unwrap_or(rest);
+    }
+    rest.strip_prefix("trait ")
+}
+
+/// An unsafe function declared in an item's markdown documentation
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnsafeFunction {
+    pub name: String,
+    /// The signature as written, e.g. `pub unsafe fn read_unaligned<T>(src: *const T) -> T`
+    pub signature: String,
+    /// The function's `Safety` section, verbatim
+    pub safety: Option<String>,
+}
+
+// Name of the function declared by a signature line such as `pub const unsafe fn read<T>(`,
+// and whether it is unsafe
+fn function_signature(text: &str) -> Option<(&str, bool)> {
+    let start = text.find("fn ")?;
+    let (qualifiers, rest) = (&text[..start], &text[start + "fn ".len()..]);
+    let is_qualifier = |word: &str| {
+        word.starts_with("pub") || word.starts_with('"') || ["const", "async", "unsafe", "extern", "default"].contains(&word)
+    };
+    if !(qualifiers.is_empty() || qualifiers.ends_with(' ')) || !qualifiers.split_whitespace().all(is_qualifier) {
+        return None;
+    }
+    let end = rest.find(|c: char| !(c.is_alphanumeric() || c == '_')).unwrap_or(rest.len());
+    let (name, after) = rest.split_at(end);
+    let is_unsafe = qualifiers.split_whitespace().any(|word| word == "unsafe");
+    (!name.is_empty() && (after.starts_with('(') || after.starts_with('<'))).then_some((name, is_unsafe))
+}
+
+/// Unsafe functions declared or documented in an item's markdown documentation, in page order
+/// and once each. A method takes the `Safety` section of the documentation under its header;
+/// in the item declaration only the item `item_name` itself has one, the page's own.
+pub fn parse_unsafe_functions(doc: &str, item_name: &str) -> Vec<UnsafeFunction> {
+    let lines: Vec<&str> = doc.lines().collect();
+    // Line, name, signature and whether it is a method header, of every unsafe function
+    let mut signatures = Vec::new();
+    // Lines of all method headers, which end the documentation of the one before
+    let mut headers = Vec::new();
+    let mut in_code_block = false;
+    for (i, line) in lines.iter().enumerate() {
+        if line.trim_start().starts_with("```") {
+            in_code_block = !in_code_block;
+            continue;
+        }
+        let is_header = !in_code_block && line.starts_with('#');
+        if !in_code_block && !is_header {
+            continue;
+        }
+        let text = plain_text(line.trim_start_matches('#').trim_end_matches('#'));
+        let Some((name, is_unsafe)) = function_signature(&text) else {
+            continue;
+        };
+        if is_header {
+            headers.push(i);
+        }
+        if is_unsafe {
+            let signature = text.trim_end_matches(['{', ';']).trim_end().to_string();
+            signatures.push((i, name.to_string(), signature, is_header));
+        }
+    }
+
+    let mut functions: Vec<UnsafeFunction> = Vec::new();
+    for (line, name, signature, is_header) in signatures {
+        let safety = if is_header {
+            let end = headers.iter().copied().find(|header| *header > line).unwrap_or(lines.len());
+            extract_section(&lines[line + 1..end].join("\n"), "Safety", false)
+        } else if name == item_name {
+            extract_section(doc, "Safety", false)
+        } else {
+            None
+        };
+        match functions.iter_mut().find(|function| function.name == name) {
+            Some(function) => {
+                if function.safety.is_none() {
+                    function.safety = safety;
+                }
+            }
+            None => functions.push(UnsafeFunction { name, signature, safety }),
+        }
+    }
+    functions
+}
+
+/// Headers of the `unsafe impl`s in an item's markdown documentation, e.g.
+/// `unsafe impl Send for Bytes`, in page order and without duplicates
+pub fn parse_unsafe_impls(doc: &str) -> Vec<String> {
+    let mut impls: Vec<String> = Vec::new();
+    for (text, parts) in impl_headers(doc) {
+        if parts.is_some() && text.starts_with("unsafe impl") && !impls.contains(&text) {
+            impls.push(text);
+        }
+    }
+    impls
+}
+
+// The items declared in the body of a trait declaration, such as `fn next(&mut self) -> Option<Self::Item>`
+// or `type Item`, without their trailing `;` or `{ ... }` body
+fn trait_items(declaration: &str) -> Vec<String> {
+    let declaration: String = declaration
+        .lines()
+        .filter(|line| !line.trim_start().starts_with("//"))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let Some(start) = find_top_level(&declaration, "{") else {
+        return Vec::new();
+    };
+
+    let mut items = Vec::new();
+    let mut item = String::new();
+    let mut depth = 0i32;
+    let mut braces = 0;
+    let mut previous = None;
+    for c in declaration[start + 1..].chars() {
+        match c {
+            _ if braces > 0 => {
+                braces += match c {
+                    '{' => 1,
+                    '}' => -1,
+                    _ => 0,
+                };
+                if braces == 0 {
+                    items.push(std::mem::take(&mut item));
+                }
+            }
+            '{' if depth == 0 => braces = 1,
+            // The end of the trait
+            '}' if depth == 0 => break,
+            ';' if depth == 0 => items.push(std::mem::take(&mut item)),
+            _ => {
+                match c {
+                    '<' | '(' | '[' => depth += 1,
+                    '>' if previous == Some('-') => {}
+                    '>' | ')' | ']' => depth -= 1,
+                    _ => {}
+                }
+                item.push(c);
+            }
+        }
+        previous = Some(c);
+    }
+    items
+        .iter()
+        .map(|item| item.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+// Byte offset of the bracket closing the one just before `text`, e.g. of the `)` ending a
+// parameter list that `text` starts right after the `(` of
+fn closing_bracket(text: &str) -> Option<usize> {
+    let mut depth = 0i32;
+    let mut previous = None;
+    for (i, c) in text.char_indices() {
+        match c {
+            '<' | '(' | '[' => depth += 1,
+            '>' if previous == Some('-') => {}
+            '>' | ')' | ']' if depth == 0 => return Some(i),
+            '>' | ')' | ']' => depth -= 1,
+            _ => {}
+        }
+        previous = Some(c);
+    }
+    None
+}
+
+// Whether `text` mentions `Self` itself, rather than only associated items like `Self::Item`
+fn mentions_self(text: &str) -> bool {
+    text.match_indices("Self").any(|(i, _)| {
+        let before = text[..i].chars().next_back();
+        let after = &text[i + "Self".len()..];
+        !before.is_some_and(|c| c.is_alphanumeric() || c == '_')
+            && !after.starts_with(|c: char| c.is_alphanumeric() || c == '_')
+            && !after.starts_with("::")
+    })
+}
+
+/// Why a trait isn't dyn compatible ("object safe"), read from its declaration on a rustdoc page:
+/// a `Sized` supertrait, associated constants, generic associated types, and methods that have no
+/// `self` receiver, have type parameters, or take or return `Self`. Items bounded by
+/// `where Self: Sized` are left out, as they don't apply to trait objects.
+pub fn object_safety_violations(declaration: &str) -> Vec<String> {
+    let mut violations = Vec::new();
+    if parse_supertrait_bounds(declaration).iter().any(|bound| bound == "Sized") {
+        violations.push("`Sized` is a supertrait".to_string());
+    }
+
+    for item in trait_items(declaration) {
+        if item.contains("Self: Sized") {
+            continue;
+        }
+        if let Some(rest) = item.strip_prefix("const ") {
+            let name = rest.split(|c: char| !(c.is_alphanumeric() || c == '_')).next().unwrap_or_default();
+            violations.push(format!("it has the associated constant `{}`", name));
+            continue;
+        }
+        if let Some(name) = associated_type_name(&format!("{};", item)) {
+            if item["type ".len() + name.len()..].starts_with('<') {
+                violations.push(format!("it has the generic associated type `{}`", name));
+            }
+            continue;
+        }
+        let Some((name, _)) = function_signature(&item) else {
+            continue;
+        };
+        let after_name = &item[item.find(&format!("fn {}", name)).unwrap_or(0) + "fn ".len() + name.len()..];
+        let (generics, rest) = match after_name.strip_prefix('<') {
+            Some(generics) => match closing_bracket(generics) {
+                Some(end) => (&generics[..end], &generics[end + 1..]),
+                None => continue,
+            },
+            None => ("", after_name),
+        };
+        let Some(parameters) = rest.strip_prefix('(') else {
+            continue;
+        };
+        let Some(end) = closing_bracket(parameters) else {
+            continue;
+        };
+        let (parameters, output) = (&parameters[..end], &parameters[end + 1..]);
+        let output = output.split(" where ").next().unwrap_or_default();
+
+        let mut parameters = parameters.split(',').map(str::trim).filter(|parameter| !parameter.is_empty());
+        let has_receiver = parameters.clone().next().is_some_and(|first| {
+            let first = first.trim_start_matches('&').trim_start();
+            let first = first.strip_prefix('\'').map_or(first, |lifetime| {
+                lifetime.split_once(' ').map_or(lifetime, |(_, rest)| rest)
+            });
+            let first = first.strip_prefix("mut ").unwrap_or(first);
+            first == "self" || first.starts_with("self:")
+        });
+        if !has_receiver {
+            violations.push(format!("the associated function `{}` has no `self` receiver", name));
+            continue;
+        }
+        parameters.next();
+        let has_type_parameters = generics.split(',').any(|parameter| {
+            let parameter = parameter.trim();
+            !parameter.is_empty() && !parameter.starts_with('\'')
+        });
+        if has_type_parameters || parameters.clone().any(|parameter| parameter.contains("impl ")) {
+            violations.push(format!("the method `{}` has type parameters", name));
+        }
+        if parameters.any(mentions_self) {
+            violations.push(format!("the method `{}` takes `Self` as a parameter", name));
+        }
+        if mentions_self(output) {
+            violations.push(format!("the method `{}` returns `Self`", name));
+        }
+    }
+    violations
+}
+
+/// A function that returns a future: an `async fn`, or one returning `impl Future<Output = T>`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AsyncMethod {
+    pub name: String,
+    /// The type awaiting the future produces, e.g. `Result<File>`
+    pub output: String,
+}
+
+/// The function declared by a signature such as `pub async fn open(path: P) -> Result<File>`,
+/// if it returns a future
+pub fn async_method(signature: &str) -> Option<AsyncMethod> {
+    let (name, _) = function_signature(signature)?;
+    let start = signature.find(&format!("fn {}", name))? + "fn ".len() + name.len();
+    let mut rest = &signature[start..];
+    if let Some(generics) = rest.strip_prefix('<') {
+        rest = &generics[closing_bracket(generics)? + 1..];
+    }
+    let parameters = rest.strip_prefix('(')?;
+    let output = &parameters[closing_bracket(parameters)? + 1..];
+    let output = output.split(" where ").next().unwrap_or_default();
+    let output = output.trim().trim_end_matches(['{', ';', ',']).trim_end().strip_prefix("->").map(str::trim);
+
+    let output = if signature[..start].split_whitespace().any(|word| word == "async") {
+        output.unwrap_or("()")
+    } else {
+        let future = output?.strip_prefix("impl ")?;
+        let arguments = &future[future.find("Future<")? + "Future<".len()..];
+        let arguments = &arguments[..closing_bracket(arguments)?];
+        arguments.strip_prefix("Output")?.trim_start().strip_prefix('=')?.trim()
+    };
+    Some(AsyncMethod { name: name.to_string(), output: output.to_string() })
+}
+
+/// Functions returning futures that are declared in an item's markdown documentation, in page
+/// order and once each
+pub fn parse_async_methods(doc: &str) -> Vec<AsyncMethod> {
+    let mut methods: Vec<AsyncMethod> = Vec::new();
+    let mut in_code_block = false;
+    for line in doc.lines() {
+        if line.trim_start().starts_with("```") {
+            in_code_block = !in_code_block;
+            continue;
+        }
+        if !in_code_block && !line.starts_with('#') {
+            continue;
+        }
+        let text = plain_text(line.trim_start_matches('#').trim_end_matches('#'));
+        if let Some(method) = async_method(&text) {
+            if !methods.iter().any(|existing| existing.name == method.name) {
+                methods.push(method);
+            }
+        }
+    }
+    methods
+}
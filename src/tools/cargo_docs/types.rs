@@ -0,0 +1,244 @@
+use std::fmt;
+use std::ops::Deref;
+
+use rmcp::schemars;
+use rmcp::Error as McpError;
+use serde::{Deserialize, Serialize};
+
+use super::error::DocError;
+
+// Longest crate name crates.io accepts
+const MAX_CRATE_NAME_LENGTH: usize = 64;
+
+/// A syntactically valid crate name, such as `serde_json` or `tokio`
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(try_from = "String", into = "String")]
+#[schemars(crate = "rmcp::schemars")]
+pub struct CrateName(String);
+
+/// Why a string isn't a valid crate name
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InvalidCrateNameError {
+    pub name: String,
+    pub reason: &'static str,
+}
+
+impl CrateName {
+    /// Check that `name` is non-empty, at most 64 characters of lowercase letters, digits,
+    /// hyphens and underscores, starts with a letter or digit, and has no `--`
+    pub fn parse(name: &str) -> Result<CrateName, InvalidCrateNameError> {
+        let invalid = |reason| {
+            Err(InvalidCrateNameError {
+                name: name.to_string(),
+                reason,
+            })
+        };
+        if name.is_empty() {
+            return invalid("it is empty");
+        }
+        if name.len() > MAX_CRATE_NAME_LENGTH {
+            return invalid("it is longer than 64 characters");
+        }
+        if !name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+        {
+            return invalid("only lowercase letters, digits, `-` and `_` are allowed");
+        }
+        if !name.starts_with(|c: char| c.is_ascii_alphanumeric()) {
+            return invalid("it must start with a letter or digit");
+        }
+        if name.contains("--") {
+            return invalid("it contains consecutive hyphens");
+        }
+        Ok(CrateName(name.to_string()))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Deref for CrateName {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<str> for CrateName {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for CrateName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl TryFrom<String> for CrateName {
+    type Error = InvalidCrateNameError;
+
+    fn try_from(name: String) -> Result<Self, Self::Error> {
+        CrateName::parse(&name)
+    }
+}
+
+impl From<CrateName> for String {
+    fn from(name: CrateName) -> String {
+        name.0
+    }
+}
+
+impl fmt::Display for InvalidCrateNameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid crate name `{}`: {}", self.name, self.reason)
+    }
+}
+
+impl std::error::Error for InvalidCrateNameError {}
+
+impl From<InvalidCrateNameError> for DocError {
+    fn from(e: InvalidCrateNameError) -> Self {
+        DocError::InvalidInput(e.to_string())
+    }
+}
+
+impl From<InvalidCrateNameError> for McpError {
+    fn from(e: InvalidCrateNameError) -> Self {
+        DocError::from(e).into()
+    }
+}
+
+/// The path of an item within its crate, without the crate name, e.g. `io::AsyncRead`
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ItemPath(Vec<String>);
+
+/// Why a string isn't a valid item path
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ItemPathError {
+    pub path: String,
+    pub reason: &'static str,
+}
+
+impl ItemPath {
+    /// Parse an item path written with `::` or `/` separators, such as `tokio::io::AsyncRead`,
+    /// `io::AsyncRead`, `AsyncRead` or `tokio/io/AsyncRead`. A leading `crate_name::` (also
+    /// written with `_` for `-`), `crate::` or a leading separator is dropped.
+    pub fn parse(crate_name: &str, raw: &str) -> Result<ItemPath, ItemPathError> {
+        let invalid = |reason| {
+            Err(ItemPathError {
+                path: raw.to_string(),
+                reason,
+            })
+        };
+        let path = raw.trim();
+        let path = path.strip_prefix("::").or_else(|| path.strip_prefix('/')).unwrap_or(path);
+        if path.is_empty() {
+            return invalid("it is empty");
+        }
+
+        let mut segments: Vec<String> = path.replace("::", "/").split('/').map(str::to_string).collect();
+        if segments.iter().any(|segment| segment.is_empty()) {
+            return invalid("it has an empty segment");
+        }
+        if segments.iter().any(|segment| segment.contains([':', ' ', '\t', '\n'])) {
+            return invalid("segments must be separated by `::` or `/`");
+        }
+        let crate_prefix = segments[0] == crate_name || segments[0] == crate_name.replace('-', "_");
+        if segments.len() > 1 && (crate_prefix || segments[0] == "crate") {
+            segments.remove(0);
+        }
+        Ok(ItemPath(segments))
+    }
+
+    pub fn segments(&self) -> &[String] {
+        &self.0
+    }
+
+    /// The segments before the item name: the path of the module defining the item
+    pub fn module_segments(&self) -> &[String] {
+        &self.0[..self.0.len() - 1]
+    }
+
+    /// The module path as used in docs.rs URLs, e.g. `io/util`; empty for the crate root
+    pub fn module_path(&self) -> String {
+        self.module_segments().join("/")
+    }
+
+    /// The last segment, e.g. `AsyncRead`
+    pub fn item_name(&self) -> &str {
+        self.0.last().expect("item paths have at least one segment")
+    }
+
+    /// For a method path such as `vec::Vec::push`, the path of the type (`vec::Vec`) and the
+    /// method name: the last segment starts with a lowercase letter or `_` and the one
+    /// before it with an uppercase letter
+    pub fn method(&self) -> Option<(ItemPath, &str)> {
+        let [.., type_name, method] = self.0.as_slice() else {
+            return None;
+        };
+        let is_type = type_name.starts_with(|c: char| c.is_uppercase());
+        let is_method = method.starts_with(|c: char| c.is_lowercase() || c == '_');
+        (is_type && is_method).then(|| (ItemPath(self.0[..self.0.len() - 1].to_vec()), method.as_str()))
+    }
+}
+
+impl fmt::Display for ItemPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0.join("::"))
+    }
+}
+
+impl fmt::Display for ItemPathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid item path `{}`: {}; expected module::path::ItemName",
+            self.path, self.reason
+        )
+    }
+}
+
+impl std::error::Error for ItemPathError {}
+
+impl From<ItemPathError> for DocError {
+    fn from(e: ItemPathError) -> Self {
+        DocError::InvalidInput(e.to_string())
+    }
+}
+
+impl From<ItemPathError> for McpError {
+    fn from(e: ItemPathError) -> Self {
+        DocError::from(e).into()
+    }
+}
+
+/// A specific version of a crate, such as `1.0.200` or `0.2.0-alpha.1`
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct CrateVersion(semver::Version);
+
+impl CrateVersion {
+    /// Parse a full semver version. Requirements such as `0.11` or `^1` don't name one version and
+    /// are rejected here; a tool's `version` argument accepts them too, see `parse_version`
+    pub fn parse(version: &str) -> Result<CrateVersion, semver::Error> {
+        semver::Version::parse(version).map(CrateVersion)
+    }
+
+    pub fn version(&self) -> &semver::Version {
+        &self.0
+    }
+
+    pub fn is_prerelease(&self) -> bool {
+        !self.0.pre.is_empty()
+    }
+}
+
+impl fmt::Display for CrateVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}